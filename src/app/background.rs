@@ -33,6 +33,7 @@ pub fn drain_messages(app: &mut App, rx: &mpsc::Receiver<BackgroundMessage>, ctx
                 usage,
                 model,
             } => {
+                app.analysis_progress = None;
                 let count = suggestions.len();
                 for s in suggestions {
                     app.suggestions.add_llm_suggestion(s);
@@ -62,7 +63,11 @@ pub fn drain_messages(app: &mut App, rx: &mpsc::Receiver<BackgroundMessage>, ctx
                 app.show_toast(&format!("{} suggestions ready ({})", count, &model));
                 app.active_model = Some(model);
             }
+            BackgroundMessage::AnalysisProgress(step) => {
+                app.analysis_progress = Some(step);
+            }
             BackgroundMessage::SuggestionsError(e) => {
+                app.analysis_progress = None;
                 // If summaries are still generating, switch to that loading state
                 if app.needs_summary_generation && app.summary_progress.is_some() {
                     app.loading = LoadingState::GeneratingSummaries;
@@ -133,6 +138,11 @@ pub fn drain_messages(app: &mut App, rx: &mpsc::Receiver<BackgroundMessage>, ctx
                             } else {
                                 Some(&glossary_clone)
                             };
+                            let tx_progress = tx_suggestions.clone();
+                            let on_progress = move |step: &str| {
+                                let _ = tx_progress
+                                    .send(BackgroundMessage::AnalysisProgress(step.to_string()));
+                            };
                             // Use agentic analysis for highest accuracy - model explores codebase with tools
                             match suggest::llm::analyze_codebase_agentic(
                                 &repo_root,
@@ -140,6 +150,7 @@ pub fn drain_messages(app: &mut App, rx: &mpsc::Receiver<BackgroundMessage>, ctx
                                 &context_clone,
                                 mem,
                                 glossary_ref,
+                                &on_progress,
                             )
                             .await
                             {
@@ -240,7 +251,16 @@ pub fn drain_messages(app: &mut App, rx: &mpsc::Receiver<BackgroundMessage>, ctx
             BackgroundMessage::PreviewReady {
                 preview,
                 file_hashes,
+                usage,
             } => {
+                // Track session cost for display
+                if let Some(u) = usage {
+                    let cost = u.cost();
+                    app.session_cost += cost;
+                    app.session_tokens += u.total_tokens;
+                    // Refresh wallet balance after spending
+                    spawn_balance_refresh(ctx.tx.clone());
+                }
                 app.loading = LoadingState::None;
                 // Set the preview in the Verify workflow step
                 app.set_verify_preview(preview, file_hashes);
@@ -304,8 +324,10 @@ pub fn drain_messages(app: &mut App, rx: &mpsc::Receiver<BackgroundMessage>, ctx
                         let original = crate::git_ops::read_file_from_head(&app.repo_path, path)
                             .unwrap_or(None)
                             .unwrap_or_default();
+                        let original = crate::index::notebook::editable_text(path, original);
                         let full_path = app.repo_path.join(path);
-                        let new_content = std::fs::read_to_string(&full_path).unwrap_or_default();
+                        let new_content =
+                            crate::index::notebook::read_editable(&full_path).unwrap_or_default();
                         (path.clone(), original, new_content)
                     })
                     .collect();
@@ -325,6 +347,33 @@ pub fn drain_messages(app: &mut App, rx: &mpsc::Receiver<BackgroundMessage>, ctx
                     .unwrap_or_default();
                 app.start_review(first_file, first_original.clone(), first_new.clone());
 
+                // Flag dependencies the fix added that the license policy doesn't clear
+                let flagged: Vec<String> = files_with_content
+                    .iter()
+                    .flat_map(|(path, original, new_content)| {
+                        crate::manifest::license::check_added_dependencies(
+                            &app.repo_path,
+                            path,
+                            original,
+                            new_content,
+                            &app.project_config.licenses,
+                        )
+                    })
+                    .map(|entry| {
+                        format!(
+                            "{} ({})",
+                            entry.name,
+                            entry.license.as_deref().unwrap_or(entry.status.label())
+                        )
+                    })
+                    .collect();
+                if !flagged.is_empty() {
+                    app.show_toast(&format!(
+                        "License policy: fix adds {}",
+                        truncate(&flagged.join(", "), 80)
+                    ));
+                }
+
                 // Trigger verification in background (all files)
                 {
                     let tx_verify = ctx.tx.clone();
@@ -421,6 +470,15 @@ pub fn drain_messages(app: &mut App, rx: &mpsc::Receiver<BackgroundMessage>, ctx
                 }
                 app.show_toast(&truncate(&e, 100));
             }
+            BackgroundMessage::QuestionFailed(e) => {
+                app.loading = LoadingState::None;
+                // A streamed answer that failed midway stays readable
+                app.fail_inquiry();
+                app.show_toast(&truncate(&e, 100));
+            }
+            BackgroundMessage::QuestionChunk(text) => {
+                app.append_inquiry(&text);
+            }
             BackgroundMessage::QuestionResponse { answer, usage, .. } => {
                 // Track session cost for display
                 if let Some(u) = usage {
@@ -434,6 +492,7 @@ pub fn drain_messages(app: &mut App, rx: &mpsc::Receiver<BackgroundMessage>, ctx
                 app.loading = LoadingState::None;
                 // Show the response in the ask cosmos panel
                 app.show_inquiry(answer);
+                save_ask_thread(app);
             }
             BackgroundMessage::QuestionResponseWithCache {
                 question,
@@ -460,6 +519,7 @@ pub fn drain_messages(app: &mut App, rx: &mpsc::Receiver<BackgroundMessage>, ctx
                 app.loading = LoadingState::None;
                 // Show the response in the ask cosmos panel
                 app.show_inquiry(answer);
+                save_ask_thread(app);
             }
             BackgroundMessage::VerificationComplete {
                 findings,
@@ -558,6 +618,17 @@ pub fn drain_messages(app: &mut App, rx: &mpsc::Receiver<BackgroundMessage>, ctx
     }
 }
 
+/// Persist the open Ask Cosmos conversation to `.cosmos/ask_threads.json`
+fn save_ask_thread(app: &App) {
+    if let Some(state) = app
+        .ask_cosmos_state
+        .as_ref()
+        .filter(|state| !state.thread.turns.is_empty())
+    {
+        let _ = cache::Cache::new(&app.repo_path).save_ask_thread(&state.thread);
+    }
+}
+
 /// Spawn a background task to fetch the wallet balance
 pub fn spawn_balance_refresh(tx: mpsc::Sender<BackgroundMessage>) {
     spawn_background(tx.clone(), "balance_fetch", async move {
@@ -568,10 +639,13 @@ pub fn spawn_balance_refresh(tx: mpsc::Sender<BackgroundMessage>) {
     });
 }
 
+/// Spawn `fut`, reporting a panic as an error. A pending over-budget
+/// approval goes to this task: its LLM calls skip the spending caps.
 pub fn spawn_background<F>(tx: mpsc::Sender<BackgroundMessage>, task_name: &'static str, fut: F)
 where
     F: Future<Output = ()> + Send + 'static,
 {
+    let fut = suggest::llm::ledger::run_approved(suggest::llm::ledger::claim_approval(), fut);
     tokio::spawn(async move {
         if let Err(panic) = AssertUnwindSafe(fut).catch_unwind().await {
             let detail = if let Some(s) = panic.downcast_ref::<&str>() {
@@ -3,14 +3,15 @@ use crate::app::messages::BackgroundMessage;
 use crate::app::RuntimeContext;
 use crate::git_ops;
 use crate::suggest;
+use crate::suggest::llm::models::Task;
 use crate::suggest::llm::FixPreview;
 use crate::suggest::Suggestion;
-use crate::ui::{ActivePanel, App, LoadingState, Overlay, ShipStep, WorkflowStep};
-use crate::util::{hash_bytes, resolve_repo_path_allow_new};
+use crate::ui::{ActivePanel, App, BudgetReplay, LoadingState, Overlay, ShipStep, WorkflowStep};
+use crate::util::{hash_bytes, resolve_repo_path_allow_new, truncate};
 use anyhow::Result;
 use crossterm::event::{KeyCode, KeyEvent};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 // =============================================================================
@@ -183,6 +184,24 @@ fn validate_apply_fix(app: &App) -> std::result::Result<ApplyContext, ApplyError
     })
 }
 
+/// Index context for a fix prompt, plus the project's license policy so
+/// fixes don't pull in disallowed dependencies
+fn fix_prompt_context(
+    app: &App,
+    ctx: &RuntimeContext,
+    path: &Path,
+    line: Option<usize>,
+) -> Option<String> {
+    let sections: Vec<String> = [
+        ctx.index.fix_context(path, line),
+        app.project_config.licenses.prompt_context(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    (!sections.is_empty()).then(|| sections.join("\n\n"))
+}
+
 /// Handle key events in normal mode (no special input active)
 pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeContext) -> Result<()> {
     match key.code {
@@ -238,82 +257,82 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeCont
                 app.navigate_up();
             }
         }
-        KeyCode::Char(' ') => {
-            // Space toggles finding selection in Review step
+        // Space toggles finding selection in Review step
+        KeyCode::Char(' ')
             if app.active_panel == ActivePanel::Suggestions
                 && app.workflow_step == WorkflowStep::Review
                 && !app.review_state.reviewing
-                && !app.review_state.fixing
-            {
-                app.review_toggle_finding();
-            }
+                && !app.review_state.fixing =>
+        {
+            app.review_toggle_finding();
         }
-        KeyCode::Char('f') => {
-            // Fix selected findings in Review step
+        // Fix selected findings in Review step
+        KeyCode::Char('f')
             if app.active_panel == ActivePanel::Suggestions
                 && app.workflow_step == WorkflowStep::Review
                 && !app.review_state.reviewing
                 && !app.review_state.fixing
-                && !app.review_state.selected.is_empty()
-            {
-                let selected_findings = app.get_selected_review_findings();
-                let file = app.review_state.file_path.clone();
-                let content = app.review_state.new_content.clone();
-                let original = app.review_state.original_content.clone();
-                let iter = app.review_state.review_iteration;
-                let fixed = app.review_state.fixed_titles.clone();
-                let repo_memory_context = app.repo_memory.to_prompt_context(12, 900);
-                let memory = if repo_memory_context.trim().is_empty() {
-                    None
-                } else {
-                    Some(repo_memory_context)
-                };
-                let tx_fix = ctx.tx.clone();
-
-                if let Some(file_path) = file {
-                    app.set_review_fixing(true);
-
-                    background::spawn_background(ctx.tx.clone(), "verification_fix", async move {
-                        let orig_ref = if iter > 1 {
-                            Some(original.as_str())
-                        } else {
-                            None
-                        };
-                        match suggest::llm::fix_review_findings(
-                            &file_path,
-                            &content,
-                            orig_ref,
-                            &selected_findings,
-                            memory,
-                            iter,
-                            &fixed,
-                        )
-                        .await
-                        {
-                            Ok(fix) => {
-                                let _ = tx_fix.send(BackgroundMessage::VerificationFixComplete {
-                                    new_content: fix.new_content,
-                                    description: fix.description,
-                                    usage: fix.usage,
-                                });
-                            }
-                            Err(e) => {
-                                let _ = tx_fix.send(BackgroundMessage::Error(e.to_string()));
-                            }
-                        }
-                    });
+                && !app.review_state.selected.is_empty() =>
+        {
+            let selected_findings = app.get_selected_review_findings();
+            let file = app.review_state.file_path.clone();
+            let content = app.review_state.new_content.clone();
+            let original = app.review_state.original_content.clone();
+            let iter = app.review_state.review_iteration;
+            let fixed = app.review_state.fixed_titles.clone();
+            let repo_memory_context = app.repo_memory.to_prompt_context(12, 900);
+            let memory = if repo_memory_context.trim().is_empty() {
+                None
+            } else {
+                Some(repo_memory_context)
+            };
+            let tx_fix = ctx.tx.clone();
+
+            if let Some(file_path) = file {
+                if !app.budget_gate(Task::Fix, BudgetReplay::Key(key)) {
+                    return Ok(());
                 }
+                app.set_review_fixing(true);
+
+                background::spawn_background(ctx.tx.clone(), "verification_fix", async move {
+                    let orig_ref = if iter > 1 {
+                        Some(original.as_str())
+                    } else {
+                        None
+                    };
+                    match suggest::llm::fix_review_findings(
+                        &file_path,
+                        &content,
+                        orig_ref,
+                        &selected_findings,
+                        memory,
+                        iter,
+                        &fixed,
+                    )
+                    .await
+                    {
+                        Ok(fix) => {
+                            let _ = tx_fix.send(BackgroundMessage::VerificationFixComplete {
+                                new_content: fix.new_content,
+                                description: fix.description,
+                                usage: fix.usage,
+                            });
+                        }
+                        Err(e) => {
+                            let _ = tx_fix.send(BackgroundMessage::Error(e.to_string()));
+                        }
+                    }
+                });
             }
         }
-        KeyCode::Char('d') => {
-            // Toggle technical details in Verify step
+        // Toggle technical details in Verify step
+        KeyCode::Char('d')
             if app.active_panel == ActivePanel::Suggestions
                 && app.workflow_step == WorkflowStep::Verify
                 && !app.verify_state.loading
-                && app.verify_state.preview.is_some()
-            {
-                app.verify_toggle_details();
-            }
+                && app.verify_state.preview.is_some() =>
+        {
+            app.verify_toggle_details();
         }
         KeyCode::Enter => {
             // If PR URL is pending, open it in browser
@@ -347,6 +366,11 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeCont
                                             app.use_cached_verify();
                                         } else {
                                             // Generate new preview
+                                            if !app
+                                                .budget_gate(Task::Preview, BudgetReplay::Key(key))
+                                            {
+                                                return Ok(());
+                                            }
                                             let additional_files_for_preview =
                                                 additional_files.clone();
                                             let summary = suggestion.summary.clone();
@@ -355,6 +379,19 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeCont
                                             let repo_root = app.repo_path.clone();
                                             let repo_memory_context =
                                                 app.repo_memory.to_prompt_context(12, 900);
+                                            let index_context = fix_prompt_context(
+                                                app,
+                                                ctx,
+                                                &file_path,
+                                                suggestion.line,
+                                            );
+                                            let mut related_tests: Vec<PathBuf> =
+                                                std::iter::once(&file_path)
+                                                    .chain(additional_files.iter())
+                                                    .flat_map(|f| ctx.index.tests_for(f))
+                                                    .collect();
+                                            related_tests.sort();
+                                            related_tests.dedup();
 
                                             // Move to Verify step (with multi-file support)
                                             app.start_verify_multi(
@@ -362,6 +399,7 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeCont
                                                 file_path.clone(),
                                                 additional_files,
                                                 summary.clone(),
+                                                related_tests,
                                             );
 
                                             background::spawn_background(
@@ -433,14 +471,16 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeCont
                                                     &suggestion_clone,
                                                     None,
                                                     mem,
+                                                    index_context,
                                                 )
                                                 .await
                                                 {
-                                                    Ok(preview) => {
+                                                    Ok((preview, usage)) => {
                                                         let _ = tx_preview.send(
                                                             BackgroundMessage::PreviewReady {
                                                                 preview,
                                                                 file_hashes,
+                                                                usage,
                                                             },
                                                         );
                                                     }
@@ -463,6 +503,9 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeCont
                                 // Use validate_apply_fix to check all preconditions
                                 match validate_apply_fix(app) {
                                     Ok(apply_ctx) => {
+                                        if !app.budget_gate(Task::Fix, BudgetReplay::Key(key)) {
+                                            return Ok(());
+                                        }
                                         // All validations passed - start applying
                                         app.loading = LoadingState::GeneratingFix;
 
@@ -473,6 +516,8 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeCont
                                         let sid = apply_ctx.suggestion_id;
                                         let fp = apply_ctx.file_path;
                                         let repo_memory_context = apply_ctx.repo_memory_context;
+                                        let index_context =
+                                            fix_prompt_context(app, ctx, &fp, suggestion.line);
 
                                         background::spawn_background(
                                             ctx.tx.clone(),
@@ -538,7 +583,7 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeCont
                                                                 }
                                                             };
                                                         let is_new = !resolved.absolute.exists();
-                                                        let content = match std::fs::read_to_string(
+                                                        let content = match crate::index::notebook::read_editable(
                                                                 &resolved.absolute,
                                                             ) {
                                                                 Ok(content) => content,
@@ -574,6 +619,7 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeCont
                                                         &suggestion,
                                                         &preview,
                                                         mem,
+                                                        index_context,
                                                     )
                                                     .await
                                                     {
@@ -613,7 +659,7 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeCont
                                                                         parent,
                                                                     );
                                                                 }
-                                                                match std::fs::write(
+                                                                match crate::index::notebook::write_editable(
                                                                     &full_path,
                                                                     &file_edit.new_content,
                                                                 ) {
@@ -707,7 +753,7 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeCont
                                                     let full_path = resolved.absolute;
                                                     let rel_path = resolved.relative;
                                                     let is_new_file = !full_path.exists();
-                                                    let content = match std::fs::read_to_string(
+                                                    let content = match crate::index::notebook::read_editable(
                                                         &full_path,
                                                     ) {
                                                         Ok(c) => c,
@@ -736,6 +782,7 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeCont
                                                         &suggestion,
                                                         &preview,
                                                         mem,
+                                                        index_context,
                                                         is_new_file,
                                                     )
                                                     .await
@@ -746,7 +793,7 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeCont
                                                                 let _ =
                                                                     std::fs::create_dir_all(parent);
                                                             }
-                                                            match std::fs::write(
+                                                            match crate::index::notebook::write_editable(
                                                                 &full_path,
                                                                 &applied_fix.new_content,
                                                             ) {
@@ -839,6 +886,9 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeCont
                                         let tx_fix = ctx.tx.clone();
 
                                         if let Some(file_path) = file {
+                                            if !app.budget_gate(Task::Fix, BudgetReplay::Key(key)) {
+                                                return Ok(());
+                                            }
                                             app.set_review_fixing(true);
 
                                             background::spawn_background(
@@ -1005,15 +1055,14 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeCont
         KeyCode::PageDown => app.page_down(),
         KeyCode::PageUp => app.page_up(),
         KeyCode::Char('?') => app.toggle_help(),
-        KeyCode::Char('a') => {
-            // Select all findings in Review step
+        // Select all findings in Review step
+        KeyCode::Char('a')
             if app.active_panel == ActivePanel::Suggestions
                 && app.workflow_step == WorkflowStep::Review
                 && !app.review_state.reviewing
-                && !app.review_state.fixing
-            {
-                app.review_select_all();
-            }
+                && !app.review_state.fixing =>
+        {
+            app.review_select_all();
         }
         KeyCode::Char('i') => {
             // Ask Cosmos - only available from home (Suggestions step, not in workflow)
@@ -1021,6 +1070,8 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeCont
                 // Silently ignore during workflow
             } else if !suggest::llm::is_available() {
                 app.show_toast("Run: cosmos --setup");
+            } else if app.loading == LoadingState::Answering {
+                app.show_toast("Wait for the answer to finish");
             } else {
                 app.start_question();
             }
@@ -1047,12 +1098,81 @@ pub(super) fn handle_normal_mode(app: &mut App, key: KeyEvent, ctx: &RuntimeCont
                 ));
             }
         }
+        KeyCode::Char('L') => {
+            // Open dependency license report
+            app.overlay = Overlay::LicenseReport { scroll: 0 };
+        }
+        KeyCode::Char('$') => {
+            // Open LLM spend and budgets
+            app.overlay = Overlay::Costs { scroll: 0 };
+        }
+        KeyCode::Char('n') if app.is_ask_cosmos_mode() => {
+            // Start a new Ask Cosmos conversation
+            if app.loading == LoadingState::Answering {
+                app.show_toast("Wait for the answer to finish");
+            } else {
+                app.new_conversation();
+            }
+        }
+        KeyCode::Left if app.is_ask_cosmos_mode() => app.ask_cosmos_switch_turn(true),
+        KeyCode::Right if app.is_ask_cosmos_mode() => app.ask_cosmos_switch_turn(false),
+        KeyCode::Char('h') => {
+            // Browse past Ask Cosmos conversations
+            let threads = crate::cache::Cache::new(&app.repo_path).load_ask_threads();
+            if threads.is_empty() {
+                app.show_toast("No past conversations yet. Press i to ask Cosmos");
+            } else {
+                app.overlay = Overlay::AskHistory {
+                    threads,
+                    selected: 0,
+                };
+            }
+        }
+        KeyCode::Char('t') => {
+            // Show the tools the model called for the current step
+            let task = match app.workflow_step {
+                WorkflowStep::Suggestions => Task::Analysis,
+                WorkflowStep::Verify => Task::Preview,
+                WorkflowStep::Review | WorkflowStep::Ship => Task::Review,
+            };
+            app.overlay = Overlay::ToolTrace { task, scroll: 0 };
+        }
+        KeyCode::Char('M') => {
+            // Open repo memory editor
+            app.open_memory_overlay();
+        }
+        // Remember the focused suggestion or review finding as a convention
+        KeyCode::Char('m') if app.active_panel == ActivePanel::Suggestions => {
+            let Some(text) = remember_text(app) else {
+                return Ok(());
+            };
+            match app.remember(&text) {
+                Ok(true) => app.show_toast(&format!("Remembered: {}", truncate(&text, 60))),
+                Ok(false) => app.show_toast("Already remembered"),
+                Err(e) => app.show_toast(&e),
+            }
+        }
         _ => {}
     }
 
     Ok(())
 }
 
+/// Memory text for the suggestion or review finding under the cursor
+fn remember_text(app: &App) -> Option<String> {
+    let text = match app.workflow_step {
+        WorkflowStep::Suggestions if !app.is_ask_cosmos_mode() => {
+            app.selected_suggestion()?.summary.clone()
+        }
+        WorkflowStep::Review if !app.review_state.reviewing => {
+            let finding = app.review_state.findings.get(app.review_state.cursor)?;
+            format!("{}: {}", finding.title.trim(), finding.description.trim())
+        }
+        _ => return None,
+    };
+    Some(truncate(text.trim(), 300))
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -1,7 +1,7 @@
 use crate::app::background;
 use crate::app::messages::BackgroundMessage;
 use crate::app::RuntimeContext;
-use crate::ui::{App, LoadingState, Overlay};
+use crate::ui::{App, BudgetReplay, LoadingState, MemoryMode, Overlay};
 use anyhow::Result;
 use crossterm::event::{KeyCode, KeyEvent};
 
@@ -28,6 +28,21 @@ pub(super) fn handle_overlay_input(
                 KeyCode::Char(' ') => {
                     app.reset_toggle_selected();
                 }
+                KeyCode::Char('g') => {
+                    let cache = crate::cache::Cache::new(&app.repo_path);
+                    let budget = app.project_config.cache_budget_bytes();
+                    match cache.collect_garbage(&app.index, budget) {
+                        Ok(report) => {
+                            app.refresh_reset_sizes();
+                            app.show_toast(&format!(
+                                "Cache GC: pruned {} entries, freed {}",
+                                report.pruned_entries,
+                                crate::util::format_bytes(report.freed_bytes)
+                            ));
+                        }
+                        Err(e) => app.show_toast(&format!("Cache GC failed: {}", e)),
+                    }
+                }
                 KeyCode::Enter => {
                     let selected = app.get_reset_selections();
                     if selected.is_empty() {
@@ -57,6 +72,135 @@ pub(super) fn handle_overlay_input(
             return Ok(());
         }
 
+        // Handle Repo Memory overlay
+        if let Overlay::Memory { mode, .. } = &app.overlay {
+            match (mode, key.code) {
+                (MemoryMode::Browse, KeyCode::Esc | KeyCode::Char('q')) => app.close_overlay(),
+                (MemoryMode::Browse, KeyCode::Down) => app.memory_navigate(1),
+                (MemoryMode::Browse, KeyCode::Up) => app.memory_navigate(-1),
+                (MemoryMode::Browse, KeyCode::Char('/')) => app.memory_start_search(),
+                (MemoryMode::Browse, KeyCode::Char('n')) => app.memory_start_edit(true),
+                (MemoryMode::Browse, KeyCode::Char('e') | KeyCode::Enter) => {
+                    app.memory_start_edit(false)
+                }
+                (MemoryMode::Browse, KeyCode::Char('d')) => app.memory_confirm_delete(),
+                (MemoryMode::Browse, KeyCode::Char('s')) => match app.memory_toggle_shared() {
+                    Ok(message) if !message.is_empty() => app.show_toast(&message),
+                    Ok(_) => {}
+                    Err(e) => app.show_toast(&e),
+                },
+                (MemoryMode::Search, KeyCode::Esc) => app.memory_end_search(true),
+                (MemoryMode::Search, KeyCode::Enter) => app.memory_end_search(false),
+                (MemoryMode::Search, KeyCode::Down) => app.memory_navigate(1),
+                (MemoryMode::Search, KeyCode::Up) => app.memory_navigate(-1),
+                (MemoryMode::Search | MemoryMode::Edit { .. }, KeyCode::Backspace) => {
+                    app.memory_input_pop()
+                }
+                (MemoryMode::Search | MemoryMode::Edit { .. }, KeyCode::Char(c)) => {
+                    app.memory_input_push(c)
+                }
+                (MemoryMode::Edit { .. }, KeyCode::Esc) => app.memory_cancel(),
+                (MemoryMode::Edit { .. }, KeyCode::Enter) => {
+                    if let Err(e) = app.memory_commit_edit() {
+                        app.show_toast(&e);
+                    }
+                }
+                (MemoryMode::ConfirmDelete, KeyCode::Char('y')) => {
+                    match app.memory_delete_selected() {
+                        Ok(()) => app.show_toast("Memory entry deleted"),
+                        Err(e) => app.show_toast(&e),
+                    }
+                }
+                (MemoryMode::ConfirmDelete, _) => app.memory_cancel(),
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        // Handle budget confirm prompt
+        if let Overlay::BudgetConfirm { replay, .. } = &app.overlay {
+            match key.code {
+                KeyCode::Char('y') => {
+                    let replay = replay.clone();
+                    crate::suggest::llm::ledger::approve();
+                    app.close_overlay();
+                    let result = match replay {
+                        BudgetReplay::Key(key) => super::normal::handle_normal_mode(app, key, ctx),
+                        BudgetReplay::Question(question) => {
+                            super::question::ask(app, ctx, question)
+                        }
+                    };
+                    // The replayed action claims the approval when it starts;
+                    // one it didn't start mustn't carry over to the next
+                    crate::suggest::llm::ledger::withdraw_approval();
+                    return result;
+                }
+                KeyCode::Char('n') | KeyCode::Esc | KeyCode::Char('q') => {
+                    app.close_overlay();
+                    app.show_toast("Cancelled: over budget");
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        // Handle Ask Cosmos history overlay
+        if let Overlay::AskHistory { threads, selected } = &mut app.overlay {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => app.close_overlay(),
+                KeyCode::Down => *selected = (*selected + 1).min(threads.len().saturating_sub(1)),
+                KeyCode::Up => *selected = selected.saturating_sub(1),
+                KeyCode::Enter => {
+                    if app.loading == LoadingState::Answering {
+                        app.show_toast("Wait for the answer to finish");
+                    } else if let Some(thread) = threads.get(*selected).cloned() {
+                        app.close_overlay();
+                        app.open_ask_thread(thread);
+                    }
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        // Handle Costs and tool trace overlays
+        if let Overlay::Costs { .. } | Overlay::ToolTrace { .. } = &app.overlay {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => app.close_overlay(),
+                KeyCode::Down => app.overlay_scroll_down(),
+                KeyCode::Up => app.overlay_scroll_up(),
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        // Handle License Report overlay
+        if let Overlay::LicenseReport { .. } = &app.overlay {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    app.close_overlay();
+                }
+                KeyCode::Down => {
+                    app.overlay_scroll_down();
+                }
+                KeyCode::Up => {
+                    app.overlay_scroll_up();
+                }
+                KeyCode::Char('e') => {
+                    let cache = crate::cache::Cache::new(&app.repo_path);
+                    match cache.save_license_report(&app.license_report) {
+                        Ok(path) => {
+                            let shown = path.strip_prefix(&app.repo_path).unwrap_or(&path);
+                            app.show_toast(&format!("Exported to {}", shown.display()));
+                        }
+                        Err(e) => app.show_toast(&format!("Export failed: {}", e)),
+                    }
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+
         // Handle Startup Check overlay
         if let Overlay::StartupCheck {
             confirming_discard, ..
@@ -160,12 +304,9 @@ pub(super) fn handle_overlay_input(
             let target = target_version.clone();
 
             match key.code {
-                // Decline update: n, Esc, or q
-                KeyCode::Char('n') | KeyCode::Esc | KeyCode::Char('q') => {
-                    // Can dismiss if not currently downloading
-                    if !is_downloading {
-                        app.close_overlay();
-                    }
+                // Decline update: n, Esc, or q (can't dismiss while downloading)
+                KeyCode::Char('n') | KeyCode::Esc | KeyCode::Char('q') if !is_downloading => {
+                    app.close_overlay();
                 }
                 // Accept update: y or Enter
                 KeyCode::Char('y') | KeyCode::Enter => {
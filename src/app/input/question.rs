@@ -2,7 +2,8 @@ use crate::app::background;
 use crate::app::messages::BackgroundMessage;
 use crate::app::RuntimeContext;
 use crate::suggest;
-use crate::ui::{App, LoadingState};
+use crate::suggest::llm::models::Task;
+use crate::ui::{App, BudgetReplay, LoadingState};
 use anyhow::Result;
 use crossterm::event::{KeyCode, KeyEvent};
 
@@ -46,19 +47,38 @@ fn submit_question(app: &mut App, ctx: &RuntimeContext) -> Result<()> {
     if question.is_empty() {
         return Ok(());
     }
+    ask(app, ctx, question)
+}
+
+/// Answer `question` from the cache or the LLM, as a follow-up when a
+/// conversation is open. Also the replay target of the budget confirm prompt.
+pub(super) fn ask(app: &mut App, ctx: &RuntimeContext, question: String) -> Result<()> {
+    let history = app
+        .ask_cosmos_state
+        .as_ref()
+        .map(|state| state.thread.turns.clone())
+        .unwrap_or_default();
 
-    // Check cache first
+    // Only an opening question can be answered from the cache; a follow-up's
+    // answer depends on the conversation
     let context_hash = compute_context_hash(app);
-    if let Some(cached_answer) = app.question_cache.get(&question, &context_hash) {
-        // Cache hit! Use cached answer directly
-        let _ = ctx.tx.send(BackgroundMessage::QuestionResponse {
-            answer: cached_answer.to_string(),
-            usage: None, // No usage for cached response
-        });
-        return Ok(());
+    if history.is_empty() {
+        if let Some(cached_answer) = app.question_cache.get(&question, &context_hash) {
+            // Cache hit! Use cached answer directly
+            let answer = cached_answer.to_string();
+            app.begin_inquiry(question);
+            let _ = ctx.tx.send(BackgroundMessage::QuestionResponse {
+                answer,
+                usage: None, // No usage for cached response
+            });
+            return Ok(());
+        }
     }
 
-    // Cache miss - send question to LLM
+    // Cache miss - send question to LLM, if the budget allows
+    if !app.budget_gate(Task::Ask, BudgetReplay::Question(question.clone())) {
+        return Ok(());
+    }
     let index_clone = ctx.index.clone();
     let context_clone = app.context.clone();
     let tx_question = ctx.tx.clone();
@@ -67,6 +87,7 @@ fn submit_question(app: &mut App, ctx: &RuntimeContext) -> Result<()> {
     let context_hash_for_cache = context_hash;
 
     app.loading = LoadingState::Answering;
+    app.begin_inquiry(question.clone());
 
     background::spawn_background(ctx.tx.clone(), "ask_question", async move {
         let mem = if repo_memory_context.trim().is_empty() {
@@ -74,8 +95,21 @@ fn submit_question(app: &mut App, ctx: &RuntimeContext) -> Result<()> {
         } else {
             Some(repo_memory_context)
         };
-        match suggest::llm::ask_question(&index_clone, &context_clone, &question, mem).await {
-            Ok((answer, usage)) => {
+        let tx_chunks = tx_question.clone();
+        let mut on_text = move |text: &str| {
+            let _ = tx_chunks.send(BackgroundMessage::QuestionChunk(text.to_string()));
+        };
+        match suggest::llm::ask_question(
+            &index_clone,
+            &context_clone,
+            &question,
+            &history,
+            mem,
+            &mut on_text,
+        )
+        .await
+        {
+            Ok((answer, usage)) if history.is_empty() => {
                 // Send response with cache metadata for storage
                 let _ = tx_question.send(BackgroundMessage::QuestionResponseWithCache {
                     question: question_for_cache,
@@ -84,8 +118,11 @@ fn submit_question(app: &mut App, ctx: &RuntimeContext) -> Result<()> {
                     context_hash: context_hash_for_cache,
                 });
             }
+            Ok((answer, usage)) => {
+                let _ = tx_question.send(BackgroundMessage::QuestionResponse { answer, usage });
+            }
             Err(e) => {
-                let _ = tx_question.send(BackgroundMessage::Error(e.to_string()));
+                let _ = tx_question.send(BackgroundMessage::QuestionFailed(e.to_string()));
             }
         }
     });
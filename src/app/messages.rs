@@ -12,6 +12,8 @@ pub enum BackgroundMessage {
         model: String,
     },
     SuggestionsError(String),
+    /// A step of a running analysis (e.g. a tool call being made)
+    AnalysisProgress(String),
     SummariesReady {
         summaries: HashMap<PathBuf, String>,
         usage: Option<suggest::llm::Usage>,
@@ -35,6 +37,7 @@ pub enum BackgroundMessage {
     PreviewReady {
         preview: suggest::llm::FixPreview,
         file_hashes: HashMap<PathBuf, String>,
+        usage: Option<suggest::llm::Usage>,
     },
     PreviewError(String),
     /// Direct fix applied (Smart preset generated + applied the change)
@@ -72,6 +75,11 @@ pub enum BackgroundMessage {
     DiscardComplete,
     /// Generic error (used for push/etc)
     Error(String),
+    /// Fragment of a streamed answer; the full answer follows as
+    /// `QuestionResponseWithCache`
+    QuestionChunk(String),
+    /// The pending Ask Cosmos question failed
+    QuestionFailed(String),
     /// Response to a user question
     QuestionResponse {
         answer: String,
@@ -14,6 +14,7 @@ use crate::context::WorkContext;
 use crate::git_ops;
 use crate::grouping::{Confidence, Layer, LayerOverride};
 use crate::index::CodebaseIndex;
+use crate::manifest::license::LicenseReport;
 use crate::suggest;
 use crate::suggest::llm::grouping as grouping_llm;
 use crate::suggest::SuggestionEngine;
@@ -39,6 +40,7 @@ pub async fn run_tui(
     context: WorkContext,
     cache_manager: cache::Cache,
     repo_path: PathBuf,
+    license_report: LicenseReport,
 ) -> Result<()> {
     // Set up terminal
     enable_raw_mode()?;
@@ -55,6 +57,8 @@ pub async fn run_tui(
     app.glossary = cache_manager.load_glossary().unwrap_or_default();
     // Load cached question answers
     app.question_cache = cache_manager.load_question_cache().unwrap_or_default();
+    app.project_config = cache_manager.load_project_config();
+    app.license_report = license_report;
 
     // Check for unsaved work and show startup overlay if needed
     if let Ok(status) = git_ops::current_status(&repo_path) {
@@ -432,6 +436,10 @@ pub async fn run_tui(
                 } else {
                     Some(&glossary_clone)
                 };
+                let tx_progress = tx_suggestions.clone();
+                let on_progress = move |step: &str| {
+                    let _ = tx_progress.send(BackgroundMessage::AnalysisProgress(step.to_string()));
+                };
                 // Use agentic analysis for highest accuracy - model explores codebase with tools
                 match suggest::llm::analyze_codebase_agentic(
                     &repo_root,
@@ -439,6 +447,7 @@ pub async fn run_tui(
                     &context_clone,
                     mem,
                     glossary_ref,
+                    &on_progress,
                 )
                 .await
                 {
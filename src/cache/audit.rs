@@ -0,0 +1,81 @@
+//! Tool-call audit log: `.cosmos/audit/<session>.jsonl`
+//!
+//! One file per Cosmos session, one line per tool call the model made
+//! during analysis, preview or review (tool, arguments, output as the model
+//! saw it, duration). Append-only like the ledger; only the most recent
+//! sessions are kept.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub(super) const AUDIT_DIR: &str = "audit";
+
+/// Session logs kept when a new session starts
+const KEEP_SESSIONS: usize = 20;
+
+/// One tool call made by the model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub at: DateTime<Utc>,
+    /// Agentic run within the session (one per `call_llm_agentic`)
+    pub run: u64,
+    /// Task name (`analysis`, `preview`, `review`)
+    pub task: String,
+    /// Tool-calling round within the run, from 1
+    pub iteration: usize,
+    pub tool: String,
+    /// Arguments as the model sent them (JSON)
+    pub arguments: String,
+    /// Output returned to the model, cut to `MAX_AUDIT_OUTPUT_BYTES`
+    pub output: String,
+    /// The output was longer than what's stored here
+    #[serde(default)]
+    pub truncated: bool,
+    pub duration_ms: u64,
+}
+
+/// Bytes of tool output kept per entry
+pub const MAX_AUDIT_OUTPUT_BYTES: usize = 2000;
+
+/// File for a session started at `started`
+pub(super) fn session_path(dir: &Path, started: DateTime<Utc>) -> PathBuf {
+    dir.join(AUDIT_DIR).join(format!(
+        "{}-{}.jsonl",
+        started.format("%Y%m%dT%H%M%SZ"),
+        std::process::id()
+    ))
+}
+
+/// Append one entry, creating the audit directory if needed
+pub(super) fn append(path: &Path, entry: &AuditEntry) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Delete all but the newest `KEEP_SESSIONS` session logs
+pub(super) fn prune(dir: &Path) -> anyhow::Result<usize> {
+    let Ok(read_dir) = fs::read_dir(dir.join(AUDIT_DIR)) else {
+        return Ok(0);
+    };
+    let mut logs: Vec<PathBuf> = read_dir
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect();
+    // Names start with the session's UTC start time, so they sort by age
+    logs.sort();
+    let excess = logs.len().saturating_sub(KEEP_SESSIONS);
+    for path in &logs[..excess] {
+        fs::remove_file(path)?;
+    }
+    Ok(excess)
+}
@@ -0,0 +1,420 @@
+//! Sharded binary index cache
+//!
+//! Layout under `.cosmos/index/`:
+//! - `manifest.bin`: format version, each file's content hash, and a digest
+//!   per shard
+//! - `shards/<xx>.bin`: the `FileIndex` entries, with their symbols, whose
+//!   path hash ends in `xx`
+//! - `details/<xx>.bin`: the same files' `FileDetail` (dependencies,
+//!   patterns, notebook cells)
+//!
+//! All are MessagePack with named fields, so added `#[serde(default)]`
+//! fields keep old shards readable. Loading reads the manifest first and
+//! decodes only the shards holding unchanged files; changed, new and
+//! unreadable files are re-indexed on their own. Detail shards aren't read
+//! at load at all: each file decodes its detail on first access, and falls
+//! back to re-reading the file if its detail shard is missing, corrupt or
+//! was replaced since. Saving only writes shards whose content changed, and
+//! skips detail shards whose files all still carry the stored detail.
+
+use super::write_atomic;
+use crate::index::{CodebaseIndex, DetailSource, FileDetail, FileIndex, IndexError, Symbol};
+use crate::util::{hash_bytes, hash_str};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+pub(super) const INDEX_DIR: &str = "index";
+const MANIFEST_FILE: &str = "manifest.bin";
+const SHARDS_DIR: &str = "shards";
+const DETAILS_DIR: &str = "details";
+
+/// Bump when the manifest or shard encoding changes incompatibly
+const INDEX_FORMAT_VERSION: u32 = 3;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    root: PathBuf,
+    cached_at: DateTime<Utc>,
+    /// Relative path -> content hash, checked against disk before any shard is read
+    files: BTreeMap<PathBuf, String>,
+    /// Shard id -> digest of its encoded bytes
+    shards: BTreeMap<String, String>,
+    /// Shard id -> digest of its detail shard's encoded bytes
+    details: BTreeMap<String, String>,
+    index_errors: Vec<IndexError>,
+}
+
+/// A file as stored in its shard. Symbols live in the index's interned table
+/// while loaded, so they're written out per file here.
+#[derive(Debug, Serialize, Deserialize)]
+struct ShardEntry<F> {
+    file: F,
+    symbols: Vec<Symbol>,
+}
+
+/// A file's detail as stored in its detail shard
+#[derive(Debug, Serialize, Deserialize)]
+struct DetailEntry<H, D> {
+    /// Content the detail was computed from
+    content_hash: H,
+    detail: D,
+}
+
+type DecodedDetails = HashMap<PathBuf, DetailEntry<String, FileDetail>>;
+
+/// An index restored from the cache
+pub struct CachedIndex {
+    pub index: CodebaseIndex,
+    /// Files that were re-indexed because they changed or their shard was unreadable
+    pub reindexed: usize,
+}
+
+/// The detail shards of a restored index, each decoded when one of its
+/// files is first asked for. They're read without the cache lock, so a
+/// shard is only trusted if it still has the digest the manifest recorded.
+#[derive(Debug)]
+struct DetailShards {
+    root: PathBuf,
+    index_dir: PathBuf,
+    digests: BTreeMap<String, String>,
+    /// Shards decoded so far, `None` if unreadable. Entries are handed out
+    /// once; each file keeps its own.
+    decoded: Mutex<HashMap<String, Option<DecodedDetails>>>,
+}
+
+impl DetailShards {
+    fn decode(&self, shard: &str) -> Option<DecodedDetails> {
+        let bytes = fs::read(shard_path(&self.index_dir, DETAILS_DIR, shard)).ok()?;
+        if self.digests.get(shard) != Some(&hash_bytes(&bytes)) {
+            return None;
+        }
+        rmp_serde::from_slice(&bytes).ok()
+    }
+}
+
+impl DetailSource for DetailShards {
+    fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn load(&self, rel_path: &Path, content_hash: &str) -> Option<FileDetail> {
+        let shard = shard_id(rel_path);
+        let mut decoded = self.decoded.lock().unwrap_or_else(|e| e.into_inner());
+        let entries = decoded
+            .entry(shard.clone())
+            .or_insert_with(|| self.decode(&shard));
+        let entry = entries
+            .as_mut()
+            .and_then(|entries| entries.remove(rel_path))
+            .filter(|entry| entry.content_hash == content_hash);
+        if entry.is_none() {
+            // The file gets re-read; removing the shard makes the next save rewrite it
+            let _ = fs::remove_file(shard_path(&self.index_dir, DETAILS_DIR, &shard));
+        }
+        entry.map(|entry| entry.detail)
+    }
+}
+
+/// Shard a relative path belongs to (256 shards)
+fn shard_id(rel_path: &Path) -> String {
+    let hash = hash_str(&rel_path.to_string_lossy());
+    hash[hash.len() - 2..].to_string()
+}
+
+fn shard_path(index_dir: &Path, dir: &str, shard: &str) -> PathBuf {
+    index_dir.join(dir).join(format!("{}.bin", shard))
+}
+
+fn read_manifest(index_dir: &Path) -> Option<Manifest> {
+    let bytes = fs::read(index_dir.join(MANIFEST_FILE)).ok()?;
+    rmp_serde::from_slice(&bytes).ok()
+}
+
+/// The index's files grouped into shards
+fn shard_entries(
+    index: &CodebaseIndex,
+) -> BTreeMap<String, BTreeMap<&PathBuf, ShardEntry<&FileIndex>>> {
+    let mut grouped: BTreeMap<_, BTreeMap<_, _>> = BTreeMap::new();
+    for (path, file_index) in &index.files {
+        let symbols = index
+            .file_symbols(file_index)
+            .map(|s| s.to_symbol())
+            .collect();
+        grouped.entry(shard_id(path)).or_default().insert(
+            path,
+            ShardEntry {
+                file: file_index,
+                symbols,
+            },
+        );
+    }
+    grouped
+}
+
+/// Write a shard unless it already holds these bytes; returns their digest
+fn write_shard(path: &Path, bytes: &[u8], previous: Option<&String>) -> anyhow::Result<String> {
+    let digest = hash_bytes(bytes);
+    if previous != Some(&digest) || !path.exists() {
+        write_atomic(path, bytes)?;
+    }
+    Ok(digest)
+}
+
+pub(super) fn save(index_dir: &Path, index: &CodebaseIndex) -> anyhow::Result<()> {
+    let previous = read_manifest(index_dir).filter(|m| m.version == INDEX_FORMAT_VERSION);
+    fs::create_dir_all(index_dir.join(SHARDS_DIR))?;
+    fs::create_dir_all(index_dir.join(DETAILS_DIR))?;
+
+    let mut shards = BTreeMap::new();
+    for (shard, entries) in &shard_entries(index) {
+        let bytes = rmp_serde::to_vec_named(entries)?;
+        let previous_digest = previous.as_ref().and_then(|m| m.shards.get(shard));
+        let path = shard_path(index_dir, SHARDS_DIR, shard);
+        shards.insert(shard.clone(), write_shard(&path, &bytes, previous_digest)?);
+    }
+
+    let mut previous_files: BTreeMap<String, BTreeMap<&PathBuf, &String>> = BTreeMap::new();
+    for (path, hash) in previous.iter().flat_map(|m| &m.files) {
+        previous_files
+            .entry(shard_id(path))
+            .or_default()
+            .insert(path, hash);
+    }
+    let mut grouped: BTreeMap<String, BTreeMap<&PathBuf, &FileIndex>> = BTreeMap::new();
+    for (path, file_index) in &index.files {
+        grouped
+            .entry(shard_id(path))
+            .or_default()
+            .insert(path, file_index);
+    }
+
+    let mut details = BTreeMap::new();
+    for (shard, files) in &grouped {
+        let path = shard_path(index_dir, DETAILS_DIR, shard);
+        let previous_digest = previous.as_ref().and_then(|m| m.details.get(shard));
+        // Same files, all still carrying the stored detail: nothing to decode or write
+        let same_files = previous_files.get(shard).is_some_and(|old| {
+            old.len() == files.len()
+                && files
+                    .iter()
+                    .all(|(p, f)| old.get(p) == Some(&&f.content_hash) && f.detail_is_cached())
+        });
+        if let Some(digest) = previous_digest.filter(|_| same_files && path.exists()) {
+            details.insert(shard.clone(), digest.clone());
+            continue;
+        }
+        let entries: BTreeMap<_, _> = files
+            .iter()
+            .map(|(p, f)| {
+                let entry = DetailEntry {
+                    content_hash: f.content_hash.as_str(),
+                    detail: f.detail(),
+                };
+                (*p, entry)
+            })
+            .collect();
+        let bytes = rmp_serde::to_vec_named(&entries)?;
+        details.insert(shard.clone(), write_shard(&path, &bytes, previous_digest)?);
+    }
+
+    // Drop shards whose files are all gone
+    if let Some(previous) = &previous {
+        for (dir, current, old) in [
+            (SHARDS_DIR, &shards, &previous.shards),
+            (DETAILS_DIR, &details, &previous.details),
+        ] {
+            for shard in old.keys() {
+                if !current.contains_key(shard) {
+                    let _ = fs::remove_file(shard_path(index_dir, dir, shard));
+                }
+            }
+        }
+    }
+
+    let manifest = Manifest {
+        version: INDEX_FORMAT_VERSION,
+        root: index.root.clone(),
+        cached_at: index.cached_at,
+        files: index
+            .files
+            .iter()
+            .map(|(path, f)| (path.clone(), f.content_hash.clone()))
+            .collect(),
+        shards,
+        details,
+        index_errors: index.index_errors.clone(),
+    };
+    write_atomic(
+        &index_dir.join(MANIFEST_FILE),
+        rmp_serde::to_vec_named(&manifest)?,
+    )?;
+    Ok(())
+}
+
+/// Restore the index, re-indexing files that changed since it was saved.
+/// `None` when there is no usable manifest (a full index is needed).
+pub(super) fn load(
+    index_dir: &Path,
+    root: &Path,
+    current_hashes: &HashMap<PathBuf, String>,
+) -> Option<CachedIndex> {
+    let manifest = read_manifest(index_dir)?;
+    if manifest.version != INDEX_FORMAT_VERSION || manifest.root != root {
+        return None;
+    }
+
+    // Only shards holding unchanged files are decoded
+    let mut wanted: BTreeMap<String, Vec<&PathBuf>> = BTreeMap::new();
+    for (path, hash) in current_hashes {
+        if manifest.files.get(path) == Some(hash) {
+            wanted.entry(shard_id(path)).or_default().push(path);
+        }
+    }
+
+    let details: Arc<dyn DetailSource> = Arc::new(DetailShards {
+        root: root.to_path_buf(),
+        index_dir: index_dir.to_path_buf(),
+        digests: manifest.details,
+        decoded: Mutex::default(),
+    });
+    let mut files = HashMap::new();
+    let mut symbols = HashMap::new();
+    for (shard, paths) in wanted {
+        let path = shard_path(index_dir, SHARDS_DIR, &shard);
+        let Some(mut entries) = fs::read(&path).ok().and_then(|bytes| {
+            rmp_serde::from_slice::<HashMap<PathBuf, ShardEntry<FileIndex>>>(&bytes).ok()
+        }) else {
+            // Its files get re-indexed; removing it makes the next save rewrite it
+            let _ = fs::remove_file(&path);
+            continue;
+        };
+        for path in paths {
+            if let Some(mut entry) = entries.remove(path) {
+                if entry.file.content_hash == current_hashes[path] {
+                    entry.file.set_detail_source(Arc::clone(&details));
+                    files.insert(path.clone(), entry.file);
+                    symbols.insert(path.clone(), entry.symbols);
+                }
+            }
+        }
+    }
+
+    let mut stale: Vec<PathBuf> = current_hashes
+        .keys()
+        .filter(|path| !files.contains_key(*path))
+        .cloned()
+        .collect();
+    stale.sort();
+
+    let mut index =
+        CodebaseIndex::from_cached_files(root, files, symbols, manifest.index_errors, &stale);
+    if stale.is_empty() {
+        index.cached_at = manifest.cached_at;
+    }
+    Some(CachedIndex {
+        index,
+        reindexed: stale.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn test_shards_rewrite_and_repair_only_changed_files() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("cosmos_index_store_test_{}", nanos));
+        let index_dir = root.join(".cosmos").join(INDEX_DIR);
+        fs::create_dir_all(root.join("src")).unwrap();
+        let names: Vec<String> = (0..20).map(|i| format!("src/m{}.rs", i)).collect();
+        for name in &names {
+            fs::write(root.join(name), "pub fn f() {} // TODO\n").unwrap();
+        }
+
+        let index = CodebaseIndex::new(&root).unwrap();
+        save(&index_dir, &index).unwrap();
+
+        // Edit one file, corrupt another file's shard and a third file's
+        // detail shard (all in different shards)
+        let edited = PathBuf::from("src/m0.rs");
+        fs::write(root.join(&edited), "pub fn f() {}\npub fn g() {}\n").unwrap();
+        let mut others = names
+            .iter()
+            .map(|n| shard_id(Path::new(n)))
+            .filter(|s| *s != shard_id(&edited));
+        let corrupt_shard = others.next().unwrap();
+        let corrupt_detail = others.find(|s| *s != corrupt_shard).unwrap();
+        let untouched = others
+            .find(|s| *s != corrupt_shard && *s != corrupt_detail)
+            .unwrap();
+        let in_shard = |shard: &String| -> Vec<PathBuf> {
+            names
+                .iter()
+                .map(PathBuf::from)
+                .filter(|n| shard_id(n) == *shard)
+                .collect()
+        };
+        fs::write(
+            shard_path(&index_dir, SHARDS_DIR, &corrupt_shard),
+            b"not msgpack",
+        )
+        .unwrap();
+        fs::write(
+            shard_path(&index_dir, DETAILS_DIR, &corrupt_detail),
+            b"not msgpack",
+        )
+        .unwrap();
+
+        let hashes = super::super::compute_current_hashes(&root).unwrap();
+        let cached = load(&index_dir, &root, &hashes).unwrap();
+        assert_eq!(cached.reindexed, 1 + in_shard(&corrupt_shard).len());
+        assert_eq!(cached.index.files.len(), names.len());
+        assert_eq!(cached.index.files[&edited].symbols.len(), 2);
+        assert_eq!(cached.index.symbols.len(), names.len() + 1);
+
+        // Detail is read on first access: a corrupt detail shard means the
+        // file itself is re-read then, not at load
+        let repaired = &in_shard(&corrupt_detail)[0];
+        fs::write(root.join(repaired), "pub fn f() {} // TODO\n// FIXME\n").unwrap();
+        assert_eq!(cached.index.files[repaired].patterns().len(), 2);
+        let kept = &in_shard(&untouched)[0];
+        assert_eq!(cached.index.files[kept].patterns().len(), 1);
+        assert!(!shard_path(&index_dir, DETAILS_DIR, &corrupt_detail).exists());
+
+        // Saving again rewrites only the edited and repaired shards
+        let modified = |dir: &str, shard: &str| {
+            fs::metadata(shard_path(&index_dir, dir, shard))
+                .unwrap()
+                .modified()
+                .unwrap()
+        };
+        let before = [
+            modified(SHARDS_DIR, &untouched),
+            modified(DETAILS_DIR, &untouched),
+        ];
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        save(&index_dir, &cached.index).unwrap();
+        let after = [
+            modified(SHARDS_DIR, &untouched),
+            modified(DETAILS_DIR, &untouched),
+        ];
+        assert_eq!(before, after);
+        assert_eq!(
+            fs::read(shard_path(&index_dir, SHARDS_DIR, &corrupt_shard)).unwrap()[..],
+            rmp_serde::to_vec_named(&shard_entries(&cached.index)[&corrupt_shard]).unwrap()[..]
+        );
+        assert!(shard_path(&index_dir, DETAILS_DIR, &corrupt_detail).exists());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,70 @@
+//! Cost ledger: `.cosmos/ledger.jsonl`
+//!
+//! One line per LLM call (task, model, tokens, cost), appended as calls
+//! finish. Append-only JSON Lines so a crash loses at most the call in
+//! flight and concurrent sessions don't overwrite each other. Not a cache:
+//! garbage collection and Reset leave it alone.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+pub(super) const LEDGER_FILE: &str = "ledger.jsonl";
+
+/// Bump (with a conversion in `read`) if an entry's shape changes
+const LEDGER_SCHEMA_VERSION: u32 = 1;
+
+/// One recorded LLM call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub at: DateTime<Utc>,
+    /// Task name (`fix`, `ask`, ...)
+    pub task: String,
+    /// Model ID as sent to the provider
+    pub model: String,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    /// USD as reported by the provider (0 when it reports none)
+    pub cost: f64,
+}
+
+/// Entries in file order. Missing file reads as empty; malformed lines are
+/// skipped. `None` if the file was written by a newer Cosmos.
+pub(super) fn read(path: &Path) -> Option<Vec<LedgerEntry>> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Some(Vec::new());
+    };
+    let mut entries = Vec::new();
+    for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let Ok(value) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        if let Some(version) = value.get(super::schema::SCHEMA_KEY) {
+            if version.as_u64().unwrap_or(0) > LEDGER_SCHEMA_VERSION as u64 {
+                return None;
+            }
+            continue;
+        }
+        if let Ok(entry) = serde_json::from_value(value) {
+            entries.push(entry);
+        }
+    }
+    Some(entries)
+}
+
+/// Append one entry, writing the version header first for a new file
+pub(super) fn append(path: &Path, entry: &LedgerEntry) -> anyhow::Result<()> {
+    let mut out = String::new();
+    if !path.exists() {
+        out.push_str(&json!({ super::schema::SCHEMA_KEY: LEDGER_SCHEMA_VERSION }).to_string());
+        out.push('\n');
+    }
+    out.push_str(&serde_json::to_value(entry)?.to_string());
+    out.push('\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(out.as_bytes())?;
+    Ok(())
+}
@@ -13,9 +13,22 @@
 //!
 //! For critical data, callers should explicitly handle errors.
 
+mod audit;
+mod index_store;
+mod ledger;
+mod schema;
+mod shared;
+
+pub use audit::{AuditEntry, MAX_AUDIT_OUTPUT_BYTES};
+pub use index_store::CachedIndex;
+pub use ledger::LedgerEntry;
+
+use crate::config::ProjectConfig;
 use crate::index::CodebaseIndex;
+use crate::manifest::license::LicenseReport;
 use chrono::{DateTime, Duration, Utc};
 use fs2::FileExt;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::{self, OpenOptions};
@@ -24,18 +37,21 @@ use std::path::{Path, PathBuf};
 use std::time::{Duration as StdDuration, Instant};
 
 const CACHE_DIR: &str = ".cosmos";
-const INDEX_CACHE_FILE: &str = "index.json";
+/// Pre-sharding JSON index, removed on the next save
+const LEGACY_INDEX_CACHE_FILE: &str = "index.json";
 const SUGGESTIONS_CACHE_FILE: &str = "suggestions.json";
 const MEMORY_FILE: &str = "memory.json";
 const GLOSSARY_FILE: &str = "glossary.json";
 const GROUPING_AI_CACHE_FILE: &str = "grouping_ai.json";
+const PROJECT_CONFIG_FILE: &str = "config.json";
+const LICENSE_REPORT_FILE: &str = "license_report.json";
 const CACHE_LOCK_TIMEOUT_SECS: u64 = 5;
 const CACHE_LOCK_RETRY_MS: u64 = 50;
 
 /// Options for selective cache reset
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ResetOption {
-    /// Clear index.json - codebase structure, symbols, patterns
+    /// Clear the index cache - codebase structure, symbols, patterns
     Index,
     /// Clear suggestions.json - generated suggestions
     Suggestions,
@@ -74,6 +90,18 @@ impl ResetOption {
         }
     }
 
+    /// Files (or directories) under `.cosmos/` this option clears
+    fn files(&self) -> &'static [&'static str] {
+        match self {
+            ResetOption::Index => &[index_store::INDEX_DIR, LEGACY_INDEX_CACHE_FILE],
+            ResetOption::Suggestions => &[SUGGESTIONS_CACHE_FILE],
+            ResetOption::Summaries => &[LLM_SUMMARIES_CACHE_FILE],
+            ResetOption::Glossary => &[GLOSSARY_FILE],
+            ResetOption::Memory => &[MEMORY_FILE],
+            ResetOption::GroupingAi => &[GROUPING_AI_CACHE_FILE],
+        }
+    }
+
     /// Get all options in display order
     pub fn all() -> Vec<ResetOption> {
         vec![
@@ -106,23 +134,63 @@ const WELCOME_SEEN_FILE: &str = "welcome_seen";
 
 /// Question answer cache file
 const QUESTION_CACHE_FILE: &str = "question_cache.json";
+const ASK_THREADS_FILE: &str = "ask_threads.json";
+/// Conversations kept in `ask_threads.json`, most recently active first
+const MAX_ASK_THREADS: usize = 50;
 
 /// Max age for question cache entries (in hours)
 const QUESTION_CACHE_HOURS: i64 = 24;
 
-/// Cached index metadata
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct IndexCache {
-    pub root: PathBuf,
-    pub file_count: usize,
-    pub symbol_count: usize,
-    pub cached_at: DateTime<Utc>,
-    pub file_hashes: HashMap<PathBuf, String>,
-}
-
 // Note: Suggestions are generated fresh each session (not cached across restarts)
 // to ensure users always see new insights from the AI exploration.
 
+// ═══════════════════════════════════════════════════════════════════════════
+//  ASK COSMOS CONVERSATIONS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// One question and its answer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AskTurn {
+    pub question: String,
+    pub answer: String,
+    pub asked_at: DateTime<Utc>,
+}
+
+/// An Ask Cosmos conversation; follow-ups see the earlier turns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AskThread {
+    pub id: uuid::Uuid,
+    pub started_at: DateTime<Utc>,
+    pub turns: Vec<AskTurn>,
+}
+
+impl Default for AskThread {
+    fn default() -> Self {
+        Self {
+            id: uuid::Uuid::new_v4(),
+            started_at: Utc::now(),
+            turns: Vec::new(),
+        }
+    }
+}
+
+impl AskThread {
+    /// The opening question, as a title for the history list
+    pub fn title(&self) -> &str {
+        self.turns.first().map_or("", |t| t.question.as_str())
+    }
+
+    /// When the last question was asked
+    pub fn updated_at(&self) -> DateTime<Utc> {
+        self.turns.last().map_or(self.started_at, |t| t.asked_at)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct AskThreads {
+    threads: Vec<AskThread>,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 //  QUESTION ANSWER CACHE - Persistent storage for AI-generated answers
 // ═══════════════════════════════════════════════════════════════════════════
@@ -350,6 +418,16 @@ impl LlmSummaryCache {
 
         changed
     }
+
+    /// Drop summaries for files that are gone or past the TTL; returns how many
+    pub fn prune(&mut self, is_live: impl Fn(&Path) -> bool) -> usize {
+        let before = self.summaries.len();
+        let now = Utc::now();
+        self.summaries.retain(|path, entry| {
+            is_live(path) && now - entry.generated_at < Duration::days(LLM_SUMMARY_CACHE_DAYS)
+        });
+        before - self.summaries.len()
+    }
 }
 
 impl Default for LlmSummaryCache {
@@ -432,6 +510,16 @@ impl GroupingAiCache {
 
         changed
     }
+
+    /// Drop entries for files that are gone or past the TTL; returns how many
+    pub fn prune(&mut self, is_live: impl Fn(&Path) -> bool) -> usize {
+        let before = self.entries.len();
+        let now = Utc::now();
+        self.entries.retain(|path, entry| {
+            is_live(path) && now - entry.generated_at < Duration::days(GROUPING_AI_CACHE_DAYS)
+        });
+        before - self.entries.len()
+    }
 }
 
 impl Default for GroupingAiCache {
@@ -468,13 +556,69 @@ pub struct MemoryEntry {
     pub id: uuid::Uuid,
     pub text: String,
     pub created_at: DateTime<Utc>,
+    /// Kept in `.cosmos/shared/` for the team; otherwise private to this clone
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub shared: bool,
 }
 
 impl RepoMemory {
+    /// Add an entry; `None` if it is blank or already remembered
+    pub fn add(&mut self, text: &str) -> Option<uuid::Uuid> {
+        let text = text.trim();
+        if text.is_empty() || self.entries.iter().any(|e| e.text.trim() == text) {
+            return None;
+        }
+        let id = uuid::Uuid::new_v4();
+        self.entries.push(MemoryEntry {
+            id,
+            text: text.to_string(),
+            created_at: Utc::now(),
+            shared: false,
+        });
+        Some(id)
+    }
+
+    /// Flip whether an entry is shared; `None` if it wasn't found
+    pub fn toggle_shared(&mut self, id: uuid::Uuid) -> Option<bool> {
+        let entry = self.entries.iter_mut().find(|e| e.id == id)?;
+        entry.shared = !entry.shared;
+        Some(entry.shared)
+    }
+
+    /// Replace an entry's text; returns whether it was found
+    pub fn update(&mut self, id: uuid::Uuid, text: &str) -> bool {
+        match self.entries.iter_mut().find(|e| e.id == id) {
+            Some(entry) => {
+                entry.text = text.trim().to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove an entry; returns whether it was found
+    pub fn remove(&mut self, id: uuid::Uuid) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.id != id);
+        self.entries.len() < before
+    }
+
+    /// Entries containing `query` (case-insensitive), newest first
+    pub fn search(&self, query: &str) -> Vec<&MemoryEntry> {
+        let query = query.trim().to_lowercase();
+        let mut found: Vec<&MemoryEntry> = self
+            .entries
+            .iter()
+            .filter(|e| query.is_empty() || e.text.to_lowercase().contains(&query))
+            .collect();
+        found.sort_by_key(|e| std::cmp::Reverse(e.created_at));
+        found
+    }
+
     /// Render a concise memory context for LLM prompts.
     pub fn to_prompt_context(&self, max_entries: usize, max_chars: usize) -> String {
         let mut entries = self.entries.clone();
-        entries.sort_by(|a, b| b.created_at.cmp(&a.created_at)); // newest first
+        entries.sort_by_key(|e| std::cmp::Reverse(e.created_at)); // newest first
 
         let mut out = String::new();
         for e in entries.into_iter().take(max_entries) {
@@ -566,7 +710,7 @@ impl DomainGlossary {
 
         // Sort by number of files (most used terms first)
         let mut sorted: Vec<_> = self.terms.iter().collect();
-        sorted.sort_by(|a, b| b.1.files.len().cmp(&a.1.files.len()));
+        sorted.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.files.len()));
 
         for (name, entry) in sorted.into_iter().take(max_terms) {
             lines.push(format!("- {}: {}", name, entry.definition));
@@ -591,6 +735,16 @@ impl DomainGlossary {
     pub fn len(&self) -> usize {
         self.terms.len()
     }
+
+    /// Forget files that are gone, and terms only they used; returns terms removed
+    pub fn prune(&mut self, is_live: impl Fn(&Path) -> bool) -> usize {
+        let before = self.terms.len();
+        for entry in self.terms.values_mut() {
+            entry.files.retain(|f| is_live(f));
+        }
+        self.terms.retain(|_, entry| !entry.files.is_empty());
+        before - self.terms.len()
+    }
 }
 
 impl Default for DomainGlossary {
@@ -687,117 +841,287 @@ impl Cache {
         Ok(CacheLock { file })
     }
 
-    /// Save full index cache (CodebaseIndex)
-    pub fn save_index_cache(&self, index: &CodebaseIndex) -> anyhow::Result<()> {
-        let _lock = self.lock(true)?;
-        let path = self.cache_dir.join(INDEX_CACHE_FILE);
-        let content = serde_json::to_string_pretty(index)?;
-        write_atomic(&path, &content)?;
-        Ok(())
-    }
-
-    /// Load index cache if valid for current repo state
-    pub fn load_index_cache(&self, root: &Path) -> Option<CodebaseIndex> {
-        let path = self.cache_dir.join(INDEX_CACHE_FILE);
+    /// Read a versioned JSON file from `.cosmos/`, migrating older versions
+    /// and writing the upgrade back in place. `None` when the file is
+    /// missing, unreadable or from a newer Cosmos; unreadable user data is
+    /// copied to `<file>.<hash>.bak` first so it can't be lost by a later save.
+    fn load_json<T: DeserializeOwned>(&self, file: &str) -> Option<T> {
+        let schema = schema::schema_for(file)?;
+        let path = self.cache_dir.join(file);
         if !path.exists() {
             return None;
         }
+        let content = {
+            let _lock = self.lock(false).ok()?;
+            fs::read_to_string(&path).ok()?
+        };
 
-        let _lock = self.lock(false).ok()?;
-        let content = fs::read_to_string(&path).ok()?;
-
-        // Try to parse as full CodebaseIndex (current format)
-        if let Ok(index) = serde_json::from_str::<CodebaseIndex>(&content) {
-            if index.root != root {
+        let upgraded = serde_json::from_str(&content)
+            .map_err(anyhow::Error::from)
+            .and_then(|value| schema::upgrade(schema, value));
+        let value = match upgraded {
+            Ok(schema::Loaded::Current(value)) => value,
+            Ok(schema::Loaded::Upgraded { value, from }) => {
+                if let Ok(_lock) = self.lock(true) {
+                    if schema.user_data {
+                        let backup = path.with_extension(format!("json.v{}.bak", from));
+                        let _ = fs::write(backup, &content);
+                    }
+                    if let Ok(upgraded) =
+                        serde_json::to_string_pretty(&schema::with_header(schema, value.clone()))
+                    {
+                        let _ = write_atomic(&path, upgraded);
+                    }
+                }
+                value
+            }
+            Ok(schema::Loaded::Newer) => return None,
+            Err(_) => {
+                self.preserve_unreadable(schema, &path);
                 return None;
             }
-            if is_index_cache_valid(root, &index) {
-                return Some(index);
+        };
+
+        match serde_json::from_value(value) {
+            Ok(loaded) => Some(loaded),
+            Err(_) => {
+                self.preserve_unreadable(schema, &path);
+                None
+            }
+        }
+    }
+
+    /// Keep a copy of user data that failed to load. Backups are named by
+    /// content, so every distinct broken version is kept exactly once.
+    fn preserve_unreadable(&self, schema: &schema::Schema, path: &Path) {
+        if !schema.user_data {
+            return;
+        }
+        let Ok(content) = fs::read(path) else {
+            return;
+        };
+        let backup = path.with_extension(format!("json.{}.bak", crate::util::hash_bytes(&content)));
+        if !backup.exists() {
+            let _ = fs::write(backup, content);
+        }
+    }
+
+    /// Write a JSON file to `.cosmos/` with the current schema version.
+    /// Refuses to overwrite user data written by a newer Cosmos.
+    fn save_json<T: Serialize>(&self, file: &str, value: &T) -> anyhow::Result<()> {
+        let schema = schema::schema_for(file)
+            .ok_or_else(|| anyhow::anyhow!("No schema registered for {}", file))?;
+        let _lock = self.lock(true)?;
+        let path = self.cache_dir.join(file);
+        if schema.user_data {
+            let existing = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok());
+            if let Some(version) = existing
+                .as_ref()
+                .map(schema::version_of)
+                .filter(|v| *v > schema.version)
+            {
+                return Err(anyhow::anyhow!(
+                    "{} was written by a newer Cosmos (schema version {}); not overwriting it",
+                    file,
+                    version
+                ));
             }
-            return None;
         }
+        let content = serde_json::to_string_pretty(&schema::with_header(
+            schema,
+            serde_json::to_value(value)?,
+        ))?;
+        write_atomic(&path, &content)?;
+        Ok(())
+    }
 
-        // Legacy format (IndexCache metadata only) - treat as miss
-        let _legacy: IndexCache = serde_json::from_str(&content).ok()?;
-        None
+    /// Save the index cache (sharded; only changed shards are rewritten)
+    pub fn save_index_cache(&self, index: &CodebaseIndex) -> anyhow::Result<()> {
+        let _lock = self.lock(true)?;
+        index_store::save(&self.cache_dir.join(index_store::INDEX_DIR), index)?;
+        let legacy = self.cache_dir.join(LEGACY_INDEX_CACHE_FILE);
+        if legacy.exists() {
+            let _ = fs::remove_file(legacy);
+        }
+        Ok(())
     }
 
-    /// Load LLM-generated summaries cache
-    pub fn load_llm_summaries_cache(&self) -> Option<LlmSummaryCache> {
-        let path = self.cache_dir.join(LLM_SUMMARIES_CACHE_FILE);
-        if !path.exists() {
+    /// Load the index cache, re-indexing files changed since it was saved
+    pub fn load_index_cache(&self, root: &Path) -> Option<CachedIndex> {
+        let index_dir = self.cache_dir.join(index_store::INDEX_DIR);
+        if !index_dir.exists() {
             return None;
         }
 
+        let current_hashes = compute_current_hashes(root).ok()?;
         let _lock = self.lock(false).ok()?;
-        let content = fs::read_to_string(&path).ok()?;
-        serde_json::from_str(&content).ok()
+        index_store::load(&index_dir, root, &current_hashes)
+    }
+
+    /// Load LLM-generated summaries cache
+    pub fn load_llm_summaries_cache(&self) -> Option<LlmSummaryCache> {
+        self.load_json(LLM_SUMMARIES_CACHE_FILE)
     }
 
     /// Save LLM-generated summaries cache
     pub fn save_llm_summaries_cache(&self, cache: &LlmSummaryCache) -> anyhow::Result<()> {
-        let _lock = self.lock(true)?;
-        let path = self.cache_dir.join(LLM_SUMMARIES_CACHE_FILE);
-        let content = serde_json::to_string_pretty(cache)?;
-        write_atomic(&path, &content)?;
-        Ok(())
+        self.save_json(LLM_SUMMARIES_CACHE_FILE, cache)
     }
 
     /// Load grouping AI cache
     pub fn load_grouping_ai_cache(&self) -> Option<GroupingAiCache> {
-        let path = self.cache_dir.join(GROUPING_AI_CACHE_FILE);
-        if !path.exists() {
-            return None;
-        }
-        let _lock = self.lock(false).ok()?;
-        let content = fs::read_to_string(&path).ok()?;
-        serde_json::from_str(&content).ok()
+        self.load_json(GROUPING_AI_CACHE_FILE)
     }
 
     /// Save grouping AI cache
     pub fn save_grouping_ai_cache(&self, cache: &GroupingAiCache) -> anyhow::Result<()> {
-        let _lock = self.lock(true)?;
-        let path = self.cache_dir.join(GROUPING_AI_CACHE_FILE);
-        let content = serde_json::to_string_pretty(cache)?;
-        write_atomic(&path, &content)?;
-        Ok(())
+        self.save_json(GROUPING_AI_CACHE_FILE, cache)
     }
 
-    /// Load repo memory (decisions/conventions) from `.cosmos/memory.json`
+    /// Load repo memory (decisions/conventions): `.cosmos/memory.json` plus
+    /// `.cosmos/shared/memory.jsonl` when sharing is on
     pub fn load_repo_memory(&self) -> RepoMemory {
-        let path = self.cache_dir.join(MEMORY_FILE);
-        if !path.exists() {
-            return RepoMemory::default();
+        let mut memory: RepoMemory = self.load_json(MEMORY_FILE).unwrap_or_default();
+        if let Some(shared) = self.read_shared(shared::read_memory) {
+            memory
+                .entries
+                .retain(|e| !shared.iter().any(|s| s.id == e.id));
+            memory.entries.extend(shared);
         }
-        let _lock = match self.lock(false) {
-            Ok(lock) => lock,
-            Err(_) => return RepoMemory::default(),
-        };
-        fs::read_to_string(&path)
-            .ok()
-            .and_then(|content| serde_json::from_str(&content).ok())
-            .unwrap_or_default()
+        memory
+    }
+
+    /// Save repo memory. With sharing on, shared entries go to the shared
+    /// file and the rest stay in the local one.
+    pub fn save_repo_memory(&self, memory: &RepoMemory) -> anyhow::Result<()> {
+        if !self.is_sharing() {
+            return self.save_json(MEMORY_FILE, memory);
+        }
+        let (shared, local): (Vec<_>, Vec<_>) =
+            memory.entries.iter().cloned().partition(|e| e.shared);
+        {
+            let _lock = self.lock(true)?;
+            shared::write_memory(&self.shared_dir(), &shared)?;
+        }
+        if local.is_empty() {
+            let _lock = self.lock(true)?;
+            self.remove_entry(MEMORY_FILE)?;
+            return Ok(());
+        }
+        self.save_json(MEMORY_FILE, &RepoMemory { entries: local })
     }
 
-    /// Load domain glossary from `.cosmos/glossary.json`
+    /// Load domain glossary: `.cosmos/glossary.json` plus
+    /// `.cosmos/shared/glossary.jsonl` when sharing is on (shared
+    /// definitions win, file lists are combined)
     pub fn load_glossary(&self) -> Option<DomainGlossary> {
-        let path = self.cache_dir.join(GLOSSARY_FILE);
-        if !path.exists() {
+        let local: Option<DomainGlossary> = self.load_json(GLOSSARY_FILE);
+        match (self.read_shared(shared::read_glossary), local) {
+            (Some(mut shared), Some(local)) => {
+                shared.merge(&local);
+                Some(shared)
+            }
+            (shared, local) => shared.or(local),
+        }
+    }
+
+    /// Save domain glossary; with sharing on it goes to the shared file only
+    pub fn save_glossary(&self, glossary: &DomainGlossary) -> anyhow::Result<()> {
+        if self.is_sharing() {
+            let _lock = self.lock(true)?;
+            return shared::write_glossary(&self.shared_dir(), glossary);
+        }
+        self.save_json(GLOSSARY_FILE, glossary)
+    }
+
+    fn shared_dir(&self) -> PathBuf {
+        self.cache_dir.join(shared::SHARED_DIR)
+    }
+
+    /// Whether this repo keeps memory and glossary in `.cosmos/shared/`
+    pub fn is_sharing(&self) -> bool {
+        self.shared_dir().is_dir()
+    }
+
+    fn read_shared<T>(&self, read: fn(&Path) -> Option<T>) -> Option<T> {
+        if !self.is_sharing() {
             return None;
         }
         let _lock = self.lock(false).ok()?;
-        fs::read_to_string(&path)
-            .ok()
-            .and_then(|content| serde_json::from_str(&content).ok())
+        read(&self.shared_dir())
     }
 
-    /// Save domain glossary to `.cosmos/glossary.json`
-    pub fn save_glossary(&self, glossary: &DomainGlossary) -> anyhow::Result<()> {
+    /// Opt in to team sharing: move memory and glossary into
+    /// `.cosmos/shared/` and stop ignoring that directory in `.gitignore`.
+    /// Returns the shared directory and whether `.gitignore` was changed.
+    pub fn enable_sharing(&self) -> anyhow::Result<(PathBuf, bool)> {
+        let mut memory = self.load_repo_memory();
+        for entry in &mut memory.entries {
+            entry.shared = true;
+        }
+        let glossary = self.load_glossary();
+        {
+            let _lock = self.lock(true)?;
+            fs::create_dir_all(self.shared_dir())?;
+        }
+        self.save_repo_memory(&memory)?;
+        if let Some(glossary) = &glossary {
+            self.save_glossary(glossary)?;
+        }
+
+        // Local copies would resurrect entries teammates delete
+        for file in [MEMORY_FILE, GLOSSARY_FILE] {
+            let _ = self.remove_entry(file);
+        }
+        let gitignore_updated = match self.cache_dir.parent() {
+            Some(root) => shared::unignore_shared(root)?,
+            None => false,
+        };
+        Ok((self.shared_dir(), gitignore_updated))
+    }
+
+    /// Every recorded LLM call, oldest first (see `ledger`)
+    pub fn load_ledger(&self) -> Vec<LedgerEntry> {
+        let path = self.cache_dir.join(ledger::LEDGER_FILE);
+        let _lock = self.lock(false).ok();
+        ledger::read(&path).unwrap_or_default()
+    }
+
+    /// Record one LLM call in `.cosmos/ledger.jsonl`
+    pub fn append_ledger(&self, entry: &LedgerEntry) -> anyhow::Result<()> {
+        let _lock = self.lock(true)?;
+        ledger::append(&self.cache_dir.join(ledger::LEDGER_FILE), entry)
+    }
+
+    /// Audit log of the session started at `started`
+    pub fn audit_log_path(&self, started: DateTime<Utc>) -> PathBuf {
+        audit::session_path(&self.cache_dir, started)
+    }
+
+    /// Record one tool call in the session's `.cosmos/audit/` log
+    pub fn append_audit(&self, started: DateTime<Utc>, entry: &AuditEntry) -> anyhow::Result<()> {
+        audit::append(&audit::session_path(&self.cache_dir, started), entry)
+    }
+
+    /// Drop the audit logs of old sessions; returns how many were removed
+    pub fn prune_audit_logs(&self) -> anyhow::Result<usize> {
+        audit::prune(&self.cache_dir)
+    }
+
+    /// Load per-project settings from `.cosmos/config.json`
+    pub fn load_project_config(&self) -> ProjectConfig {
+        self.load_json(PROJECT_CONFIG_FILE).unwrap_or_default()
+    }
+
+    /// Export the license report to `.cosmos/license_report.json`
+    pub fn save_license_report(&self, report: &LicenseReport) -> anyhow::Result<PathBuf> {
         let _lock = self.lock(true)?;
-        let path = self.cache_dir.join(GLOSSARY_FILE);
-        let content = serde_json::to_string_pretty(glossary)?;
+        let path = self.cache_dir.join(LICENSE_REPORT_FILE);
+        let content = serde_json::to_string_pretty(report)?;
         write_atomic(&path, &content)?;
-        Ok(())
+        Ok(path)
     }
 
     /// Check if user has seen the welcome overlay
@@ -815,24 +1139,32 @@ impl Cache {
 
     /// Load question answer cache
     pub fn load_question_cache(&self) -> Option<QuestionCache> {
-        let path = self.cache_dir.join(QUESTION_CACHE_FILE);
-        if !path.exists() {
-            return None;
-        }
-        let _lock = self.lock(false).ok()?;
-        let content = fs::read_to_string(&path).ok()?;
-        let mut cache: QuestionCache = serde_json::from_str(&content).ok()?;
+        let mut cache: QuestionCache = self.load_json(QUESTION_CACHE_FILE)?;
         cache.cleanup();
         Some(cache)
     }
 
     /// Save question answer cache
     pub fn save_question_cache(&self, cache: &QuestionCache) -> anyhow::Result<()> {
-        let _lock = self.lock(true)?;
-        let path = self.cache_dir.join(QUESTION_CACHE_FILE);
-        let content = serde_json::to_string_pretty(cache)?;
-        write_atomic(&path, &content)?;
-        Ok(())
+        self.save_json(QUESTION_CACHE_FILE, cache)
+    }
+
+    /// Past Ask Cosmos conversations, most recently active first
+    pub fn load_ask_threads(&self) -> Vec<AskThread> {
+        self.load_json::<AskThreads>(ASK_THREADS_FILE)
+            .map(|t| t.threads)
+            .unwrap_or_default()
+    }
+
+    /// Store a conversation, replacing its earlier copy and dropping the
+    /// least recently active past `MAX_ASK_THREADS`
+    pub fn save_ask_thread(&self, thread: &AskThread) -> anyhow::Result<()> {
+        let mut threads = self.load_ask_threads();
+        threads.retain(|t| t.id != thread.id);
+        threads.push(thread.clone());
+        threads.sort_by_key(|t| std::cmp::Reverse(t.updated_at()));
+        threads.truncate(MAX_ASK_THREADS);
+        self.save_json(ASK_THREADS_FILE, &AskThreads { threads })
     }
 
     /// Clear selected cache files only
@@ -841,19 +1173,8 @@ impl Cache {
         let mut cleared = Vec::new();
 
         for option in options {
-            let files_to_remove: Vec<&str> = match option {
-                ResetOption::Index => vec![INDEX_CACHE_FILE],
-                ResetOption::Suggestions => vec![SUGGESTIONS_CACHE_FILE],
-                ResetOption::Summaries => vec![LLM_SUMMARIES_CACHE_FILE],
-                ResetOption::Glossary => vec![GLOSSARY_FILE],
-                ResetOption::Memory => vec![MEMORY_FILE],
-                ResetOption::GroupingAi => vec![GROUPING_AI_CACHE_FILE],
-            };
-
-            for file in files_to_remove {
-                let path = self.cache_dir.join(file);
-                if path.exists() {
-                    fs::remove_file(&path)?;
+            for file in option.files() {
+                if self.remove_entry(file)? {
                     cleared.push(file.to_string());
                 }
             }
@@ -861,6 +1182,139 @@ impl Cache {
 
         Ok(cleared)
     }
+
+    /// Remove a file or directory under `.cosmos/`; `false` if it wasn't there
+    fn remove_entry(&self, name: &str) -> anyhow::Result<bool> {
+        let path = self.cache_dir.join(name);
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+        } else if path.exists() {
+            fs::remove_file(&path)?;
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Bytes on disk for what a reset option would clear
+    pub fn reset_option_size(&self, option: ResetOption) -> u64 {
+        option
+            .files()
+            .iter()
+            .map(|file| disk_size(&self.cache_dir.join(file)))
+            .sum()
+    }
+
+    /// Bytes on disk for all of `.cosmos/`
+    pub fn total_size(&self) -> u64 {
+        disk_size(&self.cache_dir)
+    }
+
+    /// Bytes on disk for the regenerable caches, which is what the budget
+    /// covers; user data can't be evicted, so it isn't counted.
+    pub fn cache_size(&self) -> u64 {
+        EVICTION_ORDER
+            .iter()
+            .map(|name| disk_size(&self.cache_dir.join(name)))
+            .sum()
+    }
+
+    /// Prune cache entries for files no longer in the index and entries past
+    /// their TTL, then evict whole regenerable caches (see `EVICTION_ORDER`)
+    /// while they're over `budget_bytes`. User data is never evicted.
+    pub fn collect_garbage(
+        &self,
+        index: &CodebaseIndex,
+        budget_bytes: u64,
+    ) -> anyhow::Result<GcReport> {
+        let before = self.total_size();
+        let is_live = |path: &Path| index.files.contains_key(path);
+        let mut report = GcReport::default();
+
+        if let Some(mut cache) = self.load_llm_summaries_cache() {
+            let normalized = cache.normalize_paths(&index.root);
+            let pruned = cache.prune(is_live);
+            if pruned > 0 || normalized {
+                self.save_llm_summaries_cache(&cache)?;
+            }
+            report.pruned_entries += pruned;
+        }
+        if let Some(mut cache) = self.load_grouping_ai_cache() {
+            let normalized = cache.normalize_paths(&index.root);
+            let pruned = cache.prune(is_live);
+            if pruned > 0 || normalized {
+                self.save_grouping_ai_cache(&cache)?;
+            }
+            report.pruned_entries += pruned;
+        }
+        if let Some(mut cache) = self.load_json::<QuestionCache>(QUESTION_CACHE_FILE) {
+            let count = cache.entries.len();
+            cache.cleanup();
+            if cache.entries.len() < count {
+                self.save_question_cache(&cache)?;
+            }
+            report.pruned_entries += count - cache.entries.len();
+        }
+        if let Some(mut glossary) = self.load_glossary() {
+            let pruned = glossary.prune(is_live);
+            if pruned > 0 {
+                self.save_glossary(&glossary)?;
+            }
+            report.pruned_entries += pruned;
+        }
+
+        for name in EVICTION_ORDER {
+            if self.cache_size() <= budget_bytes {
+                break;
+            }
+            let _lock = self.lock(true)?;
+            if self.remove_entry(name)? {
+                report.evicted.push(name.to_string());
+            }
+        }
+
+        report.freed_bytes = before.saturating_sub(self.total_size());
+        Ok(report)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  GARBAGE COLLECTION
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Regenerable caches, cheapest to rebuild first, evicted whole when they're
+/// over budget. Memory, config, backups, the ledger, Ask threads and audit
+/// logs are never listed (nor counted against the budget).
+const EVICTION_ORDER: &[&str] = &[
+    QUESTION_CACHE_FILE,
+    SUGGESTIONS_CACHE_FILE,
+    LICENSE_REPORT_FILE,
+    GROUPING_AI_CACHE_FILE,
+    GLOSSARY_FILE,
+    LLM_SUMMARIES_CACHE_FILE,
+    index_store::INDEX_DIR,
+    LEGACY_INDEX_CACHE_FILE,
+];
+
+/// What a garbage collection pass removed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GcReport {
+    /// Entries dropped for deleted files or past their TTL
+    pub pruned_entries: usize,
+    /// Whole caches evicted to get under the budget
+    pub evicted: Vec<String>,
+    pub freed_bytes: u64,
+}
+
+/// Size of a file, or of everything under a directory
+fn disk_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
 }
 
 /// Reset selected Cosmos cache files for the given repository.
@@ -872,20 +1326,11 @@ pub async fn reset_cosmos(
     cache.clear_selective(options)
 }
 
-fn is_index_cache_valid(root: &Path, index: &CodebaseIndex) -> bool {
-    let cached_hashes = compute_file_hashes(index);
-    let current_hashes = match compute_current_hashes(root) {
-        Ok(map) => map,
-        Err(_) => return false,
-    };
-    cached_hashes == current_hashes
-}
-
 fn compute_current_hashes(root: &Path) -> anyhow::Result<HashMap<PathBuf, String>> {
     let mut hashes = HashMap::new();
     for entry in walkdir::WalkDir::new(root)
         .into_iter()
-        .filter_entry(|e| !is_ignored_path(e.path()))
+        .filter_entry(|e| !crate::index::is_ignored(e.path()))
         .filter_map(|e| e.ok())
     {
         let path = entry.path();
@@ -893,8 +1338,7 @@ fn compute_current_hashes(root: &Path) -> anyhow::Result<HashMap<PathBuf, String
             continue;
         }
 
-        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-        let language = crate::index::Language::from_extension(ext);
+        let language = crate::index::Language::from_path(path);
         if language == crate::index::Language::Unknown {
             continue;
         }
@@ -926,27 +1370,6 @@ fn compute_current_hashes(root: &Path) -> anyhow::Result<HashMap<PathBuf, String
     Ok(hashes)
 }
 
-fn is_ignored_path(path: &Path) -> bool {
-    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-    let ignored = [
-        "target",
-        "node_modules",
-        ".git",
-        ".svn",
-        ".hg",
-        "dist",
-        "build",
-        "__pycache__",
-        ".pytest_cache",
-        "vendor",
-        ".idea",
-        ".vscode",
-        ".cosmos",
-    ];
-
-    ignored.contains(&name) || name.starts_with('.')
-}
-
 /// Write content atomically by writing to a temp file first, then renaming.
 ///
 /// # Platform Notes
@@ -956,7 +1379,7 @@ fn is_ignored_path(path: &Path) -> bool {
 ///   the backup rename and the final rename, the file may be left in an inconsistent
 ///   state. The backup file (.bak) can be used for recovery. For cache files, this
 ///   trade-off is acceptable as the cache can be regenerated.
-fn write_atomic(path: &Path, content: &str) -> anyhow::Result<()> {
+fn write_atomic(path: &Path, content: impl AsRef<[u8]>) -> anyhow::Result<()> {
     let tmp_path = path.with_extension("tmp");
     fs::write(&tmp_path, content)?;
 
@@ -1021,12 +1444,330 @@ mod tests {
         let cache = Cache::new(&root);
         cache.save_index_cache(&index).unwrap();
 
-        let loaded = cache.load_index_cache(&root);
-        assert!(loaded.is_some());
+        let loaded = cache.load_index_cache(&root).unwrap();
+        assert_eq!(loaded.reindexed, 0);
+        assert_eq!(loaded.index.files.len(), 1);
 
         fs::write(&file_path, "pub fn hello() { println!(\"hi\"); }").unwrap();
-        let invalidated = cache.load_index_cache(&root);
-        assert!(invalidated.is_none());
+        let refreshed = cache.load_index_cache(&root).unwrap();
+        assert_eq!(refreshed.reindexed, 1);
+        assert_eq!(
+            refreshed.index.files[Path::new("src/lib.rs")].content_hash,
+            crate::util::hash_str("pub fn hello() { println!(\"hi\"); }")
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_collect_garbage_prunes_and_enforces_budget() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("cosmos_cache_gc_test_{}", nanos));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "pub fn hello() {}").unwrap();
+        let index = CodebaseIndex::new(&root).unwrap();
+        let cache = Cache::new(&root);
+
+        let entry = |age_days: i64| LlmSummaryEntry {
+            summary: "summary".to_string(),
+            file_hash: "hash".to_string(),
+            generated_at: Utc::now() - Duration::days(age_days),
+        };
+        let mut summaries = LlmSummaryCache::new();
+        summaries
+            .summaries
+            .insert(PathBuf::from("src/lib.rs"), entry(0));
+        summaries
+            .summaries
+            .insert(PathBuf::from("src/deleted.rs"), entry(0));
+        summaries.summaries.insert(
+            PathBuf::from("src/lib.rs.old"),
+            entry(LLM_SUMMARY_CACHE_DAYS + 1),
+        );
+        cache.save_llm_summaries_cache(&summaries).unwrap();
+
+        let mut glossary = DomainGlossary::new();
+        glossary.terms.insert(
+            "Gone".to_string(),
+            GlossaryEntry {
+                definition: "only in a deleted file".to_string(),
+                files: vec![PathBuf::from("src/deleted.rs")],
+            },
+        );
+        cache.save_glossary(&glossary).unwrap();
+        cache
+            .save_json(
+                MEMORY_FILE,
+                &RepoMemory {
+                    entries: vec![MemoryEntry {
+                        id: uuid::Uuid::new_v4(),
+                        text: "keep me".to_string(),
+                        created_at: Utc::now(),
+                        shared: false,
+                    }],
+                },
+            )
+            .unwrap();
+
+        let report = cache.collect_garbage(&index, u64::MAX).unwrap();
+        assert_eq!(report.pruned_entries, 3);
+        assert!(report.evicted.is_empty());
+        let summaries = cache.load_llm_summaries_cache().unwrap();
+        assert_eq!(
+            summaries.summaries.keys().collect::<Vec<_>>(),
+            vec![Path::new("src/lib.rs")]
+        );
+        assert!(cache.load_glossary().unwrap().terms.is_empty());
+
+        // Over budget: regenerable caches go, memory stays
+        let report = cache.collect_garbage(&index, 0).unwrap();
+        assert!(report
+            .evicted
+            .contains(&LLM_SUMMARIES_CACHE_FILE.to_string()));
+        assert!(report.freed_bytes > 0);
+        assert!(cache.load_llm_summaries_cache().is_none());
+        assert_eq!(cache.load_repo_memory().entries.len(), 1);
+        assert_eq!(cache.reset_option_size(ResetOption::Summaries), 0);
+        assert!(cache.reset_option_size(ResetOption::Memory) > 0);
+
+        // Backups of user data don't count toward the budget
+        cache
+            .save_question_cache(&QuestionCache::default())
+            .unwrap();
+        fs::write(
+            cache.cache_dir.join("memory.json.v0.bak"),
+            "x".repeat(64 * 1024),
+        )
+        .unwrap();
+        let budget = cache.cache_size();
+        assert!(cache.total_size() > budget + 64 * 1024);
+        let report = cache.collect_garbage(&index, budget).unwrap();
+        assert!(report.evicted.is_empty());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_enable_sharing_moves_knowledge_and_merges_local() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("cosmos_sharing_test_{}", nanos));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n.cosmos/\n").unwrap();
+        let cache = Cache::new(&root);
+
+        let entry = |text: &str| MemoryEntry {
+            id: uuid::Uuid::new_v4(),
+            text: text.to_string(),
+            created_at: Utc::now(),
+            shared: false,
+        };
+        let team = entry("Migrations live in db/");
+        cache
+            .save_repo_memory(&RepoMemory {
+                entries: vec![team.clone()],
+            })
+            .unwrap();
+        let mut glossary = DomainGlossary::new();
+        glossary.add_term("Ledger".into(), "team".into(), PathBuf::from("src/a.rs"));
+        cache.save_glossary(&glossary).unwrap();
+
+        cache.enable_sharing().unwrap();
+        assert!(cache.is_sharing());
+        assert!(!cache.cache_dir.join(MEMORY_FILE).exists());
+        let team = MemoryEntry {
+            shared: true,
+            ..team
+        };
+        assert_eq!(
+            fs::read_to_string(root.join(".gitignore")).unwrap(),
+            "target/\n.cosmos/*\n!.cosmos/shared/\n"
+        );
+
+        // A teammate's older local-only entries merge with what was pulled
+        let mine = entry("I run tests with --release");
+        cache
+            .save_json(
+                MEMORY_FILE,
+                &RepoMemory {
+                    entries: vec![mine.clone(), team.clone()],
+                },
+            )
+            .unwrap();
+        let mut local = DomainGlossary::new();
+        local.add_term("Ledger".into(), "local".into(), PathBuf::from("src/b.rs"));
+        cache.save_json(GLOSSARY_FILE, &local).unwrap();
+
+        let memory = cache.load_repo_memory();
+        assert_eq!(memory.entries.len(), 2);
+        assert!(memory.entries.contains(&mine) && memory.entries.contains(&team));
+
+        // Saving writes back only the shared entry; the private one stays local
+        cache.save_repo_memory(&memory).unwrap();
+        assert_eq!(
+            shared::read_memory(&cache.shared_dir()).unwrap(),
+            vec![team.clone()]
+        );
+        let local_memory: RepoMemory = cache.load_json(MEMORY_FILE).unwrap();
+        assert_eq!(local_memory.entries, vec![mine.clone()]);
+        let merged = cache.load_glossary().unwrap();
+        assert_eq!(merged.terms["Ledger"].definition, "team");
+        assert_eq!(merged.terms["Ledger"].files.len(), 2);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_repo_memory_edits_and_search() {
+        let mut memory = RepoMemory::default();
+        let first = memory
+            .add("  Use anyhow for errors in the binary ")
+            .unwrap();
+        assert!(memory.add("Use anyhow for errors in the binary").is_none());
+        assert!(memory.add("   ").is_none());
+        let second = memory.add("Tests live next to the code").unwrap();
+        memory.entries[1].created_at = memory.entries[0].created_at + Duration::seconds(1);
+
+        let ids: Vec<_> = memory.search("").iter().map(|e| e.id).collect();
+        assert_eq!(ids, vec![second, first]);
+        assert_eq!(memory.search("ANYHOW").len(), 1);
+
+        assert!(memory.update(first, "Use thiserror in libraries"));
+        assert_eq!(memory.search("thiserror")[0].id, first);
+        assert!(memory.remove(second));
+        assert!(!memory.remove(second));
+        assert_eq!(memory.entries.len(), 1);
+    }
+
+    #[test]
+    fn test_ask_threads_are_upserted_most_recent_first() {
+        let root = tempfile::tempdir().unwrap();
+        let cache = Cache::new(root.path());
+        let turn = |question: &str, minutes: i64| AskTurn {
+            question: question.to_string(),
+            answer: "answer".to_string(),
+            asked_at: Utc::now() + Duration::minutes(minutes),
+        };
+
+        let mut first = AskThread::default();
+        first.turns.push(turn("What does this do?", 0));
+        cache.save_ask_thread(&first).unwrap();
+        let mut second = AskThread::default();
+        second.turns.push(turn("Where are the tests?", 1));
+        cache.save_ask_thread(&second).unwrap();
+        assert_eq!(cache.load_ask_threads()[0].id, second.id);
+
+        // A follow-up replaces the stored copy and moves it to the top
+        first.turns.push(turn("And why?", 2));
+        cache.save_ask_thread(&first).unwrap();
+        let threads = cache.load_ask_threads();
+        assert_eq!(threads.len(), 2);
+        assert_eq!(threads[0], first);
+        assert_eq!(threads[0].title(), "What does this do?");
+    }
+
+    #[test]
+    fn test_legacy_memory_is_migrated_in_place() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("cosmos_schema_test_{}", nanos));
+        let cache_dir = root.join(CACHE_DIR);
+        fs::create_dir_all(&cache_dir).unwrap();
+        let legacy = r#"["Prefer anyhow for errors", {"text": "No unwrap in src/"}]"#;
+        fs::write(cache_dir.join(MEMORY_FILE), legacy).unwrap();
+        fs::write(
+            cache_dir.join(GLOSSARY_FILE),
+            r#"{"terms": {}, "generated_at": "2025-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+
+        let cache = Cache::new(&root);
+        let memory = cache.load_repo_memory();
+        let texts: Vec<&str> = memory.entries.iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(texts, vec!["Prefer anyhow for errors", "No unwrap in src/"]);
+
+        let rewritten: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(cache_dir.join(MEMORY_FILE)).unwrap())
+                .unwrap();
+        assert_eq!(rewritten["schema_version"], 1);
+        assert_eq!(
+            fs::read_to_string(cache_dir.join("memory.json.v0.bak")).unwrap(),
+            legacy
+        );
+        // Ids assigned during the migration are stable on the next load
+        assert_eq!(cache.load_repo_memory(), memory);
+
+        assert!(cache.load_glossary().is_some());
+        let glossary = fs::read_to_string(cache_dir.join(GLOSSARY_FILE)).unwrap();
+        assert!(glossary.contains("\"schema_version\": 1"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_newer_user_data_is_left_alone() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("cosmos_schema_newer_test_{}", nanos));
+        let cache_dir = root.join(CACHE_DIR);
+        fs::create_dir_all(&cache_dir).unwrap();
+        let newer = r#"{"schema_version": 99, "notes": []}"#;
+        fs::write(cache_dir.join(MEMORY_FILE), newer).unwrap();
+
+        let cache = Cache::new(&root);
+        assert!(cache.load_repo_memory().entries.is_empty());
+        assert!(cache
+            .save_json(MEMORY_FILE, &RepoMemory::default())
+            .is_err());
+        assert_eq!(
+            fs::read_to_string(cache_dir.join(MEMORY_FILE)).unwrap(),
+            newer
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_every_broken_edit_of_user_data_is_backed_up() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("cosmos_schema_broken_test_{}", nanos));
+        let cache_dir = root.join(CACHE_DIR);
+        fs::create_dir_all(&cache_dir).unwrap();
+        let cache = Cache::new(&root);
+
+        let edits = [r#"{"entries": [{"text": "first"#, r#"{"entries": oops}"#];
+        for edit in edits {
+            fs::write(cache_dir.join(MEMORY_FILE), edit).unwrap();
+            assert!(cache.load_repo_memory().entries.is_empty());
+            // Loading the same broken file again adds no new copy
+            assert!(cache.load_repo_memory().entries.is_empty());
+            cache
+                .save_json(MEMORY_FILE, &RepoMemory::default())
+                .unwrap();
+        }
+
+        let mut backups: Vec<String> = fs::read_dir(&cache_dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".bak"))
+            .map(|e| fs::read_to_string(e.path()).unwrap())
+            .collect();
+        backups.sort();
+        let mut expected = edits.map(String::from).to_vec();
+        expected.sort();
+        assert_eq!(backups, expected);
 
         let _ = fs::remove_dir_all(&root);
     }
@@ -0,0 +1,188 @@
+//! Schema versions and migrations for the JSON files in `.cosmos/`
+//!
+//! Every file carries a top-level `"schema_version"`; files written before
+//! versioning count as version 0. On load, registered migrations step a
+//! file up one version at a time and the result is written back in place.
+//! (The binary index cache has its own format version and is rebuilt
+//! rather than migrated - see `index_store`.)
+
+use chrono::Utc;
+use serde_json::{json, Map, Value};
+
+pub(super) const SCHEMA_KEY: &str = "schema_version";
+
+/// Current schema of one JSON file
+pub(super) struct Schema {
+    pub file: &'static str,
+    pub version: u32,
+    /// Written by people rather than regenerated: backed up before a
+    /// migration rewrites it, and never overwritten by an older Cosmos
+    pub user_data: bool,
+}
+
+pub(super) const SCHEMAS: &[Schema] = &[
+    Schema {
+        file: super::LLM_SUMMARIES_CACHE_FILE,
+        version: 1,
+        user_data: false,
+    },
+    Schema {
+        file: super::GROUPING_AI_CACHE_FILE,
+        version: 1,
+        user_data: false,
+    },
+    Schema {
+        file: super::GLOSSARY_FILE,
+        version: 1,
+        user_data: false,
+    },
+    Schema {
+        file: super::QUESTION_CACHE_FILE,
+        version: 1,
+        user_data: false,
+    },
+    Schema {
+        file: super::ASK_THREADS_FILE,
+        version: 1,
+        user_data: false,
+    },
+    Schema {
+        file: super::MEMORY_FILE,
+        version: 1,
+        user_data: true,
+    },
+    Schema {
+        file: super::PROJECT_CONFIG_FILE,
+        version: 1,
+        user_data: true,
+    },
+];
+
+/// Upgrades `file` from version `from` to `from + 1`
+struct Migration {
+    file: &'static str,
+    from: u32,
+    migrate: fn(Value) -> anyhow::Result<Value>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        file: super::LLM_SUMMARIES_CACHE_FILE,
+        from: 0,
+        migrate: unchanged,
+    },
+    Migration {
+        file: super::GROUPING_AI_CACHE_FILE,
+        from: 0,
+        migrate: unchanged,
+    },
+    Migration {
+        file: super::GLOSSARY_FILE,
+        from: 0,
+        migrate: unchanged,
+    },
+    Migration {
+        file: super::QUESTION_CACHE_FILE,
+        from: 0,
+        migrate: unchanged,
+    },
+    Migration {
+        file: super::MEMORY_FILE,
+        from: 0,
+        migrate: memory_v0_to_v1,
+    },
+    Migration {
+        file: super::PROJECT_CONFIG_FILE,
+        from: 0,
+        migrate: unchanged,
+    },
+];
+
+pub(super) fn schema_for(file: &str) -> Option<&'static Schema> {
+    SCHEMAS.iter().find(|s| s.file == file)
+}
+
+/// Schema version recorded in a file (0 before versioning)
+pub(super) fn version_of(value: &Value) -> u32 {
+    value.get(SCHEMA_KEY).and_then(Value::as_u64).unwrap_or(0) as u32
+}
+
+pub(super) enum Loaded {
+    /// Already at the current version (header removed)
+    Current(Value),
+    /// Migrated from an older version (header removed)
+    Upgraded { value: Value, from: u32 },
+    /// Written by a newer Cosmos; left alone
+    Newer,
+}
+
+/// Bring a file's contents up to the current schema
+pub(super) fn upgrade(schema: &Schema, mut value: Value) -> anyhow::Result<Loaded> {
+    let from = version_of(&value);
+    if from > schema.version {
+        return Ok(Loaded::Newer);
+    }
+    if let Value::Object(map) = &mut value {
+        map.remove(SCHEMA_KEY);
+    }
+    if from == schema.version {
+        return Ok(Loaded::Current(value));
+    }
+
+    for version in from..schema.version {
+        let step = MIGRATIONS
+            .iter()
+            .find(|m| m.file == schema.file && m.from == version)
+            .ok_or_else(|| {
+                anyhow::anyhow!("No migration for {} from version {}", schema.file, version)
+            })?;
+        value = (step.migrate)(value)?;
+    }
+    Ok(Loaded::Upgraded { value, from })
+}
+
+/// Stamp the current version onto serialized contents
+pub(super) fn with_header(schema: &Schema, mut value: Value) -> Value {
+    if let Value::Object(map) = &mut value {
+        map.insert(SCHEMA_KEY.to_string(), json!(schema.version));
+    }
+    value
+}
+
+/// Versioning only added the header
+fn unchanged(value: Value) -> anyhow::Result<Value> {
+    Ok(value)
+}
+
+/// Hand-edited memory files may be a bare list, use plain strings for
+/// entries, or leave out `id`/`created_at`. Fill those in instead of
+/// failing to load (which used to silently drop every entry).
+fn memory_v0_to_v1(value: Value) -> anyhow::Result<Value> {
+    let entries = match value {
+        Value::Array(entries) => entries,
+        Value::Object(mut map) => match map.remove("entries") {
+            Some(Value::Array(entries)) => entries,
+            None | Some(Value::Null) => Vec::new(),
+            Some(_) => return Err(anyhow::anyhow!("memory `entries` is not a list")),
+        },
+        _ => return Err(anyhow::anyhow!("memory file is not an object or list")),
+    };
+
+    let now = Utc::now().to_rfc3339();
+    let entries = entries
+        .into_iter()
+        .map(|entry| {
+            let mut entry = match entry {
+                Value::String(text) => Map::from_iter([("text".to_string(), json!(text))]),
+                Value::Object(map) if map.get("text").is_some_and(Value::is_string) => map,
+                other => return Err(anyhow::anyhow!("memory entry has no text: {}", other)),
+            };
+            entry
+                .entry("id")
+                .or_insert_with(|| json!(uuid::Uuid::new_v4()));
+            entry.entry("created_at").or_insert_with(|| json!(now));
+            Ok(Value::Object(entry))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    Ok(json!({ "entries": entries }))
+}
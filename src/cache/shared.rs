@@ -0,0 +1,216 @@
+//! Team-shared knowledge files under `.cosmos/shared/`
+//!
+//! Opt-in (`cosmos share`) and meant to be committed. Each file is JSON
+//! Lines: a `{"schema_version":N}` header, then one entry per line with
+//! sorted keys, ordered by a stable key (memory by creation time and id,
+//! glossary by term). Concurrent additions from two branches then land on
+//! different lines and merge cleanly in git.
+//!
+//! Local `memory.json`/`glossary.json` stay machine-local; loading merges
+//! them with the shared entries (shared wins on conflicts). Only memory
+//! entries marked `shared` are written here, so private ones stay local.
+
+use super::{write_atomic, DomainGlossary, GlossaryEntry, MemoryEntry};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub(super) const SHARED_DIR: &str = "shared";
+pub(super) const SHARED_MEMORY_FILE: &str = "memory.jsonl";
+pub(super) const SHARED_GLOSSARY_FILE: &str = "glossary.jsonl";
+
+/// Bump (with a conversion in `read_lines`) if an entry's shape changes
+const SHARED_SCHEMA_VERSION: u32 = 1;
+
+/// One glossary line; the term is part of the entry so lines stand alone
+#[derive(Debug, Serialize, Deserialize)]
+struct SharedTerm {
+    term: String,
+    definition: String,
+    files: Vec<PathBuf>,
+}
+
+/// Parse entries from a shared file. `None` if it is missing or was
+/// written by a newer Cosmos; malformed lines (e.g. leftover merge
+/// markers) are skipped rather than losing the whole file.
+fn read_lines<T: serde::de::DeserializeOwned>(path: &Path) -> Option<Vec<T>> {
+    let content = fs::read_to_string(path).ok()?;
+    let mut entries = Vec::new();
+    for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let Ok(value) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        if let Some(version) = value.get(super::schema::SCHEMA_KEY) {
+            if version.as_u64().unwrap_or(0) > SHARED_SCHEMA_VERSION as u64 {
+                return None;
+            }
+            continue;
+        }
+        if let Ok(entry) = serde_json::from_value(value) {
+            entries.push(entry);
+        }
+    }
+    Some(entries)
+}
+
+fn write_lines<T: Serialize>(path: &Path, entries: &[T]) -> anyhow::Result<()> {
+    // `Value` objects keep keys sorted, independent of struct field order
+    let mut out = json!({ super::schema::SCHEMA_KEY: SHARED_SCHEMA_VERSION }).to_string();
+    out.push('\n');
+    for entry in entries {
+        out.push_str(&serde_json::to_value(entry)?.to_string());
+        out.push('\n');
+    }
+    write_atomic(path, out)
+}
+
+pub(super) fn read_memory(dir: &Path) -> Option<Vec<MemoryEntry>> {
+    let mut entries: Vec<MemoryEntry> = read_lines(&dir.join(SHARED_MEMORY_FILE))?;
+    for entry in &mut entries {
+        entry.shared = true;
+    }
+    Some(entries)
+}
+
+/// Write the shared memory file; the `shared` flag is implied, not stored
+pub(super) fn write_memory(dir: &Path, entries: &[MemoryEntry]) -> anyhow::Result<()> {
+    let mut entries = entries.to_vec();
+    for entry in &mut entries {
+        entry.shared = false;
+    }
+    entries.sort_by_key(|e| (e.created_at, e.id));
+    entries.dedup_by_key(|e| e.id);
+    write_lines(&dir.join(SHARED_MEMORY_FILE), &entries)
+}
+
+pub(super) fn read_glossary(dir: &Path) -> Option<DomainGlossary> {
+    let terms: Vec<SharedTerm> = read_lines(&dir.join(SHARED_GLOSSARY_FILE))?;
+    let mut glossary = DomainGlossary::new();
+    for term in terms {
+        glossary.terms.insert(
+            term.term,
+            GlossaryEntry {
+                definition: term.definition,
+                files: term.files,
+            },
+        );
+    }
+    Some(glossary)
+}
+
+pub(super) fn write_glossary(dir: &Path, glossary: &DomainGlossary) -> anyhow::Result<()> {
+    let mut terms: Vec<SharedTerm> = glossary
+        .terms
+        .iter()
+        .map(|(term, entry)| {
+            let mut files = entry.files.clone();
+            files.sort();
+            files.dedup();
+            SharedTerm {
+                term: term.clone(),
+                definition: entry.definition.clone(),
+                files,
+            }
+        })
+        .collect();
+    terms.sort_by(|a, b| a.term.cmp(&b.term));
+    write_lines(&dir.join(SHARED_GLOSSARY_FILE), &terms)
+}
+
+/// Swap a blanket `.cosmos/` ignore for one that keeps `shared/` tracked.
+/// Returns whether `.gitignore` changed.
+pub(super) fn unignore_shared(repo_root: &Path) -> anyhow::Result<bool> {
+    let path = repo_root.join(".gitignore");
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Ok(false);
+    };
+    let shared_rule = format!("!{}/{}/", super::CACHE_DIR, SHARED_DIR);
+    if content.lines().any(|l| l.trim() == shared_rule) {
+        return Ok(false);
+    }
+
+    let blanket = [
+        format!("{}/", super::CACHE_DIR),
+        super::CACHE_DIR.to_string(),
+    ];
+    let mut changed = false;
+    let lines: Vec<String> = content
+        .lines()
+        .map(|line| {
+            if blanket.iter().any(|b| line.trim() == b) {
+                changed = true;
+                format!("{}/*\n{}", super::CACHE_DIR, shared_rule)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    if changed {
+        let mut updated = lines.join("\n");
+        updated.push('\n');
+        fs::write(&path, updated)?;
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn test_shared_files_are_sorted_one_entry_per_line() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("cosmos_shared_test_{}", nanos));
+        fs::create_dir_all(&dir).unwrap();
+
+        let now = Utc::now();
+        let newer = MemoryEntry {
+            id: uuid::Uuid::new_v4(),
+            text: "Prefer anyhow in binaries".to_string(),
+            created_at: now,
+            shared: true,
+        };
+        let older = MemoryEntry {
+            id: uuid::Uuid::new_v4(),
+            text: "No unwrap in library code".to_string(),
+            created_at: now - Duration::days(1),
+            shared: true,
+        };
+        write_memory(&dir, &[newer.clone(), older.clone(), newer.clone()]).unwrap();
+
+        let content = fs::read_to_string(dir.join(SHARED_MEMORY_FILE)).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], r#"{"schema_version":1}"#);
+        assert!(lines[1].starts_with(r#"{"created_at":"#));
+        assert_eq!(read_memory(&dir).unwrap(), vec![older, newer]);
+
+        // A line mangled by a bad merge costs that line, not the file
+        fs::write(
+            dir.join(SHARED_MEMORY_FILE),
+            format!("{}\n<<<<<<< HEAD\n{}\n", lines[0], lines[2]),
+        )
+        .unwrap();
+        assert_eq!(read_memory(&dir).unwrap().len(), 1);
+
+        let mut glossary = DomainGlossary::new();
+        glossary.add_term("Zeta".into(), "last".into(), PathBuf::from("src/b.rs"));
+        glossary.add_term("Alpha".into(), "first".into(), PathBuf::from("src/b.rs"));
+        glossary.add_term("Alpha".into(), "first".into(), PathBuf::from("src/a.rs"));
+        write_glossary(&dir, &glossary).unwrap();
+        let content = fs::read_to_string(dir.join(SHARED_GLOSSARY_FILE)).unwrap();
+        assert_eq!(
+            content.lines().nth(1).unwrap(),
+            r#"{"definition":"first","files":["src/a.rs","src/b.rs"],"term":"Alpha"}"#
+        );
+        assert_eq!(read_glossary(&dir).unwrap().terms.len(), 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,8 +1,13 @@
 //! Configuration management for cosmos
 //!
-//! Stores settings in ~/.config/cosmos/config.json
+//! Stores settings in ~/.config/cosmos/config.json. Per-project policy lives
+//! in the repo's `.cosmos/config.json` (see `ProjectConfig`).
 
 use crate::keyring;
+use crate::manifest::license::LicensePolicy;
+use crate::suggest::llm::ledger::Budgets;
+use crate::suggest::llm::models::ModelSettings;
+use crate::suggest::llm::tools::ToolSettings;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::Write;
@@ -12,6 +17,74 @@ use std::path::PathBuf;
 pub struct Config {
     /// Legacy field for migration - API key is now stored in system keychain
     pub openrouter_api_key: Option<String>,
+    /// Local advisory databases (RustSec advisory-db checkouts or OSV JSON
+    /// directories) to check lockfiles against
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub advisory_dbs: Vec<PathBuf>,
+    /// LLM backend; OpenRouter when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub llm_provider: Option<ProviderSettings>,
+    /// Model IDs, token limits and task-to-tier assignments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub models: Option<ModelSettings>,
+    /// Spending caps in USD (per session, per day, per repo)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budgets: Option<Budgets>,
+    /// Which agentic tools the model gets (e.g. the raw shell)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<ToolSettings>,
+}
+
+/// Which chat-completions backend Cosmos talks to
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProviderSettings {
+    /// OpenRouter, keyed by `OPENROUTER_API_KEY` or the system keychain
+    #[default]
+    OpenRouter,
+    /// Any server speaking the OpenAI chat completions API (llama.cpp
+    /// server, vLLM, Ollama, an in-house gateway)
+    OpenAiCompatible {
+        /// Base URL up to and including the version, e.g. `http://localhost:8080/v1`
+        base_url: String,
+        /// Model name the server expects; every tier uses it unless
+        /// `models.tiers` says otherwise
+        model: String,
+        /// Environment variable holding the API key, if the server wants one
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        api_key_env: Option<String>,
+    },
+}
+
+/// Settings that belong to one repository rather than one user
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectConfig {
+    /// Allowed/denied dependency licenses
+    #[serde(default)]
+    pub licenses: LicensePolicy,
+    /// On-disk budget for `.cosmos/` in MB (default 256)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_budget_mb: Option<u64>,
+    /// Model overrides for this repo; applied on top of the user's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub models: Option<ModelSettings>,
+    /// Spending caps for this repo; each one set here replaces the user's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budgets: Option<Budgets>,
+    /// Agentic tool restrictions for this repo (can only narrow the user's)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<ToolSettings>,
+}
+
+/// `.cosmos/` budget when a project doesn't set one
+const DEFAULT_CACHE_BUDGET_MB: u64 = 256;
+
+impl ProjectConfig {
+    pub fn cache_budget_bytes(&self) -> u64 {
+        self.cache_budget_mb
+            .unwrap_or(DEFAULT_CACHE_BUDGET_MB)
+            .saturating_mul(1024 * 1024)
+    }
 }
 
 impl Config {
@@ -179,6 +252,45 @@ impl Config {
         key.starts_with("sk-")
     }
 
+    /// Advisory databases to check: `COSMOS_ADVISORY_DB` (a path list), then
+    /// `advisory_dbs`, then cargo-audit's default checkout if present
+    pub fn advisory_db_paths(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = std::env::var_os("COSMOS_ADVISORY_DB")
+            .map(|value| std::env::split_paths(&value).collect())
+            .unwrap_or_default();
+        paths.extend(self.advisory_dbs.iter().cloned());
+        if let Some(home) = dirs::home_dir() {
+            let cargo_audit = home.join(".cargo").join("advisory-db");
+            if cargo_audit.is_dir() {
+                paths.push(cargo_audit);
+            }
+        }
+        paths.dedup();
+        paths
+    }
+
+    /// The configured LLM backend. `COSMOS_LLM_BASE_URL` and
+    /// `COSMOS_LLM_MODEL` select an OpenAI-compatible server without
+    /// editing the config (`COSMOS_LLM_API_KEY` supplies its key).
+    pub fn provider_settings(&self) -> ProviderSettings {
+        if let (Ok(base_url), Ok(model)) = (
+            std::env::var("COSMOS_LLM_BASE_URL"),
+            std::env::var("COSMOS_LLM_MODEL"),
+        ) {
+            return ProviderSettings::OpenAiCompatible {
+                base_url,
+                model,
+                api_key_env: Some("COSMOS_LLM_API_KEY".to_string()),
+            };
+        }
+        self.llm_provider.clone().unwrap_or_default()
+    }
+
+    /// Whether the configured backend needs an OpenRouter key
+    pub fn needs_openrouter_key(&self) -> bool {
+        self.provider_settings() == ProviderSettings::OpenRouter
+    }
+
     /// Get the config file location for display
     pub fn config_location() -> String {
         Self::config_path()
@@ -283,4 +395,23 @@ mod tests {
         let config = Config::default();
         assert!(config.openrouter_api_key.is_none());
     }
+
+    #[test]
+    fn test_provider_settings_parse() {
+        let config: Config = serde_json::from_str(
+            r#"{"openrouter_api_key": null, "llm_provider": {"kind": "open_ai_compatible", "base_url": "http://localhost:8080/v1", "model": "qwen2.5-coder"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.llm_provider,
+            Some(ProviderSettings::OpenAiCompatible {
+                base_url: "http://localhost:8080/v1".to_string(),
+                model: "qwen2.5-coder".to_string(),
+                api_key_env: None,
+            })
+        );
+        assert!(!serde_json::to_string(&Config::default())
+            .unwrap()
+            .contains("llm_provider"));
+    }
 }
@@ -879,9 +879,8 @@ mod tests {
         );
 
         // Restore original state
-        match orig {
-            Some(val) => std::env::set_var("GITHUB_TOKEN", val),
-            None => {}
+        if let Some(val) = orig {
+            std::env::set_var("GITHUB_TOKEN", val)
         }
     }
 
@@ -846,9 +846,8 @@ mod tests {
         let index = CodebaseIndex {
             root: PathBuf::new(),
             files: HashMap::new(),
-            symbols: Vec::new(),
-            dependencies: Vec::new(),
-            patterns: Vec::new(),
+            symbols: Default::default(),
+            type_relations: Vec::new(),
             cached_at: Utc::now(),
             index_errors: Vec::new(),
         };
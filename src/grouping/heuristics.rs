@@ -70,7 +70,7 @@ pub fn categorize_codebase(index: &CodebaseIndex) -> CodebaseGrouping {
     let mut grouping = CodebaseGrouping::new();
 
     for (path, file_index) in &index.files {
-        let detection = detect_layer_with_confidence(path, file_index);
+        let detection = detect_layer_with_confidence(index, path, file_index);
         grouping.assign_file_with_confidence(path.clone(), detection.layer, detection.confidence);
     }
 
@@ -78,7 +78,11 @@ pub fn categorize_codebase(index: &CodebaseIndex) -> CodebaseGrouping {
 }
 
 /// Detect the architectural layer for a single file with confidence score
-pub fn detect_layer_with_confidence(path: &Path, file_index: &FileIndex) -> LayerDetection {
+pub fn detect_layer_with_confidence(
+    index: &CodebaseIndex,
+    path: &Path,
+    file_index: &FileIndex,
+) -> LayerDetection {
     // Priority order (reordered for better accuracy):
     // 1. Tests > Config > Infra (these are unambiguous)
     // 2. File patterns (most specific - route.ts, .component.tsx)
@@ -92,6 +96,16 @@ pub fn detect_layer_with_confidence(path: &Path, file_index: &FileIndex) -> Laye
         return LayerDetection::high(Layer::Tests);
     }
 
+    // Project files whose type settles the layer (SQL before Config/Infra so
+    // `scripts/seed.sql` lands in Database)
+    match file_index.language {
+        crate::index::Language::Sql => return LayerDetection::high(Layer::Database),
+        crate::index::Language::Dockerfile
+        | crate::index::Language::Yaml
+        | crate::index::Language::Terraform => return LayerDetection::high(Layer::Infra),
+        _ => {}
+    }
+
     // 2. Config files
     if is_config_file(path) {
         return LayerDetection::high(Layer::Config);
@@ -108,7 +122,7 @@ pub fn detect_layer_with_confidence(path: &Path, file_index: &FileIndex) -> Laye
     }
 
     // 5. Symbol-based hints
-    if let Some(layer) = detect_by_symbols(file_index) {
+    if let Some(layer) = detect_by_symbols(index, file_index) {
         return LayerDetection::medium(layer);
     }
 
@@ -118,7 +132,7 @@ pub fn detect_layer_with_confidence(path: &Path, file_index: &FileIndex) -> Laye
     }
 
     // 7. Import-based detection
-    if let Some(layer) = detect_by_imports(&file_index.dependencies) {
+    if let Some(layer) = detect_by_imports(file_index.dependencies()) {
         return LayerDetection::medium(layer);
     }
 
@@ -158,7 +172,7 @@ fn path_segments(path: &Path) -> Vec<String> {
 }
 
 /// Check if file is a test file
-fn is_test_file(path: &Path) -> bool {
+pub fn is_test_file(path: &Path) -> bool {
     let filename = path
         .file_name()
         .and_then(|n| n.to_str())
@@ -444,10 +458,8 @@ fn detect_by_file_pattern(path: &Path) -> Option<Layer> {
 }
 
 /// Detect layer based on symbol analysis
-fn detect_by_symbols(file_index: &FileIndex) -> Option<Layer> {
-    let symbols = &file_index.symbols;
-
-    if symbols.is_empty() {
+fn detect_by_symbols(index: &CodebaseIndex, file_index: &FileIndex) -> Option<Layer> {
+    if file_index.symbols.is_empty() {
         return None;
     }
 
@@ -457,7 +469,7 @@ fn detect_by_symbols(file_index: &FileIndex) -> Option<Layer> {
     let mut has_model = false;
     let mut has_hook = false;
 
-    for symbol in symbols {
+    for symbol in index.file_symbols(file_index) {
         let name_lower = symbol.name.to_lowercase();
 
         // React components (PascalCase functions/classes returning JSX)
@@ -823,6 +835,10 @@ fn detect_by_language_conventions(path: &Path, file_index: &FileIndex) -> Layer
                 Layer::Backend
             }
         }
+        Language::Sql => Layer::Database,
+        Language::Dockerfile | Language::Yaml | Language::Terraform | Language::Shell => {
+            Layer::Infra
+        }
         Language::Unknown => Layer::Unknown,
     }
 }
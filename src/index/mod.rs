@@ -1,15 +1,22 @@
 //! Codebase indexing engine for Cosmos
 //!
 //! Uses tree-sitter for multi-language AST parsing to build
-//! semantic understanding of the codebase.
+//! semantic understanding of the codebase. Project files without a grammar
+//! (Dockerfiles, CI YAML, Terraform, SQL, shell) get a lighter line-based pass.
+//! Jupyter notebooks are indexed as the Python script of their code cells.
 
+pub mod notebook;
 pub mod parser;
+pub mod project_files;
+pub mod symbols;
+pub mod test_map;
 
 use crate::util::hash_str;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
 use walkdir::WalkDir;
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -25,7 +32,7 @@ pub const GOD_MODULE_LOC_THRESHOLD: usize = 500;
 /// Maximum file size (bytes) to index for AST parsing
 pub const MAX_INDEX_FILE_BYTES: u64 = 1_000_000;
 
-/// Supported programming languages
+/// Supported programming languages and project file types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Language {
     Rust,
@@ -33,6 +40,12 @@ pub enum Language {
     TypeScript,
     Python,
     Go,
+    Dockerfile,
+    /// CI pipelines (GitHub Actions, GitLab CI, CircleCI) and compose files
+    Yaml,
+    Terraform,
+    Sql,
+    Shell,
     Unknown,
 }
 
@@ -44,10 +57,65 @@ impl Language {
             "ts" | "tsx" => Language::TypeScript,
             "py" | "pyi" => Language::Python,
             "go" => Language::Go,
+            "tf" => Language::Terraform,
+            "sql" => Language::Sql,
+            "sh" | "bash" | "zsh" => Language::Shell,
             _ => Language::Unknown,
         }
     }
 
+    /// Like `from_extension`, but also recognizes Dockerfiles (which have no
+    /// extension), notebooks and the YAML files worth indexing. Other YAML is data.
+    pub fn from_path(path: &Path) -> Self {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        if name == "dockerfile"
+            || name == "containerfile"
+            || name.starts_with("dockerfile.")
+            || name.ends_with(".dockerfile")
+        {
+            return Language::Dockerfile;
+        }
+
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        if matches!(ext, "yml" | "yaml") {
+            let in_dir = |dir: &str| path.components().any(|c| c.as_os_str() == dir);
+            let is_pipeline = name.starts_with(".gitlab-ci.")
+                || name.starts_with("docker-compose")
+                || name.starts_with("compose.")
+                || in_dir(".gitlab")
+                || in_dir(".circleci")
+                || (in_dir(".github") && in_dir("workflows"));
+            return if is_pipeline {
+                Language::Yaml
+            } else {
+                Language::Unknown
+            };
+        }
+
+        if ext.eq_ignore_ascii_case("ipynb") {
+            return Language::Python;
+        }
+
+        Language::from_extension(ext)
+    }
+
+    /// Whether files are parsed with a tree-sitter grammar (source code)
+    /// rather than the line-based project file pass
+    pub fn is_code(&self) -> bool {
+        matches!(
+            self,
+            Language::Rust
+                | Language::JavaScript
+                | Language::TypeScript
+                | Language::Python
+                | Language::Go
+        )
+    }
+
     pub fn icon(&self) -> &'static str {
         match self {
             Language::Rust => "rs",
@@ -55,17 +123,23 @@ impl Language {
             Language::TypeScript => "ts",
             Language::Python => "py",
             Language::Go => "go",
+            Language::Dockerfile => "dk",
+            Language::Yaml => "ym",
+            Language::Terraform => "tf",
+            Language::Sql => "sq",
+            Language::Shell => "sh",
             Language::Unknown => "??",
         }
     }
 }
 
-/// A symbol extracted from the AST (function, struct, class, etc.)
+/// A symbol extracted from the AST (function, struct, class, etc.). Only
+/// the parser and the cache hold these; once indexed, symbols live in the
+/// interned `CodebaseIndex::symbols` table and the file is implied.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Symbol {
     pub name: String,
     pub kind: SymbolKind,
-    pub file: PathBuf,
     pub line: usize,
     pub end_line: usize,
     pub complexity: f64,
@@ -78,7 +152,7 @@ impl Symbol {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SymbolKind {
     Function,
     Method,
@@ -90,6 +164,14 @@ pub enum SymbolKind {
     Module,
     Constant,
     Variable,
+    /// Dockerfile build stage or CI pipeline stage
+    Stage,
+    /// CI job
+    Job,
+    /// Terraform resource/data source, compose service
+    Resource,
+    /// SQL table or view
+    Table,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -108,6 +190,46 @@ pub struct Dependency {
     pub is_external: bool,
 }
 
+/// How one type relates to another in the type hierarchy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RelationKind {
+    /// `impl Trait for Type`, `implements`, Go interface satisfaction
+    Implements,
+    /// Class inheritance, Python bases, supertraits, interface extension
+    Extends,
+}
+
+impl RelationKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RelationKind::Implements => "implements",
+            RelationKind::Extends => "extends",
+        }
+    }
+}
+
+/// A type hierarchy edge: `type_name` implements/extends `parent`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeRelation {
+    pub type_name: String,
+    pub parent: String,
+    pub kind: RelationKind,
+    pub file: PathBuf,
+    pub line: usize,
+    /// Inferred structurally (Go interfaces) rather than declared in source
+    #[serde(default)]
+    pub inferred: bool,
+}
+
+/// Method names attached to a type, used for Go's structural interface matching
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MethodSet {
+    pub type_name: String,
+    pub is_interface: bool,
+    pub methods: Vec<String>,
+    pub line: usize,
+}
+
 /// Recognized code patterns
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Pattern {
@@ -142,6 +264,12 @@ pub enum PatternKind {
     UnusedImport,
     /// TODO/FIXME marker
     TodoMarker,
+    /// Injection-style sink fed by a non-constant argument (SQL, shell, eval, innerHTML)
+    InjectionRisk,
+    /// Image, action or module reference without a pinned version (`:latest`, `@main`)
+    UnpinnedVersion,
+    /// Credential-looking value written inline in config
+    HardcodedSecret,
 }
 
 impl PatternKind {
@@ -155,8 +283,19 @@ impl PatternKind {
             PatternKind::MissingErrorHandling => PatternSeverity::High,
             PatternKind::UnusedImport => PatternSeverity::Low,
             PatternKind::TodoMarker => PatternSeverity::Info,
+            PatternKind::InjectionRisk => PatternSeverity::High,
+            PatternKind::UnpinnedVersion => PatternSeverity::Medium,
+            PatternKind::HardcodedSecret => PatternSeverity::High,
         }
     }
+
+    /// Whether this pattern points at a security risk rather than a maintainability issue
+    pub fn is_security(&self) -> bool {
+        matches!(
+            self,
+            PatternKind::InjectionRisk | PatternKind::HardcodedSecret
+        )
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -183,14 +322,18 @@ pub struct FileSummary {
 }
 
 impl FileSummary {
-    /// Generate a static summary from file index data
-    pub fn from_file_index(file_index: &FileIndex, rel_path: &Path, root: &Path) -> Self {
+    /// Generate a static summary from file index data and the file's symbols
+    pub fn from_file_index(
+        file_index: &FileIndex,
+        symbols: &[Symbol],
+        rel_path: &Path,
+        root: &Path,
+    ) -> Self {
         // Infer purpose from filename and exports
-        let purpose = infer_purpose(rel_path, &file_index.symbols, file_index.language);
+        let purpose = infer_purpose(rel_path, symbols, file_index.language);
 
         // Get public exports
-        let exports: Vec<String> = file_index
-            .symbols
+        let exports: Vec<String> = symbols
             .iter()
             .filter(|s| s.visibility == Visibility::Public)
             .map(|s| s.name.clone())
@@ -198,8 +341,7 @@ impl FileSummary {
             .collect();
 
         // Build metrics string
-        let func_count = file_index
-            .symbols
+        let func_count = symbols
             .iter()
             .filter(|s| matches!(s.kind, SymbolKind::Function | SymbolKind::Method))
             .count();
@@ -211,7 +353,7 @@ impl FileSummary {
 
         // depends_on will be populated by the codebase index
         let depends_on: Vec<PathBuf> = file_index
-            .dependencies
+            .dependencies()
             .iter()
             .filter(|d| !d.is_external)
             .filter_map(|d| resolve_import_path(&d.import_path, rel_path, root))
@@ -624,6 +766,45 @@ fn resolve_import_path(import: &str, from_file: &Path, root: &Path) -> Option<Pa
     None
 }
 
+/// The bulkier per-file results. The cache stores them apart from the rest
+/// of `FileIndex` and a restored index decodes them on first access.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileDetail {
+    pub dependencies: Vec<Dependency>,
+    pub patterns: Vec<Pattern>,
+    /// Code cell positions for notebooks, whose symbols and lines refer to
+    /// the extracted cell script rather than the JSON on disk
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notebook_cells: Vec<notebook::NotebookCell>,
+}
+
+/// Where a restored file's detail comes from when first asked for
+pub trait DetailSource: std::fmt::Debug + Send + Sync {
+    /// Root the cached paths are relative to
+    fn root(&self) -> &Path;
+
+    /// The stored detail of `rel_path`, unless it's missing, unreadable or
+    /// was stored for other content than `content_hash`
+    fn load(&self, rel_path: &Path, content_hash: &str) -> Option<FileDetail>;
+}
+
+/// A file's detail, computed or waiting in a `DetailSource`. Clones of the
+/// index share the cell, so each file is decoded at most once.
+#[derive(Debug, Clone, Default)]
+struct LazyDetail {
+    cell: Arc<OnceLock<FileDetail>>,
+    source: Option<Arc<dyn DetailSource>>,
+}
+
+impl LazyDetail {
+    fn ready(detail: FileDetail) -> Self {
+        Self {
+            cell: Arc::new(OnceLock::from(detail)),
+            source: None,
+        }
+    }
+}
+
 /// Index of a single file
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileIndex {
@@ -634,9 +815,29 @@ pub struct FileIndex {
     /// Stable hash of file contents for cache invalidation
     #[serde(default)]
     pub content_hash: String,
-    pub symbols: Vec<Symbol>,
-    pub dependencies: Vec<Dependency>,
-    pub patterns: Vec<Pattern>,
+    /// This file's entries in `CodebaseIndex::symbols`, assigned when the
+    /// index is linked
+    #[serde(skip)]
+    pub symbols: symbols::SymbolIds,
+    /// Dependencies, patterns and notebook cells; see `detail()`
+    #[serde(skip)]
+    detail: LazyDetail,
+    /// Severity-weighted pattern count, kept next to the detail so ranking
+    /// files doesn't decode it
+    #[serde(default)]
+    pub pattern_weight: f64,
+    /// Type hierarchy edges declared in this file
+    #[serde(default)]
+    pub relations: Vec<TypeRelation>,
+    /// Go method sets (receivers and interfaces) for structural matching
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub method_sets: Vec<MethodSet>,
+    /// Test files that exercise this file (naming conventions and imports)
+    #[serde(default)]
+    pub covering_tests: Vec<PathBuf>,
+    /// File carries its own tests (Rust `#[cfg(test)]` module)
+    #[serde(default)]
+    pub has_inline_tests: bool,
     pub complexity: f64,
     pub last_modified: DateTime<Utc>,
     /// File summary for quick reference
@@ -651,21 +852,65 @@ pub struct FileIndex {
 }
 
 impl FileIndex {
-    pub fn suggestion_density(&self) -> f64 {
-        let pattern_weight: f64 = self
-            .patterns
+    /// Whether any test exercises this file; `false` is the "no tests" flag
+    pub fn has_tests(&self) -> bool {
+        self.has_inline_tests || !self.covering_tests.is_empty()
+    }
+
+    /// Dependencies, patterns and notebook cells. A file restored from the
+    /// cache decodes them from its shard here, or re-reads the file if the
+    /// shard is gone, corrupt or out of date.
+    pub fn detail(&self) -> &FileDetail {
+        self.detail.cell.get_or_init(|| {
+            let Some(source) = &self.detail.source else {
+                return FileDetail::default();
+            };
+            let rel_path = self.path.strip_prefix(source.root()).unwrap_or(&self.path);
+            source
+                .load(rel_path, &self.content_hash)
+                .or_else(|| {
+                    index_file(source.root(), &self.path, self.language)
+                        .ok()
+                        .map(|(file_index, _)| file_index.detail().clone())
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    pub fn dependencies(&self) -> &[Dependency] {
+        &self.detail().dependencies
+    }
+
+    pub fn patterns(&self) -> &[Pattern] {
+        &self.detail().patterns
+    }
+
+    /// Leave the detail to be loaded from `source` on first access
+    pub fn set_detail_source(&mut self, source: Arc<dyn DetailSource>) {
+        self.detail = LazyDetail {
+            cell: Arc::default(),
+            source: Some(source),
+        };
+    }
+
+    /// Whether the detail is (or will be) the one in the `DetailSource` it
+    /// was restored from, rather than freshly computed
+    pub fn detail_is_cached(&self) -> bool {
+        self.detail.source.is_some()
+    }
+
+    /// For notebooks, the `(cell, line within cell)` a script line came from
+    pub fn notebook_location(&self, line: usize) -> Option<(usize, usize)> {
+        self.detail()
+            .notebook_cells
             .iter()
-            .map(|p| match p.kind.severity() {
-                PatternSeverity::High => 3.0,
-                PatternSeverity::Medium => 2.0,
-                PatternSeverity::Low => 1.0,
-                PatternSeverity::Info => 0.5,
-            })
-            .sum();
+            .find_map(|c| c.locate(line))
+    }
 
+    pub fn suggestion_density(&self) -> f64 {
         // Normalize by file size
         if self.loc > 0 {
-            pattern_weight / (self.loc as f64 / 100.0)
+            self.pattern_weight / (self.loc as f64 / 100.0)
         } else {
             0.0
         }
@@ -690,9 +935,12 @@ impl FileIndex {
 pub struct CodebaseIndex {
     pub root: PathBuf,
     pub files: HashMap<PathBuf, FileIndex>,
-    pub symbols: Vec<Symbol>,
-    pub dependencies: Vec<Dependency>,
-    pub patterns: Vec<Pattern>,
+    /// Cross-file symbol lookups, rebuilt from `files` rather than cached
+    #[serde(skip)]
+    pub symbols: symbols::SymbolTable,
+    /// Type hierarchy edges across the codebase (declared and inferred)
+    #[serde(default)]
+    pub type_relations: Vec<TypeRelation>,
     pub cached_at: DateTime<Utc>,
     #[serde(default)]
     pub index_errors: Vec<IndexError>,
@@ -701,26 +949,64 @@ pub struct CodebaseIndex {
 impl CodebaseIndex {
     /// Create a new index for a codebase
     pub fn new(root: &Path) -> anyhow::Result<Self> {
-        let mut index = Self {
+        let mut index = Self::empty(root);
+        let symbols = index.scan(root)?;
+        index.link_files(symbols);
+        Ok(index)
+    }
+
+    /// Rebuild an index from cached per-file entries and their symbols,
+    /// re-indexing only the `stale` files (changed, new, or whose cache shard
+    /// couldn't be read)
+    pub fn from_cached_files(
+        root: &Path,
+        mut files: HashMap<PathBuf, FileIndex>,
+        mut symbols: HashMap<PathBuf, Vec<Symbol>>,
+        index_errors: Vec<IndexError>,
+        stale: &[PathBuf],
+    ) -> Self {
+        let mut index = Self::empty(root);
+        index.index_errors = index_errors
+            .into_iter()
+            .filter(|e| !stale.contains(&e.path))
+            .collect();
+        for rel_path in stale {
+            let path = root.join(rel_path);
+            match index_file(root, &path, Language::from_path(&path)) {
+                Ok((file_index, file_symbols)) => {
+                    files.insert(rel_path.clone(), file_index);
+                    symbols.insert(rel_path.clone(), file_symbols);
+                }
+                Err(err) => {
+                    files.remove(rel_path);
+                    symbols.remove(rel_path);
+                    index.index_errors.push(IndexError {
+                        path: rel_path.clone(),
+                        reason: err.to_string(),
+                    });
+                }
+            }
+        }
+        index.files = files;
+        index.link_files(symbols);
+        index
+    }
+
+    fn empty(root: &Path) -> Self {
+        Self {
             root: root.to_path_buf(),
             files: HashMap::new(),
-            symbols: Vec::new(),
-            dependencies: Vec::new(),
-            patterns: Vec::new(),
+            symbols: symbols::SymbolTable::default(),
+            type_relations: Vec::new(),
             cached_at: Utc::now(),
             index_errors: Vec::new(),
-        };
-
-        index.scan(root)?;
-
-        // Build the dependency graph after all files are indexed
-        index.build_dependency_graph();
-
-        Ok(index)
+        }
     }
 
-    /// Scan directory and index all supported files
-    fn scan(&mut self, root: &Path) -> anyhow::Result<()> {
+    /// Scan directory and index all supported files, returning their symbols
+    /// for `link_files`
+    fn scan(&mut self, root: &Path) -> anyhow::Result<HashMap<PathBuf, Vec<Symbol>>> {
+        let mut symbols = HashMap::new();
         for entry in WalkDir::new(root)
             .into_iter()
             .filter_entry(|e| !is_ignored(e.path()))
@@ -731,25 +1017,18 @@ impl CodebaseIndex {
                 continue;
             }
 
-            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-
-            let language = Language::from_extension(ext);
+            let language = Language::from_path(path);
             if language == Language::Unknown {
                 continue;
             }
 
-            match self.index_file(path, language) {
-                Ok(file_index) => {
-                    // Aggregate symbols, dependencies, patterns
-                    self.symbols.extend(file_index.symbols.clone());
-                    self.dependencies.extend(file_index.dependencies.clone());
-                    self.patterns.extend(file_index.patterns.clone());
-
-                    let rel_path = path.strip_prefix(root).unwrap_or(path).to_path_buf();
+            let rel_path = path.strip_prefix(root).unwrap_or(path).to_path_buf();
+            match index_file(root, path, language) {
+                Ok((file_index, file_symbols)) => {
+                    symbols.insert(rel_path.clone(), file_symbols);
                     self.files.insert(rel_path, file_index);
                 }
                 Err(err) => {
-                    let rel_path = path.strip_prefix(root).unwrap_or(path).to_path_buf();
                     self.index_errors.push(IndexError {
                         path: rel_path,
                         reason: err.to_string(),
@@ -758,108 +1037,34 @@ impl CodebaseIndex {
             }
         }
 
-        Ok(())
+        Ok(symbols)
     }
 
-    /// Index a single file
-    fn index_file(&self, path: &Path, language: Language) -> anyhow::Result<FileIndex> {
-        let metadata = std::fs::metadata(path)?;
-        if metadata.len() > MAX_INDEX_FILE_BYTES {
-            return Err(anyhow::anyhow!(
-                "File too large to index ({} bytes, limit {} bytes)",
-                metadata.len(),
-                MAX_INDEX_FILE_BYTES
-            ));
+    /// Build the cross-file views once every file is indexed: type
+    /// relations, the symbol table, the dependency graph, inferred type
+    /// relations and test links. `symbols` are the files' parsed symbols;
+    /// the table takes them over.
+    fn link_files(&mut self, mut symbols: HashMap<PathBuf, Vec<Symbol>>) {
+        let mut paths: Vec<&PathBuf> = self.files.keys().collect();
+        paths.sort();
+        for path in paths {
+            let file_index = &self.files[path];
+            self.type_relations
+                .extend(file_index.relations.iter().cloned());
         }
-
-        let bytes = std::fs::read(path)?;
-        if bytes.len() as u64 > MAX_INDEX_FILE_BYTES {
-            return Err(anyhow::anyhow!(
-                "File too large to index ({} bytes, limit {} bytes)",
-                bytes.len(),
-                MAX_INDEX_FILE_BYTES
-            ));
+        // Every indexed file gets a (possibly empty) range, and only those
+        symbols.retain(|path, _| self.files.contains_key(path));
+        for path in self.files.keys() {
+            symbols.entry(path.clone()).or_default();
         }
-
-        let content = String::from_utf8(bytes)
-            .map_err(|_| anyhow::anyhow!("File is not valid UTF-8, skipping"))?;
-
-        let modified = metadata
-            .modified()
-            .map(DateTime::<Utc>::from)
-            .unwrap_or_else(|_| Utc::now());
-
-        let loc = content.lines().count();
-        let sloc = content.lines().filter(|l| !l.trim().is_empty()).count();
-        let content_hash = hash_str(&content);
-
-        // Parse with tree-sitter
-        let (symbols, deps) = parser::parse_file(path, &content, language)?;
-
-        // Detect patterns
-        let mut patterns = Vec::new();
-
-        // Check for long functions
-        for sym in &symbols {
-            if matches!(sym.kind, SymbolKind::Function | SymbolKind::Method)
-                && sym.line_count() > LONG_FUNCTION_THRESHOLD
-            {
-                patterns.push(Pattern {
-                    kind: PatternKind::LongFunction,
-                    file: path.to_path_buf(),
-                    line: sym.line,
-                    description: format!("{} is {} lines", sym.name, sym.line_count()),
-                });
-            }
+        self.symbols = symbols::SymbolTable::build(&symbols);
+        for (path, file_index) in self.files.iter_mut() {
+            file_index.symbols = self.symbols.ids_of(path);
         }
 
-        // Check for god module
-        if loc > GOD_MODULE_LOC_THRESHOLD {
-            patterns.push(Pattern {
-                kind: PatternKind::GodModule,
-                file: path.to_path_buf(),
-                line: 1,
-                description: format!("File has {} lines", loc),
-            });
-        }
-
-        // Scan for TODO/FIXME
-        for (i, line) in content.lines().enumerate() {
-            let upper = line.to_uppercase();
-            if upper.contains("TODO") || upper.contains("FIXME") || upper.contains("HACK") {
-                patterns.push(Pattern {
-                    kind: PatternKind::TodoMarker,
-                    file: path.to_path_buf(),
-                    line: i + 1,
-                    description: line.trim().to_string(),
-                });
-            }
-        }
-
-        // Calculate complexity (simplified cyclomatic)
-        let complexity = calculate_complexity(&content, language);
-
-        let mut file_index = FileIndex {
-            path: path.to_path_buf(),
-            language,
-            loc,
-            sloc,
-            content_hash,
-            symbols,
-            dependencies: deps,
-            patterns,
-            complexity,
-            last_modified: modified,
-            summary: FileSummary::default(),
-            layer: None,
-            feature: None,
-        };
-
-        // Generate summary (rel_path will be set properly after insertion)
-        let rel_path = path.strip_prefix(&self.root).unwrap_or(path);
-        file_index.summary = FileSummary::from_file_index(&file_index, rel_path, &self.root);
-
-        Ok(file_index)
+        self.build_dependency_graph();
+        self.infer_go_interface_relations();
+        self.link_tests();
     }
 
     /// Build the dependency graph (populate used_by for all files)
@@ -949,7 +1154,10 @@ impl CodebaseIndex {
             }
         }
 
-        // Now update each file's used_by
+        // Now update each file's used_by (clearing edges cached from an earlier build)
+        for file_index in self.files.values_mut() {
+            file_index.summary.used_by.clear();
+        }
         for (path, used_by) in used_by_map {
             if let Some(file_index) = self.files.get_mut(&path) {
                 file_index.summary.used_by = used_by;
@@ -957,7 +1165,278 @@ impl CodebaseIndex {
         }
     }
 
-    /// Get files sorted by suggestion density (most actionable first)
+    /// Add inferred `Implements` edges for Go types whose methods cover an interface.
+    ///
+    /// Best-effort: only matches method names, and only within a package directory.
+    pub fn infer_go_interface_relations(&mut self) {
+        // (package dir, type name) -> (file, line, methods)
+        let mut interfaces: HashMap<(PathBuf, String), (PathBuf, usize, Vec<String>)> =
+            HashMap::new();
+        let mut concrete: HashMap<(PathBuf, String), (PathBuf, usize, HashSet<String>)> =
+            HashMap::new();
+
+        for file_index in self.files.values() {
+            let dir = file_index
+                .path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            for set in &file_index.method_sets {
+                let key = (dir.clone(), set.type_name.clone());
+                if set.is_interface {
+                    interfaces.insert(
+                        key,
+                        (file_index.path.clone(), set.line, set.methods.clone()),
+                    );
+                } else {
+                    let entry = concrete
+                        .entry(key)
+                        .or_insert_with(|| (file_index.path.clone(), set.line, HashSet::new()));
+                    entry.2.extend(set.methods.iter().cloned());
+                }
+            }
+        }
+
+        // Point concrete types at their declaration rather than their first method
+        for symbol in self.symbols.of_kind(SymbolKind::Struct) {
+            let file = self.root.join(symbol.file);
+            let dir = file.parent().map(Path::to_path_buf).unwrap_or_default();
+            if let Some(entry) = concrete.get_mut(&(dir, symbol.name.to_string())) {
+                entry.0 = file;
+                entry.1 = symbol.line;
+            }
+        }
+
+        let mut inferred = Vec::new();
+        for ((dir, iface), (_, _, required)) in &interfaces {
+            if required.is_empty() {
+                continue;
+            }
+            for ((type_dir, type_name), (file, line, methods)) in &concrete {
+                if type_dir == dir && required.iter().all(|m| methods.contains(m)) {
+                    inferred.push(TypeRelation {
+                        type_name: type_name.clone(),
+                        parent: iface.clone(),
+                        kind: RelationKind::Implements,
+                        file: file.clone(),
+                        line: *line,
+                        inferred: true,
+                    });
+                }
+            }
+        }
+
+        inferred.sort_by(|a, b| (&a.parent, &a.type_name).cmp(&(&b.parent, &b.type_name)));
+        self.type_relations.retain(|r| !r.inferred);
+        self.type_relations.extend(inferred);
+    }
+
+    /// Populate `covering_tests` on every file from the test map
+    pub fn link_tests(&mut self) {
+        let map = test_map::build_test_map(self);
+        for (path, file_index) in self.files.iter_mut() {
+            file_index.covering_tests = map.get(path).cloned().unwrap_or_default();
+        }
+    }
+
+    /// Tests to run after changing a file (the file itself if it has inline tests)
+    pub fn tests_for(&self, rel_path: &Path) -> Vec<PathBuf> {
+        let Some(file_index) = self.files.get(rel_path) else {
+            return Vec::new();
+        };
+        let mut tests = Vec::new();
+        if file_index.has_inline_tests {
+            tests.push(rel_path.to_path_buf());
+        }
+        tests.extend(file_index.covering_tests.iter().cloned());
+        tests
+    }
+
+    /// Prompt section naming the tests that cover a file, or flagging that none do
+    pub fn test_context(&self, rel_path: &Path) -> Option<String> {
+        let file_index = self.files.get(rel_path)?;
+        if crate::grouping::heuristics::is_test_file(rel_path) {
+            return None;
+        }
+        let tests = self.tests_for(rel_path);
+        if tests.is_empty() {
+            return Some(format!(
+                "TESTS: no tests cover {} (complexity {:.0})",
+                rel_path.display(),
+                file_index.complexity
+            ));
+        }
+        let body = tests
+            .iter()
+            .take(8)
+            .map(|t| format!("- {}", t.display()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(format!(
+            "TESTS covering this file (run after changing it):\n{}",
+            body
+        ))
+    }
+
+    /// All types that implement or extend `parent`
+    pub fn implementations_of(&self, parent: &str) -> Vec<&TypeRelation> {
+        self.type_relations
+            .iter()
+            .filter(|r| r.parent == parent)
+            .collect()
+    }
+
+    /// Human-readable hierarchy lines for a file: what its types implement, who
+    /// implements the traits/interfaces it defines, and sibling implementations
+    /// that may need to change together.
+    pub fn type_hierarchy_for_file(&self, rel_path: &Path) -> Vec<String> {
+        let Some(file_index) = self.files.get(rel_path) else {
+            return Vec::new();
+        };
+        let abs_path = &file_index.path;
+        let describe = |r: &TypeRelation| {
+            let rel = r.file.strip_prefix(&self.root).unwrap_or(&r.file);
+            format!("{} ({}:{})", r.type_name, rel.display(), r.line)
+        };
+
+        let mut lines = Vec::new();
+
+        // Edges declared (or inferred) for types in this file
+        let own: Vec<&TypeRelation> = self
+            .type_relations
+            .iter()
+            .filter(|r| &r.file == abs_path)
+            .collect();
+        for r in &own {
+            lines.push(format!(
+                "{} {} {}{}",
+                r.type_name,
+                r.kind.label(),
+                r.parent,
+                if r.inferred { " (inferred)" } else { "" }
+            ));
+        }
+
+        // Implementors of traits/interfaces/classes defined here
+        for symbol in self.file_symbols(file_index) {
+            if !matches!(
+                symbol.kind,
+                SymbolKind::Trait | SymbolKind::Interface | SymbolKind::Class | SymbolKind::Struct
+            ) {
+                continue;
+            }
+            let implementors: Vec<String> = self
+                .implementations_of(symbol.name)
+                .into_iter()
+                .filter(|r| &r.file != abs_path)
+                .map(describe)
+                .collect();
+            if !implementors.is_empty() {
+                lines.push(format!(
+                    "{} is implemented by {}",
+                    symbol.name,
+                    implementors.join(", ")
+                ));
+            }
+        }
+
+        // Other implementations of the parents this file's types implement
+        let mut seen_parents = HashSet::new();
+        for r in &own {
+            if !seen_parents.insert(r.parent.as_str()) {
+                continue;
+            }
+            let siblings: Vec<String> = self
+                .implementations_of(&r.parent)
+                .into_iter()
+                .filter(|s| &s.file != abs_path)
+                .map(describe)
+                .collect();
+            if !siblings.is_empty() {
+                lines.push(format!(
+                    "{} is also implemented by {}",
+                    r.parent,
+                    siblings.join(", ")
+                ));
+            }
+        }
+
+        lines
+    }
+
+    /// Prompt section describing the type hierarchy around a file, if any
+    pub fn type_hierarchy_context(&self, rel_path: &Path) -> Option<String> {
+        let lines = self.type_hierarchy_for_file(rel_path);
+        if lines.is_empty() {
+            return None;
+        }
+        let body = lines
+            .iter()
+            .take(12)
+            .map(|l| format!("- {}", l))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(format!(
+            "TYPE HIERARCHY (implementations that may need to change together):\n{}",
+            body
+        ))
+    }
+
+    /// Prompt section naming the symbol that encloses a line, and where else
+    /// a symbol with that name is defined
+    pub fn symbol_context(&self, rel_path: &Path, line: usize) -> Option<String> {
+        let symbol = self.symbols.symbol_at_line(rel_path, line)?;
+        let mut lines = vec![format!(
+            "- Line {} is inside {:?} `{}` (lines {}-{})",
+            line, symbol.kind, symbol.name, symbol.line, symbol.end_line
+        )];
+        let elsewhere: Vec<String> = self
+            .symbols
+            .find_symbol(symbol.name)
+            .filter(|s| s.file != rel_path)
+            .take(5)
+            .map(|s| format!("{}:{}", s.file.display(), s.line))
+            .collect();
+        if !elsewhere.is_empty() {
+            lines.push(format!(
+                "- `{}` is also defined at {}",
+                symbol.name,
+                elsewhere.join(", ")
+            ));
+        }
+        Some(format!("ENCLOSING SYMBOL:\n{}", lines.join("\n")))
+    }
+
+    /// Index-derived context for fix prompts: enclosing symbol, type hierarchy
+    /// plus covering tests
+    pub fn fix_context(&self, rel_path: &Path, line: Option<usize>) -> Option<String> {
+        let sections: Vec<String> = [
+            line.and_then(|line| self.symbol_context(rel_path, line)),
+            self.type_hierarchy_context(rel_path),
+            self.test_context(rel_path),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        (!sections.is_empty()).then(|| sections.join("\n\n"))
+    }
+
+    /// A file's symbols, resolved through the table, in line order
+    pub fn file_symbols<'a>(
+        &'a self,
+        file_index: &FileIndex,
+    ) -> impl Iterator<Item = symbols::SymbolRef<'a>> + 'a {
+        self.symbols.of_file(file_index.symbols)
+    }
+
+    /// Every file's patterns in path order; each file's detail is decoded
+    /// only once the iterator reaches it
+    pub fn patterns(&self) -> impl Iterator<Item = &Pattern> + '_ {
+        let mut files: Vec<_> = self.files.iter().collect();
+        files.sort_by_key(|(path, _)| *path);
+        files.into_iter().flat_map(|(_, f)| f.patterns())
+    }
+
     /// Get total statistics
     pub fn stats(&self) -> IndexStats {
         IndexStats {
@@ -986,6 +1465,142 @@ impl CodebaseIndex {
     }
 }
 
+/// Index a single file, returning its entry and parsed symbols
+fn index_file(
+    root: &Path,
+    path: &Path,
+    language: Language,
+) -> anyhow::Result<(FileIndex, Vec<Symbol>)> {
+    let metadata = std::fs::metadata(path)?;
+    if metadata.len() > MAX_INDEX_FILE_BYTES {
+        return Err(anyhow::anyhow!(
+            "File too large to index ({} bytes, limit {} bytes)",
+            metadata.len(),
+            MAX_INDEX_FILE_BYTES
+        ));
+    }
+
+    let bytes = std::fs::read(path)?;
+    if bytes.len() as u64 > MAX_INDEX_FILE_BYTES {
+        return Err(anyhow::anyhow!(
+            "File too large to index ({} bytes, limit {} bytes)",
+            bytes.len(),
+            MAX_INDEX_FILE_BYTES
+        ));
+    }
+
+    let raw = String::from_utf8(bytes)
+        .map_err(|_| anyhow::anyhow!("File is not valid UTF-8, skipping"))?;
+    // The cache compares hashes of the bytes on disk, so hash before extracting cells
+    let content_hash = hash_str(&raw);
+    let (content, notebook_cells) = if notebook::is_notebook(path) {
+        notebook::Notebook::parse(&raw)?.script()
+    } else {
+        (raw, Vec::new())
+    };
+
+    let modified = metadata
+        .modified()
+        .map(DateTime::<Utc>::from)
+        .unwrap_or_else(|_| Utc::now());
+
+    let loc = content.lines().count();
+    let sloc = content.lines().filter(|l| !l.trim().is_empty()).count();
+
+    // Parse with tree-sitter (AST-level patterns such as injection sinks come back too)
+    let parser::ParsedFile {
+        symbols,
+        dependencies: deps,
+        patterns: ast_patterns,
+        relations,
+        method_sets,
+        has_inline_tests,
+    } = parser::parse_file(path, &content, language)?;
+
+    // Detect patterns
+    let mut patterns = ast_patterns;
+
+    // Check for long functions
+    for sym in &symbols {
+        if matches!(sym.kind, SymbolKind::Function | SymbolKind::Method)
+            && sym.line_count() > LONG_FUNCTION_THRESHOLD
+        {
+            patterns.push(Pattern {
+                kind: PatternKind::LongFunction,
+                file: path.to_path_buf(),
+                line: sym.line,
+                description: format!("{} is {} lines", sym.name, sym.line_count()),
+            });
+        }
+    }
+
+    // Check for god module
+    if loc > GOD_MODULE_LOC_THRESHOLD {
+        patterns.push(Pattern {
+            kind: PatternKind::GodModule,
+            file: path.to_path_buf(),
+            line: 1,
+            description: format!("File has {} lines", loc),
+        });
+    }
+
+    // Scan for TODO/FIXME
+    for (i, line) in content.lines().enumerate() {
+        let upper = line.to_uppercase();
+        if upper.contains("TODO") || upper.contains("FIXME") || upper.contains("HACK") {
+            patterns.push(Pattern {
+                kind: PatternKind::TodoMarker,
+                file: path.to_path_buf(),
+                line: i + 1,
+                description: line.trim().to_string(),
+            });
+        }
+    }
+
+    let pattern_weight = patterns
+        .iter()
+        .map(|p| match p.kind.severity() {
+            PatternSeverity::High => 3.0,
+            PatternSeverity::Medium => 2.0,
+            PatternSeverity::Low => 1.0,
+            PatternSeverity::Info => 0.5,
+        })
+        .sum();
+
+    // Calculate complexity (simplified cyclomatic)
+    let complexity = calculate_complexity(&content, language);
+
+    let mut file_index = FileIndex {
+        path: path.to_path_buf(),
+        language,
+        loc,
+        sloc,
+        content_hash,
+        symbols: symbols::SymbolIds::default(),
+        detail: LazyDetail::ready(FileDetail {
+            dependencies: deps,
+            patterns,
+            notebook_cells,
+        }),
+        pattern_weight,
+        relations,
+        method_sets,
+        covering_tests: Vec::new(),
+        has_inline_tests,
+        complexity,
+        last_modified: modified,
+        summary: FileSummary::default(),
+        layer: None,
+        feature: None,
+    };
+
+    // Generate summary (rel_path will be set properly after insertion)
+    let rel_path = path.strip_prefix(root).unwrap_or(path);
+    file_index.summary = FileSummary::from_file_index(&file_index, &symbols, rel_path, root);
+
+    Ok((file_index, symbols))
+}
+
 #[derive(Debug, Clone)]
 pub struct IndexStats {
     pub file_count: usize,
@@ -1022,7 +1637,7 @@ fn calculate_complexity(content: &str, _language: Language) -> f64 {
 }
 
 /// Check if a path should be ignored
-fn is_ignored(path: &Path) -> bool {
+pub(crate) fn is_ignored(path: &Path) -> bool {
     let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
 
     // Common ignore patterns
@@ -1042,9 +1657,12 @@ fn is_ignored(path: &Path) -> bool {
         ".cosmos",
     ];
 
-    ignored.contains(&name) || name.starts_with('.')
+    ignored.contains(&name) || (name.starts_with('.') && !INDEXED_DOT_PATHS.contains(&name))
 }
 
+/// Hidden directories/files that hold CI configuration worth indexing
+const INDEXED_DOT_PATHS: &[&str] = &[".github", ".gitlab", ".circleci", ".gitlab-ci.yml"];
+
 /// Normalize a path by removing redundant components
 fn normalize_path(path: &Path) -> PathBuf {
     let mut result = PathBuf::new();
@@ -1077,6 +1695,19 @@ mod tests {
         assert_eq!(Language::from_extension("py"), Language::Python);
         assert_eq!(Language::from_extension("go"), Language::Go);
         assert_eq!(Language::from_extension("txt"), Language::Unknown);
+        assert_eq!(Language::from_extension("tf"), Language::Terraform);
+        assert_eq!(
+            Language::from_path(Path::new("docker/Dockerfile.prod")),
+            Language::Dockerfile
+        );
+        assert_eq!(
+            Language::from_path(Path::new(".github/workflows/ci.yml")),
+            Language::Yaml
+        );
+        assert_eq!(
+            Language::from_path(Path::new("config/settings.yml")),
+            Language::Unknown
+        );
     }
 
     #[test]
@@ -1109,4 +1740,137 @@ mod tests {
 
         let _ = fs::remove_dir_all(&root);
     }
+
+    #[test]
+    fn test_type_hierarchy_across_files() {
+        let mut root = std::env::temp_dir();
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        root.push(format!("cosmos_hierarchy_test_{}", nanos));
+
+        let src_dir = root.join("src");
+        let go_dir = root.join("store");
+        fs::create_dir_all(&src_dir).unwrap();
+        fs::create_dir_all(&go_dir).unwrap();
+        fs::write(
+            src_dir.join("store.rs"),
+            "pub trait Store { fn get(&self); }\n",
+        )
+        .unwrap();
+        fs::write(
+            src_dir.join("memory.rs"),
+            "pub struct Memory;\nimpl Store for Memory { fn get(&self) {} }\n",
+        )
+        .unwrap();
+        fs::write(
+            go_dir.join("store.go"),
+            "package store\n\ntype Getter interface {\n\tGet() string\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            go_dir.join("mem.go"),
+            "package store\n\ntype Mem struct{}\n\nfunc (m *Mem) Get() string { return \"\" }\n",
+        )
+        .unwrap();
+
+        let index = CodebaseIndex::new(&root).unwrap();
+
+        let lines = index.type_hierarchy_for_file(Path::new("src/store.rs"));
+        assert_eq!(
+            lines,
+            vec!["Store is implemented by Memory (src/memory.rs:2)".to_string()]
+        );
+
+        let go = index.implementations_of("Getter");
+        assert_eq!(go.len(), 1);
+        assert_eq!(go[0].type_name, "Mem");
+        assert!(go[0].inferred);
+        assert_eq!(go[0].line, 3);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_link_tests_by_convention_and_inline() {
+        let mut root = std::env::temp_dir();
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        root.push(format!("cosmos_test_map_test_{}", nanos));
+
+        let pkg = root.join("pkg");
+        let app = root.join("app");
+        let tests = root.join("tests");
+        fs::create_dir_all(&pkg).unwrap();
+        fs::create_dir_all(&app).unwrap();
+        fs::create_dir_all(&tests).unwrap();
+        fs::write(pkg.join("parse.go"), "package pkg\n\nfunc Parse() {}\n").unwrap();
+        fs::write(
+            pkg.join("parse_test.go"),
+            "package pkg\n\nfunc TestParse(t *testing.T) {}\n",
+        )
+        .unwrap();
+        fs::write(app.join("models.py"), "class User:\n    pass\n").unwrap();
+        fs::write(app.join("billing.py"), "def charge():\n    pass\n").unwrap();
+        fs::write(
+            tests.join("test_accounts.py"),
+            "from app.billing import charge\n\ndef test_charge():\n    charge()\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("lib.rs"),
+            "pub fn f() {}\n#[cfg(test)]\nmod tests {}\n",
+        )
+        .unwrap();
+
+        let index = CodebaseIndex::new(&root).unwrap();
+
+        assert_eq!(
+            index.tests_for(Path::new("pkg/parse.go")),
+            vec![PathBuf::from("pkg/parse_test.go")]
+        );
+        assert_eq!(
+            index.tests_for(Path::new("app/billing.py")),
+            vec![PathBuf::from("tests/test_accounts.py")]
+        );
+        assert!(!index.files[Path::new("app/models.py")].has_tests());
+        assert!(index.files[Path::new("lib.rs")].has_tests());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_index_notebook_code_cells() {
+        let mut root = std::env::temp_dir();
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        root.push(format!("cosmos_notebook_index_test_{}", nanos));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("explore.ipynb"),
+            r##"{"cells": [{"cell_type": "markdown", "metadata": {}, "source": ["# Notes"]}, {"cell_type": "code", "metadata": {}, "outputs": [], "source": ["import numpy as np\n", "\n", "def scale(x):\n", "    return np.array(x) * 2\n"]}], "metadata": {}, "nbformat": 4, "nbformat_minor": 5}"##,
+        )
+        .unwrap();
+
+        let index = CodebaseIndex::new(&root).unwrap();
+        let file = &index.files[Path::new("explore.ipynb")];
+
+        assert_eq!(file.language, Language::Python);
+        let scale = index
+            .file_symbols(file)
+            .find(|s| s.name == "scale")
+            .unwrap();
+        assert_eq!(file.notebook_location(scale.line), Some((1, 3)));
+        assert!(file
+            .dependencies()
+            .iter()
+            .any(|d| d.import_path == "import numpy as np" && d.line == 2));
+
+        let _ = fs::remove_dir_all(&root);
+    }
 }
@@ -0,0 +1,500 @@
+//! Jupyter notebook support
+//!
+//! Notebooks are indexed (and shown to the fix flow) as a Python script made
+//! of their code cells, each introduced by a `# %% [cell N]` marker. Edits
+//! to that script are written back by splicing only the changed cells'
+//! `source` arrays into the original JSON text, so outputs, metadata and
+//! the notebook's own formatting stay byte-for-byte intact.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const CELL_MARKER_PREFIX: &str = "# %% [cell ";
+
+/// Where one code cell sits in the extracted script
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotebookCell {
+    /// Index into the notebook's `cells` array
+    pub cell: usize,
+    /// Script line (1-based) of the cell's first source line
+    pub first_line: usize,
+    pub line_count: usize,
+}
+
+impl NotebookCell {
+    /// `(cell, line within cell)` for a script line inside this cell
+    pub fn locate(&self, script_line: usize) -> Option<(usize, usize)> {
+        (script_line >= self.first_line && script_line < self.first_line + self.line_count)
+            .then(|| (self.cell, script_line - self.first_line + 1))
+    }
+}
+
+pub fn is_notebook(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("ipynb"))
+}
+
+/// A parsed notebook: the raw JSON plus each code cell's source and span
+#[derive(Debug)]
+pub struct Notebook {
+    raw: String,
+    code_cells: Vec<CodeCell>,
+}
+
+#[derive(Debug)]
+struct CodeCell {
+    index: usize,
+    source: String,
+    /// Byte range of the `source` value in `raw`
+    span: (usize, usize),
+}
+
+impl Notebook {
+    pub fn parse(raw: &str) -> anyhow::Result<Self> {
+        let json: serde_json::Value = serde_json::from_str(raw)?;
+        let cells = json
+            .get("cells")
+            .and_then(|c| c.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Notebook has no cells array"))?;
+        let spans = source_spans(raw)?;
+
+        let mut code_cells = Vec::new();
+        for (index, cell) in cells.iter().enumerate() {
+            if cell.get("cell_type").and_then(|t| t.as_str()) != Some("code") {
+                continue;
+            }
+            let span = spans
+                .get(index)
+                .copied()
+                .flatten()
+                .ok_or_else(|| anyhow::anyhow!("Code cell {} has no source", index))?;
+            let source = match cell.get("source") {
+                Some(serde_json::Value::Array(lines)) => {
+                    lines.iter().filter_map(|l| l.as_str()).collect::<String>()
+                }
+                Some(serde_json::Value::String(text)) => text.clone(),
+                _ => String::new(),
+            };
+            code_cells.push(CodeCell {
+                index,
+                source,
+                span,
+            });
+        }
+
+        Ok(Self {
+            raw: raw.to_string(),
+            code_cells,
+        })
+    }
+
+    /// The code cells as one Python script, with the line mapping back to cells
+    pub fn script(&self) -> (String, Vec<NotebookCell>) {
+        let mut script = String::new();
+        let mut cells = Vec::new();
+        let mut line = 1;
+        for cell in &self.code_cells {
+            let start = script.len();
+            script.push_str(&format!("{}{}]\n", CELL_MARKER_PREFIX, cell.index));
+            script.push_str(cell_body(&cell.source));
+            script.push_str("\n\n");
+            // Marker, body (blank lines included; an empty cell still takes a
+            // line) and the blank separator
+            let written = script[start..].matches('\n').count();
+            cells.push(NotebookCell {
+                cell: cell.index,
+                first_line: line + 1,
+                line_count: written - 2,
+            });
+            line += written;
+        }
+        (script, cells)
+    }
+
+    /// Write an edited script back into the notebook JSON. Cells whose code
+    /// didn't change are left exactly as they were.
+    pub fn with_script(&self, script: &str) -> anyhow::Result<String> {
+        let edited = split_script(script)?;
+        let expected: Vec<usize> = self.code_cells.iter().map(|c| c.index).collect();
+        let found: Vec<usize> = edited.iter().map(|(index, _)| *index).collect();
+        if expected != found {
+            return Err(anyhow::anyhow!(
+                "Edit changed the notebook's cell markers (expected cells {:?}, found {:?})",
+                expected,
+                found
+            ));
+        }
+
+        let mut output = self.raw.clone();
+        // Splice from the end so earlier spans stay valid
+        for (cell, (_, body)) in self.code_cells.iter().zip(&edited).rev() {
+            if cell_body(&cell.source) == body.as_str() {
+                continue;
+            }
+            let (start, end) = cell.span;
+            let replacement = source_json(&self.raw, start, end, body);
+            output.replace_range(start..end, &replacement);
+        }
+        Ok(output)
+    }
+}
+
+/// Cell source without its trailing newline (how it reads in the script)
+fn cell_body(source: &str) -> &str {
+    source.strip_suffix('\n').unwrap_or(source)
+}
+
+/// Split a script back into `(cell index, body)` pairs
+fn split_script(script: &str) -> anyhow::Result<Vec<(usize, String)>> {
+    let mut cells: Vec<(usize, String)> = Vec::new();
+    for line in script.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if let Some(rest) = trimmed.strip_prefix(CELL_MARKER_PREFIX) {
+            let index = rest
+                .strip_suffix(']')
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(|| anyhow::anyhow!("Malformed cell marker: {}", trimmed))?;
+            cells.push((index, String::new()));
+        } else if let Some((_, body)) = cells.last_mut() {
+            body.push_str(line);
+        } else if !trimmed.is_empty() {
+            return Err(anyhow::anyhow!("Code found before the first cell marker"));
+        }
+    }
+
+    // Drop the blank separator line (and the body's final newline)
+    for (_, body) in &mut cells {
+        let trimmed = body
+            .strip_suffix("\n\n")
+            .or_else(|| body.strip_suffix('\n'))
+            .unwrap_or(body)
+            .to_string();
+        *body = trimmed;
+    }
+    Ok(cells)
+}
+
+/// Serialize a cell body the way the notebook already writes `source`:
+/// an array of lines (or a single string) indented like its neighbours
+fn source_json(raw: &str, start: usize, end: usize, body: &str) -> String {
+    let original = &raw[start..end];
+    if original.starts_with('"') {
+        return serde_json::to_string(body).unwrap_or_default();
+    }
+    if body.is_empty() {
+        return "[]".to_string();
+    }
+
+    let line_start = raw[..start].rfind('\n').map_or(0, |i| i + 1);
+    let key_indent: String = raw[line_start..start]
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect();
+    // Reuse the existing item indentation when there is one (nbformat uses 1 space)
+    let item_indent = original
+        .split_once('\n')
+        .map(|(_, rest)| {
+            rest.chars()
+                .take_while(|c| *c == ' ' || *c == '\t')
+                .collect()
+        })
+        .filter(|indent: &String| !indent.is_empty() && !original.trim_end().ends_with("[]"))
+        .unwrap_or_else(|| format!("{} ", key_indent));
+
+    let items: Vec<String> = body
+        .split_inclusive('\n')
+        .map(|line| {
+            format!(
+                "{}{}",
+                item_indent,
+                serde_json::to_string(line).unwrap_or_default()
+            )
+        })
+        .collect();
+    format!("[\n{}\n{}]", items.join(",\n"), key_indent)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  SOURCE SPANS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Byte span of each cell's `source` value, by cell index. serde_json doesn't
+/// expose positions, so this walks the top-level structure by hand.
+fn source_spans(raw: &str) -> anyhow::Result<Vec<Option<(usize, usize)>>> {
+    let mut scanner = Scanner {
+        bytes: raw.as_bytes(),
+        pos: 0,
+    };
+    let mut spans = Vec::new();
+
+    scanner.expect(b'{')?;
+    while let Some(key) = scanner.next_key()? {
+        if key != "cells" {
+            scanner.skip_value()?;
+            continue;
+        }
+        scanner.expect(b'[')?;
+        while scanner.next_item(b']')? {
+            let mut source = None;
+            scanner.expect(b'{')?;
+            while let Some(cell_key) = scanner.next_key()? {
+                scanner.skip_ws();
+                let start = scanner.pos;
+                scanner.skip_value()?;
+                if cell_key == "source" {
+                    source = Some((start, scanner.pos));
+                }
+            }
+            spans.push(source);
+        }
+    }
+    Ok(spans)
+}
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    fn skip_ws(&mut self) {
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_ws();
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> anyhow::Result<()> {
+        if self.peek() != Some(byte) {
+            return Err(anyhow::anyhow!(
+                "Malformed notebook JSON at byte {}",
+                self.pos
+            ));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Advance past `,` to the next array item; `false` (consuming `close`) at the end
+    fn next_item(&mut self, close: u8) -> anyhow::Result<bool> {
+        match self.peek() {
+            Some(b) if b == close => {
+                self.pos += 1;
+                Ok(false)
+            }
+            Some(b',') => {
+                self.pos += 1;
+                Ok(true)
+            }
+            Some(_) => Ok(true),
+            None => Err(anyhow::anyhow!("Unexpected end of notebook JSON")),
+        }
+    }
+
+    /// Next object key (consuming the `:`), or `None` at the closing `}`
+    fn next_key(&mut self) -> anyhow::Result<Option<String>> {
+        if !self.next_item(b'}')? {
+            return Ok(None);
+        }
+        self.skip_ws();
+        let start = self.pos;
+        self.skip_string()?;
+        let key: String = serde_json::from_slice(&self.bytes[start..self.pos])?;
+        self.expect(b':')?;
+        Ok(Some(key))
+    }
+
+    fn skip_string(&mut self) -> anyhow::Result<()> {
+        self.expect(b'"')?;
+        while let Some(&b) = self.bytes.get(self.pos) {
+            self.pos += 1;
+            match b {
+                b'\\' => self.pos += 1,
+                b'"' => return Ok(()),
+                _ => {}
+            }
+        }
+        Err(anyhow::anyhow!("Unterminated string in notebook JSON"))
+    }
+
+    fn skip_value(&mut self) -> anyhow::Result<()> {
+        match self.peek() {
+            Some(b'"') => self.skip_string(),
+            Some(b'{') => {
+                self.pos += 1;
+                while self.next_key()?.is_some() {
+                    self.skip_value()?;
+                }
+                Ok(())
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                while self.next_item(b']')? {
+                    self.skip_value()?;
+                }
+                Ok(())
+            }
+            Some(_) => {
+                // Number, true, false, null
+                while self
+                    .bytes
+                    .get(self.pos)
+                    .is_some_and(|b| !matches!(b, b',' | b'}' | b']') && !b.is_ascii_whitespace())
+                {
+                    self.pos += 1;
+                }
+                Ok(())
+            }
+            None => Err(anyhow::anyhow!("Unexpected end of notebook JSON")),
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  FILE I/O
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// The text Cosmos reads, shows and edits for a file: the cell script for
+/// notebooks, the raw content for everything else
+pub fn editable_text(path: &Path, raw: String) -> String {
+    if !is_notebook(path) {
+        return raw;
+    }
+    match Notebook::parse(&raw) {
+        Ok(notebook) => notebook.script().0,
+        Err(_) => raw,
+    }
+}
+
+/// Read a file as editable text (see `editable_text`)
+pub fn read_editable(path: &Path) -> std::io::Result<String> {
+    std::fs::read_to_string(path).map(|raw| editable_text(path, raw))
+}
+
+/// Write edited text back. Notebooks get their changed cells spliced into the
+/// JSON on disk; other files are written as-is.
+pub fn write_editable(path: &Path, content: &str) -> anyhow::Result<()> {
+    if is_notebook(path) && path.exists() {
+        let raw = std::fs::read_to_string(path)?;
+        let updated = Notebook::parse(&raw)?.with_script(content)?;
+        std::fs::write(path, updated)?;
+    } else {
+        std::fs::write(path, content)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": [
+    "# Analysis"
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "metadata": {"tags": ["setup"]},
+   "outputs": [{"name": "stdout", "output_type": "stream", "text": ["ok\n"]}],
+   "source": [
+    "import pandas as pd\n",
+    "df = pd.read_csv(\"data.csv\")"
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 2,
+   "metadata": {},
+   "outputs": [],
+   "source": [
+    "def total(df):\n",
+    "    return df[\"x\"].sum()\n"
+   ]
+  }
+ ],
+ "metadata": {"kernelspec": {"name": "python3"}},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"##;
+
+    #[test]
+    fn test_notebook_script_and_mapping() {
+        let notebook = Notebook::parse(NOTEBOOK).unwrap();
+        let (script, cells) = notebook.script();
+
+        assert_eq!(
+            script,
+            "# %% [cell 1]\nimport pandas as pd\ndf = pd.read_csv(\"data.csv\")\n\n# %% [cell 2]\ndef total(df):\n    return df[\"x\"].sum()\n\n"
+        );
+        assert_eq!(cells[0].locate(3), Some((1, 2)));
+        assert_eq!(cells[1].first_line, 6);
+        assert_eq!(cells[1].locate(7), Some((2, 2)));
+        assert_eq!(cells[1].locate(8), None);
+
+        // Unchanged script round-trips byte for byte
+        assert_eq!(notebook.with_script(&script).unwrap(), NOTEBOOK);
+    }
+
+    #[test]
+    fn test_notebook_mapping_counts_trailing_blank_lines() {
+        let raw = r#"{"cells": [
+  {"cell_type": "code", "source": ["x = 1\n", "\n"]},
+  {"cell_type": "code", "source": []},
+  {"cell_type": "code", "source": ["y = 2"]}
+ ]}"#;
+        let notebook = Notebook::parse(raw).unwrap();
+        let (script, cells) = notebook.script();
+
+        let lines: Vec<&str> = script.lines().collect();
+        for cell in &cells {
+            assert_eq!(
+                lines[cell.first_line - 2],
+                format!("# %% [cell {}]", cell.cell)
+            );
+        }
+        assert_eq!(cells[0].line_count, 2);
+        assert_eq!(cells[1].line_count, 1);
+        assert_eq!(lines[cells[2].first_line - 1], "y = 2");
+        assert_eq!(cells[2].locate(cells[2].first_line), Some((2, 1)));
+        assert_eq!(notebook.with_script(&script).unwrap(), raw);
+    }
+
+    #[test]
+    fn test_notebook_write_back_preserves_other_cells() {
+        let notebook = Notebook::parse(NOTEBOOK).unwrap();
+        let (script, _) = notebook.script();
+        let edited = script.replace("df[\"x\"].sum()", "df[\"x\"].fillna(0).sum()");
+
+        let updated = notebook.with_script(&edited).unwrap();
+        let before: serde_json::Value = serde_json::from_str(NOTEBOOK).unwrap();
+        let after: serde_json::Value = serde_json::from_str(&updated).unwrap();
+
+        assert_eq!(
+            after["cells"][2]["source"],
+            serde_json::json!(["def total(df):\n", "    return df[\"x\"].fillna(0).sum()"])
+        );
+        assert_eq!(after["cells"][1], before["cells"][1]);
+        assert_eq!(after["cells"][0], before["cells"][0]);
+        assert_eq!(after["metadata"], before["metadata"]);
+        assert!(updated.contains("   \"source\": [\n    \"def total(df):\\n\",\n"));
+
+        let broken = edited.replace("# %% [cell 2]\n", "");
+        assert!(notebook.with_script(&broken).is_err());
+    }
+}
@@ -1,15 +1,29 @@
 //! Tree-sitter based parser for multi-language AST analysis
 
-use super::{Dependency, Language, Symbol, SymbolKind, Visibility};
+use super::{
+    Dependency, Language, MethodSet, Pattern, PatternKind, RelationKind, Symbol, SymbolKind,
+    TypeRelation, Visibility,
+};
 use std::path::Path;
 use tree_sitter::Parser;
 
-/// Parse a file and extract symbols and dependencies
-pub fn parse_file(
-    path: &Path,
-    content: &str,
-    language: Language,
-) -> anyhow::Result<(Vec<Symbol>, Vec<Dependency>)> {
+/// Everything extracted from a single parse of a file
+#[derive(Debug, Default)]
+pub struct ParsedFile {
+    pub symbols: Vec<Symbol>,
+    pub dependencies: Vec<Dependency>,
+    /// AST-level patterns (currently injection-style sinks)
+    pub patterns: Vec<Pattern>,
+    /// Declared type hierarchy edges (impls, extends, implements, bases)
+    pub relations: Vec<TypeRelation>,
+    /// Go method sets, matched structurally once the whole codebase is indexed
+    pub method_sets: Vec<MethodSet>,
+    /// Rust `#[cfg(test)]` items or `#[test]` functions in the file itself
+    pub has_inline_tests: bool,
+}
+
+/// Parse a file and extract symbols, dependencies and AST-level patterns
+pub fn parse_file(path: &Path, content: &str, language: Language) -> anyhow::Result<ParsedFile> {
     let mut parser = Parser::new();
 
     // Set the language
@@ -19,7 +33,9 @@ pub fn parse_file(
         Language::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
         Language::Python => tree_sitter_python::LANGUAGE.into(),
         Language::Go => tree_sitter_go::LANGUAGE.into(),
-        Language::Unknown => return Ok((Vec::new(), Vec::new())),
+        Language::Unknown => return Ok(ParsedFile::default()),
+        // No grammar - project files get a line-based pass instead
+        _ => return Ok(super::project_files::parse(path, content, language)),
     };
 
     parser.set_language(&ts_language)?;
@@ -32,11 +48,11 @@ pub fn parse_file(
 
     // Extract symbols and dependencies based on language
     let symbols = match language {
-        Language::Rust => extract_rust_symbols(&root, content, path),
-        Language::JavaScript | Language::TypeScript => extract_js_symbols(&root, content, path),
-        Language::Python => extract_python_symbols(&root, content, path),
-        Language::Go => extract_go_symbols(&root, content, path),
-        Language::Unknown => Vec::new(),
+        Language::Rust => extract_rust_symbols(&root, content),
+        Language::JavaScript | Language::TypeScript => extract_js_symbols(&root, content),
+        Language::Python => extract_python_symbols(&root, content),
+        Language::Go => extract_go_symbols(&root, content),
+        _ => Vec::new(),
     };
 
     let dependencies = match language {
@@ -44,25 +60,40 @@ pub fn parse_file(
         Language::JavaScript | Language::TypeScript => extract_js_deps(&root, content, path),
         Language::Python => extract_python_deps(&root, content, path),
         Language::Go => extract_go_deps(&root, content, path),
-        Language::Unknown => Vec::new(),
+        _ => Vec::new(),
     };
 
-    Ok((symbols, dependencies))
+    let patterns = detect_injection_sinks(&root, content, path, language);
+    let relations = extract_type_relations(&root, content, path, language);
+    let method_sets = if language == Language::Go {
+        extract_go_method_sets(&root, content)
+    } else {
+        Vec::new()
+    };
+    let has_inline_tests = language == Language::Rust && has_rust_test_items(&root, content);
+
+    Ok(ParsedFile {
+        symbols,
+        dependencies,
+        patterns,
+        relations,
+        method_sets,
+        has_inline_tests,
+    })
 }
 
 /// Extract symbols from Rust code
-fn extract_rust_symbols(root: &tree_sitter::Node, content: &str, path: &Path) -> Vec<Symbol> {
+fn extract_rust_symbols(root: &tree_sitter::Node, content: &str) -> Vec<Symbol> {
     let mut symbols = Vec::new();
     let mut cursor = root.walk();
 
-    extract_rust_symbols_recursive(&mut cursor, content, path, &mut symbols);
+    extract_rust_symbols_recursive(&mut cursor, content, &mut symbols);
     symbols
 }
 
 fn extract_rust_symbols_recursive(
     cursor: &mut tree_sitter::TreeCursor,
     content: &str,
-    path: &Path,
     symbols: &mut Vec<Symbol>,
 ) {
     loop {
@@ -82,7 +113,6 @@ fn extract_rust_symbols_recursive(
                     symbols.push(Symbol {
                         name,
                         kind: SymbolKind::Function,
-                        file: path.to_path_buf(),
                         line: node.start_position().row + 1,
                         end_line: node.end_position().row + 1,
                         complexity: estimate_complexity(&node, content),
@@ -92,8 +122,9 @@ fn extract_rust_symbols_recursive(
             }
             "impl_item" => {
                 // Extract methods from impl blocks
-                if cursor.goto_first_child() {
-                    extract_rust_symbols_recursive(cursor, content, path, symbols);
+                let has_children = cursor.goto_first_child();
+                if has_children {
+                    extract_rust_symbols_recursive(cursor, content, symbols);
                     cursor.goto_parent();
                 }
             }
@@ -103,7 +134,6 @@ fn extract_rust_symbols_recursive(
                     symbols.push(Symbol {
                         name,
                         kind: SymbolKind::Struct,
-                        file: path.to_path_buf(),
                         line: node.start_position().row + 1,
                         end_line: node.end_position().row + 1,
                         complexity: 1.0,
@@ -121,7 +151,6 @@ fn extract_rust_symbols_recursive(
                     symbols.push(Symbol {
                         name,
                         kind: SymbolKind::Enum,
-                        file: path.to_path_buf(),
                         line: node.start_position().row + 1,
                         end_line: node.end_position().row + 1,
                         complexity: 1.0,
@@ -139,7 +168,6 @@ fn extract_rust_symbols_recursive(
                     symbols.push(Symbol {
                         name,
                         kind: SymbolKind::Trait,
-                        file: path.to_path_buf(),
                         line: node.start_position().row + 1,
                         end_line: node.end_position().row + 1,
                         complexity: 1.0,
@@ -157,7 +185,6 @@ fn extract_rust_symbols_recursive(
                     symbols.push(Symbol {
                         name,
                         kind: SymbolKind::Module,
-                        file: path.to_path_buf(),
                         line: node.start_position().row + 1,
                         end_line: node.end_position().row + 1,
                         complexity: 1.0,
@@ -175,7 +202,6 @@ fn extract_rust_symbols_recursive(
                     symbols.push(Symbol {
                         name,
                         kind: SymbolKind::Constant,
-                        file: path.to_path_buf(),
                         line: node.start_position().row + 1,
                         end_line: node.end_position().row + 1,
                         complexity: 1.0,
@@ -192,7 +218,7 @@ fn extract_rust_symbols_recursive(
 
         // Recurse into children
         if cursor.goto_first_child() {
-            extract_rust_symbols_recursive(cursor, content, path, symbols);
+            extract_rust_symbols_recursive(cursor, content, symbols);
             cursor.goto_parent();
         }
 
@@ -241,7 +267,7 @@ fn extract_rust_deps(root: &tree_sitter::Node, content: &str, path: &Path) -> Ve
 }
 
 /// Extract symbols from JavaScript/TypeScript code
-fn extract_js_symbols(root: &tree_sitter::Node, content: &str, path: &Path) -> Vec<Symbol> {
+fn extract_js_symbols(root: &tree_sitter::Node, content: &str) -> Vec<Symbol> {
     let mut symbols = Vec::new();
     let mut cursor = root.walk();
 
@@ -256,7 +282,6 @@ fn extract_js_symbols(root: &tree_sitter::Node, content: &str, path: &Path) -> V
                     symbols.push(Symbol {
                         name,
                         kind: SymbolKind::Function,
-                        file: path.to_path_buf(),
                         line: node.start_position().row + 1,
                         end_line: node.end_position().row + 1,
                         complexity: estimate_complexity(&node, content),
@@ -273,7 +298,6 @@ fn extract_js_symbols(root: &tree_sitter::Node, content: &str, path: &Path) -> V
                             symbols.push(Symbol {
                                 name,
                                 kind: SymbolKind::Function,
-                                file: path.to_path_buf(),
                                 line: node.start_position().row + 1,
                                 end_line: node.end_position().row + 1,
                                 complexity: estimate_complexity(&node, content),
@@ -289,7 +313,6 @@ fn extract_js_symbols(root: &tree_sitter::Node, content: &str, path: &Path) -> V
                     symbols.push(Symbol {
                         name,
                         kind: SymbolKind::Class,
-                        file: path.to_path_buf(),
                         line: node.start_position().row + 1,
                         end_line: node.end_position().row + 1,
                         complexity: 1.0,
@@ -303,7 +326,6 @@ fn extract_js_symbols(root: &tree_sitter::Node, content: &str, path: &Path) -> V
                     symbols.push(Symbol {
                         name,
                         kind: SymbolKind::Method,
-                        file: path.to_path_buf(),
                         line: node.start_position().row + 1,
                         end_line: node.end_position().row + 1,
                         complexity: estimate_complexity(&node, content),
@@ -317,7 +339,6 @@ fn extract_js_symbols(root: &tree_sitter::Node, content: &str, path: &Path) -> V
                     symbols.push(Symbol {
                         name,
                         kind: SymbolKind::Interface,
-                        file: path.to_path_buf(),
                         line: node.start_position().row + 1,
                         end_line: node.end_position().row + 1,
                         complexity: 1.0,
@@ -377,7 +398,7 @@ fn extract_js_deps(root: &tree_sitter::Node, content: &str, path: &Path) -> Vec<
 }
 
 /// Extract symbols from Python code
-fn extract_python_symbols(root: &tree_sitter::Node, content: &str, path: &Path) -> Vec<Symbol> {
+fn extract_python_symbols(root: &tree_sitter::Node, content: &str) -> Vec<Symbol> {
     let mut symbols = Vec::new();
     let mut cursor = root.walk();
 
@@ -402,7 +423,6 @@ fn extract_python_symbols(root: &tree_sitter::Node, content: &str, path: &Path)
                     symbols.push(Symbol {
                         name,
                         kind: SymbolKind::Function,
-                        file: path.to_path_buf(),
                         line: node.start_position().row + 1,
                         end_line: node.end_position().row + 1,
                         complexity: estimate_complexity(&node, content),
@@ -416,7 +436,6 @@ fn extract_python_symbols(root: &tree_sitter::Node, content: &str, path: &Path)
                     symbols.push(Symbol {
                         name,
                         kind: SymbolKind::Class,
-                        file: path.to_path_buf(),
                         line: node.start_position().row + 1,
                         end_line: node.end_position().row + 1,
                         complexity: 1.0,
@@ -472,7 +491,7 @@ fn extract_python_deps(root: &tree_sitter::Node, content: &str, path: &Path) ->
 }
 
 /// Extract symbols from Go code
-fn extract_go_symbols(root: &tree_sitter::Node, content: &str, path: &Path) -> Vec<Symbol> {
+fn extract_go_symbols(root: &tree_sitter::Node, content: &str) -> Vec<Symbol> {
     let mut symbols = Vec::new();
     let mut cursor = root.walk();
 
@@ -498,7 +517,6 @@ fn extract_go_symbols(root: &tree_sitter::Node, content: &str, path: &Path) -> V
                     symbols.push(Symbol {
                         name,
                         kind: SymbolKind::Function,
-                        file: path.to_path_buf(),
                         line: node.start_position().row + 1,
                         end_line: node.end_position().row + 1,
                         complexity: estimate_complexity(&node, content),
@@ -523,7 +541,6 @@ fn extract_go_symbols(root: &tree_sitter::Node, content: &str, path: &Path) -> V
                     symbols.push(Symbol {
                         name,
                         kind: SymbolKind::Method,
-                        file: path.to_path_buf(),
                         line: node.start_position().row + 1,
                         end_line: node.end_position().row + 1,
                         complexity: estimate_complexity(&node, content),
@@ -547,7 +564,6 @@ fn extract_go_symbols(root: &tree_sitter::Node, content: &str, path: &Path) -> V
                         symbols.push(Symbol {
                             name,
                             kind: sym_kind,
-                            file: path.to_path_buf(),
                             line: node.start_position().row + 1,
                             end_line: node.end_position().row + 1,
                             complexity: 1.0,
@@ -613,6 +629,748 @@ fn extract_go_deps(root: &tree_sitter::Node, content: &str, path: &Path) -> Vec<
     }
 }
 
+// Type hierarchy
+
+/// Extract declared type relationships: Rust impls and supertraits, JS/TS
+/// extends/implements, Python base classes
+fn extract_type_relations(
+    root: &tree_sitter::Node,
+    content: &str,
+    path: &Path,
+    language: Language,
+) -> Vec<TypeRelation> {
+    let mut relations = Vec::new();
+    let mut cursor = root.walk();
+
+    loop {
+        let node = cursor.node();
+        let line = node.start_position().row + 1;
+        let mut push = |type_name: &str, parent: &str, kind: RelationKind| {
+            if !type_name.is_empty() && !parent.is_empty() {
+                relations.push(TypeRelation {
+                    type_name: type_name.to_string(),
+                    parent: parent.to_string(),
+                    kind,
+                    file: path.to_path_buf(),
+                    line,
+                    inferred: false,
+                });
+            }
+        };
+
+        match (language, node.kind()) {
+            (Language::Rust, "impl_item") => {
+                if let (Some(trait_node), Some(type_node)) = (
+                    node.child_by_field_name("trait"),
+                    node.child_by_field_name("type"),
+                ) {
+                    push(
+                        &base_type_name(&get_node_text(&type_node, content)),
+                        &base_type_name(&get_node_text(&trait_node, content)),
+                        RelationKind::Implements,
+                    );
+                }
+            }
+            (Language::Rust, "trait_item") => {
+                if let (Some(name), Some(bounds)) = (
+                    node.child_by_field_name("name"),
+                    node.child_by_field_name("bounds"),
+                ) {
+                    let name = get_node_text(&name, content);
+                    for bound in named_children(&bounds) {
+                        if bound.kind() != "lifetime" {
+                            push(
+                                &name,
+                                &base_type_name(&get_node_text(&bound, content)),
+                                RelationKind::Extends,
+                            );
+                        }
+                    }
+                }
+            }
+            (
+                Language::JavaScript | Language::TypeScript,
+                "class_declaration" | "abstract_class_declaration" | "class",
+            ) => {
+                let name = node
+                    .child_by_field_name("name")
+                    .map(|n| get_node_text(&n, content))
+                    .unwrap_or_default();
+                for heritage in named_children(&node).filter(|c| c.kind() == "class_heritage") {
+                    for clause in named_children(&heritage) {
+                        let kind = match clause.kind() {
+                            "implements_clause" => RelationKind::Implements,
+                            _ => RelationKind::Extends,
+                        };
+                        if matches!(clause.kind(), "extends_clause" | "implements_clause") {
+                            for parent in
+                                named_children(&clause).filter(|p| p.kind() != "type_arguments")
+                            {
+                                push(
+                                    &name,
+                                    &base_type_name(&get_node_text(&parent, content)),
+                                    kind,
+                                );
+                            }
+                        } else {
+                            // Plain JavaScript: `class A extends B` has the expression directly
+                            push(
+                                &name,
+                                &base_type_name(&get_node_text(&clause, content)),
+                                kind,
+                            );
+                        }
+                    }
+                }
+            }
+            (Language::TypeScript, "interface_declaration") => {
+                let name = node
+                    .child_by_field_name("name")
+                    .map(|n| get_node_text(&n, content))
+                    .unwrap_or_default();
+                for clause in named_children(&node).filter(|c| c.kind() == "extends_type_clause") {
+                    for parent in named_children(&clause) {
+                        push(
+                            &name,
+                            &base_type_name(&get_node_text(&parent, content)),
+                            RelationKind::Extends,
+                        );
+                    }
+                }
+            }
+            (Language::Python, "class_definition") => {
+                if let (Some(name), Some(bases)) = (
+                    node.child_by_field_name("name"),
+                    node.child_by_field_name("superclasses"),
+                ) {
+                    let name = get_node_text(&name, content);
+                    for base in named_children(&bases)
+                        .filter(|b| matches!(b.kind(), "identifier" | "attribute" | "subscript"))
+                    {
+                        let parent = base_type_name(&get_node_text(&base, content));
+                        if parent != "object" {
+                            push(&name, &parent, RelationKind::Extends);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        if cursor.goto_first_child() {
+            continue;
+        }
+
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                return relations;
+            }
+        }
+    }
+}
+
+/// Collect Go interface method lists and receiver methods per type
+fn extract_go_method_sets(root: &tree_sitter::Node, content: &str) -> Vec<MethodSet> {
+    let mut sets: Vec<MethodSet> = Vec::new();
+    let mut cursor = root.walk();
+
+    loop {
+        let node = cursor.node();
+
+        match node.kind() {
+            "type_spec" => {
+                if let (Some(name), Some(ty)) = (
+                    node.child_by_field_name("name"),
+                    node.child_by_field_name("type"),
+                ) {
+                    if ty.kind() == "interface_type" {
+                        let methods = named_children(&ty)
+                            .filter(|m| matches!(m.kind(), "method_elem" | "method_spec"))
+                            .filter_map(|m| m.child_by_field_name("name"))
+                            .map(|n| get_node_text(&n, content))
+                            .collect();
+                        sets.push(MethodSet {
+                            type_name: get_node_text(&name, content),
+                            is_interface: true,
+                            methods,
+                            line: node.start_position().row + 1,
+                        });
+                    }
+                }
+            }
+            "method_declaration" => {
+                let receiver = node
+                    .child_by_field_name("receiver")
+                    .and_then(|r| named_children(&r).next())
+                    .and_then(|p| p.child_by_field_name("type"))
+                    .map(|t| base_type_name(&get_node_text(&t, content)));
+                let method = node
+                    .child_by_field_name("name")
+                    .map(|n| get_node_text(&n, content));
+                if let (Some(receiver), Some(method)) = (receiver, method) {
+                    match sets
+                        .iter_mut()
+                        .find(|s| !s.is_interface && s.type_name == receiver)
+                    {
+                        Some(set) => set.methods.push(method),
+                        None => sets.push(MethodSet {
+                            type_name: receiver,
+                            is_interface: false,
+                            methods: vec![method],
+                            line: node.start_position().row + 1,
+                        }),
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        if cursor.goto_first_child() {
+            continue;
+        }
+
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                return sets;
+            }
+        }
+    }
+}
+
+/// Reduce a type reference to its bare name: `&mut foo::Bar<T>` -> `Bar`, `*Repo` -> `Repo`
+fn base_type_name(text: &str) -> String {
+    let trimmed = text
+        .trim()
+        .trim_start_matches('&')
+        .trim_start_matches("mut ")
+        .trim_start_matches("dyn ")
+        .trim_start_matches('*')
+        .trim();
+    let without_generics = trimmed
+        .split(['<', '[', '('])
+        .next()
+        .unwrap_or(trimmed)
+        .trim();
+    without_generics
+        .rsplit(['.', ':'])
+        .next()
+        .unwrap_or(without_generics)
+        .to_string()
+}
+
+fn named_children<'a>(
+    node: &tree_sitter::Node<'a>,
+) -> impl Iterator<Item = tree_sitter::Node<'a>> + 'a {
+    let node = *node;
+    (0..node.named_child_count()).filter_map(move |i| node.named_child(i))
+}
+
+// Security sinks
+
+/// Programs that interpret their arguments as a shell script
+const SHELL_PROGRAMS: &[&str] = &["sh", "bash", "zsh", "dash", "cmd", "cmd.exe", "powershell"];
+
+/// Method names that send a query string to a database driver
+const SQL_METHODS: &[&str] = &[
+    "execute",
+    "executemany",
+    "executescript",
+    "execute_batch",
+    "query",
+    "query_row",
+    "query_map",
+    "prepare",
+    "raw",
+    "Query",
+    "QueryRow",
+    "QueryContext",
+    "QueryRowContext",
+    "Exec",
+    "ExecContext",
+    "Prepare",
+    "PrepareContext",
+    "Raw",
+    "$queryRawUnsafe",
+    "$executeRawUnsafe",
+];
+
+/// Whether any attribute marks test code: `#[cfg(test)]`, `#[test]` or a
+/// runner's `#[tokio::test]`. Comments and strings mentioning them don't count.
+fn has_rust_test_items(root: &tree_sitter::Node, content: &str) -> bool {
+    let mut cursor = root.walk();
+
+    loop {
+        let node = cursor.node();
+        if node.kind() == "attribute_item" {
+            let attribute: String = get_node_text(&node, content)
+                .trim_start_matches("#[")
+                .trim_end_matches(']')
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect();
+            if attribute == "cfg(test)" || attribute.rsplit("::").next() == Some("test") {
+                return true;
+            }
+        }
+
+        if cursor.goto_first_child() {
+            continue;
+        }
+
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                return false;
+            }
+        }
+    }
+}
+
+/// Walk the AST and flag injection-style sinks that receive non-constant input
+fn detect_injection_sinks(
+    root: &tree_sitter::Node,
+    content: &str,
+    path: &Path,
+    language: Language,
+) -> Vec<Pattern> {
+    let mut patterns = Vec::new();
+    let mut cursor = root.walk();
+
+    loop {
+        let node = cursor.node();
+
+        let finding = match language {
+            Language::Rust => rust_injection_sink(&node, content),
+            Language::JavaScript | Language::TypeScript => js_injection_sink(&node, content),
+            Language::Python => python_injection_sink(&node, content),
+            Language::Go => go_injection_sink(&node, content),
+            _ => None,
+        };
+
+        if let Some(description) = finding {
+            patterns.push(Pattern {
+                kind: PatternKind::InjectionRisk,
+                file: path.to_path_buf(),
+                line: node.start_position().row + 1,
+                description,
+            });
+        }
+
+        if cursor.goto_first_child() {
+            continue;
+        }
+
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                return patterns;
+            }
+        }
+    }
+}
+
+fn rust_injection_sink(node: &tree_sitter::Node, content: &str) -> Option<String> {
+    if node.kind() != "call_expression" {
+        return None;
+    }
+    let function = node.child_by_field_name("function")?;
+    let first_arg = node.child_by_field_name("arguments")?.named_child(0)?;
+
+    match function.kind() {
+        "field_expression" => {
+            let method = get_node_text(&function.child_by_field_name("field")?, content);
+            if SQL_METHODS.contains(&method.as_str()) && is_formatted_string(&first_arg, content) {
+                return Some(format!(
+                    "SQL built with format!() passed to {}() - use bound parameters",
+                    method
+                ));
+            }
+        }
+        "scoped_identifier" | "identifier" => {
+            let name = get_node_text(&function, content);
+            if name.ends_with("Command::new") {
+                if is_formatted_string(&first_arg, content) {
+                    return Some("Command::new() program built from formatted input".to_string());
+                }
+                let program = string_literal_value(&first_arg, content)?;
+                if SHELL_PROGRAMS.contains(&program.as_str())
+                    && chain_has_formatted_arg(node, content)
+                {
+                    return Some(format!(
+                        "Shell command run via Command::new(\"{}\") with interpolated arguments",
+                        program
+                    ));
+                }
+            } else if (name.ends_with("::query") || name.ends_with("::query_as"))
+                && is_formatted_string(&first_arg, content)
+            {
+                return Some(format!(
+                    "SQL built with format!() passed to {}() - use bind()",
+                    name
+                ));
+            }
+        }
+        _ => {}
+    }
+
+    None
+}
+
+fn js_injection_sink(node: &tree_sitter::Node, content: &str) -> Option<String> {
+    match node.kind() {
+        "call_expression" => {
+            let function = node.child_by_field_name("function")?;
+            let first_arg = node.child_by_field_name("arguments")?.named_child(0)?;
+            let (object, name) = match function.kind() {
+                "identifier" => (String::new(), get_node_text(&function, content)),
+                "member_expression" => (
+                    function
+                        .child_by_field_name("object")
+                        .map(|o| get_node_text(&o, content))
+                        .unwrap_or_default(),
+                    get_node_text(&function.child_by_field_name("property")?, content),
+                ),
+                _ => return None,
+            };
+
+            if name == "eval" && object.is_empty() && !is_constant_string(&first_arg) {
+                return Some("eval() called with a non-constant argument".to_string());
+            }
+            if matches!(name.as_str(), "exec" | "execSync")
+                && (object.is_empty() || object.ends_with("child_process") || object == "cp")
+                && is_formatted_string(&first_arg, content)
+            {
+                return Some(format!(
+                    "Shell command built with string interpolation passed to {}()",
+                    name
+                ));
+            }
+            if SQL_METHODS.contains(&name.as_str())
+                && !object.is_empty()
+                && is_formatted_string(&first_arg, content)
+            {
+                return Some(format!(
+                    "SQL built with string interpolation passed to {}() - use placeholders",
+                    name
+                ));
+            }
+            if matches!(name.as_str(), "write" | "writeln")
+                && object == "document"
+                && !is_constant_string(&first_arg)
+            {
+                return Some(format!("document.{}() called with non-constant HTML", name));
+            }
+            if name == "insertAdjacentHTML" {
+                let html = node.child_by_field_name("arguments")?.named_child(1)?;
+                if !is_constant_string(&html) {
+                    return Some("insertAdjacentHTML() called with non-constant HTML".to_string());
+                }
+            }
+            None
+        }
+        "new_expression" => {
+            let constructor = node.child_by_field_name("constructor")?;
+            if get_node_text(&constructor, content) != "Function" {
+                return None;
+            }
+            let args = node.child_by_field_name("arguments")?;
+            let dynamic = (0..args.named_child_count())
+                .filter_map(|i| args.named_child(i))
+                .any(|arg| !is_constant_string(&arg));
+            dynamic.then(|| "new Function() built from non-constant source".to_string())
+        }
+        "assignment_expression" | "augmented_assignment_expression" => {
+            let left = node.child_by_field_name("left")?;
+            let right = node.child_by_field_name("right")?;
+            if left.kind() != "member_expression" {
+                return None;
+            }
+            let property = get_node_text(&left.child_by_field_name("property")?, content);
+            if matches!(property.as_str(), "innerHTML" | "outerHTML") && !is_constant_string(&right)
+            {
+                return Some(format!("{} assigned a non-constant value", property));
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+fn python_injection_sink(node: &tree_sitter::Node, content: &str) -> Option<String> {
+    if node.kind() != "call" {
+        return None;
+    }
+    let function = node.child_by_field_name("function")?;
+    let args = node.child_by_field_name("arguments")?;
+    let first_arg = args.named_child(0)?;
+    if first_arg.kind() == "keyword_argument" {
+        return None;
+    }
+
+    let name = get_node_text(&function, content);
+    let method = match function.kind() {
+        "attribute" => get_node_text(&function.child_by_field_name("attribute")?, content),
+        _ => name.clone(),
+    };
+
+    if matches!(name.as_str(), "eval" | "exec") && !is_constant_string(&first_arg) {
+        return Some(format!("{}() called with a non-constant argument", name));
+    }
+
+    if matches!(
+        name.as_str(),
+        "os.system" | "os.popen" | "subprocess.getoutput" | "subprocess.getstatusoutput"
+    ) && !is_constant_string(&first_arg)
+    {
+        return Some(format!(
+            "Shell command passed to {}() is not constant",
+            name
+        ));
+    }
+
+    if name.starts_with("subprocess.") && !is_constant_string(&first_arg) {
+        let shell_true = (0..args.named_child_count())
+            .filter_map(|i| args.named_child(i))
+            .filter(|arg| arg.kind() == "keyword_argument")
+            .any(|arg| {
+                let key = arg
+                    .child_by_field_name("name")
+                    .map(|n| get_node_text(&n, content));
+                let value = arg
+                    .child_by_field_name("value")
+                    .map(|v| get_node_text(&v, content));
+                key.as_deref() == Some("shell") && value.as_deref() == Some("True")
+            });
+        if shell_true {
+            return Some(format!(
+                "{}() runs a non-constant command with shell=True",
+                name
+            ));
+        }
+    }
+
+    if function.kind() == "attribute"
+        && SQL_METHODS.contains(&method.as_str())
+        && is_formatted_string(&first_arg, content)
+    {
+        return Some(format!(
+            "SQL built with string formatting passed to {}() - use query parameters",
+            method
+        ));
+    }
+
+    None
+}
+
+fn go_injection_sink(node: &tree_sitter::Node, content: &str) -> Option<String> {
+    if node.kind() != "call_expression" {
+        return None;
+    }
+    let function = node.child_by_field_name("function")?;
+    if function.kind() != "selector_expression" {
+        return None;
+    }
+    let args = node.child_by_field_name("arguments")?;
+    let arg_nodes: Vec<_> = (0..args.named_child_count())
+        .filter_map(|i| args.named_child(i))
+        .collect();
+    let name = get_node_text(&function, content);
+    let method = get_node_text(&function.child_by_field_name("field")?, content);
+
+    if name == "exec.Command" || name == "exec.CommandContext" {
+        // CommandContext takes the context first
+        let program_idx = usize::from(name == "exec.CommandContext");
+        let program = arg_nodes.get(program_idx)?;
+        if is_formatted_string(program, content) {
+            return Some(format!("{}() program built from formatted input", name));
+        }
+        let is_shell = string_literal_value(program, content)
+            .map(|p| SHELL_PROGRAMS.contains(&p.as_str()))
+            .unwrap_or(false);
+        if is_shell
+            && arg_nodes[program_idx + 1..]
+                .iter()
+                .any(|arg| is_formatted_string(arg, content))
+        {
+            return Some(format!(
+                "Shell command built with string formatting passed to {}()",
+                name
+            ));
+        }
+        return None;
+    }
+
+    // Only the query itself matters; later arguments are bound parameters.
+    // The `*Context` variants take the context first.
+    let query_idx = usize::from(method.ends_with("Context"));
+    if SQL_METHODS.contains(&method.as_str())
+        && arg_nodes
+            .get(query_idx)
+            .is_some_and(|query| is_formatted_string(query, content))
+    {
+        return Some(format!(
+            "SQL built with string formatting passed to {}() - use placeholders",
+            method
+        ));
+    }
+
+    None
+}
+
+/// True if the node is a string literal with no interpolation
+fn is_constant_string(node: &tree_sitter::Node) -> bool {
+    match node.kind() {
+        "string_literal" | "raw_string_literal" | "interpreted_string_literal" => true,
+        // Python f-strings and JS template literals are only constant without substitutions
+        "string" | "template_string" => {
+            !has_child_kind(node, "interpolation") && !has_child_kind(node, "template_substitution")
+        }
+        "concatenated_string" => (0..node.named_child_count())
+            .filter_map(|i| node.named_child(i))
+            .all(|c| is_constant_string(&c)),
+        "parenthesized_expression" => node
+            .named_child(0)
+            .map(|c| is_constant_string(&c))
+            .unwrap_or(false),
+        "binary_expression" | "binary_operator" => {
+            match (
+                node.child_by_field_name("left"),
+                node.child_by_field_name("right"),
+            ) {
+                (Some(l), Some(r)) => is_constant_string(&l) && is_constant_string(&r),
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// True if the node builds a string from non-constant parts
+/// (format!, f-strings, `%`/`.format()`, template literals, `+` concatenation, Sprintf)
+fn is_formatted_string(node: &tree_sitter::Node, content: &str) -> bool {
+    match node.kind() {
+        "macro_invocation" => node
+            .child_by_field_name("macro")
+            .map(|m| get_node_text(&m, content) == "format")
+            .unwrap_or(false),
+        "reference_expression" | "parenthesized_expression" => node
+            .child_by_field_name("value")
+            .or_else(|| node.named_child(0))
+            .map(|v| is_formatted_string(&v, content))
+            .unwrap_or(false),
+        "string" | "template_string" | "concatenated_string" => !is_constant_string(node),
+        "binary_expression" | "binary_operator" => {
+            let operator = node
+                .child_by_field_name("operator")
+                .map(|o| get_node_text(&o, content))
+                .unwrap_or_default();
+            let (Some(left), Some(right)) = (
+                node.child_by_field_name("left"),
+                node.child_by_field_name("right"),
+            ) else {
+                return false;
+            };
+            let touches_string = is_string_like(&left) || is_string_like(&right);
+            match operator.as_str() {
+                "+" => touches_string && !is_constant_string(node),
+                "%" => is_string_like(&left),
+                _ => false,
+            }
+        }
+        "call" | "call_expression" => {
+            let Some(function) = node.child_by_field_name("function") else {
+                return false;
+            };
+            let name = get_node_text(&function, content);
+            name == "fmt.Sprintf"
+                || (function.kind() == "attribute"
+                    && name.ends_with(".format")
+                    && function
+                        .child_by_field_name("object")
+                        .map(|o| is_string_like(&o))
+                        .unwrap_or(false))
+        }
+        _ => false,
+    }
+}
+
+fn is_string_like(node: &tree_sitter::Node) -> bool {
+    matches!(
+        node.kind(),
+        "string"
+            | "string_literal"
+            | "raw_string_literal"
+            | "interpreted_string_literal"
+            | "template_string"
+            | "concatenated_string"
+    )
+}
+
+fn has_child_kind(node: &tree_sitter::Node, kind: &str) -> bool {
+    (0..node.named_child_count())
+        .filter_map(|i| node.named_child(i))
+        .any(|c| c.kind() == kind)
+}
+
+/// Text of a plain string literal without its quotes
+fn string_literal_value(node: &tree_sitter::Node, content: &str) -> Option<String> {
+    if !is_constant_string(node) || !is_string_like(node) {
+        return None;
+    }
+    Some(
+        get_node_text(node, content)
+            .trim_matches(|c| c == '"' || c == '\'' || c == '`')
+            .to_string(),
+    )
+}
+
+/// Check a Rust builder chain like `Command::new("sh").arg("-c").arg(format!(..))`
+/// for a formatted argument anywhere after the constructor call
+fn chain_has_formatted_arg(node: &tree_sitter::Node, content: &str) -> bool {
+    let mut current = *node;
+    while let Some(parent) = current.parent() {
+        if !matches!(parent.kind(), "field_expression" | "call_expression") {
+            break;
+        }
+        if parent.kind() == "call_expression" {
+            let is_arg_call = parent
+                .child_by_field_name("function")
+                .and_then(|f| f.child_by_field_name("field"))
+                .map(|f| matches!(get_node_text(&f, content).as_str(), "arg" | "args"))
+                .unwrap_or(false);
+            if is_arg_call && subtree_has_format(&parent, content) {
+                return true;
+            }
+        }
+        current = parent;
+    }
+    false
+}
+
+fn subtree_has_format(node: &tree_sitter::Node, content: &str) -> bool {
+    let Some(args) = node.child_by_field_name("arguments") else {
+        return false;
+    };
+    let mut cursor = args.walk();
+    loop {
+        if is_formatted_string(&cursor.node(), content) {
+            return true;
+        }
+        if cursor.goto_first_child() {
+            continue;
+        }
+        loop {
+            if cursor.node() == args {
+                return false;
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+            if !cursor.goto_parent() {
+                return false;
+            }
+        }
+    }
+}
+
 // Helper functions
 
 fn get_node_text(node: &tree_sitter::Node, content: &str) -> String {
@@ -664,7 +1422,9 @@ mod tests {
             }
         "#;
 
-        let (symbols, _) = parse_file(Path::new("test.rs"), content, Language::Rust).unwrap();
+        let symbols = parse_file(Path::new("test.rs"), content, Language::Rust)
+            .unwrap()
+            .symbols;
 
         assert!(!symbols.is_empty());
     }
@@ -681,8 +1441,158 @@ mod tests {
             }
         "#;
 
-        let (symbols, _) = parse_file(Path::new("test.js"), content, Language::JavaScript).unwrap();
+        let symbols = parse_file(Path::new("test.js"), content, Language::JavaScript)
+            .unwrap()
+            .symbols;
 
         assert!(!symbols.is_empty());
     }
+
+    #[test]
+    fn test_rust_inline_tests_ignore_comments_and_strings() {
+        let has_tests = |content: &str| {
+            parse_file(Path::new("lib.rs"), content, Language::Rust)
+                .unwrap()
+                .has_inline_tests
+        };
+        assert!(has_tests("#[cfg(test)]\nmod tests {}\n"));
+        assert!(has_tests("#[test]\nfn works() {}\n"));
+        assert!(has_tests("#[tokio::test]\nasync fn works() {}\n"));
+        assert!(!has_tests(
+            "// add #[cfg(test)] later\nconst HINT: &str = \"#[cfg(test)]\";\n"
+        ));
+        assert!(!has_tests("#[cfg(not(test))]\nfn real() {}\n"));
+    }
+
+    fn injection_lines(path: &str, content: &str, language: Language) -> Vec<usize> {
+        parse_file(Path::new(path), content, language)
+            .unwrap()
+            .patterns
+            .iter()
+            .filter(|p| p.kind == PatternKind::InjectionRisk)
+            .map(|p| p.line)
+            .collect()
+    }
+
+    #[test]
+    fn test_python_injection_sinks() {
+        let content = r#"
+cursor.execute(f"SELECT * FROM users WHERE id = {user_id}")
+cursor.execute("SELECT * FROM users WHERE id = %s", (user_id,))
+cursor.execute("DELETE FROM t WHERE name = '%s'" % name)
+subprocess.run(f"ls {path}", shell=True)
+subprocess.run(["ls", path])
+eval(expr)
+eval("1 + 1")
+"#;
+        assert_eq!(
+            injection_lines("app.py", content, Language::Python),
+            vec![2, 4, 5, 7]
+        );
+    }
+
+    #[test]
+    fn test_js_injection_sinks() {
+        let content = r#"
+el.innerHTML = userInput;
+el.innerHTML = "<b>static</b>";
+eval(code);
+const f = new Function("a", body);
+db.query(`SELECT * FROM users WHERE id = ${id}`);
+db.query("SELECT * FROM users WHERE id = ?", [id]);
+re.exec(text);
+"#;
+        assert_eq!(
+            injection_lines("app.js", content, Language::JavaScript),
+            vec![2, 4, 5, 6]
+        );
+    }
+
+    #[test]
+    fn test_rust_injection_sinks() {
+        let content = r#"
+fn run(name: &str) {
+    Command::new("sh").arg("-c").arg(format!("echo {}", name)).output();
+    Command::new("git").arg(format!("--author={}", name)).output();
+    conn.execute(&format!("DELETE FROM t WHERE name = '{}'", name), []);
+    conn.execute("DELETE FROM t WHERE name = ?1", [name]);
+}
+"#;
+        assert_eq!(
+            injection_lines("lib.rs", content, Language::Rust),
+            vec![3, 5]
+        );
+    }
+
+    #[test]
+    fn test_go_injection_sinks() {
+        let content = r#"
+package main
+
+func run(name string) {
+    exec.Command("sh", "-c", "echo "+name)
+    exec.Command("git", "log", name)
+    db.Query(fmt.Sprintf("SELECT * FROM t WHERE name = '%s'", name))
+    db.Query("SELECT * FROM t WHERE name = $1", name)
+    db.Query("SELECT * FROM t WHERE id = ?", fmt.Sprint(id))
+    db.QueryContext(ctx, "SELECT * FROM t WHERE id = ?", "x"+id)
+    db.ExecContext(ctx, "DELETE FROM t WHERE id = "+id)
+}
+"#;
+        assert_eq!(
+            injection_lines("main.go", content, Language::Go),
+            vec![5, 7, 11]
+        );
+    }
+
+    fn relation_triples(path: &str, content: &str, language: Language) -> Vec<String> {
+        parse_file(Path::new(path), content, language)
+            .unwrap()
+            .relations
+            .iter()
+            .map(|r| format!("{} {} {}", r.type_name, r.kind.label(), r.parent))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_type_relations() {
+        let content = r#"
+pub trait Store: Send + Sync {}
+impl Store for MemoryStore {}
+impl<T: Clone> std::fmt::Display for Wrapper<T> {}
+impl MemoryStore { fn new() -> Self { MemoryStore } }
+"#;
+        assert_eq!(
+            relation_triples("lib.rs", content, Language::Rust),
+            vec![
+                "Store extends Send",
+                "Store extends Sync",
+                "MemoryStore implements Store",
+                "Wrapper implements Display",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ts_and_python_type_relations() {
+        let ts = r#"
+interface Repo extends Base<User> {}
+class PgRepo extends SqlRepo implements Repo, Disposable {}
+"#;
+        assert_eq!(
+            relation_triples("repo.ts", ts, Language::TypeScript),
+            vec![
+                "Repo extends Base",
+                "PgRepo extends SqlRepo",
+                "PgRepo implements Repo",
+                "PgRepo implements Disposable",
+            ]
+        );
+
+        let py = "class Handler(base.BaseHandler, Generic[T], metaclass=ABCMeta):\n    pass\n";
+        assert_eq!(
+            relation_triples("handler.py", py, Language::Python),
+            vec!["Handler extends BaseHandler", "Handler extends Generic"]
+        );
+    }
 }
@@ -0,0 +1,706 @@
+//! Line-based indexing for project files without a tree-sitter grammar
+//!
+//! Dockerfiles, CI/compose YAML, Terraform, SQL and shell scripts don't need
+//! a full parse to be useful: stages, jobs, resources, tables and functions
+//! give the grouping and analysis passes something to work with, and a few
+//! cheap checks (unpinned images/actions/modules, inline secrets, scripts
+//! that ignore errors) surface the usual infra fixes.
+
+use super::parser::ParsedFile;
+use super::{Dependency, Language, Pattern, PatternKind, Symbol, SymbolKind, Visibility};
+use regex::Regex;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Extract symbols, dependencies and patterns from a project file
+pub fn parse(path: &Path, content: &str, language: Language) -> ParsedFile {
+    let mut parsed = ParsedFile::default();
+    match language {
+        Language::Dockerfile => parse_dockerfile(path, content, &mut parsed),
+        Language::Yaml => parse_pipeline_yaml(path, content, &mut parsed),
+        Language::Terraform => parse_terraform(path, content, &mut parsed),
+        Language::Sql => parse_sql(path, content, &mut parsed),
+        Language::Shell => parse_shell(path, content, &mut parsed),
+        _ => {}
+    }
+    parsed
+}
+
+fn symbol(name: &str, kind: SymbolKind, line: usize, end_line: usize) -> Symbol {
+    Symbol {
+        name: name.to_string(),
+        kind,
+        line,
+        end_line: end_line.max(line),
+        complexity: 1.0,
+        visibility: Visibility::Public,
+    }
+}
+
+fn pattern(path: &Path, kind: PatternKind, line: usize, description: String) -> Pattern {
+    Pattern {
+        kind,
+        file: path.to_path_buf(),
+        line,
+        description,
+    }
+}
+
+fn external(path: &Path, import_path: &str, line: usize) -> Dependency {
+    Dependency {
+        from_file: path.to_path_buf(),
+        import_path: import_path.to_string(),
+        line,
+        is_external: true,
+    }
+}
+
+/// `KEY=value` / `key: value` pairs that look like a credential written inline.
+/// References (`$VAR`, `${{ secrets.X }}`, `var.x`) are fine.
+fn is_inline_secret(key: &str, value: &str) -> bool {
+    let key = key.to_lowercase().replace('-', "_");
+    let secret_key = [
+        "password",
+        "passwd",
+        "secret",
+        "token",
+        "api_key",
+        "apikey",
+        "access_key",
+        "private_key",
+    ]
+    .iter()
+    .any(|k| key.contains(k));
+    if !secret_key {
+        return false;
+    }
+
+    let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+    value.len() >= 4
+        && !value.starts_with(['$', '{', '<', '('])
+        && !value.contains("${")
+        && !value.starts_with("var.")
+        && !value.starts_with("local.")
+        && !["true", "false", "null", "none", "changeme"].contains(&value.to_lowercase().as_str())
+}
+
+fn secret_pattern(path: &Path, line: usize, key: &str) -> Pattern {
+    pattern(
+        path,
+        PatternKind::HardcodedSecret,
+        line,
+        format!("{} is set to a literal value", key.trim()),
+    )
+}
+
+/// Container image without a digest whose tag is missing or `latest`
+fn is_unpinned_image(image: &str) -> bool {
+    if image.contains('@') || image.starts_with('$') || image == "scratch" {
+        return false;
+    }
+    let last_segment = image.rsplit('/').next().unwrap_or(image);
+    match last_segment.rsplit_once(':') {
+        Some((_, tag)) => tag == "latest",
+        None => true,
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  DOCKERFILE
+// ═══════════════════════════════════════════════════════════════════════════
+
+fn parse_dockerfile(path: &Path, content: &str, parsed: &mut ParsedFile) {
+    let mut stage_names: Vec<String> = Vec::new();
+    let mut open_stage: Option<usize> = None;
+    let mut last_line = 0;
+
+    for (i, raw) in content.lines().enumerate() {
+        let line_no = i + 1;
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (instruction, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+
+        match instruction.to_uppercase().as_str() {
+            "FROM" => {
+                if let Some(idx) = open_stage {
+                    parsed.symbols[idx].end_line = last_line;
+                }
+                let args: Vec<&str> = rest
+                    .split_whitespace()
+                    .filter(|a| !a.starts_with("--"))
+                    .collect();
+                let Some(image) = args.first().copied() else {
+                    continue;
+                };
+                let alias = match args.as_slice() {
+                    [_, as_kw, alias, ..] if as_kw.eq_ignore_ascii_case("as") => Some(*alias),
+                    _ => None,
+                };
+
+                // `FROM builder` refers to an earlier stage, not an image
+                if !stage_names.iter().any(|s| s == image) {
+                    parsed.dependencies.push(external(path, image, line_no));
+                    if is_unpinned_image(image) {
+                        parsed.patterns.push(pattern(
+                            path,
+                            PatternKind::UnpinnedVersion,
+                            line_no,
+                            format!("Base image {} isn't pinned to a version", image),
+                        ));
+                    }
+                }
+
+                let name = alias.unwrap_or(image);
+                stage_names.push(name.to_string());
+                open_stage = Some(parsed.symbols.len());
+                parsed
+                    .symbols
+                    .push(symbol(name, SymbolKind::Stage, line_no, line_no));
+            }
+            "ENV" | "ARG" => {
+                let (key, value) = rest
+                    .split_once('=')
+                    .or_else(|| rest.split_once(char::is_whitespace))
+                    .unwrap_or((rest, ""));
+                if is_inline_secret(key, value) {
+                    parsed.patterns.push(secret_pattern(path, line_no, key));
+                }
+            }
+            _ => {}
+        }
+        last_line = line_no;
+    }
+
+    if let Some(idx) = open_stage {
+        parsed.symbols[idx].end_line = last_line;
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  CI / COMPOSE YAML
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Top-level GitLab CI keys that configure the pipeline rather than name a job
+const GITLAB_RESERVED_KEYS: &[&str] = &[
+    "stages",
+    "variables",
+    "include",
+    "default",
+    "workflow",
+    "image",
+    "services",
+    "cache",
+    "before_script",
+    "after_script",
+];
+
+fn parse_pipeline_yaml(path: &Path, content: &str, parsed: &mut ParsedFile) {
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let gitlab =
+        name.starts_with(".gitlab-ci.") || path.components().any(|c| c.as_os_str() == ".gitlab");
+
+    let mut section = String::new();
+    let mut child_indent: Option<usize> = None;
+    let mut open: Option<usize> = None;
+    let mut last_line = 0;
+
+    let close = |parsed: &mut ParsedFile, open: &mut Option<usize>, end: usize| {
+        if let Some(idx) = open.take() {
+            parsed.symbols[idx].end_line = end;
+        }
+    };
+
+    for (i, raw) in content.lines().enumerate() {
+        let line_no = i + 1;
+        let trimmed = raw.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = raw.len() - raw.trim_start().len();
+        let key = yaml_key(trimmed);
+
+        if indent == 0 {
+            close(parsed, &mut open, last_line);
+            section = key.unwrap_or_default().to_string();
+            child_indent = None;
+            if gitlab
+                && !section.starts_with('.')
+                && !GITLAB_RESERVED_KEYS.contains(&section.as_str())
+            {
+                open = Some(parsed.symbols.len());
+                parsed
+                    .symbols
+                    .push(symbol(&section, SymbolKind::Job, line_no, line_no));
+            }
+        } else if section == "jobs" || section == "services" {
+            let level = *child_indent.get_or_insert(indent);
+            if indent == level {
+                if let Some(key) = key {
+                    close(parsed, &mut open, last_line);
+                    let kind = if section == "jobs" {
+                        SymbolKind::Job
+                    } else {
+                        SymbolKind::Resource
+                    };
+                    open = Some(parsed.symbols.len());
+                    parsed.symbols.push(symbol(key, kind, line_no, line_no));
+                }
+            }
+        } else if section == "stages" {
+            if let Some(stage) = trimmed.strip_prefix("- ") {
+                let stage = stage.trim().trim_matches(|c| c == '"' || c == '\'');
+                parsed
+                    .symbols
+                    .push(symbol(stage, SymbolKind::Stage, line_no, line_no));
+            }
+        }
+
+        let item = trimmed.strip_prefix("- ").unwrap_or(trimmed);
+        if let Some((key, value)) = item.split_once(':') {
+            let key = key.trim();
+            let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+            match key {
+                "uses" if !value.is_empty() => {
+                    check_action_ref(path, value, line_no, parsed);
+                }
+                "image" if !value.is_empty() => {
+                    parsed.dependencies.push(external(path, value, line_no));
+                    if is_unpinned_image(value) {
+                        parsed.patterns.push(pattern(
+                            path,
+                            PatternKind::UnpinnedVersion,
+                            line_no,
+                            format!("Image {} isn't pinned to a version", value),
+                        ));
+                    }
+                }
+                _ if !value.is_empty() && is_inline_secret(key, value) => {
+                    parsed.patterns.push(secret_pattern(path, line_no, key));
+                }
+                _ => {}
+            }
+        }
+        last_line = line_no;
+    }
+
+    close(parsed, &mut open, last_line);
+}
+
+/// `name:` at the start of a line (mapping key with a nested value)
+fn yaml_key(trimmed: &str) -> Option<&str> {
+    let key = trimmed.strip_suffix(':')?;
+    (!key.is_empty() && !key.contains(' ') && !key.starts_with('-'))
+        .then(|| key.trim_matches(|c| c == '"' || c == '\''))
+}
+
+/// `uses: owner/action@ref` - branch refs move under you; tags and SHAs don't
+fn check_action_ref(path: &Path, value: &str, line: usize, parsed: &mut ParsedFile) {
+    if value.starts_with("./") {
+        return;
+    }
+    if let Some(image) = value.strip_prefix("docker://") {
+        parsed.dependencies.push(external(path, image, line));
+        return;
+    }
+
+    let (action, reference) = value.split_once('@').unwrap_or((value, ""));
+    parsed.dependencies.push(external(path, action, line));
+    if matches!(reference, "" | "main" | "master" | "latest" | "HEAD") {
+        parsed.patterns.push(pattern(
+            path,
+            PatternKind::UnpinnedVersion,
+            line,
+            format!("Action {} isn't pinned to a tag or commit", value),
+        ));
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  TERRAFORM
+// ═══════════════════════════════════════════════════════════════════════════
+
+fn terraform_block_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r#"^(resource|data|module|variable|output)\s+((?:"[^"]*"\s*)+)"#)
+            .expect("valid terraform block regex")
+    })
+}
+
+fn assignment_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r#"^\s*(?:export\s+)?([A-Za-z_][\w-]*)\s*=\s*(".*"|'.*'|\S+)"#)
+            .expect("valid assignment regex")
+    })
+}
+
+fn parse_terraform(path: &Path, content: &str, parsed: &mut ParsedFile) {
+    let lines: Vec<&str> = content.lines().collect();
+
+    for (i, raw) in lines.iter().enumerate() {
+        let line_no = i + 1;
+        let trimmed = raw.trim();
+
+        if let Some(caps) = terraform_block_re().captures(trimmed) {
+            let block = &caps[1];
+            let labels: Vec<&str> = caps[2]
+                .split('"')
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .collect();
+            let end_line = brace_block_end(&lines, i);
+            let (name, kind) = match (block, labels.as_slice()) {
+                ("resource", [kind, name, ..]) => {
+                    (format!("{}.{}", kind, name), SymbolKind::Resource)
+                }
+                ("data", [kind, name, ..]) => {
+                    (format!("data.{}.{}", kind, name), SymbolKind::Resource)
+                }
+                ("module", [name, ..]) => (format!("module.{}", name), SymbolKind::Module),
+                ("variable", [name, ..]) => (format!("var.{}", name), SymbolKind::Variable),
+                ("output", [name, ..]) => (format!("output.{}", name), SymbolKind::Constant),
+                _ => continue,
+            };
+            parsed.symbols.push(symbol(&name, kind, line_no, end_line));
+
+            if block == "module" {
+                check_module_source(path, &lines[i..end_line], line_no, &name, parsed);
+            }
+            continue;
+        }
+
+        if let Some(caps) = assignment_re().captures(raw) {
+            if is_inline_secret(&caps[1], &caps[2]) {
+                parsed
+                    .patterns
+                    .push(secret_pattern(path, line_no, &caps[1]));
+            }
+        }
+    }
+}
+
+/// Registry modules without a `version` float to whatever was published last
+fn check_module_source(
+    path: &Path,
+    block: &[&str],
+    line: usize,
+    name: &str,
+    parsed: &mut ParsedFile,
+) {
+    let value_of = |key: &str| {
+        block.iter().find_map(|l| {
+            let caps = assignment_re().captures(l)?;
+            (&caps[1] == key).then(|| caps[2].trim_matches('"').to_string())
+        })
+    };
+    let Some(source) = value_of("source") else {
+        return;
+    };
+    if source.starts_with("./") || source.starts_with("../") {
+        return;
+    }
+
+    parsed.dependencies.push(external(path, &source, line));
+    let pinned_by_ref = source.contains("?ref=");
+    let is_registry = !source.contains("::") && !source.contains("://");
+    if value_of("version").is_none() && !pinned_by_ref && is_registry {
+        parsed.patterns.push(pattern(
+            path,
+            PatternKind::UnpinnedVersion,
+            line,
+            format!("{} uses {} without a version constraint", name, source),
+        ));
+    }
+}
+
+/// Line number (1-based) where the `{ ... }` block opened on `start` closes
+fn brace_block_end(lines: &[&str], start: usize) -> usize {
+    let mut depth = 0i32;
+    let mut opened = false;
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        let code = line.split('#').next().unwrap_or(line);
+        for c in code.chars() {
+            match c {
+                '{' => {
+                    depth += 1;
+                    opened = true;
+                }
+                '}' => depth -= 1,
+                _ => {}
+            }
+        }
+        if opened && depth <= 0 {
+            return i + 1;
+        }
+    }
+    lines.len().max(start + 1)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  SQL
+// ═══════════════════════════════════════════════════════════════════════════
+
+fn sql_create_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r#"(?i)^\s*create\s+(?:or\s+replace\s+)?(?:(?:temp|temporary|unlogged|materialized)\s+)*(table|view|function|procedure|type)\s+(?:if\s+not\s+exists\s+)?([\w."`\[\]]+)"#,
+        )
+        .expect("valid sql create regex")
+    })
+}
+
+fn sql_password_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)\b(password|identified\s+by)\s+'[^']{4,}'")
+            .expect("valid sql password regex")
+    })
+}
+
+fn parse_sql(path: &Path, content: &str, parsed: &mut ParsedFile) {
+    let lines: Vec<&str> = content.lines().collect();
+
+    for (i, line) in lines.iter().enumerate() {
+        let line_no = i + 1;
+        if let Some(caps) = sql_create_re().captures(line) {
+            let kind = match caps[1].to_lowercase().as_str() {
+                "table" | "view" => SymbolKind::Table,
+                "type" => SymbolKind::Struct,
+                _ => SymbolKind::Function,
+            };
+            let name: String = caps[2]
+                .chars()
+                .filter(|c| !matches!(c, '"' | '`' | '[' | ']'))
+                .collect();
+            let end_line = statement_end(&lines, i);
+            parsed.symbols.push(symbol(&name, kind, line_no, end_line));
+        }
+        if let Some(caps) = sql_password_re().captures(line) {
+            parsed
+                .patterns
+                .push(secret_pattern(path, line_no, &caps[1].to_uppercase()));
+        }
+    }
+}
+
+/// Line where the statement starting at `start` ends: the first `;` outside a
+/// `$$`-quoted function body
+fn statement_end(lines: &[&str], start: usize) -> usize {
+    let mut dollar_quotes = 0;
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        dollar_quotes += line.matches("$$").count();
+        if dollar_quotes % 2 == 0 && line.trim_end().ends_with(';') {
+            return i + 1;
+        }
+    }
+    lines.len()
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  SHELL
+// ═══════════════════════════════════════════════════════════════════════════
+
+fn shell_function_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"^\s*(?:function\s+([A-Za-z_][\w:-]*)\s*(?:\(\))?|([A-Za-z_][\w:-]*)\s*\(\))\s*\{?\s*$")
+            .expect("valid shell function regex")
+    })
+}
+
+fn parse_shell(path: &Path, content: &str, parsed: &mut ParsedFile) {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut exits_on_error = lines
+        .first()
+        .is_some_and(|l| l.starts_with("#!") && l.split_whitespace().any(|a| a == "-e"));
+
+    for (i, raw) in lines.iter().enumerate() {
+        let line_no = i + 1;
+        let trimmed = raw.trim();
+        if trimmed.starts_with('#') {
+            continue;
+        }
+
+        if trimmed.starts_with("set ")
+            && (trimmed.contains("errexit")
+                || trimmed
+                    .split_whitespace()
+                    .any(|a| a.starts_with('-') && !a.starts_with("--") && a.contains('e')))
+        {
+            exits_on_error = true;
+        }
+
+        if let Some(caps) = shell_function_re().captures(raw) {
+            let name = caps
+                .get(1)
+                .or_else(|| caps.get(2))
+                .map_or("", |m| m.as_str());
+            let end_line = brace_block_end(&lines, i);
+            parsed
+                .symbols
+                .push(symbol(name, SymbolKind::Function, line_no, end_line));
+            continue;
+        }
+
+        if let Some(caps) = assignment_re().captures(raw) {
+            if is_inline_secret(&caps[1], &caps[2]) {
+                parsed
+                    .patterns
+                    .push(secret_pattern(path, line_no, &caps[1]));
+            }
+        }
+    }
+
+    let has_commands = lines
+        .iter()
+        .any(|l| !l.trim().is_empty() && !l.trim().starts_with('#'));
+    if has_commands && !exits_on_error {
+        parsed.patterns.push(pattern(
+            path,
+            PatternKind::MissingErrorHandling,
+            1,
+            "Script keeps going after a failed command (no `set -e`)".to_string(),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(parsed: &ParsedFile, kind: SymbolKind) -> Vec<(String, usize, usize)> {
+        parsed
+            .symbols
+            .iter()
+            .filter(|s| s.kind == kind)
+            .map(|s| (s.name.clone(), s.line, s.end_line))
+            .collect()
+    }
+
+    fn pattern_lines(parsed: &ParsedFile, kind: PatternKind) -> Vec<usize> {
+        parsed
+            .patterns
+            .iter()
+            .filter(|p| p.kind == kind)
+            .map(|p| p.line)
+            .collect()
+    }
+
+    #[test]
+    fn test_dockerfile_stages_and_patterns() {
+        let content = "FROM rust:latest AS builder\nRUN cargo build\n\nFROM debian:bookworm-slim\nCOPY --from=builder /app /app\nENV API_TOKEN=abc123secret\nENV RUST_LOG=info\n";
+        let parsed = parse(Path::new("Dockerfile"), content, Language::Dockerfile);
+
+        assert_eq!(
+            names(&parsed, SymbolKind::Stage),
+            vec![
+                ("builder".to_string(), 1, 2),
+                ("debian:bookworm-slim".to_string(), 4, 7)
+            ]
+        );
+        assert_eq!(parsed.dependencies.len(), 2);
+        assert_eq!(
+            pattern_lines(&parsed, PatternKind::UnpinnedVersion),
+            vec![1]
+        );
+        assert_eq!(
+            pattern_lines(&parsed, PatternKind::HardcodedSecret),
+            vec![6]
+        );
+    }
+
+    #[test]
+    fn test_ci_yaml_jobs() {
+        let github = "name: CI\non: [push]\njobs:\n  build:\n    runs-on: ubuntu-latest\n    steps:\n      - uses: actions/checkout@v4\n      - uses: some/action@main\n  test:\n    runs-on: ubuntu-latest\n    env:\n      DEPLOY_TOKEN: ${{ secrets.DEPLOY_TOKEN }}\n";
+        let parsed = parse(
+            Path::new(".github/workflows/ci.yml"),
+            github,
+            Language::Yaml,
+        );
+        assert_eq!(
+            names(&parsed, SymbolKind::Job),
+            vec![("build".to_string(), 4, 8), ("test".to_string(), 9, 12)]
+        );
+        assert_eq!(
+            pattern_lines(&parsed, PatternKind::UnpinnedVersion),
+            vec![8]
+        );
+        assert!(pattern_lines(&parsed, PatternKind::HardcodedSecret).is_empty());
+
+        let gitlab = "stages:\n  - build\n  - deploy\n\n.template:\n  image: node\n\ncompile:\n  stage: build\n  script: make\n";
+        let parsed = parse(Path::new(".gitlab-ci.yml"), gitlab, Language::Yaml);
+        assert_eq!(
+            names(&parsed, SymbolKind::Stage),
+            vec![("build".to_string(), 2, 2), ("deploy".to_string(), 3, 3)]
+        );
+        assert_eq!(
+            names(&parsed, SymbolKind::Job),
+            vec![("compile".to_string(), 8, 10)]
+        );
+        assert_eq!(
+            pattern_lines(&parsed, PatternKind::UnpinnedVersion),
+            vec![6]
+        );
+    }
+
+    #[test]
+    fn test_terraform_sql_and_shell() {
+        let tf = "module \"vpc\" {\n  source = \"terraform-aws-modules/vpc/aws\"\n}\n\nresource \"aws_db_instance\" \"main\" {\n  password = \"hunter22\"\n  tags = {\n    env = \"prod\"\n  }\n}\n\nvariable \"region\" {}\n";
+        let parsed = parse(Path::new("main.tf"), tf, Language::Terraform);
+        assert_eq!(
+            names(&parsed, SymbolKind::Resource),
+            vec![("aws_db_instance.main".to_string(), 5, 10)]
+        );
+        assert_eq!(
+            names(&parsed, SymbolKind::Module),
+            vec![("module.vpc".to_string(), 1, 3)]
+        );
+        assert_eq!(names(&parsed, SymbolKind::Variable).len(), 1);
+        assert_eq!(
+            pattern_lines(&parsed, PatternKind::UnpinnedVersion),
+            vec![1]
+        );
+        assert_eq!(
+            pattern_lines(&parsed, PatternKind::HardcodedSecret),
+            vec![6]
+        );
+
+        let sql = "CREATE TABLE IF NOT EXISTS \"users\" (\n  id serial primary key\n);\nCREATE OR REPLACE FUNCTION touch() RETURNS trigger AS $$\nBEGIN\n  RETURN NEW;\nEND;\n$$ LANGUAGE plpgsql;\n";
+        let parsed = parse(Path::new("migrations/001.sql"), sql, Language::Sql);
+        assert_eq!(
+            names(&parsed, SymbolKind::Table),
+            vec![("users".to_string(), 1, 3)]
+        );
+        assert_eq!(
+            names(&parsed, SymbolKind::Function),
+            vec![("touch".to_string(), 4, 8)]
+        );
+
+        let sh = "#!/bin/bash\nDB_PASSWORD=\"s3cretpass\"\n\ndeploy() {\n  echo hi\n}\n";
+        let parsed = parse(Path::new("scripts/deploy.sh"), sh, Language::Shell);
+        assert_eq!(
+            names(&parsed, SymbolKind::Function),
+            vec![("deploy".to_string(), 4, 6)]
+        );
+        assert_eq!(
+            pattern_lines(&parsed, PatternKind::HardcodedSecret),
+            vec![2]
+        );
+        assert_eq!(
+            pattern_lines(&parsed, PatternKind::MissingErrorHandling),
+            vec![1]
+        );
+
+        let strict = "#!/usr/bin/env bash\nset -euo pipefail\necho ok\n";
+        let parsed = parse(Path::new("ok.sh"), strict, Language::Shell);
+        assert!(parsed.patterns.is_empty());
+    }
+}
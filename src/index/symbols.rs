@@ -0,0 +1,298 @@
+//! Interned symbol table
+//!
+//! The only in-memory copy of every symbol, built from the per-file
+//! `Symbol` records the parser returns. File paths and names are stored
+//! once and referenced by id; `FileIndex::symbols` is just its file's range
+//! of entries. Symbols are indexed by name, by file (sorted by line) and by
+//! kind, so "where is `Foo` defined" or "what encloses line 120" don't scan
+//! the whole repo.
+
+use super::{Symbol, SymbolKind, Visibility};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// One symbol, with its name and file as ids into the table
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    name: u32,
+    file: u32,
+    kind: SymbolKind,
+    line: u32,
+    end_line: u32,
+    complexity: f32,
+    visibility: Visibility,
+}
+
+/// A symbol as returned by table queries
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymbolRef<'a> {
+    pub name: &'a str,
+    /// Path relative to the repository root
+    pub file: &'a Path,
+    pub kind: SymbolKind,
+    pub line: usize,
+    pub end_line: usize,
+    pub complexity: f64,
+    pub visibility: Visibility,
+}
+
+impl SymbolRef<'_> {
+    pub fn line_count(&self) -> usize {
+        self.end_line.saturating_sub(self.line) + 1
+    }
+
+    /// An owned record, as the parser produces and the cache stores
+    pub fn to_symbol(self) -> Symbol {
+        Symbol {
+            name: self.name.to_string(),
+            kind: self.kind,
+            line: self.line,
+            end_line: self.end_line,
+            complexity: self.complexity,
+            visibility: self.visibility,
+        }
+    }
+}
+
+/// A file's symbols: a range of entries in the table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SymbolIds {
+    start: u32,
+    end: u32,
+}
+
+impl SymbolIds {
+    pub fn len(&self) -> usize {
+        (self.end - self.start) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    /// Sorted, so a path's id is its position
+    paths: Vec<PathBuf>,
+    /// Sorted and deduplicated, so a name's id is its position
+    names: Vec<Box<str>>,
+    /// Grouped by file, then ordered by line (outer symbols first)
+    entries: Vec<Entry>,
+    /// `entries` range for each path id
+    file_ranges: Vec<SymbolIds>,
+    /// Entry ids for each name id
+    by_name: Vec<Vec<u32>>,
+    by_kind: HashMap<SymbolKind, Vec<u32>>,
+}
+
+impl SymbolTable {
+    /// Build the table from each file's parsed symbols, keyed by relative path
+    pub fn build(files: &HashMap<PathBuf, Vec<Symbol>>) -> Self {
+        let mut paths: Vec<&PathBuf> = files.keys().collect();
+        paths.sort();
+
+        let mut names: Vec<&str> = files
+            .values()
+            .flat_map(|symbols| symbols.iter().map(|s| s.name.as_str()))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+
+        let mut entries = Vec::new();
+        let mut file_ranges = Vec::with_capacity(paths.len());
+        for (file_id, path) in paths.iter().enumerate() {
+            let start = entries.len() as u32;
+            let mut symbols: Vec<_> = files[*path].iter().collect();
+            symbols.sort_by_key(|s| (s.line, std::cmp::Reverse(s.end_line)));
+            for symbol in symbols {
+                let name = names
+                    .binary_search(&symbol.name.as_str())
+                    .unwrap_or_default();
+                entries.push(Entry {
+                    name: name as u32,
+                    file: file_id as u32,
+                    kind: symbol.kind,
+                    line: symbol.line as u32,
+                    end_line: symbol.end_line.max(symbol.line) as u32,
+                    complexity: symbol.complexity as f32,
+                    visibility: symbol.visibility,
+                });
+            }
+            file_ranges.push(SymbolIds {
+                start,
+                end: entries.len() as u32,
+            });
+        }
+
+        let mut by_name = vec![Vec::new(); names.len()];
+        let mut by_kind: HashMap<SymbolKind, Vec<u32>> = HashMap::new();
+        for (id, entry) in entries.iter().enumerate() {
+            by_name[entry.name as usize].push(id as u32);
+            by_kind.entry(entry.kind).or_default().push(id as u32);
+        }
+
+        Self {
+            paths: paths.into_iter().cloned().collect(),
+            names: names.into_iter().map(Box::from).collect(),
+            entries,
+            file_ranges,
+            by_name,
+            by_kind,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = SymbolRef<'_>> + '_ {
+        self.entries.iter().map(|e| self.resolve(e))
+    }
+
+    /// Every definition named exactly `name`
+    pub fn find_symbol<'a>(&'a self, name: &str) -> impl Iterator<Item = SymbolRef<'a>> + 'a {
+        let ids = self
+            .names
+            .binary_search_by(|n| n.as_ref().cmp(name))
+            .ok()
+            .map(|id| self.by_name[id].as_slice())
+            .unwrap_or_default();
+        self.ids(ids)
+    }
+
+    pub fn of_kind(&self, kind: SymbolKind) -> impl Iterator<Item = SymbolRef<'_>> + '_ {
+        let ids = self
+            .by_kind
+            .get(&kind)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        self.ids(ids)
+    }
+
+    /// The entry range of a file (relative path); empty if it isn't indexed
+    pub fn ids_of(&self, rel_path: &Path) -> SymbolIds {
+        self.paths
+            .binary_search_by(|p| p.as_path().cmp(rel_path))
+            .map(|id| self.file_ranges[id])
+            .unwrap_or_default()
+    }
+
+    /// The symbols behind a file's ids, in line order
+    pub fn of_file(&self, ids: SymbolIds) -> impl Iterator<Item = SymbolRef<'_>> + '_ {
+        self.entries
+            .get(ids.start as usize..ids.end as usize)
+            .unwrap_or_default()
+            .iter()
+            .map(|e| self.resolve(e))
+    }
+
+    /// Symbols in a file (relative path), in line order
+    pub fn symbols_in_file(&self, rel_path: &Path) -> impl Iterator<Item = SymbolRef<'_>> + '_ {
+        self.of_file(self.ids_of(rel_path))
+    }
+
+    /// Symbols in a file that overlap lines `start..=end`
+    pub fn symbols_in_range(
+        &self,
+        rel_path: &Path,
+        start: usize,
+        end: usize,
+    ) -> Vec<SymbolRef<'_>> {
+        self.symbols_in_file(rel_path)
+            .take_while(|s| s.line <= end)
+            .filter(|s| s.end_line >= start)
+            .collect()
+    }
+
+    /// The innermost symbol enclosing `line` (a method rather than its impl)
+    pub fn symbol_at_line(&self, rel_path: &Path, line: usize) -> Option<SymbolRef<'_>> {
+        self.symbols_in_range(rel_path, line, line)
+            .into_iter()
+            .min_by_key(|s| s.line_count())
+    }
+
+    fn ids<'a>(&'a self, ids: &'a [u32]) -> impl Iterator<Item = SymbolRef<'a>> + 'a {
+        ids.iter()
+            .map(|&id| self.resolve(&self.entries[id as usize]))
+    }
+
+    fn resolve(&self, entry: &Entry) -> SymbolRef<'_> {
+        SymbolRef {
+            name: &self.names[entry.name as usize],
+            file: &self.paths[entry.file as usize],
+            kind: entry.kind,
+            line: entry.line as usize,
+            end_line: entry.end_line as usize,
+            complexity: entry.complexity as f64,
+            visibility: entry.visibility,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::index::{CodebaseIndex, SymbolKind};
+    use std::fs;
+    use std::path::Path;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn test_symbol_table_lookups() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("cosmos_symbols_test_{}", nanos));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(
+            root.join("src/lib.rs"),
+            "pub struct Config {\n    pub name: String,\n}\n\nimpl Config {\n    pub fn new() -> Self {\n        Self { name: String::new() }\n    }\n}\n\nfn helper() {}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/other.rs"),
+            "pub fn new() {}\npub struct Config;\n",
+        )
+        .unwrap();
+
+        let index = CodebaseIndex::new(&root).unwrap();
+        let table = &index.symbols;
+        let lib = Path::new("src/lib.rs");
+
+        let configs: Vec<_> = table.find_symbol("Config").map(|s| s.file).collect();
+        assert!(configs.contains(&lib));
+        assert!(configs.contains(&Path::new("src/other.rs")));
+        assert_eq!(table.find_symbol("missing").count(), 0);
+
+        let inner = table.symbol_at_line(lib, 7).unwrap();
+        assert_eq!((inner.name, inner.line, inner.end_line), ("new", 6, 8));
+        assert!(table.symbol_at_line(lib, 10).is_none());
+
+        let names: Vec<_> = table
+            .symbols_in_range(lib, 9, 11)
+            .iter()
+            .map(|s| s.name)
+            .collect();
+        assert!(names.contains(&"helper"));
+        assert!(!names.contains(&"new"));
+
+        assert!(table
+            .of_kind(SymbolKind::Struct)
+            .all(|s| s.name == "Config"));
+        assert_eq!(
+            table.len(),
+            index.files.values().map(|f| f.symbols.len()).sum::<usize>()
+        );
+        let lib_index = &index.files[lib];
+        assert_eq!(table.ids_of(lib), lib_index.symbols);
+        let records: Vec<_> = index
+            .file_symbols(lib_index)
+            .map(|s| s.to_symbol())
+            .collect();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0].name, "Config");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
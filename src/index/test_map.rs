@@ -0,0 +1,278 @@
+//! Test-to-subject mapping
+//!
+//! Links test files to the source files they exercise, using naming
+//! conventions (`foo_test.go`, `test_foo.py`, `foo.spec.ts`, `tests/foo.rs`)
+//! and the test file's own imports. Rust files with an inline
+//! `#[cfg(test)]` module cover themselves and are flagged separately.
+
+use super::{CodebaseIndex, Language};
+use crate::grouping::heuristics::is_test_file;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Directory names that hold tests rather than the code under test
+const TEST_DIRS: &[&str] = &["test", "tests", "__tests__", "__test__", "spec", "specs"];
+
+/// File stems that never identify a single subject
+const GENERIC_STEMS: &[&str] = &[
+    "mod", "index", "__init__", "conftest", "lib", "main", "test", "tests", "spec", "utils",
+    "helpers", "setup",
+];
+
+/// Map each source file (relative path) to the test files that exercise it
+pub fn build_test_map(index: &CodebaseIndex) -> HashMap<PathBuf, Vec<PathBuf>> {
+    let mut by_stem: HashMap<String, Vec<&PathBuf>> = HashMap::new();
+    let mut by_module: HashMap<String, &PathBuf> = HashMap::new();
+
+    for (path, file_index) in &index.files {
+        if is_test_file(path) {
+            continue;
+        }
+        if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+            by_stem.entry(stem.to_string()).or_default().push(path);
+        }
+        if file_index.language == Language::Python {
+            for suffix in module_suffixes(path) {
+                by_module.entry(suffix).or_insert(path);
+            }
+        }
+    }
+
+    let mut map: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+
+    for (test_path, test_index) in &index.files {
+        if !is_test_file(test_path) {
+            continue;
+        }
+
+        let mut subjects: HashSet<&PathBuf> = HashSet::new();
+
+        // Naming conventions
+        if let Some(stem) = subject_stem(test_path) {
+            if let Some(candidates) = by_stem.get(&stem) {
+                subjects.extend(closest_candidates(test_path, candidates));
+            }
+        }
+
+        // Resolved relative imports (JS/TS, Rust `crate::`/`super::`)
+        for dep in &test_index.summary.depends_on {
+            if let Some((path, _)) = index.files.get_key_value(dep) {
+                if !is_test_file(path) {
+                    subjects.insert(path);
+                }
+            }
+        }
+
+        // Absolute imports the dependency graph doesn't resolve
+        for dep in test_index.dependencies() {
+            match test_index.language {
+                Language::Python => {
+                    if let Some(path) = python_module(&dep.import_path)
+                        .and_then(|module| by_module.get(&module).copied())
+                    {
+                        subjects.insert(path);
+                    }
+                }
+                Language::Rust if dep.is_external => {
+                    for candidate in rust_integration_candidates(&dep.import_path) {
+                        if let Some((path, _)) = index.files.get_key_value(&candidate) {
+                            subjects.insert(path);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for subject in subjects {
+            if subject != test_path {
+                map.entry(subject.clone())
+                    .or_default()
+                    .push(test_path.clone());
+            }
+        }
+    }
+
+    for tests in map.values_mut() {
+        tests.sort();
+        tests.dedup();
+    }
+
+    map
+}
+
+/// The subject's file stem for a test file, e.g. `foo_test.go` -> `foo`,
+/// `test_foo.py` -> `foo`, `foo.spec.ts` -> `foo`, `tests/foo.rs` -> `foo`
+fn subject_stem(test_path: &Path) -> Option<String> {
+    let file_name = test_path.file_name()?.to_str()?;
+    // `foo.test.ts` -> `foo`; `foo_test.go` -> `foo_test`
+    let base = file_name.split('.').next().unwrap_or(file_name);
+
+    let mut stem = base;
+    for prefix in ["test_", "spec_"] {
+        stem = stem.strip_prefix(prefix).unwrap_or(stem);
+    }
+    for suffix in ["_tests", "_test", "_spec"] {
+        stem = stem.strip_suffix(suffix).unwrap_or(stem);
+    }
+
+    if stem.is_empty() || GENERIC_STEMS.contains(&stem) {
+        None
+    } else {
+        Some(stem.to_string())
+    }
+}
+
+/// Pick the candidate subjects nearest to the test: same directory first
+/// (treating `__tests__/` and friends as their parent), otherwise the ones
+/// sharing the longest directory prefix.
+fn closest_candidates<'a>(test_path: &Path, candidates: &[&'a PathBuf]) -> Vec<&'a PathBuf> {
+    let test_dir: Vec<String> = test_path
+        .parent()
+        .map(|p| {
+            p.components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .filter(|c| !TEST_DIRS.contains(&c.as_str()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let score = |candidate: &Path| -> usize {
+        candidate
+            .parent()
+            .map(|p| {
+                p.components()
+                    .zip(test_dir.iter())
+                    .take_while(|(a, b)| a.as_os_str().to_string_lossy() == b.as_str())
+                    .count()
+            })
+            .unwrap_or(0)
+    };
+
+    let same_dir: Vec<&PathBuf> = candidates
+        .iter()
+        .copied()
+        .filter(|c| {
+            c.parent().map(|p| p.components().count()).unwrap_or(0) == test_dir.len()
+                && score(c) == test_dir.len()
+        })
+        .collect();
+    if !same_dir.is_empty() {
+        return same_dir;
+    }
+
+    let best = candidates.iter().map(|c| score(c)).max().unwrap_or(0);
+    let nearest: Vec<&PathBuf> = candidates
+        .iter()
+        .copied()
+        .filter(|c| score(c) == best)
+        .collect();
+
+    // Too many equally distant matches means the name is ambiguous
+    if nearest.len() > 3 {
+        Vec::new()
+    } else {
+        nearest
+    }
+}
+
+/// Dotted module names a Python file can be imported as: `src/app/models.py`
+/// -> `src.app.models`, `app.models`, `models`
+fn module_suffixes(path: &Path) -> Vec<String> {
+    let mut parts: Vec<String> = path
+        .with_extension("")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    if parts.last().map(|p| p == "__init__").unwrap_or(false) {
+        parts.pop();
+    }
+    (0..parts.len()).map(|i| parts[i..].join(".")).collect()
+}
+
+/// Module named by a Python import statement (`from app.models import User`)
+fn python_module(import: &str) -> Option<String> {
+    let import = import.trim();
+    let module = if let Some(rest) = import.strip_prefix("from ") {
+        rest.split(" import").next()?
+    } else {
+        let rest = import.strip_prefix("import ")?;
+        rest.split([',', ' ']).next()?
+    };
+    let module = module.trim().trim_start_matches('.');
+    (!module.is_empty()).then(|| module.to_string())
+}
+
+/// Source files an integration test reaches through the crate's public API:
+/// `mycrate::cache::Cache` -> `src/cache.rs`, `src/cache/mod.rs`
+fn rust_integration_candidates(import: &str) -> Vec<PathBuf> {
+    let mut segments = import.split("::").map(str::trim);
+    let root = segments.next().unwrap_or("");
+    if matches!(root, "std" | "core" | "alloc") {
+        return Vec::new();
+    }
+    match segments.next() {
+        Some(module)
+            if !module.is_empty()
+                && module
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') =>
+        {
+            vec![
+                PathBuf::from(format!("src/{}.rs", module)),
+                PathBuf::from(format!("src/{}/mod.rs", module)),
+            ]
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subject_stem_conventions() {
+        assert_eq!(
+            subject_stem(Path::new("pkg/foo_test.go")).as_deref(),
+            Some("foo")
+        );
+        assert_eq!(
+            subject_stem(Path::new("tests/test_foo.py")).as_deref(),
+            Some("foo")
+        );
+        assert_eq!(
+            subject_stem(Path::new("src/foo.spec.ts")).as_deref(),
+            Some("foo")
+        );
+        assert_eq!(
+            subject_stem(Path::new("tests/cache.rs")).as_deref(),
+            Some("cache")
+        );
+        assert_eq!(subject_stem(Path::new("tests/conftest.py")), None);
+    }
+
+    #[test]
+    fn test_closest_candidates_prefers_same_dir() {
+        let near = PathBuf::from("web/src/Button.tsx");
+        let far = PathBuf::from("admin/Button.tsx");
+        let picked = closest_candidates(
+            Path::new("web/src/__tests__/Button.test.tsx"),
+            &[&near, &far],
+        );
+        assert_eq!(picked, vec![&near]);
+    }
+
+    #[test]
+    fn test_python_module_parsing() {
+        assert_eq!(
+            python_module("from app.models import User").as_deref(),
+            Some("app.models")
+        );
+        assert_eq!(
+            python_module("import app.db as db").as_deref(),
+            Some("app.db")
+        );
+        assert!(module_suffixes(Path::new("src/app/models.py")).contains(&"app.models".into()));
+    }
+}
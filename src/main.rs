@@ -13,6 +13,7 @@ mod github;
 mod grouping;
 mod index;
 mod keyring;
+mod manifest;
 mod onboarding;
 mod suggest;
 mod ui;
@@ -23,7 +24,7 @@ mod util;
 mod git_ops;
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use context::WorkContext;
 use index::CodebaseIndex;
 use std::path::{Path, PathBuf};
@@ -36,7 +37,8 @@ use suggest::SuggestionEngine;
     long_about = "C O S M O S\n\n\
                   Uses AST-based indexing and AI to suggest improvements,\n\
                   bug fixes, features, and optimizations.",
-    version
+    version,
+    args_conflicts_with_subcommands = true
 )]
 struct Args {
     /// Path to the repository (defaults to current directory)
@@ -50,12 +52,49 @@ struct Args {
     /// Authenticate with GitHub for PR creation
     #[arg(long)]
     github_login: bool,
+
+    /// Record every LLM request and response to a cassette file
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Answer LLM requests from a recorded cassette (no network or API key)
+    #[arg(long, value_name = "FILE")]
+    replay: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Write a CycloneDX SBOM listing each dependency and the files that import it
+    Sbom {
+        /// Path to the repository (defaults to current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Write the SBOM to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Keep repo memory and glossary in .cosmos/shared/ so they can be committed
+    Share {
+        /// Path to the repository (defaults to current directory)
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
 
+    match args.command {
+        Some(Command::Sbom { path, output }) => return export_sbom(&path, output.as_deref()),
+        Some(Command::Share { path }) => return share_knowledge(&path),
+        None => {}
+    }
+
     // Handle --setup flag (BYOK mode)
     if args.setup {
         return setup_api_key();
@@ -66,20 +105,33 @@ async fn main() -> Result<()> {
         return github_login().await;
     }
 
-    // Check if onboarding is needed (missing API key or GitHub auth)
-    if onboarding::needs_onboarding() {
+    let path = args.path.canonicalize()?;
+
+    // Record or replay LLM calls
+    if let Some(file) = &args.record {
+        suggest::llm::cassette::install(suggest::llm::cassette::Cassette::record(file, &path)?);
+    } else if let Some(file) = &args.replay {
+        suggest::llm::cassette::install(suggest::llm::cassette::Cassette::replay(file, &path)?);
+    }
+
+    // Check if onboarding is needed (missing API key or GitHub auth). A
+    // replayed session needs neither.
+    if args.replay.is_none() && onboarding::needs_onboarding() {
         onboarding::run_onboarding()
             .await
             .map_err(|e| anyhow::anyhow!("{}", e))?;
 
         // Setup completed, verify API key is accessible
         let mut config = config::Config::load();
-        match config.get_api_key() {
-            Some(_) => {
+        let api_key = config.needs_openrouter_key().then(|| config.get_api_key());
+        match api_key {
+            // Self-hosted backend; no OpenRouter key involved
+            None => {}
+            Some(Some(_)) => {
                 eprintln!("  + API key verified and ready to use");
                 eprintln!();
             }
-            None => {
+            Some(None) => {
                 eprintln!("  ! Warning: API key was saved but cannot be read back.");
                 eprintln!("  ! This may be due to keychain access issues.");
                 eprintln!("  ! Workaround: Set OPENROUTER_API_KEY environment variable.");
@@ -91,30 +143,82 @@ async fn main() -> Result<()> {
         }
     }
 
-    let path = args.path.canonicalize()?;
-
     // Initialize cache
     let cache_manager = cache::Cache::new(&path);
+    let project_config = cache_manager.load_project_config();
+
+    // Resolve model settings before anything talks to an LLM
+    let user_config = config::Config::load();
+    let models = suggest::llm::models::ModelTable::resolve(
+        &user_config.provider_settings(),
+        user_config.models.as_ref(),
+        project_config.models.as_ref(),
+    )
+    .map_err(|e| anyhow::anyhow!("Invalid model configuration:\n{}", e))?;
+    suggest::llm::models::install(models);
+    let budgets = suggest::llm::ledger::Budgets::layered(
+        user_config.budgets.as_ref(),
+        project_config.budgets.as_ref(),
+    )
+    .map_err(|e| anyhow::anyhow!("Invalid budget configuration:\n{}", e))?;
+    suggest::llm::ledger::install(&path, budgets);
+    suggest::llm::audit::install(&path);
 
     // Initialize index (fast, synchronous)
     let index = init_index(&path, &cache_manager)?;
     let context = init_context(&path)?;
 
+    // Drop cache entries for deleted files and keep .cosmos/ under budget
+    let budget = project_config.cache_budget_bytes();
+    if let Ok(report) = cache_manager.collect_garbage(&index, budget) {
+        if !report.evicted.is_empty() {
+            eprintln!(
+                "  Cache over budget, cleared: {}",
+                report.evicted.join(", ")
+            );
+        }
+    }
+
     // Create suggestion engine (LLM suggestions generated on demand)
-    let suggestions = SuggestionEngine::new(index.clone());
+    let mut suggestions = SuggestionEngine::new(index.clone());
+
+    // Static dependency checks run up front - no LLM cost
+    let manifests = manifest::ManifestInventory::load(&path);
+    suggestions.add_static_suggestions(manifests.static_suggestions(&index));
+    let advisories =
+        manifest::advisories::AdvisoryDb::load(&user_config.advisory_db_paths(), &manifests.locked);
+    if !advisories.is_empty() {
+        suggestions
+            .add_static_suggestions(manifests.vulnerability_suggestions(&index, &advisories));
+    }
+    let license_report =
+        manifest::license::LicenseReport::build(&path, &manifests, &project_config.licenses);
+    suggestions.add_static_suggestions(license_report.suggestions());
 
     // Run TUI with background LLM tasks
-    app::run_tui(index, suggestions, context, cache_manager, path).await
+    app::run_tui(
+        index,
+        suggestions,
+        context,
+        cache_manager,
+        path,
+        license_report,
+    )
+    .await
 }
 
 /// Initialize the codebase index
 fn init_index(path: &Path, cache_manager: &cache::Cache) -> Result<CodebaseIndex> {
-    if let Some(index) = cache_manager.load_index_cache(path) {
+    if let Some(cache::CachedIndex { index, reindexed }) = cache_manager.load_index_cache(path) {
         let stats = index.stats();
         eprintln!(
             "  Loaded index cache: {} files, {} symbols",
             stats.file_count, stats.symbol_count
         );
+        if reindexed > 0 {
+            eprintln!("  Re-indexed {} changed files", reindexed);
+            let _ = cache_manager.save_index_cache(&index);
+        }
         if stats.skipped_files > 0 {
             eprintln!(
                 "  Skipped {} files during last index build",
@@ -155,6 +259,50 @@ fn init_index(path: &Path, cache_manager: &cache::Cache) -> Result<CodebaseIndex
     Ok(index)
 }
 
+/// Move memory and glossary into the committed `.cosmos/shared/` layout
+fn share_knowledge(path: &Path) -> Result<()> {
+    let path = path.canonicalize()?;
+    let cache_manager = cache::Cache::new(&path);
+    let (shared_dir, gitignore_updated) = cache_manager.enable_sharing()?;
+    let memory = cache_manager.load_repo_memory();
+    let terms = cache_manager.load_glossary().map_or(0, |g| g.len());
+
+    let shown = shared_dir.strip_prefix(&path).unwrap_or(&shared_dir);
+    eprintln!(
+        "  + Shared {} memory entries and {} glossary terms in {}",
+        memory.entries.len(),
+        terms,
+        shown.display()
+    );
+    if gitignore_updated {
+        eprintln!("  + Updated .gitignore so {} is tracked", shown.display());
+    }
+    eprintln!("  Commit {} to share it with your team", shown.display());
+    Ok(())
+}
+
+/// Write a CycloneDX SBOM for the repository (no LLM, no TUI)
+fn export_sbom(path: &Path, output: Option<&Path>) -> Result<()> {
+    let path = path.canonicalize()?;
+    let cache_manager = cache::Cache::new(&path);
+    let index = init_index(&path, &cache_manager)?;
+    let manifests = manifest::ManifestInventory::load(&path);
+    let license_policy = cache_manager.load_project_config().licenses;
+    let licenses = manifest::license::LicenseReport::build(&path, &manifests, &license_policy);
+
+    let bom = manifest::sbom::cyclonedx(&path, &index, &manifests, &licenses);
+    let content = serde_json::to_string_pretty(&bom)?;
+    match output {
+        Some(output) => {
+            std::fs::write(output, content + "\n")?;
+            let count = bom["components"].as_array().map_or(0, |c| c.len());
+            eprintln!("  + Wrote {} components to {}", count, output.display());
+        }
+        None => println!("{}", content),
+    }
+    Ok(())
+}
+
 /// Initialize the work context
 fn init_context(path: &Path) -> Result<WorkContext> {
     eprint!("  Loading context...");
@@ -0,0 +1,603 @@
+//! Local advisory databases
+//!
+//! Loads security advisories from disk - a checked-out RustSec
+//! `advisory-db` or a directory of OSV JSON records (e.g. an unzipped
+//! `all.zip` export) - and matches them against locked package versions.
+//! Nothing here touches the network.
+
+use super::lockfile::LockedPackage;
+use super::parse::{toml_array_strings, toml_entries, toml_string};
+use super::Ecosystem;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// A published vulnerability for one package
+#[derive(Debug, Clone)]
+pub struct Advisory {
+    pub id: String,
+    /// CVE/GHSA identifiers for the same issue
+    pub aliases: Vec<String>,
+    pub ecosystem: Ecosystem,
+    pub package: String,
+    pub summary: String,
+    /// Severity as the database states it (`CRITICAL`, `HIGH`, `MODERATE`, ...)
+    pub severity: Option<String>,
+    affected: AffectedVersions,
+}
+
+#[derive(Debug, Clone)]
+enum AffectedVersions {
+    /// RustSec: every version not matching a `patched`/`unaffected` requirement
+    RustSec {
+        patched: Vec<String>,
+        unaffected: Vec<String>,
+    },
+    /// OSV: introduced/fixed ranges plus explicitly listed versions
+    Osv {
+        ranges: Vec<Vec<(OsvEvent, String)>>,
+        versions: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OsvEvent {
+    Introduced,
+    Fixed,
+    LastAffected,
+}
+
+impl Advisory {
+    /// Whether `version` falls in the affected range
+    pub fn affects(&self, version: &str) -> bool {
+        match &self.affected {
+            AffectedVersions::RustSec {
+                patched,
+                unaffected,
+            } => !patched
+                .iter()
+                .chain(unaffected.iter())
+                .any(|req| matches_requirement(version, req)),
+            AffectedVersions::Osv { ranges, versions } => {
+                versions.iter().any(|v| v == version)
+                    || ranges.iter().any(|events| in_osv_range(version, events))
+            }
+        }
+    }
+
+    /// Human-readable fixed versions (`>= 1.2.3`)
+    pub fn fixed_versions(&self) -> Vec<String> {
+        match &self.affected {
+            AffectedVersions::RustSec { patched, .. } => patched.clone(),
+            AffectedVersions::Osv { ranges, .. } => ranges
+                .iter()
+                .flatten()
+                .filter(|(event, _)| *event == OsvEvent::Fixed)
+                .map(|(_, v)| format!(">= {}", v))
+                .collect(),
+        }
+    }
+
+    /// Critical/high per the database
+    pub fn is_severe(&self) -> bool {
+        self.severity
+            .as_deref()
+            .map(|s| matches!(s.to_uppercase().as_str(), "CRITICAL" | "HIGH"))
+            .unwrap_or(false)
+    }
+}
+
+/// Advisories loaded from local databases
+#[derive(Debug, Clone, Default)]
+pub struct AdvisoryDb {
+    pub advisories: Vec<Advisory>,
+    /// Databases that were found and read
+    pub sources: Vec<PathBuf>,
+}
+
+impl AdvisoryDb {
+    /// Load every database in `paths` that exists. A directory with a
+    /// `crates/` subdirectory is read as RustSec, anything else as OSV JSON.
+    /// Only advisories for packages in `wanted` are kept.
+    pub fn load(paths: &[PathBuf], wanted: &[LockedPackage]) -> Self {
+        let names: HashSet<(Ecosystem, &str)> = wanted
+            .iter()
+            .map(|p| (p.ecosystem, p.name.as_str()))
+            .collect();
+        let mut db = Self::default();
+
+        for path in paths {
+            if !path.is_dir() {
+                continue;
+            }
+            if path.join("crates").is_dir() {
+                db.load_rustsec(path, &names);
+            } else {
+                db.load_osv(path, &names);
+            }
+            db.sources.push(path.clone());
+        }
+
+        db
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// Advisories affecting a locked package
+    pub fn matching<'a>(
+        &'a self,
+        package: &'a LockedPackage,
+    ) -> impl Iterator<Item = &'a Advisory> {
+        self.advisories.iter().filter(move |a| {
+            a.ecosystem == package.ecosystem
+                && a.package == package.name
+                && a.affects(&package.version)
+        })
+    }
+
+    /// `crates/<name>/RUSTSEC-*.md` - TOML front matter in a ```toml fence,
+    /// then a `# Title` heading
+    fn load_rustsec(&mut self, root: &Path, names: &HashSet<(Ecosystem, &str)>) {
+        for (ecosystem, name) in names {
+            if *ecosystem != Ecosystem::Cargo {
+                continue;
+            }
+            let Ok(entries) = std::fs::read_dir(root.join("crates").join(name)) else {
+                continue;
+            };
+            for entry in entries.filter_map(|e| e.ok()) {
+                let Ok(content) = std::fs::read_to_string(entry.path()) else {
+                    continue;
+                };
+                if let Some(advisory) = parse_rustsec(&content) {
+                    self.advisories.push(advisory);
+                }
+            }
+        }
+    }
+
+    fn load_osv(&mut self, root: &Path, names: &HashSet<(Ecosystem, &str)>) {
+        for entry in WalkDir::new(root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().and_then(|x| x.to_str()) == Some("json"))
+        {
+            let Ok(content) = std::fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            self.advisories.extend(
+                parse_osv(&content)
+                    .into_iter()
+                    .filter(|a| names.contains(&(a.ecosystem, a.package.as_str()))),
+            );
+        }
+    }
+}
+
+fn parse_rustsec(content: &str) -> Option<Advisory> {
+    // Legacy advisories are plain TOML; current ones wrap it in a markdown fence
+    let (front, body) = match content.split_once("```toml") {
+        Some((_, rest)) => rest.split_once("```").unwrap_or((rest, "")),
+        None => (content, ""),
+    };
+
+    let mut id = None;
+    let mut package = None;
+    let mut title = None;
+    let mut aliases = Vec::new();
+    let mut severity = None;
+    let mut patched = Vec::new();
+    let mut unaffected = Vec::new();
+
+    for entry in toml_entries(front) {
+        match (entry.section.as_str(), entry.key.as_str()) {
+            ("advisory", "id") => id = toml_string(&entry.value),
+            ("advisory", "package") => package = toml_string(&entry.value),
+            ("advisory", "title") => title = toml_string(&entry.value),
+            ("advisory", "aliases") => {
+                aliases = toml_array_strings(&entry.value)
+                    .into_iter()
+                    .map(|(s, _)| s)
+                    .collect()
+            }
+            // Withdrawn advisories and notices (unmaintained, unsound) aren't vulnerabilities
+            ("advisory", "withdrawn") | ("advisory", "informational") => return None,
+            ("advisory", "cvss") => {
+                severity = toml_string(&entry.value).and_then(|v| cvss_severity(&v))
+            }
+            ("versions", "patched") => {
+                patched = toml_array_strings(&entry.value)
+                    .into_iter()
+                    .map(|(s, _)| s)
+                    .collect()
+            }
+            ("versions", "unaffected") => {
+                unaffected = toml_array_strings(&entry.value)
+                    .into_iter()
+                    .map(|(s, _)| s)
+                    .collect()
+            }
+            _ => {}
+        }
+    }
+
+    let title = title.or_else(|| {
+        body.lines()
+            .find_map(|l| l.trim().strip_prefix("# "))
+            .map(|t| t.trim().to_string())
+    });
+
+    Some(Advisory {
+        id: id?,
+        aliases,
+        ecosystem: Ecosystem::Cargo,
+        package: package?,
+        summary: title.unwrap_or_default(),
+        severity,
+        affected: AffectedVersions::RustSec {
+            patched,
+            unaffected,
+        },
+    })
+}
+
+/// Rough CVSS v3 rating from the vector's impact metrics - RustSec only ships
+/// the vector, and the full score formula isn't worth carrying for a label
+fn cvss_severity(vector: &str) -> Option<String> {
+    let metric = |name: &str| {
+        vector
+            .split('/')
+            .find_map(|part| part.strip_prefix(&format!("{}:", name)))
+    };
+    let high_impacts = ["C", "I", "A"]
+        .iter()
+        .filter(|m| metric(m) == Some("H"))
+        .count();
+    let network = metric("AV")? == "N";
+    let label = match (high_impacts, network) {
+        (2.., true) => "CRITICAL",
+        (1.., _) => "HIGH",
+        _ => "MODERATE",
+    };
+    Some(label.to_string())
+}
+
+/// One OSV record can list several affected packages
+fn parse_osv(content: &str) -> Vec<Advisory> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+    let str_field = |v: &serde_json::Value, key: &str| {
+        v.get(key).and_then(|x| x.as_str()).map(|s| s.to_string())
+    };
+
+    let Some(id) = str_field(&json, "id") else {
+        return Vec::new();
+    };
+    if json.get("withdrawn").is_some() {
+        return Vec::new();
+    }
+    let aliases: Vec<String> = json
+        .get("aliases")
+        .and_then(|a| a.as_array())
+        .map(|a| {
+            a.iter()
+                .filter_map(|s| s.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let summary = str_field(&json, "summary")
+        .or_else(|| str_field(&json, "details").map(|d| d.lines().next().unwrap_or("").to_string()))
+        .unwrap_or_default();
+    let severity = json
+        .get("database_specific")
+        .and_then(|d| str_field(d, "severity"));
+
+    let Some(affected) = json.get("affected").and_then(|a| a.as_array()) else {
+        return Vec::new();
+    };
+
+    affected
+        .iter()
+        .filter_map(|entry| {
+            let package = entry.get("package")?;
+            let ecosystem = match package.get("ecosystem")?.as_str()? {
+                "crates.io" => Ecosystem::Cargo,
+                "npm" => Ecosystem::Npm,
+                "PyPI" => Ecosystem::PyPI,
+                "Go" => Ecosystem::Go,
+                _ => return None,
+            };
+            let name = str_field(package, "name")?;
+
+            let ranges = entry
+                .get("ranges")
+                .and_then(|r| r.as_array())
+                .map(|ranges| {
+                    ranges
+                        .iter()
+                        .filter(|r| r.get("type").and_then(|t| t.as_str()) != Some("GIT"))
+                        .map(osv_events)
+                        .collect()
+                })
+                .unwrap_or_default();
+            let versions = entry
+                .get("versions")
+                .and_then(|v| v.as_array())
+                .map(|v| {
+                    v.iter()
+                        .filter_map(|s| s.as_str().map(|s| s.to_string()))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            Some(Advisory {
+                id: id.clone(),
+                aliases: aliases.clone(),
+                ecosystem,
+                package: name,
+                summary: summary.clone(),
+                severity: severity.clone(),
+                affected: AffectedVersions::Osv { ranges, versions },
+            })
+        })
+        .collect()
+}
+
+fn osv_events(range: &serde_json::Value) -> Vec<(OsvEvent, String)> {
+    let Some(events) = range.get("events").and_then(|e| e.as_array()) else {
+        return Vec::new();
+    };
+    events
+        .iter()
+        .filter_map(|event| {
+            let (kind, key) = [
+                (OsvEvent::Introduced, "introduced"),
+                (OsvEvent::Fixed, "fixed"),
+                (OsvEvent::LastAffected, "last_affected"),
+            ]
+            .into_iter()
+            .find(|(_, key)| event.get(*key).is_some())?;
+            Some((kind, event.get(key)?.as_str()?.to_string()))
+        })
+        .collect()
+}
+
+/// Walk introduced/fixed events in order; an open `introduced` runs to infinity
+fn in_osv_range(version: &str, events: &[(OsvEvent, String)]) -> bool {
+    let mut start: Option<&str> = None;
+    for (event, bound) in events {
+        match event {
+            OsvEvent::Introduced => start = Some(bound),
+            OsvEvent::Fixed | OsvEvent::LastAffected => {
+                if let Some(from) = start.take() {
+                    let after_start = from == "0" || compare_versions(version, from).is_ge();
+                    let end = compare_versions(version, bound);
+                    let before_end = if *event == OsvEvent::Fixed {
+                        end.is_lt()
+                    } else {
+                        end.is_le()
+                    };
+                    if after_start && before_end {
+                        return true;
+                    }
+                }
+            }
+        }
+    }
+    start.is_some_and(|from| from == "0" || compare_versions(version, from).is_ge())
+}
+
+/// Semver requirement as used by RustSec: `>= 1.2.3, < 2`, `^0.9.5`, `~1.1`, bare `1.2`
+fn matches_requirement(version: &str, requirement: &str) -> bool {
+    requirement
+        .split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .all(|comparator| {
+            let (op, bound) = ["<=", ">=", "<", ">", "=", "^", "~"]
+                .iter()
+                .find_map(|op| comparator.strip_prefix(op).map(|rest| (*op, rest.trim())))
+                .unwrap_or(("^", comparator));
+            let ord = compare_versions(version, bound);
+            match op {
+                "<=" => ord.is_le(),
+                ">=" => ord.is_ge(),
+                "<" => ord.is_lt(),
+                ">" => ord.is_gt(),
+                "=" => ord.is_eq(),
+                "~" => ord.is_ge() && compare_versions(version, &tilde_ceiling(bound)).is_lt(),
+                _ => ord.is_ge() && compare_versions(version, &caret_ceiling(bound)).is_lt(),
+            }
+        })
+}
+
+/// Exclusive upper bound of `^bound`: bump the first non-zero component
+fn caret_ceiling(bound: &str) -> String {
+    let parts = Version::parse(bound).release;
+    let pivot = parts
+        .iter()
+        .position(|p| *p != 0)
+        .unwrap_or(parts.len().saturating_sub(1));
+    bump(&parts, pivot)
+}
+
+/// Exclusive upper bound of `~bound`: bump minor if given, else major
+fn tilde_ceiling(bound: &str) -> String {
+    let parts = Version::parse(bound).release;
+    bump(&parts, if parts.len() > 1 { 1 } else { 0 })
+}
+
+fn bump(parts: &[u64], index: usize) -> String {
+    let mut out: Vec<u64> = parts.iter().take(index + 1).copied().collect();
+    if let Some(last) = out.last_mut() {
+        *last += 1;
+    }
+    out.iter()
+        .map(|p| p.to_string())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Loose version for ordering semver, PEP 440 and Go versions alike:
+/// numeric release components, then an optional pre-release or post tag
+#[derive(Debug)]
+struct Version {
+    release: Vec<u64>,
+    pre: Option<String>,
+    post: bool,
+}
+
+impl Version {
+    fn parse(text: &str) -> Self {
+        let text = text.trim().trim_start_matches(['v', 'V', '=', ' ']);
+        let text = text.split('+').next().unwrap_or(text);
+        let end = text
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(text.len());
+        let release = text[..end]
+            .split('.')
+            .filter(|p| !p.is_empty())
+            .map(|p| p.parse().unwrap_or(0))
+            .collect();
+        let rest = text[end..].trim_start_matches(['-', '.', '_']);
+        let post = rest.starts_with("post");
+        Self {
+            release,
+            pre: (!rest.is_empty() && !post).then(|| rest.to_string()),
+            post,
+        }
+    }
+}
+
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a, b) = (Version::parse(a), Version::parse(b));
+    let len = a.release.len().max(b.release.len());
+    for i in 0..len {
+        let x = a.release.get(i).copied().unwrap_or(0);
+        let y = b.release.get(i).copied().unwrap_or(0);
+        if x != y {
+            return x.cmp(&y);
+        }
+    }
+    match (&a.pre, &b.pre) {
+        (None, None) => a.post.cmp(&b.post),
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(x), Some(y)) => compare_pre(x, y),
+    }
+}
+
+/// Pre-release tags token by token (`rc.10`, `rc10`, `beta-2`), numerically
+/// where both tokens are numbers
+fn compare_pre(a: &str, b: &str) -> Ordering {
+    let (left, right) = (pre_tokens(a), pre_tokens(b));
+    for (x, y) in left.iter().zip(right.iter()) {
+        let ord = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            _ => x.cmp(y),
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    left.len().cmp(&right.len())
+}
+
+/// Runs of digits or letters, dropping separators
+fn pre_tokens(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start: Option<(usize, bool)> = None;
+    for (i, c) in text.char_indices() {
+        let kind = c.is_ascii_digit();
+        match start {
+            Some((from, digits)) if !c.is_alphanumeric() || digits != kind => {
+                tokens.push(&text[from..i]);
+                start = c.is_alphanumeric().then_some((i, kind));
+            }
+            None if c.is_alphanumeric() => start = Some((i, kind)),
+            _ => {}
+        }
+    }
+    if let Some((from, _)) = start {
+        tokens.push(&text[from..]);
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_ordering_and_requirements() {
+        assert!(compare_versions("1.10.0", "1.9.9").is_gt());
+        assert!(compare_versions("v1.2.0", "1.2").is_eq());
+        assert!(compare_versions("2.0.0-rc.1", "2.0.0").is_lt());
+        assert!(compare_versions("2.0.0rc2", "2.0.0rc10").is_lt());
+        assert!(compare_versions("1.0.post1", "1.0").is_gt());
+
+        assert!(matches_requirement("0.9.7", "^0.9.5"));
+        assert!(!matches_requirement("0.10.0", "^0.9.5"));
+        assert!(matches_requirement("1.4.2", ">= 1.2.3, < 2"));
+        assert!(!matches_requirement("1.1.9", "~1.2"));
+        assert!(matches_requirement("0.1.2", "< 0.2"));
+    }
+
+    #[test]
+    fn test_rustsec_advisory() {
+        let content = r#"```toml
+[advisory]
+id = "RUSTSEC-2020-0071"
+package = "time"
+date = "2020-11-18"
+aliases = ["CVE-2020-26235"]
+cvss = "CVSS:3.1/AV:L/AC:H/PR:N/UI:R/S:U/C:N/I:N/A:H"
+
+[versions]
+patched = [">= 0.2.23"]
+unaffected = ["= 0.2.0", "= 0.2.1"]
+```
+
+# Potential segfault in the time crate
+"#;
+        let advisory = parse_rustsec(content).unwrap();
+        assert_eq!(advisory.id, "RUSTSEC-2020-0071");
+        assert_eq!(advisory.summary, "Potential segfault in the time crate");
+        assert_eq!(advisory.aliases, vec!["CVE-2020-26235".to_string()]);
+        assert_eq!(advisory.severity.as_deref(), Some("HIGH"));
+        assert!(advisory.affects("0.1.43"));
+        assert!(!advisory.affects("0.2.1"));
+        assert!(!advisory.affects("0.3.5"));
+        assert_eq!(advisory.fixed_versions(), vec![">= 0.2.23".to_string()]);
+
+        let notice = "```toml\n[advisory]\nid = \"RUSTSEC-2021-0001\"\npackage = \"x\"\ninformational = \"unmaintained\"\n```\n";
+        assert!(parse_rustsec(notice).is_none());
+    }
+
+    #[test]
+    fn test_osv_advisory() {
+        let content = r#"{
+  "id": "GHSA-p6mc-m468-83gw",
+  "summary": "Prototype Pollution in lodash",
+  "aliases": ["CVE-2020-8203"],
+  "database_specific": {"severity": "HIGH"},
+  "affected": [{
+    "package": {"ecosystem": "npm", "name": "lodash"},
+    "ranges": [{"type": "SEMVER", "events": [{"introduced": "3.7.0"}, {"fixed": "4.17.19"}]}]
+  }]
+}"#;
+        let advisories = parse_osv(content);
+        assert_eq!(advisories.len(), 1);
+        let advisory = &advisories[0];
+        assert_eq!(advisory.ecosystem, Ecosystem::Npm);
+        assert!(advisory.is_severe());
+        assert!(advisory.affects("4.17.15"));
+        assert!(!advisory.affects("4.17.19"));
+        assert!(!advisory.affects("3.6.0"));
+        assert_eq!(advisory.fixed_versions(), vec![">= 4.17.19".to_string()]);
+    }
+}
@@ -0,0 +1,876 @@
+//! Dependency license compliance
+//!
+//! Finds each dependency's license in the lockfile itself (npm), or in
+//! package metadata already on disk - `vendor/`, `node_modules/`, Python
+//! virtualenvs, the Cargo registry and Go module cache - and evaluates it
+//! against the allow/deny lists in the project config.
+
+use super::lockfile::LockedPackage;
+use super::parse::{parse_manifest, toml_entries, toml_string};
+use super::{Ecosystem, ManifestInventory};
+use crate::suggest::{Priority, Suggestion, SuggestionKind, SuggestionSource};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Allow/deny lists of SPDX identifiers. Entries ending in `*` match by
+/// prefix (`GPL-*`), and `GPL-3.0` also covers `GPL-3.0-only`/`-or-later`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LicensePolicy {
+    /// Licenses dependencies may use; empty allows anything not denied
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Licenses dependencies must not use
+    #[serde(default)]
+    pub deny: Vec<String>,
+    /// Treat dependencies without license information as violations
+    #[serde(default)]
+    pub deny_unknown: bool,
+}
+
+/// Result of checking one license expression against the policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LicenseStatus {
+    Allowed,
+    /// Every choice in the expression includes a denied license
+    Denied {
+        licenses: Vec<String>,
+    },
+    /// Not denied, but not on a non-empty allow list either
+    NotAllowed,
+    /// No license information found locally
+    Unknown,
+}
+
+impl LicenseStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LicenseStatus::Allowed => "allowed",
+            LicenseStatus::Denied { .. } => "denied",
+            LicenseStatus::NotAllowed => "not allowed",
+            LicenseStatus::Unknown => "unknown",
+        }
+    }
+}
+
+impl LicensePolicy {
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty() && !self.deny_unknown
+    }
+
+    /// Evaluate an SPDX expression (`MIT OR Apache-2.0`, legacy `MIT/Apache-2.0`)
+    pub fn evaluate(&self, license: Option<&str>) -> LicenseStatus {
+        let Some(license) = license.map(str::trim).filter(|l| !l.is_empty()) else {
+            return LicenseStatus::Unknown;
+        };
+        let choices = spdx_choices(license);
+        if choices.is_empty() {
+            return LicenseStatus::Unknown;
+        }
+
+        let denied = |id: &str| self.deny.iter().any(|p| license_matches(p, id));
+        let allowed = |id: &str| {
+            !denied(id)
+                && (self.allow.is_empty() || self.allow.iter().any(|p| license_matches(p, id)))
+        };
+
+        if choices.iter().any(|ids| ids.iter().all(|id| allowed(id))) {
+            return LicenseStatus::Allowed;
+        }
+        if choices.iter().all(|ids| ids.iter().any(|id| denied(id))) {
+            let mut licenses: Vec<String> = choices
+                .iter()
+                .flatten()
+                .filter(|id| denied(id))
+                .cloned()
+                .collect();
+            licenses.sort();
+            licenses.dedup();
+            return LicenseStatus::Denied { licenses };
+        }
+        LicenseStatus::NotAllowed
+    }
+
+    /// Whether a status breaks the policy
+    pub fn violated_by(&self, status: &LicenseStatus) -> bool {
+        match status {
+            LicenseStatus::Allowed => false,
+            LicenseStatus::Denied { .. } | LicenseStatus::NotAllowed => true,
+            LicenseStatus::Unknown => self.deny_unknown,
+        }
+    }
+
+    /// One-line reminder for fix prompts so new dependencies respect the policy
+    pub fn prompt_context(&self) -> Option<String> {
+        if self.allow.is_empty() && self.deny.is_empty() {
+            return None;
+        }
+        let mut parts = Vec::new();
+        if !self.deny.is_empty() {
+            parts.push(format!(
+                "never add dependencies licensed {}",
+                self.deny.join(", ")
+            ));
+        }
+        if !self.allow.is_empty() {
+            parts.push(format!(
+                "new dependencies must be {}",
+                self.allow.join(", ")
+            ));
+        }
+        Some(format!("LICENSE POLICY: {}", parts.join("; ")))
+    }
+}
+
+/// Disjunctive normal form of an SPDX expression: each inner list is one
+/// acceptable combination. `WITH` exceptions are dropped to their base license.
+fn spdx_choices(expression: &str) -> Vec<Vec<String>> {
+    let spaced = expression
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .replace('/', " OR ");
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+    let mut pos = 0;
+    parse_or(&tokens, &mut pos)
+}
+
+fn parse_or(tokens: &[&str], pos: &mut usize) -> Vec<Vec<String>> {
+    let mut choices = parse_and(tokens, pos);
+    while tokens
+        .get(*pos)
+        .is_some_and(|t| t.eq_ignore_ascii_case("OR"))
+    {
+        *pos += 1;
+        choices.extend(parse_and(tokens, pos));
+    }
+    choices
+}
+
+fn parse_and(tokens: &[&str], pos: &mut usize) -> Vec<Vec<String>> {
+    let mut choices = parse_atom(tokens, pos);
+    while tokens
+        .get(*pos)
+        .is_some_and(|t| t.eq_ignore_ascii_case("AND"))
+    {
+        *pos += 1;
+        let right = parse_atom(tokens, pos);
+        choices = choices
+            .iter()
+            .flat_map(|left| {
+                right.iter().map(move |r| {
+                    let mut combined = left.clone();
+                    combined.extend(r.iter().cloned());
+                    combined
+                })
+            })
+            .collect();
+    }
+    choices
+}
+
+fn parse_atom(tokens: &[&str], pos: &mut usize) -> Vec<Vec<String>> {
+    match tokens.get(*pos) {
+        Some(&"(") => {
+            *pos += 1;
+            let inner = parse_or(tokens, pos);
+            if tokens.get(*pos) == Some(&")") {
+                *pos += 1;
+            }
+            inner
+        }
+        Some(id) => {
+            *pos += 1;
+            if tokens
+                .get(*pos)
+                .is_some_and(|t| t.eq_ignore_ascii_case("WITH"))
+            {
+                *pos += 2;
+            }
+            vec![vec![id.to_string()]]
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Policy entry vs SPDX id, ignoring case and `-only`/`-or-later`/`+` suffixes
+fn license_matches(pattern: &str, id: &str) -> bool {
+    let pattern = pattern.trim().to_lowercase();
+    let id = id.to_lowercase();
+    if let Some(prefix) = pattern.strip_suffix('*') {
+        return id.starts_with(prefix);
+    }
+    let base = id
+        .trim_end_matches('+')
+        .trim_end_matches("-only")
+        .trim_end_matches("-or-later");
+    pattern == id || pattern == base
+}
+
+/// One dependency's license and verdict
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseEntry {
+    pub name: String,
+    pub version: Option<String>,
+    pub ecosystem: Ecosystem,
+    pub license: Option<String>,
+    /// Where the license came from (`package-lock.json`, `node_modules`, ...)
+    pub source: Option<String>,
+    /// Manifest (or lockfile) that brings the dependency in
+    pub manifest: PathBuf,
+    pub status: LicenseStatus,
+}
+
+/// The project's own declared license
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectLicense {
+    pub manifest: PathBuf,
+    pub license: String,
+}
+
+/// Every dependency's license, evaluated against the policy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseReport {
+    pub generated_at: DateTime<Utc>,
+    pub policy: LicensePolicy,
+    pub project_licenses: Vec<ProjectLicense>,
+    pub entries: Vec<LicenseEntry>,
+}
+
+impl Default for LicenseReport {
+    fn default() -> Self {
+        Self {
+            generated_at: Utc::now(),
+            policy: LicensePolicy::default(),
+            project_licenses: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl LicenseReport {
+    /// Locked packages plus declared dependencies no lockfile pins
+    pub fn build(root: &Path, inventory: &ManifestInventory, policy: &LicensePolicy) -> Self {
+        let resolver = LicenseResolver::new(root, inventory);
+        let groups = inventory.groups();
+        let manifest_for = |ecosystem: Ecosystem, file: &Path| {
+            let dir = file.parent().unwrap_or(Path::new(""));
+            groups
+                .iter()
+                .find(|g| g.ecosystem == ecosystem && g.dir == dir)
+                .map(|g| g.primary().path.clone())
+                .unwrap_or_else(|| file.to_path_buf())
+        };
+
+        let mut entries = Vec::new();
+        let mut seen: HashSet<(Ecosystem, PathBuf, String)> = HashSet::new();
+
+        for package in &inventory.locked {
+            let dir = package.lockfile.parent().unwrap_or(Path::new(""));
+            seen.insert((package.ecosystem, dir.to_path_buf(), package.name.clone()));
+            let (license, source) = match &package.license {
+                Some(license) => (Some(license.clone()), Some(lockfile_label(package))),
+                None => resolver
+                    .resolve(
+                        package.ecosystem,
+                        &package.name,
+                        Some(&package.version),
+                        dir,
+                    )
+                    .map(|(l, s)| (Some(l), Some(s)))
+                    .unwrap_or((None, None)),
+            };
+            entries.push(LicenseEntry {
+                name: package.name.clone(),
+                version: Some(package.version.clone()),
+                ecosystem: package.ecosystem,
+                status: policy.evaluate(license.as_deref()),
+                license,
+                source,
+                manifest: manifest_for(package.ecosystem, &package.lockfile),
+            });
+        }
+
+        for manifest in &inventory.manifests {
+            let dir = manifest.dir();
+            for dep in &manifest.dependencies {
+                if !seen.insert((dep.ecosystem, dir.to_path_buf(), dep.name.clone())) {
+                    continue;
+                }
+                let resolved = resolver.resolve(dep.ecosystem, &dep.name, None, dir);
+                let license = resolved.as_ref().map(|(l, _)| l.clone());
+                entries.push(LicenseEntry {
+                    name: dep.name.clone(),
+                    version: dep.version.clone(),
+                    ecosystem: dep.ecosystem,
+                    status: policy.evaluate(license.as_deref()),
+                    license,
+                    source: resolved.map(|(_, s)| s),
+                    manifest: manifest_for(dep.ecosystem, &manifest.path),
+                });
+            }
+        }
+
+        entries.sort_by(|a, b| (&a.manifest, &a.name).cmp(&(&b.manifest, &b.name)));
+
+        Self {
+            generated_at: Utc::now(),
+            policy: policy.clone(),
+            project_licenses: inventory
+                .manifests
+                .iter()
+                .filter_map(|m| {
+                    m.license.as_ref().map(|license| ProjectLicense {
+                        manifest: m.path.clone(),
+                        license: license.clone(),
+                    })
+                })
+                .collect(),
+            entries,
+        }
+    }
+
+    /// Entries that break the policy
+    pub fn violations(&self) -> impl Iterator<Item = &LicenseEntry> {
+        self.entries
+            .iter()
+            .filter(|e| self.policy.violated_by(&e.status))
+    }
+
+    pub fn unknown_count(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| e.status == LicenseStatus::Unknown)
+            .count()
+    }
+
+    /// Violations as suggestions, one per manifest
+    pub fn suggestions(&self) -> Vec<Suggestion> {
+        let mut by_manifest: BTreeMap<&Path, Vec<&LicenseEntry>> = BTreeMap::new();
+        for entry in self.violations() {
+            by_manifest.entry(&entry.manifest).or_default().push(entry);
+        }
+
+        by_manifest
+            .into_iter()
+            .map(|(manifest, entries)| {
+                let denied = entries
+                    .iter()
+                    .any(|e| matches!(e.status, LicenseStatus::Denied { .. }));
+                let described: Vec<String> = entries
+                    .iter()
+                    .map(|e| {
+                        format!(
+                            "{} ({})",
+                            e.name,
+                            e.license.as_deref().unwrap_or("unknown license")
+                        )
+                    })
+                    .collect();
+                let summary = if entries.len() == 1 {
+                    format!(
+                        "{} pulls in {}, which the license policy doesn't allow",
+                        manifest.display(),
+                        described[0]
+                    )
+                } else {
+                    format!(
+                        "{} pulls in {} dependencies the license policy doesn't allow",
+                        manifest.display(),
+                        entries.len()
+                    )
+                };
+
+                let mut detail = String::from(
+                    "The project config restricts dependency licenses. These dependencies \
+                     (direct or transitive) break that policy:\n",
+                );
+                for (entry, text) in entries.iter().zip(&described) {
+                    detail.push_str(&format!(
+                        "\n- {} {}: {}",
+                        text,
+                        entry.version.as_deref().unwrap_or(""),
+                        entry.status.label()
+                    ));
+                }
+                detail.push_str(
+                    "\n\nReplace them with alternatives under an allowed license, or get an \
+                     exception added to the policy.",
+                );
+
+                Suggestion::new(
+                    SuggestionKind::Quality,
+                    if denied {
+                        Priority::High
+                    } else {
+                        Priority::Medium
+                    },
+                    manifest.to_path_buf(),
+                    summary,
+                    SuggestionSource::Static,
+                )
+                .with_detail(detail)
+            })
+            .collect()
+    }
+}
+
+/// Dependencies a manifest edit adds that break the policy (or whose
+/// license can't be found locally), for checking fixes before they ship
+pub fn check_added_dependencies(
+    root: &Path,
+    manifest_path: &Path,
+    before: &str,
+    after: &str,
+    policy: &LicensePolicy,
+) -> Vec<LicenseEntry> {
+    if policy.is_empty() {
+        return Vec::new();
+    }
+    let Some(new_manifest) = parse_manifest(manifest_path, after) else {
+        return Vec::new();
+    };
+    let existing: HashSet<String> = parse_manifest(manifest_path, before)
+        .map(|m| m.dependencies.into_iter().map(|d| d.name).collect())
+        .unwrap_or_default();
+
+    let inventory = ManifestInventory {
+        manifests: vec![new_manifest.clone()],
+        locked: Vec::new(),
+    };
+    let resolver = LicenseResolver::new(root, &inventory);
+    let dir = new_manifest.dir();
+
+    new_manifest
+        .dependencies
+        .iter()
+        .filter(|d| !existing.contains(&d.name))
+        .filter_map(|dep| {
+            let resolved = resolver.resolve(dep.ecosystem, &dep.name, None, dir);
+            let license = resolved.as_ref().map(|(l, _)| l.clone());
+            let status = policy.evaluate(license.as_deref());
+            (status != LicenseStatus::Allowed).then(|| LicenseEntry {
+                name: dep.name.clone(),
+                version: dep.version.clone(),
+                ecosystem: dep.ecosystem,
+                license,
+                source: resolved.map(|(_, s)| s),
+                manifest: manifest_path.to_path_buf(),
+                status,
+            })
+        })
+        .collect()
+}
+
+fn lockfile_label(package: &LockedPackage) -> String {
+    package
+        .lockfile
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Looks up package metadata that's already on disk
+struct LicenseResolver {
+    root: PathBuf,
+    cargo_registries: Vec<PathBuf>,
+    go_mod_cache: Option<PathBuf>,
+    /// (project dir, normalized distribution name) -> METADATA path
+    python_metadata: HashMap<(PathBuf, String), PathBuf>,
+}
+
+impl LicenseResolver {
+    fn new(root: &Path, inventory: &ManifestInventory) -> Self {
+        let cargo_home = std::env::var_os("CARGO_HOME")
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|h| h.join(".cargo")));
+        let cargo_registries = cargo_home
+            .and_then(|home| std::fs::read_dir(home.join("registry").join("src")).ok())
+            .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
+            .unwrap_or_default();
+
+        let go_mod_cache = std::env::var_os("GOMODCACHE")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("GOPATH").map(|p| PathBuf::from(p).join("pkg/mod")))
+            .or_else(|| dirs::home_dir().map(|h| h.join("go/pkg/mod")))
+            .filter(|p| p.is_dir());
+
+        let mut python_metadata = HashMap::new();
+        let python_dirs: HashSet<&Path> = inventory
+            .manifests
+            .iter()
+            .filter(|m| m.ecosystem == Ecosystem::PyPI)
+            .map(|m| m.dir())
+            .chain(
+                inventory
+                    .locked
+                    .iter()
+                    .filter(|p| p.ecosystem == Ecosystem::PyPI)
+                    .map(|p| p.lockfile.parent().unwrap_or(Path::new(""))),
+            )
+            .collect();
+        for dir in python_dirs {
+            for site_packages in site_packages_dirs(&root.join(dir)) {
+                let Ok(entries) = std::fs::read_dir(&site_packages) else {
+                    continue;
+                };
+                for entry in entries.filter_map(|e| e.ok()) {
+                    let file_name = entry.file_name().to_string_lossy().to_string();
+                    let Some(stem) = file_name.strip_suffix(".dist-info") else {
+                        continue;
+                    };
+                    let name = stem.split('-').next().unwrap_or(stem);
+                    python_metadata
+                        .entry((dir.to_path_buf(), normalize_python_name(name)))
+                        .or_insert_with(|| entry.path().join("METADATA"));
+                }
+            }
+        }
+
+        Self {
+            root: root.to_path_buf(),
+            cargo_registries,
+            go_mod_cache,
+            python_metadata,
+        }
+    }
+
+    /// License and where it was found
+    fn resolve(
+        &self,
+        ecosystem: Ecosystem,
+        name: &str,
+        version: Option<&str>,
+        dir: &Path,
+    ) -> Option<(String, String)> {
+        let project = self.root.join(dir);
+        match ecosystem {
+            Ecosystem::Cargo => {
+                let mut candidates = vec![(project.join("vendor").join(name), "vendor")];
+                if let Some(version) = version {
+                    let versioned = format!("{}-{}", name, version);
+                    candidates.push((project.join("vendor").join(&versioned), "vendor"));
+                    candidates.extend(
+                        self.cargo_registries
+                            .iter()
+                            .map(|r| (r.join(&versioned), "cargo registry")),
+                    );
+                }
+                candidates.into_iter().find_map(|(path, source)| {
+                    let content = std::fs::read_to_string(path.join("Cargo.toml")).ok()?;
+                    Some((cargo_license(&content)?, source.to_string()))
+                })
+            }
+            Ecosystem::Npm => {
+                let path = project.join("node_modules").join(name).join("package.json");
+                let content = std::fs::read_to_string(path).ok()?;
+                let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+                Some((npm_license(&json)?, "node_modules".to_string()))
+            }
+            Ecosystem::PyPI => {
+                let path = self
+                    .python_metadata
+                    .get(&(dir.to_path_buf(), normalize_python_name(name)))?;
+                let content = std::fs::read_to_string(path).ok()?;
+                Some((python_license(&content)?, "site-packages".to_string()))
+            }
+            Ecosystem::Go => {
+                let mut candidates = vec![(project.join("vendor").join(name), "vendor")];
+                if let (Some(cache), Some(version)) = (&self.go_mod_cache, version) {
+                    candidates.push((
+                        cache.join(format!("{}@{}", escape_go_module(name), version)),
+                        "go module cache",
+                    ));
+                }
+                candidates.into_iter().find_map(|(path, source)| {
+                    Some((license_from_files(&path)?, source.to_string()))
+                })
+            }
+        }
+    }
+}
+
+fn cargo_license(content: &str) -> Option<String> {
+    toml_entries(content)
+        .into_iter()
+        .find(|e| e.section == "package" && e.key == "license")
+        .and_then(|e| toml_string(&e.value))
+}
+
+/// `"license": "MIT"`, `{"type": "MIT"}` or legacy `"licenses": [{"type": ..}]`
+fn npm_license(json: &serde_json::Value) -> Option<String> {
+    let as_id = |v: &serde_json::Value| {
+        v.as_str().map(|s| s.to_string()).or_else(|| {
+            v.get("type")
+                .and_then(|t| t.as_str())
+                .map(|s| s.to_string())
+        })
+    };
+    if let Some(license) = json.get("license").and_then(as_id) {
+        return Some(license);
+    }
+    let ids: Vec<String> = json
+        .get("licenses")?
+        .as_array()?
+        .iter()
+        .filter_map(as_id)
+        .collect();
+    (!ids.is_empty()).then(|| ids.join(" OR "))
+}
+
+/// Core metadata: `License-Expression`, a short `License` field, or the
+/// trove classifiers
+fn python_license(metadata: &str) -> Option<String> {
+    let header = |name: &str| {
+        metadata
+            .lines()
+            .take_while(|l| !l.is_empty())
+            .find_map(|l| l.strip_prefix(name).map(|v| v.trim().to_string()))
+    };
+    if let Some(expression) = header("License-Expression:") {
+        return Some(expression);
+    }
+    if let Some(license) = header("License:").filter(|l| !l.is_empty() && l.len() <= 40) {
+        if license != "UNKNOWN" {
+            return Some(license);
+        }
+    }
+
+    let classifiers: Vec<String> = metadata
+        .lines()
+        .filter_map(|l| l.strip_prefix("Classifier: License :: "))
+        .filter_map(|c| c.rsplit(" :: ").next())
+        .map(classifier_to_spdx)
+        .collect();
+    (!classifiers.is_empty()).then(|| classifiers.join(" OR "))
+}
+
+fn classifier_to_spdx(classifier: &str) -> String {
+    let known = [
+        ("MIT License", "MIT"),
+        ("Apache Software License", "Apache-2.0"),
+        ("BSD License", "BSD-3-Clause"),
+        ("ISC License (ISCL)", "ISC"),
+        ("Mozilla Public License 2.0 (MPL 2.0)", "MPL-2.0"),
+        ("GNU General Public License v2 (GPLv2)", "GPL-2.0"),
+        ("GNU General Public License v3 (GPLv3)", "GPL-3.0"),
+        ("GNU Lesser General Public License v3 (LGPLv3)", "LGPL-3.0"),
+        ("GNU Affero General Public License v3", "AGPL-3.0"),
+        ("The Unlicense (Unlicense)", "Unlicense"),
+        ("Python Software Foundation License", "PSF-2.0"),
+    ];
+    known
+        .iter()
+        .find(|(name, _)| *name == classifier)
+        .map(|(_, spdx)| spdx.to_string())
+        .unwrap_or_else(|| classifier.to_string())
+}
+
+/// Identify a license from a LICENSE/COPYING file's text
+fn license_from_files(dir: &Path) -> Option<String> {
+    let entries = std::fs::read_dir(dir).ok()?;
+    let text = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().to_uppercase();
+            name.starts_with("LICENSE")
+                || name.starts_with("LICENCE")
+                || name.starts_with("COPYING")
+        })
+        .find_map(|e| std::fs::read_to_string(e.path()).ok())?;
+    detect_license_text(&text)
+}
+
+fn detect_license_text(text: &str) -> Option<String> {
+    let has = |needle: &str| text.contains(needle);
+    let id = if has("GNU AFFERO GENERAL PUBLIC LICENSE") {
+        "AGPL-3.0"
+    } else if has("GNU LESSER GENERAL PUBLIC LICENSE") {
+        if has("Version 3") {
+            "LGPL-3.0"
+        } else {
+            "LGPL-2.1"
+        }
+    } else if has("GNU GENERAL PUBLIC LICENSE") {
+        if has("Version 3") {
+            "GPL-3.0"
+        } else {
+            "GPL-2.0"
+        }
+    } else if has("Mozilla Public License Version 2.0") {
+        "MPL-2.0"
+    } else if has("Apache License") && has("Version 2.0") {
+        "Apache-2.0"
+    } else if has("Permission is hereby granted, free of charge") {
+        "MIT"
+    } else if has("Redistribution and use in source and binary forms") {
+        if has("Neither the name") || has("names of its contributors") {
+            "BSD-3-Clause"
+        } else {
+            "BSD-2-Clause"
+        }
+    } else if has("Permission to use, copy, modify, and/or distribute this software") {
+        "ISC"
+    } else if has("This is free and unencumbered software released into the public domain") {
+        "Unlicense"
+    } else {
+        return None;
+    };
+    Some(id.to_string())
+}
+
+/// Virtualenv site-packages directories under a project
+fn site_packages_dirs(project: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    for venv in [".venv", "venv", "env"] {
+        let venv = project.join(venv);
+        // Windows layout
+        let windows = venv.join("Lib").join("site-packages");
+        if windows.is_dir() {
+            dirs.push(windows);
+        }
+        let Ok(entries) = std::fs::read_dir(venv.join("lib")) else {
+            continue;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let site_packages = entry.path().join("site-packages");
+            if site_packages.is_dir() {
+                dirs.push(site_packages);
+            }
+        }
+    }
+    dirs
+}
+
+/// PEP 503 normalization with `_` so it matches dist-info directory names
+fn normalize_python_name(name: &str) -> String {
+    name.to_lowercase().replace(['-', '.'], "_")
+}
+
+/// The module cache escapes capitals: `github.com/BurntSushi` -> `github.com/!burnt!sushi`
+fn escape_go_module(module: &str) -> String {
+    let mut escaped = String::with_capacity(module.len());
+    for c in module.chars() {
+        if c.is_ascii_uppercase() {
+            escaped.push('!');
+            escaped.push(c.to_ascii_lowercase());
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn test_policy_evaluation() {
+        let policy = LicensePolicy {
+            allow: vec!["MIT".into(), "Apache-2.0".into(), "BSD-*".into()],
+            deny: vec!["GPL-3.0".into(), "AGPL-*".into()],
+            deny_unknown: false,
+        };
+
+        assert_eq!(
+            policy.evaluate(Some("MIT OR Apache-2.0")),
+            LicenseStatus::Allowed
+        );
+        assert_eq!(
+            policy.evaluate(Some("GPL-3.0-only OR MIT")),
+            LicenseStatus::Allowed
+        );
+        assert_eq!(
+            policy.evaluate(Some("BSD-3-Clause")),
+            LicenseStatus::Allowed
+        );
+        assert_eq!(
+            policy.evaluate(Some("GPL-3.0-or-later")),
+            LicenseStatus::Denied {
+                licenses: vec!["GPL-3.0-or-later".into()]
+            }
+        );
+        assert_eq!(
+            policy.evaluate(Some("(MIT AND AGPL-3.0) OR GPL-3.0+")),
+            LicenseStatus::Denied {
+                licenses: vec!["AGPL-3.0".into(), "GPL-3.0+".into()]
+            }
+        );
+        assert_eq!(policy.evaluate(Some("MPL-2.0")), LicenseStatus::NotAllowed);
+        assert_eq!(
+            policy.evaluate(Some("Apache-2.0 WITH LLVM-exception")),
+            LicenseStatus::Allowed
+        );
+        assert_eq!(policy.evaluate(None), LicenseStatus::Unknown);
+        assert!(!policy.violated_by(&LicenseStatus::Unknown));
+    }
+
+    #[test]
+    fn test_report_from_local_metadata() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("cosmos_license_test_{}", nanos));
+        fs::create_dir_all(root.join("node_modules/left-pad")).unwrap();
+        fs::write(
+            root.join("package.json"),
+            r#"{"name": "web", "license": "MIT", "dependencies": {"left-pad": "1", "copyleft": "1"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("package-lock.json"),
+            r#"{"lockfileVersion": 3, "packages": {"node_modules/copyleft": {"version": "1.0.0", "license": "GPL-3.0-only"}}}"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("node_modules/left-pad/package.json"),
+            r#"{"name": "left-pad", "license": {"type": "WTFPL"}}"#,
+        )
+        .unwrap();
+
+        let policy = LicensePolicy {
+            allow: Vec::new(),
+            deny: vec!["GPL-*".into()],
+            deny_unknown: false,
+        };
+        let inventory = ManifestInventory::load(&root);
+        let report = LicenseReport::build(&root, &inventory, &policy);
+
+        assert_eq!(report.project_licenses.len(), 1);
+        let licenses: Vec<(&str, Option<&str>, Option<&str>)> = report
+            .entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.license.as_deref(), e.source.as_deref()))
+            .collect();
+        assert_eq!(
+            licenses,
+            vec![
+                ("copyleft", Some("GPL-3.0-only"), Some("package-lock.json")),
+                ("left-pad", Some("WTFPL"), Some("node_modules")),
+            ]
+        );
+        assert_eq!(report.violations().count(), 1);
+
+        let suggestions = report.suggestions();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].priority, Priority::High);
+        assert_eq!(suggestions[0].file, PathBuf::from("package.json"));
+
+        let added = check_added_dependencies(
+            &root,
+            Path::new("package.json"),
+            r#"{"dependencies": {}}"#,
+            r#"{"dependencies": {"left-pad": "1", "mystery": "2"}}"#,
+            &policy,
+        );
+        let names: Vec<&str> = added.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["mystery"]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,282 @@
+//! Lockfile parsers
+//!
+//! Resolved package versions from Cargo.lock, package-lock.json,
+//! pnpm-lock.yaml, go.sum and poetry.lock. Manifests say what a project
+//! asks for; lockfiles say what it actually installs, which is what
+//! advisories are matched against.
+
+use super::parse::{toml_entries, toml_string};
+use super::Ecosystem;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// A package version pinned by a lockfile
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    pub ecosystem: Ecosystem,
+    /// Lockfile path, relative to the repo root
+    pub lockfile: PathBuf,
+    /// SPDX license, for lockfiles that record it (npm v2/v3)
+    pub license: Option<String>,
+}
+
+/// Whether `parse_lockfile` handles files with this name
+pub fn is_lockfile(name: &str) -> bool {
+    matches!(
+        name,
+        "Cargo.lock"
+            | "poetry.lock"
+            | "package-lock.json"
+            | "npm-shrinkwrap.json"
+            | "pnpm-lock.yaml"
+            | "go.sum"
+    )
+}
+
+/// Parse a lockfile by file name; `None` for anything else
+pub fn parse_lockfile(path: &Path, content: &str) -> Option<Vec<LockedPackage>> {
+    let name = path.file_name()?.to_str()?;
+    let unlicensed = |pairs: Vec<(String, String)>| {
+        pairs
+            .into_iter()
+            .map(|(name, version)| (name, version, None))
+            .collect::<Vec<_>>()
+    };
+    let (ecosystem, packages) = match name {
+        "Cargo.lock" => (
+            Ecosystem::Cargo,
+            unlicensed(parse_toml_packages(content, true)),
+        ),
+        "poetry.lock" => (
+            Ecosystem::PyPI,
+            unlicensed(parse_toml_packages(content, false)),
+        ),
+        "package-lock.json" | "npm-shrinkwrap.json" => {
+            (Ecosystem::Npm, parse_package_lock(content)?)
+        }
+        "pnpm-lock.yaml" => (Ecosystem::Npm, unlicensed(parse_pnpm_lock(content))),
+        "go.sum" => (Ecosystem::Go, unlicensed(parse_go_sum(content))),
+        _ => return None,
+    };
+
+    let mut seen = HashSet::new();
+    Some(
+        packages
+            .into_iter()
+            .filter(|(name, version, _)| seen.insert((name.clone(), version.clone())))
+            .map(|(name, version, license)| LockedPackage {
+                name,
+                version,
+                ecosystem,
+                lockfile: path.to_path_buf(),
+                license,
+            })
+            .collect(),
+    )
+}
+
+/// `[[package]]` tables with `name`/`version` keys (Cargo.lock, poetry.lock).
+/// Cargo.lock entries without `source` are the workspace's own crates.
+fn parse_toml_packages(content: &str, require_source: bool) -> Vec<(String, String)> {
+    let mut packages = Vec::new();
+    let mut current: Option<(usize, Option<String>, Option<String>, bool)> = None;
+
+    let mut flush = |current: &mut Option<(usize, Option<String>, Option<String>, bool)>| {
+        if let Some((_, Some(name), Some(version), has_source)) = current.take() {
+            if has_source || !require_source {
+                packages.push((name, version));
+            }
+        }
+    };
+
+    for entry in toml_entries(content) {
+        if entry.section != "package" {
+            continue;
+        }
+        if current.as_ref().map(|c| c.0) != Some(entry.section_line) {
+            flush(&mut current);
+            current = Some((entry.section_line, None, None, false));
+        }
+        if let Some(c) = current.as_mut() {
+            match entry.key.as_str() {
+                "name" => c.1 = toml_string(&entry.value),
+                "version" => c.2 = toml_string(&entry.value),
+                "source" => c.3 = true,
+                _ => {}
+            }
+        }
+    }
+    flush(&mut current);
+
+    packages
+}
+
+/// npm lockfile v2/v3 (`packages`) with a fallback to v1 (`dependencies`).
+/// Only v2/v3 entries carry a `license`.
+fn parse_package_lock(content: &str) -> Option<Vec<(String, String, Option<String>)>> {
+    let json: serde_json::Value = serde_json::from_str(content).ok()?;
+    let mut packages = Vec::new();
+
+    if let Some(entries) = json.get("packages").and_then(|v| v.as_object()) {
+        for (key, entry) in entries {
+            // "" is the root project; keys without node_modules are workspace links
+            let Some((_, name)) = key.rsplit_once("node_modules/") else {
+                continue;
+            };
+            if let Some(version) = entry.get("version").and_then(|v| v.as_str()) {
+                let license = entry
+                    .get("license")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string());
+                packages.push((name.to_string(), version.to_string(), license));
+            }
+        }
+    } else if let Some(deps) = json.get("dependencies") {
+        let mut pairs = Vec::new();
+        collect_v1_dependencies(deps, &mut pairs);
+        packages.extend(
+            pairs
+                .into_iter()
+                .map(|(name, version)| (name, version, None)),
+        );
+    }
+
+    Some(packages)
+}
+
+fn collect_v1_dependencies(deps: &serde_json::Value, out: &mut Vec<(String, String)>) {
+    let Some(deps) = deps.as_object() else {
+        return;
+    };
+    for (name, entry) in deps {
+        if let Some(version) = entry.get("version").and_then(|v| v.as_str()) {
+            out.push((name.clone(), version.to_string()));
+        }
+        if let Some(nested) = entry.get("dependencies") {
+            collect_v1_dependencies(nested, out);
+        }
+    }
+}
+
+/// Keys of the top-level `packages:` map. Handles v5 (`/name/1.0.0`),
+/// v6 (`/name@1.0.0(peer@2)`) and v9 (`'@scope/name@1.0.0'`) spellings.
+fn parse_pnpm_lock(content: &str) -> Vec<(String, String)> {
+    let mut packages = Vec::new();
+    let mut in_packages = false;
+
+    for line in content.lines() {
+        if !line.starts_with(' ') && !line.trim().is_empty() {
+            in_packages = line.trim_end() == "packages:";
+            continue;
+        }
+        // Package keys sit exactly one level (two spaces) in
+        if !in_packages || !line.starts_with("  ") || line.starts_with("   ") {
+            continue;
+        }
+        let key = line
+            .trim()
+            .trim_end_matches(':')
+            .trim_matches(|c| c == '\'' || c == '"')
+            .trim_start_matches('/');
+        let key = key.split('(').next().unwrap_or(key);
+
+        let pair = match key.rfind('@') {
+            Some(at) if at > 0 => Some((&key[..at], &key[at + 1..])),
+            _ => key.rsplit_once('/'),
+        };
+        if let Some((name, version)) = pair {
+            let version = version.split('_').next().unwrap_or(version);
+            if !name.is_empty() && !version.is_empty() {
+                packages.push((name.to_string(), version.to_string()));
+            }
+        }
+    }
+
+    packages
+}
+
+/// `module version[/go.mod] hash` lines. `/go.mod`-only lines are versions
+/// consulted during resolution but not built, so they're skipped.
+fn parse_go_sum(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let module = parts.next()?;
+            let version = parts.next()?;
+            if version.ends_with("/go.mod") {
+                return None;
+            }
+            Some((
+                module.to_string(),
+                version.trim_end_matches("+incompatible").to_string(),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(path: &str, content: &str) -> Vec<(String, String)> {
+        let mut out: Vec<_> = parse_lockfile(Path::new(path), content)
+            .unwrap()
+            .into_iter()
+            .map(|p| (p.name, p.version))
+            .collect();
+        out.sort();
+        out
+    }
+
+    fn pair(name: &str, version: &str) -> (String, String) {
+        (name.to_string(), version.to_string())
+    }
+
+    #[test]
+    fn test_parse_cargo_and_poetry_locks() {
+        let cargo = "version = 3\n\n[[package]]\nname = \"demo\"\nversion = \"0.1.0\"\n\n[[package]]\nname = \"time\"\nversion = \"0.1.43\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n";
+        assert_eq!(pairs("Cargo.lock", cargo), vec![pair("time", "0.1.43")]);
+
+        let poetry = "[[package]]\nname = \"jinja2\"\nversion = \"2.10\"\n\n[package.dependencies]\nmarkupsafe = \">=0.23\"\n\n[[package]]\nname = \"markupsafe\"\nversion = \"2.1.3\"\n";
+        assert_eq!(
+            pairs("poetry.lock", poetry),
+            vec![pair("jinja2", "2.10"), pair("markupsafe", "2.1.3")]
+        );
+    }
+
+    #[test]
+    fn test_parse_npm_locks() {
+        let v3 = r#"{"lockfileVersion": 3, "packages": {
+            "": {"name": "web"},
+            "node_modules/lodash": {"version": "4.17.20"},
+            "node_modules/a/node_modules/@scope/b": {"version": "1.0.0"},
+            "packages/ui": {"link": true}
+        }}"#;
+        assert_eq!(
+            pairs("package-lock.json", v3),
+            vec![pair("@scope/b", "1.0.0"), pair("lodash", "4.17.20")]
+        );
+
+        let pnpm = "lockfileVersion: '6.0'\n\ndependencies:\n  lodash:\n    specifier: ^4\n\npackages:\n\n  /lodash@4.17.20:\n    resolution: {integrity: x}\n\n  /@babel/core@7.20.0(supports-color@5.5.0):\n    dev: true\n\n  /minimist/1.2.5_abc:\n    dev: false\n";
+        assert_eq!(
+            pairs("pnpm-lock.yaml", pnpm),
+            vec![
+                pair("@babel/core", "7.20.0"),
+                pair("lodash", "4.17.20"),
+                pair("minimist", "1.2.5"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_go_sum() {
+        let content = "github.com/gin-gonic/gin v1.6.0 h1:abc=\ngithub.com/gin-gonic/gin v1.6.0/go.mod h1:def=\ngithub.com/old/dep v1.0.0/go.mod h1:ghi=\n";
+        assert_eq!(
+            pairs("go.sum", content),
+            vec![pair("github.com/gin-gonic/gin", "v1.6.0")]
+        );
+    }
+}
@@ -0,0 +1,1450 @@
+//! Dependency manifests
+//!
+//! Reads declared dependencies (name, version, scope) from Cargo.toml,
+//! package.json, pyproject.toml / requirements*.txt and go.mod, and
+//! cross-references them against the external imports recorded in the index
+//! to find packages that are declared but never used, or used but never
+//! declared. Findings become static suggestions - no LLM cost.
+
+pub mod advisories;
+pub mod license;
+pub mod lockfile;
+mod parse;
+pub mod sbom;
+
+use self::advisories::{Advisory, AdvisoryDb};
+use self::lockfile::LockedPackage;
+use crate::index::{is_ignored, CodebaseIndex, FileIndex, Language};
+use crate::suggest::{Priority, Suggestion, SuggestionKind, SuggestionSource};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Package ecosystem a manifest belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Ecosystem {
+    Cargo,
+    Npm,
+    PyPI,
+    Go,
+}
+
+impl Ecosystem {
+    /// Ecosystem whose manifests govern files in this language
+    pub fn for_language(language: Language) -> Option<Self> {
+        match language {
+            Language::Rust => Some(Ecosystem::Cargo),
+            Language::JavaScript | Language::TypeScript => Some(Ecosystem::Npm),
+            Language::Python => Some(Ecosystem::PyPI),
+            Language::Go => Some(Ecosystem::Go),
+            _ => None,
+        }
+    }
+}
+
+/// Where a declared dependency is needed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum DependencyScope {
+    /// Shipped with the code
+    Runtime,
+    /// Tests, linting, local tooling
+    Dev,
+    /// Build scripts (`[build-dependencies]`)
+    Build,
+    /// Expected from the host (`peerDependencies`)
+    Peer,
+    /// Extras / `optionalDependencies`
+    Optional,
+    /// Pulled in transitively but pinned in the manifest (`// indirect` in go.mod)
+    Indirect,
+}
+
+impl DependencyScope {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DependencyScope::Runtime => "runtime",
+            DependencyScope::Dev => "dev",
+            DependencyScope::Build => "build",
+            DependencyScope::Peer => "peer",
+            DependencyScope::Optional => "optional",
+            DependencyScope::Indirect => "indirect",
+        }
+    }
+}
+
+/// A dependency declared in a manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeclaredDependency {
+    pub name: String,
+    /// Version or requirement as written (`^1.2`, `>=2.31`, `v1.8.0`)
+    pub version: Option<String>,
+    pub scope: DependencyScope,
+    pub ecosystem: Ecosystem,
+    /// Manifest path, relative to the repo root
+    pub manifest: PathBuf,
+    pub line: usize,
+}
+
+impl DeclaredDependency {
+    fn new(
+        name: &str,
+        version: Option<String>,
+        scope: DependencyScope,
+        ecosystem: Ecosystem,
+        manifest: &Path,
+        line: usize,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            version,
+            scope,
+            ecosystem,
+            manifest: manifest.to_path_buf(),
+            line,
+        }
+    }
+}
+
+/// One parsed manifest file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Relative to the repo root
+    pub path: PathBuf,
+    pub ecosystem: Ecosystem,
+    /// Package / module name the manifest declares (`module` for go.mod)
+    pub package_name: Option<String>,
+    /// `[lib] name` override (Cargo only)
+    pub lib_name: Option<String>,
+    pub dependencies: Vec<DeclaredDependency>,
+    /// Script commands (package.json `scripts`), which can use packages without importing them
+    pub scripts: Vec<String>,
+    /// The project's own declared license
+    pub license: Option<String>,
+}
+
+impl Manifest {
+    fn new(path: &Path, ecosystem: Ecosystem) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            ecosystem,
+            package_name: None,
+            lib_name: None,
+            dependencies: Vec::new(),
+            scripts: Vec::new(),
+            license: None,
+        }
+    }
+
+    /// Directory the manifest governs
+    pub fn dir(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new(""))
+    }
+}
+
+/// What's wrong with a dependency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FindingKind {
+    /// Declared but nothing imports it
+    Unused,
+    /// Imported but not declared in the governing manifest
+    Undeclared,
+}
+
+/// A mismatch between a manifest and the code it governs
+#[derive(Debug, Clone)]
+pub struct DependencyFinding {
+    pub kind: FindingKind,
+    pub name: String,
+    /// Manifest to change
+    pub manifest: PathBuf,
+    /// Declaration line (unused) - `None` for undeclared packages
+    pub line: Option<usize>,
+    pub scope: Option<DependencyScope>,
+    /// Files and lines importing the package (undeclared only)
+    pub importers: Vec<(PathBuf, usize)>,
+}
+
+/// A locked package version with known advisories
+#[derive(Debug, Clone)]
+pub struct VulnerabilityFinding {
+    pub package: LockedPackage,
+    pub advisories: Vec<Advisory>,
+    /// Manifest to bump the requirement in (the lockfile if there is none)
+    pub manifest: PathBuf,
+    /// Declaration line when the package is a direct dependency
+    pub line: Option<usize>,
+    /// Files and lines importing the package; empty for transitive dependencies
+    pub importers: Vec<(PathBuf, usize)>,
+}
+
+/// All manifests and lockfiles in a repository
+#[derive(Debug, Clone, Default)]
+pub struct ManifestInventory {
+    pub manifests: Vec<Manifest>,
+    /// Resolved versions from every lockfile
+    pub locked: Vec<LockedPackage>,
+}
+
+impl ManifestInventory {
+    /// Find and parse every manifest and lockfile under `root`, skipping
+    /// the same directories the index skips
+    pub fn load(root: &Path) -> Self {
+        let mut manifests = Vec::new();
+        let mut locked = Vec::new();
+        for entry in WalkDir::new(root)
+            .into_iter()
+            .filter_entry(|e| !is_ignored(e.path()))
+            .filter_map(|e| e.ok())
+        {
+            let name = entry.file_name().to_str().unwrap_or_default();
+            if !entry.file_type().is_file()
+                || !(parse::is_manifest(name) || lockfile::is_lockfile(name))
+            {
+                continue;
+            }
+            let rel = entry
+                .path()
+                .strip_prefix(root)
+                .unwrap_or(entry.path())
+                .to_path_buf();
+            let Ok(content) = std::fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            if let Some(manifest) = parse::parse_manifest(&rel, &content) {
+                manifests.push(manifest);
+            } else if let Some(packages) = lockfile::parse_lockfile(&rel, &content) {
+                locked.extend(packages);
+            }
+        }
+        manifests.sort_by(|a, b| a.path.cmp(&b.path));
+        locked.sort_by(|a, b| (&a.lockfile, &a.name).cmp(&(&b.lockfile, &b.name)));
+        Self { manifests, locked }
+    }
+
+    /// Cross-reference declared dependencies against the index's imports
+    pub fn findings(&self, index: &CodebaseIndex) -> Vec<DependencyFinding> {
+        let groups = self.groups();
+        let locals = LocalNames::collect(index, &self.manifests);
+        let usage = UsageScanner::new();
+
+        // Raw package references per group: imports (with location) and other usage
+        let mut imports: Vec<Vec<(String, PathBuf, usize)>> = vec![Vec::new(); groups.len()];
+        let mut mentions: Vec<HashSet<String>> = vec![HashSet::new(); groups.len()];
+        let mut file_counts = vec![0usize; groups.len()];
+
+        for (path, file_index) in &index.files {
+            let Some(ecosystem) = Ecosystem::for_language(file_index.language) else {
+                continue;
+            };
+            let Some(group) = owning_group(&groups, ecosystem, path) else {
+                continue;
+            };
+            file_counts[group] += 1;
+            for (package, line) in imported_packages(file_index, ecosystem, &locals) {
+                imports[group].push((package, path.clone(), line));
+            }
+            if matches!(ecosystem, Ecosystem::Cargo | Ecosystem::Npm) {
+                if let Ok(content) = std::fs::read_to_string(index.root.join(path)) {
+                    mentions[group].extend(usage.scan(ecosystem, &content));
+                }
+            }
+        }
+
+        let mut findings = Vec::new();
+        for (i, group) in groups.iter().enumerate() {
+            // Nothing indexed under this manifest (e.g. only .vue files) - can't judge
+            if file_counts[i] == 0 {
+                continue;
+            }
+
+            let declared = group.declared_keys();
+            let mut used: HashSet<&str> = HashSet::new();
+            let mut undeclared: BTreeMap<String, Vec<(PathBuf, usize)>> = BTreeMap::new();
+
+            for (package, path, line) in &imports[i] {
+                match resolve(group.ecosystem, package, &declared) {
+                    Some(key) => {
+                        used.insert(key);
+                    }
+                    None => {
+                        let declared_above = groups
+                            .iter()
+                            .filter(|g| g.is_ancestor_of(group))
+                            .any(|g| resolve(g.ecosystem, package, &g.declared_keys()).is_some());
+                        if !declared_above {
+                            undeclared
+                                .entry(undeclared_name(group.ecosystem, package))
+                                .or_default()
+                                .push((path.clone(), *line));
+                        }
+                    }
+                }
+            }
+            for package in &mentions[i] {
+                if let Some(key) = resolve(group.ecosystem, package, &declared) {
+                    used.insert(key);
+                }
+            }
+
+            for manifest in &group.manifests {
+                for dep in &manifest.dependencies {
+                    if !expects_import(dep)
+                        || package_keys(dep.ecosystem, &dep.name)
+                            .iter()
+                            .any(|k| used.contains(k.as_str()))
+                        || used_by_script(dep, manifest)
+                    {
+                        continue;
+                    }
+                    findings.push(DependencyFinding {
+                        kind: FindingKind::Unused,
+                        name: dep.name.clone(),
+                        manifest: dep.manifest.clone(),
+                        line: Some(dep.line),
+                        scope: Some(dep.scope),
+                        importers: Vec::new(),
+                    });
+                }
+            }
+
+            for (name, mut importers) in undeclared {
+                importers.sort();
+                importers.dedup();
+                findings.push(DependencyFinding {
+                    kind: FindingKind::Undeclared,
+                    name,
+                    manifest: group.primary().path.clone(),
+                    line: None,
+                    scope: None,
+                    importers,
+                });
+            }
+        }
+
+        findings
+    }
+
+    /// Findings as static suggestions, one per manifest and finding kind
+    pub fn static_suggestions(&self, index: &CodebaseIndex) -> Vec<Suggestion> {
+        let mut grouped: BTreeMap<(PathBuf, FindingKind), Vec<DependencyFinding>> = BTreeMap::new();
+        for finding in self.findings(index) {
+            grouped
+                .entry((finding.manifest.clone(), finding.kind))
+                .or_default()
+                .push(finding);
+        }
+
+        grouped
+            .into_iter()
+            .map(|((manifest, kind), findings)| match kind {
+                FindingKind::Unused => unused_suggestion(&manifest, &findings),
+                FindingKind::Undeclared => undeclared_suggestion(&manifest, &findings),
+            })
+            .collect()
+    }
+
+    /// Locked packages matching an advisory, with the code that imports them
+    pub fn vulnerabilities(
+        &self,
+        index: &CodebaseIndex,
+        db: &AdvisoryDb,
+    ) -> Vec<VulnerabilityFinding> {
+        let groups = self.groups();
+        let imports = self.external_imports(index);
+
+        let mut findings = Vec::new();
+        for package in &self.locked {
+            let advisories: Vec<Advisory> = db.matching(package).cloned().collect();
+            if advisories.is_empty() {
+                continue;
+            }
+
+            let dir = package.lockfile.parent().unwrap_or(Path::new(""));
+            let importers = importers_of(&imports, package.ecosystem, dir, &package.name);
+
+            let group = groups
+                .iter()
+                .find(|g| g.ecosystem == package.ecosystem && g.dir == dir);
+            let line = group.and_then(|g| {
+                g.manifests
+                    .iter()
+                    .flat_map(|m| m.dependencies.iter())
+                    .find(|d| d.name == package.name)
+                    .map(|d| d.line)
+            });
+
+            findings.push(VulnerabilityFinding {
+                package: package.clone(),
+                advisories,
+                manifest: group
+                    .map(|g| g.primary().path.clone())
+                    .unwrap_or_else(|| package.lockfile.clone()),
+                line,
+                importers,
+            });
+        }
+
+        findings
+    }
+
+    /// Every external import in the index, tagged with its ecosystem
+    fn external_imports<'a>(&self, index: &'a CodebaseIndex) -> Vec<ExternalImport<'a>> {
+        let locals = LocalNames::collect(index, &self.manifests);
+        let mut imports = Vec::new();
+        for (path, file_index) in &index.files {
+            if let Some(ecosystem) = Ecosystem::for_language(file_index.language) {
+                for (package, line) in imported_packages(file_index, ecosystem, &locals) {
+                    imports.push((ecosystem, path, package, line));
+                }
+            }
+        }
+        imports
+    }
+
+    /// Vulnerable packages as security suggestions, one per locked version
+    pub fn vulnerability_suggestions(
+        &self,
+        index: &CodebaseIndex,
+        db: &AdvisoryDb,
+    ) -> Vec<Suggestion> {
+        self.vulnerabilities(index, db)
+            .iter()
+            .map(vulnerability_suggestion)
+            .collect()
+    }
+
+    /// Manifests grouped by ecosystem and directory (pyproject.toml and
+    /// requirements.txt side by side describe one project)
+    fn groups(&self) -> Vec<ManifestGroup<'_>> {
+        let mut groups: Vec<ManifestGroup> = Vec::new();
+        for manifest in &self.manifests {
+            match groups
+                .iter_mut()
+                .find(|g| g.ecosystem == manifest.ecosystem && g.dir == manifest.dir())
+            {
+                Some(group) => group.manifests.push(manifest),
+                None => groups.push(ManifestGroup {
+                    ecosystem: manifest.ecosystem,
+                    dir: manifest.dir().to_path_buf(),
+                    manifests: vec![manifest],
+                }),
+            }
+        }
+        groups
+    }
+}
+
+/// Manifests of one ecosystem governing one directory
+struct ManifestGroup<'a> {
+    ecosystem: Ecosystem,
+    dir: PathBuf,
+    manifests: Vec<&'a Manifest>,
+}
+
+impl ManifestGroup<'_> {
+    /// Import keys of every declared dependency
+    fn declared_keys(&self) -> HashSet<String> {
+        self.manifests
+            .iter()
+            .flat_map(|m| m.dependencies.iter())
+            .flat_map(|d| package_keys(d.ecosystem, &d.name))
+            .collect()
+    }
+
+    /// The manifest new dependencies belong in
+    fn primary(&self) -> &Manifest {
+        let rank = |m: &Manifest| match m.path.file_name().and_then(|n| n.to_str()) {
+            Some("pyproject.toml") => 0,
+            Some("requirements.txt") => 1,
+            _ => 2,
+        };
+        self.manifests
+            .iter()
+            .copied()
+            .min_by_key(|m| rank(m))
+            .unwrap_or(self.manifests[0])
+    }
+
+    /// JS and Python resolve packages installed further up the tree
+    /// (workspaces, shared virtualenvs); Cargo and Go don't
+    fn is_ancestor_of(&self, other: &ManifestGroup) -> bool {
+        matches!(self.ecosystem, Ecosystem::Npm | Ecosystem::PyPI)
+            && self.ecosystem == other.ecosystem
+            && self.dir != other.dir
+            && other.dir.starts_with(&self.dir)
+    }
+}
+
+/// Index of the group whose directory most closely contains `path`
+fn owning_group(groups: &[ManifestGroup], ecosystem: Ecosystem, path: &Path) -> Option<usize> {
+    groups
+        .iter()
+        .enumerate()
+        .filter(|(_, g)| g.ecosystem == ecosystem && path.starts_with(&g.dir))
+        .max_by_key(|(_, g)| g.dir.components().count())
+        .map(|(i, _)| i)
+}
+
+/// (ecosystem, importing file, package, line)
+type ExternalImport<'a> = (Ecosystem, &'a PathBuf, String, usize);
+
+/// Files under `dir` that import package `name`, with the import line
+fn importers_of(
+    imports: &[ExternalImport],
+    ecosystem: Ecosystem,
+    dir: &Path,
+    name: &str,
+) -> Vec<(PathBuf, usize)> {
+    let keys: HashSet<String> = package_keys(ecosystem, name).into_iter().collect();
+    let mut importers: Vec<(PathBuf, usize)> = imports
+        .iter()
+        .filter(|(eco, path, import, _)| {
+            *eco == ecosystem && path.starts_with(dir) && resolve(*eco, import, &keys).is_some()
+        })
+        .map(|(_, path, _, line)| ((*path).clone(), *line))
+        .collect();
+    importers.sort();
+    importers.dedup();
+    importers
+}
+
+/// Match an imported package against declared keys, returning the key it resolves to
+fn resolve<'a>(
+    ecosystem: Ecosystem,
+    package: &str,
+    declared: &'a HashSet<String>,
+) -> Option<&'a str> {
+    match ecosystem {
+        // Imports name packages inside a module: longest declared module prefix wins
+        Ecosystem::Go => declared
+            .iter()
+            .filter(|module| {
+                package == module.as_str() || package.starts_with(&format!("{}/", module))
+            })
+            .max_by_key(|module| module.len())
+            .map(|s| s.as_str()),
+        Ecosystem::PyPI => declared.get(&package.to_lowercase()).map(|s| s.as_str()),
+        _ => declared.get(package).map(|s| s.as_str()),
+    }
+}
+
+/// Display name for a package nobody declared
+fn undeclared_name(ecosystem: Ecosystem, package: &str) -> String {
+    match ecosystem {
+        // github.com/org/repo/sub/pkg -> github.com/org/repo
+        Ecosystem::Go => {
+            let parts: Vec<&str> = package.split('/').collect();
+            let hosted = ["github.com", "gitlab.com", "bitbucket.org"].contains(&parts[0]);
+            if hosted && parts.len() > 3 {
+                parts[..3].join("/")
+            } else {
+                package.to_string()
+            }
+        }
+        _ => package.to_string(),
+    }
+}
+
+/// Names code uses to import a package
+fn package_keys(ecosystem: Ecosystem, name: &str) -> Vec<String> {
+    match ecosystem {
+        Ecosystem::Cargo => vec![name.replace('-', "_")],
+        Ecosystem::Npm | Ecosystem::Go => vec![name.to_string()],
+        Ecosystem::PyPI => {
+            let normalized = name.to_lowercase().replace(['-', '.'], "_");
+            let mut keys: Vec<String> = PYTHON_IMPORT_ALIASES
+                .iter()
+                .filter(|(dist, _)| *dist == normalized)
+                .map(|(_, module)| module.to_lowercase())
+                .collect();
+            keys.push(normalized);
+            keys
+        }
+    }
+}
+
+/// Whether a declared dependency should show up in imports at all
+fn expects_import(dep: &DeclaredDependency) -> bool {
+    match (dep.ecosystem, dep.scope) {
+        (_, DependencyScope::Peer | DependencyScope::Optional | DependencyScope::Indirect) => false,
+        // Test and build crates are imported like any other crate
+        (Ecosystem::Cargo, _) => true,
+        (Ecosystem::Npm, DependencyScope::Runtime) => {
+            !dep.name.starts_with("@types/") && dep.name != "typescript"
+        }
+        (Ecosystem::PyPI, DependencyScope::Runtime) => {
+            let normalized = dep.name.to_lowercase().replace('_', "-");
+            !PYTHON_NON_IMPORTED.contains(&normalized.as_str())
+        }
+        (Ecosystem::Go, DependencyScope::Runtime) => true,
+        // JS/Python dev dependencies are mostly CLI tools
+        _ => false,
+    }
+}
+
+/// package.json scripts run CLIs (`next dev`, `prisma generate`) that are never imported
+fn used_by_script(dep: &DeclaredDependency, manifest: &Manifest) -> bool {
+    let bin = dep.name.rsplit('/').next().unwrap_or(&dep.name);
+    manifest.scripts.iter().any(|script| {
+        script
+            .split(|c: char| c.is_whitespace() || c == '&' || c == ';' || c == '|')
+            .any(|word| word == dep.name || word == bin)
+    })
+}
+
+/// Names that look like external packages but live in the repo or the standard library
+struct LocalNames {
+    rust: HashSet<String>,
+    python: HashSet<String>,
+    npm: HashSet<String>,
+    go_modules: Vec<String>,
+}
+
+impl LocalNames {
+    fn collect(index: &CodebaseIndex, manifests: &[Manifest]) -> Self {
+        let mut rust: HashSet<String> = RUST_BUILTIN_ROOTS.iter().map(|s| s.to_string()).collect();
+        let mut python: HashSet<String> = PYTHON_STDLIB.iter().map(|s| s.to_string()).collect();
+        let mut npm: HashSet<String> = NODE_BUILTINS.iter().map(|s| s.to_string()).collect();
+        let mut go_modules = Vec::new();
+
+        // Module files and directories (`use cache::Cache` in a binary, first-party Python packages)
+        for (path, file_index) in &index.files {
+            let names = path
+                .with_extension("")
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>();
+            match file_index.language {
+                Language::Rust => rust.extend(names),
+                Language::Python => python.extend(names.into_iter().map(|n| n.to_lowercase())),
+                _ => {}
+            }
+        }
+
+        for manifest in manifests {
+            let Some(name) = &manifest.package_name else {
+                continue;
+            };
+            match manifest.ecosystem {
+                Ecosystem::Cargo => {
+                    rust.insert(name.replace('-', "_"));
+                    if let Some(lib) = &manifest.lib_name {
+                        rust.insert(lib.replace('-', "_"));
+                    }
+                }
+                Ecosystem::Npm => {
+                    npm.insert(name.clone());
+                }
+                Ecosystem::PyPI => {
+                    python.insert(name.to_lowercase().replace('-', "_"));
+                }
+                Ecosystem::Go => go_modules.push(name.clone()),
+            }
+        }
+
+        Self {
+            rust,
+            python,
+            npm,
+            go_modules,
+        }
+    }
+}
+
+/// External packages a file imports, with the import line
+fn imported_packages(
+    file_index: &FileIndex,
+    ecosystem: Ecosystem,
+    locals: &LocalNames,
+) -> Vec<(String, usize)> {
+    let mut packages = Vec::new();
+    for dep in file_index.dependencies().iter().filter(|d| d.is_external) {
+        let names = match ecosystem {
+            Ecosystem::Cargo => rust_import_root(&dep.import_path)
+                .filter(|root| !locals.rust.contains(root))
+                .into_iter()
+                .collect(),
+            Ecosystem::Npm => npm_package(&dep.import_path)
+                .filter(|package| !locals.npm.contains(package))
+                .into_iter()
+                .collect(),
+            Ecosystem::PyPI => python_import_roots(&dep.import_path)
+                .into_iter()
+                .filter(|root| !locals.python.contains(&root.to_lowercase()))
+                .collect(),
+            Ecosystem::Go => {
+                let path = dep.import_path.trim();
+                let first = path.split('/').next().unwrap_or("");
+                let is_local = locals
+                    .go_modules
+                    .iter()
+                    .any(|m| path == m || path.starts_with(&format!("{}/", m)));
+                // Standard library paths have no domain (`net/http`)
+                if first.contains('.') && !is_local {
+                    vec![path.to_string()]
+                } else {
+                    Vec::new()
+                }
+            }
+        };
+        packages.extend(names.into_iter().map(|name| (name, dep.line)));
+    }
+    packages
+}
+
+/// `serde::{Deserialize, Serialize}` / `pub use ::tokio::sync` -> crate name
+fn rust_import_root(import: &str) -> Option<String> {
+    let mut text = import.trim();
+    if text.starts_with("pub ") || text.starts_with("pub(") {
+        text = text.split_once(char::is_whitespace).map(|(_, r)| r)?.trim();
+    }
+    let text = text.trim_start_matches("::");
+    let root = text
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .next()
+        .unwrap_or("");
+    (!root.is_empty()).then(|| root.to_string())
+}
+
+/// `lodash/fp` -> `lodash`, `@scope/pkg/sub` -> `@scope/pkg`; `None` for
+/// builtins, path aliases and URLs
+fn npm_package(import: &str) -> Option<String> {
+    let import = import.trim();
+    if import.is_empty()
+        || import.starts_with(['.', '/', '~', '#'])
+        || import.starts_with("@/")
+        || import.contains(':')
+    {
+        return None;
+    }
+    let mut parts = import.split('/');
+    let first = parts.next()?;
+    if first.starts_with('@') {
+        let second = parts.next()?;
+        Some(format!("{}/{}", first, second))
+    } else {
+        Some(first.to_string())
+    }
+}
+
+/// Top-level modules of a Python import statement:
+/// `import os.path, yaml as y` -> [os, yaml]; `from PIL import Image` -> [PIL]
+fn python_import_roots(import: &str) -> Vec<String> {
+    let import = import.trim();
+    let modules: Vec<&str> = if let Some(rest) = import.strip_prefix("from ") {
+        rest.split(" import").next().into_iter().collect()
+    } else if let Some(rest) = import.strip_prefix("import ") {
+        rest.split(',').collect()
+    } else {
+        Vec::new()
+    };
+
+    modules
+        .into_iter()
+        .map(|m| m.split(" as ").next().unwrap_or("").trim())
+        .filter(|m| !m.is_empty() && !m.starts_with('.'))
+        .map(|m| m.split('.').next().unwrap_or(m).to_string())
+        .collect()
+}
+
+/// Package references outside import statements: Rust paths and macros
+/// used without a `use` (`anyhow::Result`, `#[tokio::main]`), and CommonJS
+/// `require` / dynamic `import()` / re-exports in JS
+struct UsageScanner {
+    rust_path: Regex,
+    js_module: Regex,
+}
+
+impl UsageScanner {
+    fn new() -> Self {
+        Self {
+            rust_path: Regex::new(
+                r"(?:^|[^\w:])([a-z_][a-z0-9_]*)::|extern crate\s+([a-z_][a-z0-9_]*)",
+            )
+            .expect("valid regex"),
+            js_module: Regex::new(r#"(?:require\s*\(|import\s*\(|from)\s*['"]([^'"]+)['"]"#)
+                .expect("valid regex"),
+        }
+    }
+
+    fn scan(&self, ecosystem: Ecosystem, content: &str) -> HashSet<String> {
+        match ecosystem {
+            Ecosystem::Cargo => self
+                .rust_path
+                .captures_iter(content)
+                .filter_map(|c| c.get(1).or_else(|| c.get(2)))
+                .map(|m| m.as_str().to_string())
+                .collect(),
+            Ecosystem::Npm => self
+                .js_module
+                .captures_iter(content)
+                .filter_map(|c| npm_package(&c[1]))
+                .collect(),
+            _ => HashSet::new(),
+        }
+    }
+}
+
+fn unused_suggestion(manifest: &Path, findings: &[DependencyFinding]) -> Suggestion {
+    let names: Vec<&str> = findings.iter().map(|f| f.name.as_str()).collect();
+    let summary = if findings.len() == 1 {
+        format!(
+            "{} declares `{}` but nothing imports it",
+            manifest.display(),
+            names[0]
+        )
+    } else {
+        format!(
+            "{} declares {} dependencies nothing imports: {}",
+            manifest.display(),
+            findings.len(),
+            name_list(&names)
+        )
+    };
+
+    let mut detail = String::from(
+        "Unused dependencies add install/build time and attack surface. \
+         Remove them from the manifest (and lockfile) if nothing needs them:\n",
+    );
+    for finding in findings {
+        detail.push_str(&format!(
+            "\n- {} ({}, line {})",
+            finding.name,
+            finding.scope.map(|s| s.label()).unwrap_or("runtime"),
+            finding.line.unwrap_or(0)
+        ));
+    }
+    detail.push_str(
+        "\n\nPackages loaded by plugins, config files or by name at runtime can look unused - \
+         check before removing.",
+    );
+
+    let mut suggestion = Suggestion::new(
+        SuggestionKind::Quality,
+        Priority::Low,
+        manifest.to_path_buf(),
+        summary,
+        SuggestionSource::Static,
+    )
+    .with_detail(detail);
+    if let Some(line) = findings.iter().filter_map(|f| f.line).min() {
+        suggestion = suggestion.with_line(line);
+    }
+    suggestion
+}
+
+fn undeclared_suggestion(manifest: &Path, findings: &[DependencyFinding]) -> Suggestion {
+    let names: Vec<&str> = findings.iter().map(|f| f.name.as_str()).collect();
+    let summary = if findings.len() == 1 {
+        format!(
+            "`{}` is imported but not declared in {}",
+            names[0],
+            manifest.display()
+        )
+    } else {
+        format!(
+            "{} packages are imported but not declared in {}: {}",
+            findings.len(),
+            manifest.display(),
+            name_list(&names)
+        )
+    };
+
+    let mut detail = String::from(
+        "These imports only work because the package happens to be installed \
+         (transitively, globally, or in a parent project). A clean install or an \
+         unrelated upgrade can break them. Declare them in the manifest:\n",
+    );
+    for finding in findings {
+        let locations: Vec<String> = finding
+            .importers
+            .iter()
+            .take(3)
+            .map(|(path, line)| format!("{}:{}", path.display(), line))
+            .collect();
+        let more = finding.importers.len().saturating_sub(3);
+        detail.push_str(&format!(
+            "\n- {} (imported in {}",
+            finding.name,
+            locations.join(", ")
+        ));
+        if more > 0 {
+            detail.push_str(&format!(" +{} more", more));
+        }
+        detail.push(')');
+    }
+
+    Suggestion::new(
+        SuggestionKind::BugFix,
+        Priority::Medium,
+        manifest.to_path_buf(),
+        summary,
+        SuggestionSource::Static,
+    )
+    .with_detail(detail)
+}
+
+fn vulnerability_suggestion(finding: &VulnerabilityFinding) -> Suggestion {
+    let package = &finding.package;
+    let ids: Vec<&str> = finding.advisories.iter().map(|a| a.id.as_str()).collect();
+    let summary = if ids.len() == 1 {
+        format!(
+            "`{}` {} has a known vulnerability ({})",
+            package.name, package.version, ids[0]
+        )
+    } else {
+        format!(
+            "`{}` {} has {} known vulnerabilities ({})",
+            package.name,
+            package.version,
+            ids.len(),
+            name_list(&ids)
+        )
+    };
+
+    let mut detail = format!(
+        "{} pins {} {}, which is affected by:\n",
+        package.lockfile.display(),
+        package.name,
+        package.version
+    );
+    for advisory in &finding.advisories {
+        detail.push_str(&format!("\n- {}", advisory.id));
+        if !advisory.aliases.is_empty() {
+            detail.push_str(&format!(" ({})", advisory.aliases.join(", ")));
+        }
+        if let Some(severity) = &advisory.severity {
+            detail.push_str(&format!(" [{}]", severity.to_uppercase()));
+        }
+        if !advisory.summary.is_empty() {
+            detail.push_str(&format!(": {}", advisory.summary));
+        }
+        let fixed = advisory.fixed_versions();
+        if fixed.is_empty() {
+            detail.push_str("\n  No fixed version published - consider replacing the package");
+        } else {
+            detail.push_str(&format!("\n  Fixed in: {}", fixed.join(", ")));
+        }
+    }
+
+    if finding.importers.is_empty() {
+        detail.push_str(
+            "\n\nNothing imports it directly - it comes in transitively. Upgrade the \
+             dependency that pulls it in, or pin a fixed version, then regenerate the lockfile.",
+        );
+    } else {
+        detail.push_str("\n\nImported by:");
+        for (path, line) in finding.importers.iter().take(5) {
+            detail.push_str(&format!("\n- {}:{}", path.display(), line));
+        }
+        if finding.importers.len() > 5 {
+            detail.push_str(&format!("\n- +{} more", finding.importers.len() - 5));
+        }
+        detail.push_str(&format!(
+            "\n\nRaise the requirement in {} to a fixed version and regenerate the lockfile.",
+            finding.manifest.display()
+        ));
+    }
+
+    let severe = finding.advisories.iter().any(|a| a.is_severe());
+    let priority = if severe || !finding.importers.is_empty() {
+        Priority::High
+    } else {
+        Priority::Medium
+    };
+
+    let mut suggestion = Suggestion::new(
+        SuggestionKind::Security,
+        priority,
+        finding.manifest.clone(),
+        summary,
+        SuggestionSource::Static,
+    )
+    .with_detail(detail);
+    if let Some(line) = finding.line {
+        suggestion = suggestion.with_line(line);
+    }
+    suggestion
+}
+
+/// `a, b, c +2 more`
+fn name_list(names: &[&str]) -> String {
+    let shown = names.iter().take(4).copied().collect::<Vec<_>>().join(", ");
+    if names.len() > 4 {
+        format!("{} +{} more", shown, names.len() - 4)
+    } else {
+        shown
+    }
+}
+
+/// Path roots that are never crates.io dependencies
+const RUST_BUILTIN_ROOTS: &[&str] = &[
+    "std",
+    "core",
+    "alloc",
+    "proc_macro",
+    "test",
+    "crate",
+    "self",
+    "super",
+    "bool",
+    "char",
+    "str",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "usize",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+    "isize",
+    "f32",
+    "f64",
+];
+
+/// Distribution names whose import name differs (normalized dist, module)
+const PYTHON_IMPORT_ALIASES: &[(&str, &str)] = &[
+    ("pillow", "PIL"),
+    ("pyyaml", "yaml"),
+    ("beautifulsoup4", "bs4"),
+    ("scikit_learn", "sklearn"),
+    ("scikit_image", "skimage"),
+    ("python_dateutil", "dateutil"),
+    ("python_dotenv", "dotenv"),
+    ("opencv_python", "cv2"),
+    ("opencv_python_headless", "cv2"),
+    ("psycopg2_binary", "psycopg2"),
+    ("pyjwt", "jwt"),
+    ("attrs", "attr"),
+    ("protobuf", "google"),
+    ("djangorestframework", "rest_framework"),
+    ("pymupdf", "fitz"),
+    ("msgpack_python", "msgpack"),
+    ("pyserial", "serial"),
+    ("pycryptodome", "Crypto"),
+    ("google_cloud_storage", "google"),
+    ("setuptools", "pkg_resources"),
+];
+
+/// Runtime packages used by servers, drivers or entry points rather than imports
+const PYTHON_NON_IMPORTED: &[&str] = &[
+    "gunicorn",
+    "uvicorn",
+    "hypercorn",
+    "daphne",
+    "psycopg2",
+    "psycopg2-binary",
+    "psycopg",
+    "mysqlclient",
+    "python-multipart",
+    "setuptools",
+    "wheel",
+    "pip",
+];
+
+/// Node.js core modules
+const NODE_BUILTINS: &[&str] = &[
+    "assert",
+    "async_hooks",
+    "buffer",
+    "child_process",
+    "cluster",
+    "console",
+    "crypto",
+    "dgram",
+    "dns",
+    "events",
+    "fs",
+    "http",
+    "http2",
+    "https",
+    "module",
+    "net",
+    "os",
+    "path",
+    "perf_hooks",
+    "process",
+    "querystring",
+    "readline",
+    "stream",
+    "string_decoder",
+    "timers",
+    "tls",
+    "tty",
+    "url",
+    "util",
+    "v8",
+    "vm",
+    "worker_threads",
+    "zlib",
+];
+
+/// Python standard library top-level modules
+const PYTHON_STDLIB: &[&str] = &[
+    "__future__",
+    "abc",
+    "argparse",
+    "array",
+    "ast",
+    "asyncio",
+    "atexit",
+    "base64",
+    "bdb",
+    "binascii",
+    "bisect",
+    "builtins",
+    "bz2",
+    "calendar",
+    "cmath",
+    "cmd",
+    "codecs",
+    "collections",
+    "colorsys",
+    "concurrent",
+    "configparser",
+    "contextlib",
+    "contextvars",
+    "copy",
+    "copyreg",
+    "cProfile",
+    "csv",
+    "ctypes",
+    "curses",
+    "dataclasses",
+    "datetime",
+    "dbm",
+    "decimal",
+    "difflib",
+    "dis",
+    "doctest",
+    "email",
+    "encodings",
+    "enum",
+    "errno",
+    "faulthandler",
+    "fcntl",
+    "filecmp",
+    "fileinput",
+    "fnmatch",
+    "fractions",
+    "ftplib",
+    "functools",
+    "gc",
+    "getopt",
+    "getpass",
+    "gettext",
+    "glob",
+    "graphlib",
+    "grp",
+    "gzip",
+    "hashlib",
+    "heapq",
+    "hmac",
+    "html",
+    "http",
+    "imaplib",
+    "importlib",
+    "inspect",
+    "io",
+    "ipaddress",
+    "itertools",
+    "json",
+    "keyword",
+    "linecache",
+    "locale",
+    "logging",
+    "lzma",
+    "mailbox",
+    "marshal",
+    "math",
+    "mimetypes",
+    "mmap",
+    "multiprocessing",
+    "netrc",
+    "numbers",
+    "operator",
+    "optparse",
+    "os",
+    "pathlib",
+    "pdb",
+    "pickle",
+    "pkgutil",
+    "platform",
+    "plistlib",
+    "poplib",
+    "posixpath",
+    "pprint",
+    "profile",
+    "pstats",
+    "pty",
+    "pwd",
+    "py_compile",
+    "queue",
+    "quopri",
+    "random",
+    "re",
+    "readline",
+    "reprlib",
+    "resource",
+    "runpy",
+    "sched",
+    "secrets",
+    "select",
+    "selectors",
+    "shelve",
+    "shlex",
+    "shutil",
+    "signal",
+    "site",
+    "smtplib",
+    "socket",
+    "socketserver",
+    "sqlite3",
+    "ssl",
+    "stat",
+    "statistics",
+    "string",
+    "stringprep",
+    "struct",
+    "subprocess",
+    "symtable",
+    "sys",
+    "sysconfig",
+    "syslog",
+    "tarfile",
+    "tempfile",
+    "termios",
+    "textwrap",
+    "threading",
+    "time",
+    "timeit",
+    "tkinter",
+    "token",
+    "tokenize",
+    "tomllib",
+    "trace",
+    "traceback",
+    "tracemalloc",
+    "tty",
+    "turtle",
+    "types",
+    "typing",
+    "unicodedata",
+    "unittest",
+    "urllib",
+    "uuid",
+    "venv",
+    "warnings",
+    "wave",
+    "weakref",
+    "webbrowser",
+    "winreg",
+    "wsgiref",
+    "xml",
+    "xmlrpc",
+    "zipfile",
+    "zipimport",
+    "zlib",
+    "zoneinfo",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_repo(name: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("cosmos_manifest_{}_{}", name, nanos));
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn summarize(findings: &[DependencyFinding]) -> Vec<(FindingKind, String)> {
+        let mut out: Vec<_> = findings.iter().map(|f| (f.kind, f.name.clone())).collect();
+        out.sort();
+        out
+    }
+
+    #[test]
+    fn test_rust_unused_and_undeclared() {
+        let root = temp_repo("rust");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"demo-app\"\n\n[dependencies]\nserde = \"1\"\nanyhow = \"1\"\nregex = \"1\"\ntree-sitter = \"0.24\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/main.rs"),
+            "mod util;\nuse serde::Serialize;\nuse util::helper;\nuse rand::Rng;\nuse std::fmt;\n\nfn main() -> anyhow::Result<()> {\n    let _l = tree_sitter::Parser::new();\n    Ok(())\n}\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/util.rs"), "pub fn helper() {}\n").unwrap();
+
+        let index = CodebaseIndex::new(&root).unwrap();
+        let inventory = ManifestInventory::load(&root);
+        let findings = inventory.findings(&index);
+
+        assert_eq!(
+            summarize(&findings),
+            vec![
+                (FindingKind::Unused, "regex".to_string()),
+                (FindingKind::Undeclared, "rand".to_string()),
+            ]
+        );
+        let undeclared = findings
+            .iter()
+            .find(|f| f.kind == FindingKind::Undeclared)
+            .unwrap();
+        assert_eq!(
+            undeclared.importers,
+            vec![(PathBuf::from("src/main.rs"), 4)]
+        );
+
+        let suggestions = inventory.static_suggestions(&index);
+        assert_eq!(suggestions.len(), 2);
+        assert!(suggestions
+            .iter()
+            .all(|s| s.file == Path::new("Cargo.toml") && s.source == SuggestionSource::Static));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_python_and_npm_findings() {
+        let root = temp_repo("mixed");
+        fs::create_dir_all(root.join("app")).unwrap();
+        fs::create_dir_all(root.join("web/src")).unwrap();
+        fs::write(
+            root.join("requirements.txt"),
+            "PyYAML==6.0\nrequests\ngunicorn\n",
+        )
+        .unwrap();
+        fs::write(root.join("requirements-dev.txt"), "pytest\n").unwrap();
+        fs::write(
+            root.join("app/main.py"),
+            "import os\nimport yaml\nfrom app import helpers\nimport numpy as np\n",
+        )
+        .unwrap();
+        fs::write(root.join("app/helpers.py"), "def f():\n    pass\n").unwrap();
+        fs::write(
+            root.join("web/package.json"),
+            r#"{"name": "web", "scripts": {"dev": "vite"}, "dependencies": {"react": "18", "lodash": "4", "vite": "5"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("web/src/app.ts"),
+            "import React from 'react';\nimport fs from 'fs';\nimport { z } from 'zod/v4';\n",
+        )
+        .unwrap();
+
+        let index = CodebaseIndex::new(&root).unwrap();
+        let findings = ManifestInventory::load(&root).findings(&index);
+
+        assert_eq!(
+            summarize(&findings),
+            vec![
+                (FindingKind::Unused, "lodash".to_string()),
+                (FindingKind::Unused, "requests".to_string()),
+                (FindingKind::Undeclared, "numpy".to_string()),
+                (FindingKind::Undeclared, "zod".to_string()),
+            ]
+        );
+        let numpy = findings.iter().find(|f| f.name == "numpy").unwrap();
+        assert_eq!(numpy.manifest, PathBuf::from("requirements.txt"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_vulnerable_locked_packages() {
+        let root = temp_repo("vuln");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"demo\"\n\n[dependencies]\ntime = \"0.1\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("Cargo.lock"),
+            "[[package]]\nname = \"demo\"\nversion = \"0.1.0\"\n\n[[package]]\nname = \"time\"\nversion = \"0.1.43\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n\n[[package]]\nname = \"smallvec\"\nversion = \"1.6.0\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/main.rs"),
+            "use time::Duration;\n\nfn main() {}\n",
+        )
+        .unwrap();
+
+        let db_root = temp_repo("advisory_db");
+        for (name, id, patched) in [
+            ("time", "RUSTSEC-2020-0071", ">= 0.2.23"),
+            ("smallvec", "RUSTSEC-2021-0003", ">= 1.6.1"),
+        ] {
+            let dir = db_root.join("crates").join(name);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(
+                dir.join(format!("{}.md", id)),
+                format!(
+                    "```toml\n[advisory]\nid = \"{}\"\npackage = \"{}\"\n\n[versions]\npatched = [\"{}\"]\n```\n\n# Issue in {}\n",
+                    id, name, patched, name
+                ),
+            )
+            .unwrap();
+        }
+
+        let index = CodebaseIndex::new(&root).unwrap();
+        let inventory = ManifestInventory::load(&root);
+        let db = AdvisoryDb::load(std::slice::from_ref(&db_root), &inventory.locked);
+        let mut findings = inventory.vulnerabilities(&index, &db);
+        findings.sort_by(|a, b| a.package.name.cmp(&b.package.name));
+
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].package.name, "smallvec");
+        assert!(findings[0].importers.is_empty());
+        assert_eq!(findings[1].package.name, "time");
+        assert_eq!(
+            findings[1].importers,
+            vec![(PathBuf::from("src/main.rs"), 1)]
+        );
+        assert_eq!(findings[1].manifest, PathBuf::from("Cargo.toml"));
+        assert_eq!(findings[1].line, Some(5));
+
+        let suggestions = inventory.vulnerability_suggestions(&index, &db);
+        assert!(suggestions
+            .iter()
+            .all(|s| s.kind == SuggestionKind::Security));
+
+        let _ = fs::remove_dir_all(&root);
+        let _ = fs::remove_dir_all(&db_root);
+    }
+
+    #[test]
+    fn test_import_name_helpers() {
+        assert_eq!(
+            rust_import_root("pub(crate) ::tokio::sync::{mpsc, oneshot}").as_deref(),
+            Some("tokio")
+        );
+        assert_eq!(npm_package("@scope/pkg/sub").as_deref(), Some("@scope/pkg"));
+        assert_eq!(npm_package("node:fs"), None);
+        assert_eq!(
+            python_import_roots("import os.path, yaml as y"),
+            vec!["os".to_string(), "yaml".to_string()]
+        );
+        assert_eq!(
+            undeclared_name(Ecosystem::Go, "github.com/org/repo/pkg/sub"),
+            "github.com/org/repo"
+        );
+    }
+}
@@ -0,0 +1,689 @@
+//! Manifest parsers
+//!
+//! Small hand-rolled readers for the handful of manifest formats Cosmos
+//! understands. They only need declared dependencies, so they skip most of
+//! each format rather than pulling in full TOML/requirements parsers.
+
+use super::{DeclaredDependency, DependencyScope, Ecosystem, Manifest};
+use std::path::Path;
+
+/// Parse a manifest by file name; `None` for unrelated or unreadable files
+pub fn parse_manifest(path: &Path, content: &str) -> Option<Manifest> {
+    let name = path.file_name()?.to_str()?;
+    match name {
+        "Cargo.toml" => Some(parse_cargo_toml(path, content)),
+        "package.json" => parse_package_json(path, content),
+        "pyproject.toml" => Some(parse_pyproject(path, content)),
+        "go.mod" => Some(parse_go_mod(path, content)),
+        _ if is_requirements_file(name) => Some(parse_requirements(path, content)),
+        _ => None,
+    }
+}
+
+/// Whether `parse_manifest` handles files with this name
+pub fn is_manifest(name: &str) -> bool {
+    matches!(
+        name,
+        "Cargo.toml" | "package.json" | "pyproject.toml" | "go.mod"
+    ) || is_requirements_file(name)
+}
+
+/// `requirements.txt`, `requirements-dev.txt`, `dev-requirements.txt`, ...
+fn is_requirements_file(name: &str) -> bool {
+    name.ends_with(".txt") && name.contains("requirements")
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  CARGO
+// ═══════════════════════════════════════════════════════════════════════════
+
+fn parse_cargo_toml(path: &Path, content: &str) -> Manifest {
+    let mut manifest = Manifest::new(path, Ecosystem::Cargo);
+
+    for entry in toml_entries(content) {
+        if entry.section == "package" && entry.key == "name" {
+            manifest.package_name = toml_string(&entry.value);
+            continue;
+        }
+        if entry.section == "package" && entry.key == "license" {
+            manifest.license = toml_string(&entry.value);
+            continue;
+        }
+        if entry.section == "lib" && entry.key == "name" {
+            manifest.lib_name = toml_string(&entry.value);
+            continue;
+        }
+
+        // `[dependencies.foo]` table form
+        if let Some((scope, name)) = cargo_dependency_table(&entry.section) {
+            if !manifest.dependencies.iter().any(|d| d.name == name) {
+                manifest.dependencies.push(DeclaredDependency::new(
+                    name,
+                    None,
+                    scope,
+                    Ecosystem::Cargo,
+                    path,
+                    entry.section_line,
+                ));
+            }
+            if entry.key == "version" {
+                if let Some(dep) = manifest.dependencies.iter_mut().find(|d| d.name == name) {
+                    dep.version = toml_string(&entry.value);
+                }
+            }
+            continue;
+        }
+
+        if let Some(scope) = cargo_dependency_section(&entry.section) {
+            let version =
+                toml_string(&entry.value).or_else(|| toml_inline_field(&entry.value, "version"));
+            manifest.dependencies.push(DeclaredDependency::new(
+                &entry.key,
+                version,
+                scope,
+                Ecosystem::Cargo,
+                path,
+                entry.line,
+            ));
+        }
+    }
+
+    manifest
+}
+
+/// Scope for `[dependencies]`, `[dev-dependencies]`, `[target.'cfg(..)'.dependencies]`, ...
+/// `[workspace.dependencies]` only declares versions for members, so it is skipped.
+fn cargo_dependency_section(section: &str) -> Option<DependencyScope> {
+    let last = if section.starts_with("target.") {
+        section.rsplit('.').next()?
+    } else {
+        section
+    };
+    match last {
+        "dependencies" => Some(DependencyScope::Runtime),
+        "dev-dependencies" => Some(DependencyScope::Dev),
+        "build-dependencies" => Some(DependencyScope::Build),
+        _ => None,
+    }
+}
+
+/// `[dependencies.foo]` -> (Runtime, "foo")
+fn cargo_dependency_table(section: &str) -> Option<(DependencyScope, &str)> {
+    if section.starts_with("target.") || section.starts_with("workspace.") {
+        return None;
+    }
+    let (table, name) = section.split_once('.')?;
+    let scope = cargo_dependency_section(table)?;
+    Some((scope, name.trim_matches('"')))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  NPM
+// ═══════════════════════════════════════════════════════════════════════════
+
+fn parse_package_json(path: &Path, content: &str) -> Option<Manifest> {
+    let json: serde_json::Value = serde_json::from_str(content).ok()?;
+    let mut manifest = Manifest::new(path, Ecosystem::Npm);
+    manifest.package_name = json
+        .get("name")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+    manifest.license = json
+        .get("license")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    if let Some(scripts) = json.get("scripts").and_then(|v| v.as_object()) {
+        manifest.scripts = scripts
+            .values()
+            .filter_map(|v| v.as_str())
+            .map(|s| s.to_string())
+            .collect();
+    }
+
+    let lines: Vec<&str> = content.lines().collect();
+    let sections = [
+        ("dependencies", DependencyScope::Runtime),
+        ("devDependencies", DependencyScope::Dev),
+        ("peerDependencies", DependencyScope::Peer),
+        ("optionalDependencies", DependencyScope::Optional),
+    ];
+
+    for (section, scope) in sections {
+        let Some(deps) = json.get(section).and_then(|v| v.as_object()) else {
+            continue;
+        };
+        let section_line = find_line(&lines, 0, &format!("\"{}\"", section)).unwrap_or(0);
+        for (name, version) in deps {
+            let line = find_line(&lines, section_line, &format!("\"{}\"", name))
+                .map(|l| l + 1)
+                .unwrap_or(section_line + 1);
+            manifest.dependencies.push(DeclaredDependency::new(
+                name,
+                version.as_str().map(|s| s.to_string()),
+                scope,
+                Ecosystem::Npm,
+                path,
+                line,
+            ));
+        }
+    }
+
+    Some(manifest)
+}
+
+/// First line index at or after `from` containing `needle`
+fn find_line(lines: &[&str], from: usize, needle: &str) -> Option<usize> {
+    lines
+        .iter()
+        .enumerate()
+        .skip(from)
+        .find(|(_, line)| line.contains(needle))
+        .map(|(i, _)| i)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  PYTHON
+// ═══════════════════════════════════════════════════════════════════════════
+
+fn parse_pyproject(path: &Path, content: &str) -> Manifest {
+    let mut manifest = Manifest::new(path, Ecosystem::PyPI);
+
+    for entry in toml_entries(content) {
+        let section = entry.section.as_str();
+        match (section, entry.key.as_str()) {
+            ("project" | "tool.poetry", "name") => {
+                manifest.package_name = toml_string(&entry.value);
+            }
+            // `license = "MIT"` or the older `license = {text = "MIT"}`
+            ("project" | "tool.poetry", "license") => {
+                manifest.license =
+                    toml_string(&entry.value).or_else(|| toml_inline_field(&entry.value, "text"));
+            }
+            // PEP 621
+            ("project", "dependencies") => {
+                push_requirements(&mut manifest, &entry, DependencyScope::Runtime);
+            }
+            ("project.optional-dependencies", group) => {
+                let scope = if is_dev_group(group) {
+                    DependencyScope::Dev
+                } else {
+                    DependencyScope::Optional
+                };
+                push_requirements(&mut manifest, &entry, scope);
+            }
+            // PEP 735
+            ("dependency-groups", _) => {
+                push_requirements(&mut manifest, &entry, DependencyScope::Dev);
+            }
+            // Poetry
+            (_, "python") if section.starts_with("tool.poetry") => {}
+            ("tool.poetry.dependencies", name) => {
+                push_poetry_dependency(&mut manifest, name, &entry, DependencyScope::Runtime);
+            }
+            ("tool.poetry.dev-dependencies", name) => {
+                push_poetry_dependency(&mut manifest, name, &entry, DependencyScope::Dev);
+            }
+            (_, name)
+                if section.starts_with("tool.poetry.group.")
+                    && section.ends_with(".dependencies") =>
+            {
+                push_poetry_dependency(&mut manifest, name, &entry, DependencyScope::Dev);
+            }
+            _ => {}
+        }
+    }
+
+    manifest
+}
+
+fn parse_requirements(path: &Path, content: &str) -> Manifest {
+    let mut manifest = Manifest::new(path, Ecosystem::PyPI);
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    let scope = if is_dev_group(file_name) {
+        DependencyScope::Dev
+    } else {
+        DependencyScope::Runtime
+    };
+
+    for (i, raw) in content.lines().enumerate() {
+        let line = raw.split(" #").next().unwrap_or("").trim();
+        // Options (-r, -e, --index-url) and bare URLs don't name a package
+        if line.is_empty() || line.starts_with('#') || line.starts_with('-') {
+            continue;
+        }
+        if line.contains("://") && !line.contains(" @ ") {
+            continue;
+        }
+        if let Some((name, version)) = parse_pep508(line) {
+            manifest.dependencies.push(DeclaredDependency::new(
+                &name,
+                version,
+                scope,
+                Ecosystem::PyPI,
+                path,
+                i + 1,
+            ));
+        }
+    }
+
+    manifest
+}
+
+fn is_dev_group(name: &str) -> bool {
+    let name = name.to_lowercase();
+    ["dev", "test", "lint", "docs", "typing"]
+        .iter()
+        .any(|group| name.contains(group))
+}
+
+fn push_requirements(manifest: &mut Manifest, entry: &TomlEntry, scope: DependencyScope) {
+    for (spec, offset) in toml_array_strings(&entry.value) {
+        if let Some((name, version)) = parse_pep508(&spec) {
+            let path = manifest.path.clone();
+            manifest.dependencies.push(DeclaredDependency::new(
+                &name,
+                version,
+                scope,
+                Ecosystem::PyPI,
+                &path,
+                entry.line + offset,
+            ));
+        }
+    }
+}
+
+fn push_poetry_dependency(
+    manifest: &mut Manifest,
+    name: &str,
+    entry: &TomlEntry,
+    scope: DependencyScope,
+) {
+    let version = toml_string(&entry.value).or_else(|| toml_inline_field(&entry.value, "version"));
+    let path = manifest.path.clone();
+    manifest.dependencies.push(DeclaredDependency::new(
+        name,
+        version,
+        scope,
+        Ecosystem::PyPI,
+        &path,
+        entry.line,
+    ));
+}
+
+/// Split a PEP 508 requirement into name and version specifier:
+/// `requests[socks]>=2.31; python_version < "3.12"` -> ("requests", ">=2.31")
+fn parse_pep508(spec: &str) -> Option<(String, Option<String>)> {
+    let spec = spec.split(';').next().unwrap_or("").trim();
+    let name_end = spec
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.'))
+        .unwrap_or(spec.len());
+    let name = &spec[..name_end];
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut rest = spec[name_end..].trim();
+    // Extras
+    if rest.starts_with('[') {
+        rest = rest.split_once(']').map(|(_, r)| r.trim()).unwrap_or("");
+    }
+    let version = rest
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .trim()
+        .to_string();
+
+    Some((
+        name.to_string(),
+        (!version.is_empty() && !version.starts_with('@')).then_some(version),
+    ))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  GO
+// ═══════════════════════════════════════════════════════════════════════════
+
+fn parse_go_mod(path: &Path, content: &str) -> Manifest {
+    let mut manifest = Manifest::new(path, Ecosystem::Go);
+    let mut in_require = false;
+
+    for (i, raw) in content.lines().enumerate() {
+        let line = raw.trim();
+        if let Some(module) = line.strip_prefix("module ") {
+            manifest.package_name = Some(module.trim().trim_matches('"').to_string());
+            continue;
+        }
+
+        let spec = if in_require {
+            if line.starts_with(')') {
+                in_require = false;
+                continue;
+            }
+            line
+        } else if line.starts_with("require (") || line == "require(" {
+            in_require = true;
+            continue;
+        } else if let Some(spec) = line.strip_prefix("require ") {
+            spec
+        } else {
+            continue;
+        };
+
+        let (spec, comment) = match spec.split_once("//") {
+            Some((spec, comment)) => (spec.trim(), comment.trim()),
+            None => (spec.trim(), ""),
+        };
+        let mut parts = spec.split_whitespace();
+        let (Some(module), Some(version)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        let scope = if comment.starts_with("indirect") {
+            DependencyScope::Indirect
+        } else {
+            DependencyScope::Runtime
+        };
+        manifest.dependencies.push(DeclaredDependency::new(
+            module,
+            Some(version.to_string()),
+            scope,
+            Ecosystem::Go,
+            path,
+            i + 1,
+        ));
+    }
+
+    manifest
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  MINIMAL TOML
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// One `key = value` line with the table it belongs to
+#[derive(Debug)]
+pub(super) struct TomlEntry {
+    pub(super) section: String,
+    /// 1-based line of the enclosing `[section]` header
+    pub(super) section_line: usize,
+    pub(super) key: String,
+    /// Raw value; multi-line arrays keep their newlines
+    pub(super) value: String,
+    /// 1-based line of the key
+    pub(super) line: usize,
+}
+
+/// Walk a TOML document as flat `section / key = value` entries.
+/// Handles the subset manifests use: tables, quoted keys, inline tables on
+/// one line and arrays spanning several lines.
+pub(super) fn toml_entries(content: &str) -> Vec<TomlEntry> {
+    let mut entries = Vec::new();
+    let mut section = String::new();
+    let mut section_line = 0;
+    let mut lines = content.lines().enumerate();
+
+    while let Some((i, raw)) = lines.next() {
+        let line = strip_toml_comment(raw).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            section = line
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .split('.')
+                .map(|part| part.trim())
+                .collect::<Vec<_>>()
+                .join(".");
+            section_line = i + 1;
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let mut value = value.trim().to_string();
+
+        // Keep reading until brackets/braces balance
+        let mut depth = bracket_depth(&value);
+        while depth > 0 {
+            let Some((_, next)) = lines.next() else {
+                break;
+            };
+            let next = strip_toml_comment(next);
+            depth += bracket_depth(next);
+            value.push('\n');
+            value.push_str(next.trim());
+        }
+
+        entries.push(TomlEntry {
+            section: section.clone(),
+            section_line,
+            key: key.trim().trim_matches('"').trim_matches('\'').to_string(),
+            value,
+            line: i + 1,
+        });
+    }
+
+    entries
+}
+
+/// Drop a trailing `# comment` that isn't inside a string
+fn strip_toml_comment(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '#') => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Net `[`/`{` nesting outside string literals
+fn bracket_depth(text: &str) -> i32 {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    for c in text.chars() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '[' | '{') => depth += 1,
+            (None, ']' | '}') => depth -= 1,
+            _ => {}
+        }
+    }
+    depth
+}
+
+/// Value of a plain string: `"1.0"` -> `1.0`
+pub(super) fn toml_string(value: &str) -> Option<String> {
+    let value = value.trim();
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let inner = &value[1..];
+    inner.find(quote).map(|end| inner[..end].to_string())
+}
+
+/// Field of an inline table: `{ version = "1", features = [..] }`, "version" -> `1`
+fn toml_inline_field(value: &str, field: &str) -> Option<String> {
+    let inner = value.trim().strip_prefix('{')?.strip_suffix('}')?;
+    inner.split(',').find_map(|pair| {
+        let (key, val) = pair.split_once('=')?;
+        (key.trim() == field).then(|| toml_string(val)).flatten()
+    })
+}
+
+/// String items of an array with the line offset each sits on
+pub(super) fn toml_array_strings(value: &str) -> Vec<(String, usize)> {
+    let mut items = Vec::new();
+    for (offset, line) in value.lines().enumerate() {
+        let mut rest = line;
+        while let Some(start) = rest.find(['"', '\'']) {
+            let quote = rest[start..].chars().next().unwrap_or('"');
+            let after = &rest[start + 1..];
+            let Some(end) = after.find(quote) else {
+                break;
+            };
+            items.push((after[..end].to_string(), offset));
+            rest = &after[end + 1..];
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(manifest: &Manifest) -> Vec<(&str, Option<&str>, DependencyScope, usize)> {
+        manifest
+            .dependencies
+            .iter()
+            .map(|d| (d.name.as_str(), d.version.as_deref(), d.scope, d.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_cargo_toml() {
+        let content = r#"[package]
+name = "demo-app"
+
+[dependencies]
+serde = { version = "1.0", features = ["derive"] } # comment
+anyhow = "1"
+local = { path = "../local" }
+
+[dev-dependencies]
+tempfile = "3"
+
+[target.'cfg(unix)'.dependencies]
+libc = "0.2"
+
+[build-dependencies.cc]
+version = "1.0"
+
+[workspace.dependencies]
+shared = "2"
+"#;
+        let manifest = parse_manifest(Path::new("Cargo.toml"), content).unwrap();
+        assert_eq!(manifest.package_name.as_deref(), Some("demo-app"));
+        assert_eq!(
+            names(&manifest),
+            vec![
+                ("serde", Some("1.0"), DependencyScope::Runtime, 5),
+                ("anyhow", Some("1"), DependencyScope::Runtime, 6),
+                ("local", None, DependencyScope::Runtime, 7),
+                ("tempfile", Some("3"), DependencyScope::Dev, 10),
+                ("libc", Some("0.2"), DependencyScope::Runtime, 13),
+                ("cc", Some("1.0"), DependencyScope::Build, 15),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_package_json() {
+        let content = r#"{
+  "name": "web",
+  "scripts": { "build": "vite build" },
+  "dependencies": {
+    "react": "^18.2.0",
+    "@tanstack/query": "5.0.0"
+  },
+  "devDependencies": {
+    "vite": "^5.0.0"
+  }
+}"#;
+        let manifest = parse_manifest(Path::new("web/package.json"), content).unwrap();
+        assert_eq!(manifest.package_name.as_deref(), Some("web"));
+        assert_eq!(manifest.scripts, vec!["vite build".to_string()]);
+        let mut deps = names(&manifest);
+        deps.sort();
+        assert_eq!(
+            deps,
+            vec![
+                (
+                    "@tanstack/query",
+                    Some("5.0.0"),
+                    DependencyScope::Runtime,
+                    6
+                ),
+                ("react", Some("^18.2.0"), DependencyScope::Runtime, 5),
+                ("vite", Some("^5.0.0"), DependencyScope::Dev, 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_python_manifests() {
+        let pyproject = r#"[project]
+name = "svc"
+dependencies = [
+    "requests[socks]>=2.31; python_version < '3.12'",
+    "PyYAML",
+]
+
+[project.optional-dependencies]
+test = ["pytest>=7"]
+
+[tool.poetry.dependencies]
+python = "^3.11"
+httpx = { version = "^0.27", extras = ["http2"] }
+"#;
+        let manifest = parse_manifest(Path::new("pyproject.toml"), pyproject).unwrap();
+        assert_eq!(
+            names(&manifest),
+            vec![
+                ("requests", Some(">=2.31"), DependencyScope::Runtime, 4),
+                ("PyYAML", None, DependencyScope::Runtime, 5),
+                ("pytest", Some(">=7"), DependencyScope::Dev, 9),
+                ("httpx", Some("^0.27"), DependencyScope::Runtime, 13),
+            ]
+        );
+
+        let requirements = "# pinned\nflask==3.0.0\n-r base.txt\nboto3 >= 1.34  # aws\n";
+        let manifest = parse_manifest(Path::new("requirements-dev.txt"), requirements).unwrap();
+        assert_eq!(
+            names(&manifest),
+            vec![
+                ("flask", Some("==3.0.0"), DependencyScope::Dev, 2),
+                ("boto3", Some(">= 1.34"), DependencyScope::Dev, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_go_mod() {
+        let content = "module example.com/svc\n\ngo 1.22\n\nrequire github.com/spf13/cobra v1.8.0\n\nrequire (\n\tgithub.com/stretchr/testify v1.9.0\n\tgolang.org/x/sys v0.20.0 // indirect\n)\n";
+        let manifest = parse_manifest(Path::new("go.mod"), content).unwrap();
+        assert_eq!(manifest.package_name.as_deref(), Some("example.com/svc"));
+        assert_eq!(
+            names(&manifest),
+            vec![
+                (
+                    "github.com/spf13/cobra",
+                    Some("v1.8.0"),
+                    DependencyScope::Runtime,
+                    5
+                ),
+                (
+                    "github.com/stretchr/testify",
+                    Some("v1.9.0"),
+                    DependencyScope::Runtime,
+                    8
+                ),
+                (
+                    "golang.org/x/sys",
+                    Some("v0.20.0"),
+                    DependencyScope::Indirect,
+                    9
+                ),
+            ]
+        );
+    }
+}
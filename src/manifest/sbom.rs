@@ -0,0 +1,339 @@
+//! CycloneDX SBOM export
+//!
+//! Components come from lockfiles (exact versions) plus declared
+//! dependencies no lockfile pins. Unlike a plain SBOM tool, each component
+//! carries `evidence.occurrences` listing the indexed files that import it.
+
+use super::license::LicenseReport;
+use super::{importers_of, DependencyScope, Ecosystem, ManifestInventory};
+use crate::index::CodebaseIndex;
+use chrono::{SecondsFormat, Utc};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+const SPEC_VERSION: &str = "1.6";
+
+/// One component before serialization
+struct Component {
+    name: String,
+    version: Option<String>,
+    ecosystem: Ecosystem,
+    /// Declared scope, `None` for packages only a lockfile mentions
+    scope: Option<DependencyScope>,
+    manifest: PathBuf,
+    importers: Vec<(PathBuf, usize)>,
+}
+
+/// Build a CycloneDX JSON document for the repository
+pub fn cyclonedx(
+    root: &Path,
+    index: &CodebaseIndex,
+    inventory: &ManifestInventory,
+    licenses: &LicenseReport,
+) -> Value {
+    let imports = inventory.external_imports(index);
+    let groups = inventory.groups();
+
+    // purl -> component, so the same package pinned by two lockfiles appears once
+    let mut components: BTreeMap<String, Component> = BTreeMap::new();
+    let mut add = |component: Component| {
+        let purl = purl(
+            component.ecosystem,
+            &component.name,
+            component.version.as_deref(),
+        );
+        match components.get_mut(&purl) {
+            Some(existing) => {
+                existing.importers.extend(component.importers);
+                existing.importers.sort();
+                existing.importers.dedup();
+                existing.scope = existing.scope.or(component.scope);
+            }
+            None => {
+                components.insert(purl, component);
+            }
+        }
+    };
+
+    let declared_scope = |ecosystem: Ecosystem, dir: &Path, name: &str| {
+        groups
+            .iter()
+            .filter(|g| g.ecosystem == ecosystem && g.dir == dir)
+            .flat_map(|g| g.manifests.iter())
+            .flat_map(|m| m.dependencies.iter())
+            .find(|d| d.name == name)
+            .map(|d| d.scope)
+    };
+    let primary_manifest = |ecosystem: Ecosystem, dir: &Path, fallback: &Path| {
+        groups
+            .iter()
+            .find(|g| g.ecosystem == ecosystem && g.dir == dir)
+            .map(|g| g.primary().path.clone())
+            .unwrap_or_else(|| fallback.to_path_buf())
+    };
+
+    let mut locked_names: HashMap<(Ecosystem, &Path), Vec<&str>> = HashMap::new();
+    for package in &inventory.locked {
+        let dir = package.lockfile.parent().unwrap_or(Path::new(""));
+        locked_names
+            .entry((package.ecosystem, dir))
+            .or_default()
+            .push(&package.name);
+        add(Component {
+            name: package.name.clone(),
+            version: Some(package.version.clone()),
+            ecosystem: package.ecosystem,
+            scope: declared_scope(package.ecosystem, dir, &package.name),
+            manifest: primary_manifest(package.ecosystem, dir, &package.lockfile),
+            importers: importers_of(&imports, package.ecosystem, dir, &package.name),
+        });
+    }
+    for manifest in &inventory.manifests {
+        let dir = manifest.dir();
+        let locked = locked_names.get(&(manifest.ecosystem, dir));
+        for dep in &manifest.dependencies {
+            if locked.is_some_and(|names| names.contains(&dep.name.as_str())) {
+                continue;
+            }
+            add(Component {
+                name: dep.name.clone(),
+                version: exact_version(dep.ecosystem, dep.version.as_deref()),
+                ecosystem: dep.ecosystem,
+                scope: Some(dep.scope),
+                manifest: primary_manifest(dep.ecosystem, dir, &manifest.path),
+                importers: importers_of(&imports, dep.ecosystem, dir, &dep.name),
+            });
+        }
+    }
+
+    let license_of: HashMap<(Ecosystem, &str, Option<&str>), &str> = licenses
+        .entries
+        .iter()
+        .filter_map(|e| {
+            let license = e.license.as_deref()?;
+            Some((
+                (e.ecosystem, e.name.as_str(), e.version.as_deref()),
+                license,
+            ))
+        })
+        .collect();
+
+    let project_name = inventory
+        .manifests
+        .iter()
+        .filter(|m| m.dir() == Path::new(""))
+        .find_map(|m| m.package_name.clone())
+        .or_else(|| root.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "project".to_string());
+    let mut project = json!({
+        "type": "application",
+        "bom-ref": "project",
+        "name": project_name,
+    });
+    if let Some(license) = licenses.project_licenses.first() {
+        project["licenses"] = json!([{ "expression": license.license }]);
+    }
+
+    let mut direct = Vec::new();
+    let mut entries = Vec::new();
+    for (purl, component) in &components {
+        let is_direct = component
+            .scope
+            .is_some_and(|s| s != DependencyScope::Indirect);
+        if is_direct {
+            direct.push(purl.clone());
+        }
+
+        let mut entry = json!({
+            "type": "library",
+            "bom-ref": purl,
+            "name": component.name,
+            "purl": purl,
+            "scope": cyclonedx_scope(component.scope),
+            "properties": [
+                { "name": "cosmos:manifest", "value": component.manifest.display().to_string() },
+                { "name": "cosmos:direct", "value": is_direct.to_string() },
+            ],
+        });
+        if let Some(version) = &component.version {
+            entry["version"] = json!(version);
+        }
+        let lookup = (
+            component.ecosystem,
+            component.name.as_str(),
+            component.version.as_deref(),
+        );
+        if let Some(license) = license_of.get(&lookup) {
+            entry["licenses"] = json!([{ "expression": license }]);
+        }
+        if !component.importers.is_empty() {
+            let occurrences: Vec<Value> = component
+                .importers
+                .iter()
+                .map(|(path, line)| json!({ "location": path.display().to_string(), "line": line }))
+                .collect();
+            entry["evidence"] = json!({ "occurrences": occurrences });
+        }
+        entries.push(entry);
+    }
+
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": SPEC_VERSION,
+        "serialNumber": format!("urn:uuid:{}", uuid::Uuid::new_v4()),
+        "version": 1,
+        "metadata": {
+            "timestamp": Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            "tools": {
+                "components": [{
+                    "type": "application",
+                    "name": "cosmos",
+                    "version": env!("CARGO_PKG_VERSION"),
+                }],
+            },
+            "component": project,
+        },
+        "components": entries,
+        "dependencies": [{ "ref": "project", "dependsOn": direct }],
+    })
+}
+
+/// Package URL (https://github.com/package-url/purl-spec)
+fn purl(ecosystem: Ecosystem, name: &str, version: Option<&str>) -> String {
+    let (kind, name) = match ecosystem {
+        Ecosystem::Cargo => ("cargo", name.to_string()),
+        Ecosystem::Npm => ("npm", name.replace('@', "%40")),
+        Ecosystem::PyPI => ("pypi", name.to_lowercase().replace(['_', '.'], "-")),
+        Ecosystem::Go => ("golang", name.to_string()),
+    };
+    match version {
+        Some(version) => format!("pkg:{}/{}@{}", kind, name, version),
+        None => format!("pkg:{}/{}", kind, name),
+    }
+}
+
+/// A manifest requirement that pins one version (`==1.2.3`, `=1.2.3`, npm's
+/// bare `1.2.3`, go.mod's `v1.2.3`); ranges aren't component versions
+fn exact_version(ecosystem: Ecosystem, requirement: Option<&str>) -> Option<String> {
+    let requirement = requirement?.trim();
+    let pinned = match ecosystem {
+        Ecosystem::PyPI => requirement.strip_prefix("==")?,
+        Ecosystem::Cargo => requirement.strip_prefix('=')?,
+        Ecosystem::Npm | Ecosystem::Go => requirement,
+    }
+    .trim();
+    let exact = pinned
+        .trim_start_matches('v')
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_digit())
+        && !pinned.contains(['*', ',', ' ', '<', '>', '|', '^', '~']);
+    exact.then(|| pinned.to_string())
+}
+
+/// Dev-only dependencies don't ship, so they're `excluded`
+fn cyclonedx_scope(scope: Option<DependencyScope>) -> &'static str {
+    match scope {
+        Some(DependencyScope::Dev | DependencyScope::Build) => "excluded",
+        Some(DependencyScope::Optional | DependencyScope::Peer) => "optional",
+        _ => "required",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::license::LicensePolicy;
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn test_cyclonedx_components_and_evidence() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("cosmos_sbom_test_{}", nanos));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"demo\"\nlicense = \"MIT\"\n\n[dependencies]\nserde_json = \"1\"\n\n[dev-dependencies]\ntempfile = \"=3.8.0\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("Cargo.lock"),
+            "[[package]]\nname = \"demo\"\nversion = \"0.1.0\"\n\n[[package]]\nname = \"serde_json\"\nversion = \"1.0.100\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n\n[[package]]\nname = \"itoa\"\nversion = \"1.0.9\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/main.rs"),
+            "use serde_json::Value;\n\nfn main() {}\n",
+        )
+        .unwrap();
+
+        let index = CodebaseIndex::new(&root).unwrap();
+        let inventory = ManifestInventory::load(&root);
+        let licenses = LicenseReport::build(&root, &inventory, &LicensePolicy::default());
+        let bom = cyclonedx(&root, &index, &inventory, &licenses);
+
+        assert_eq!(bom["bomFormat"], "CycloneDX");
+        assert_eq!(bom["metadata"]["component"]["name"], "demo");
+        assert_eq!(
+            bom["metadata"]["component"]["licenses"][0]["expression"],
+            "MIT"
+        );
+
+        let components = bom["components"].as_array().unwrap();
+        let purls: Vec<&str> = components
+            .iter()
+            .map(|c| c["purl"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            purls,
+            vec![
+                "pkg:cargo/itoa@1.0.9",
+                "pkg:cargo/serde_json@1.0.100",
+                "pkg:cargo/tempfile@3.8.0",
+            ]
+        );
+
+        let serde = &components[1];
+        assert_eq!(serde["scope"], "required");
+        assert_eq!(
+            serde["evidence"]["occurrences"][0]["location"],
+            "src/main.rs"
+        );
+        assert_eq!(serde["evidence"]["occurrences"][0]["line"], 1);
+        assert!(components[0].get("evidence").is_none());
+        assert_eq!(components[2]["scope"], "excluded");
+
+        let direct = bom["dependencies"][0]["dependsOn"].as_array().unwrap();
+        assert_eq!(direct.len(), 2);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_purl_and_exact_versions() {
+        assert_eq!(
+            purl(Ecosystem::Npm, "@babel/core", Some("7.20.0")),
+            "pkg:npm/%40babel/core@7.20.0"
+        );
+        assert_eq!(
+            purl(Ecosystem::PyPI, "Flask_Login", None),
+            "pkg:pypi/flask-login"
+        );
+        assert_eq!(
+            exact_version(Ecosystem::PyPI, Some("==2.31.0")),
+            Some("2.31.0".to_string())
+        );
+        assert_eq!(exact_version(Ecosystem::PyPI, Some(">=2")), None);
+        assert_eq!(exact_version(Ecosystem::Npm, Some("^4.17.21")), None);
+        assert_eq!(
+            exact_version(Ecosystem::Go, Some("v1.9.1")),
+            Some("v1.9.1".to_string())
+        );
+        assert_eq!(exact_version(Ecosystem::Cargo, Some("1.0")), None);
+    }
+}
@@ -11,8 +11,9 @@ use std::io::{self, IsTerminal, Write};
 pub fn needs_onboarding() -> bool {
     let config = Config::load();
 
-    // Onboarding needed if missing API key or GitHub auth
-    !config.has_api_key() || !github::is_authenticated()
+    // Onboarding needed if missing API key or GitHub auth (self-hosted
+    // backends don't use the OpenRouter key)
+    (config.needs_openrouter_key() && !config.has_api_key()) || !github::is_authenticated()
 }
 
 /// Run the onboarding flow (async for GitHub auth)
@@ -24,20 +25,23 @@ pub async fn run_onboarding() -> Result<(), String> {
         );
     }
 
-    // Step 1: OpenRouter API key (required)
-    loop {
-        match crate::config::setup_api_key_interactive() {
-            Ok(_) => break,
-            Err(err) if err == "No API key provided" => {
-                println!();
-                println!("  An API key is required to continue.");
-                print!("  Press Enter to try again, or Ctrl+C to exit...");
-                io::stdout().flush().map_err(|e| e.to_string())?;
-                let mut _input = String::new();
-                let _ = io::stdin().read_line(&mut _input);
-                println!();
+    // Step 1: OpenRouter API key (required unless a self-hosted backend is configured)
+    let config = Config::load();
+    if config.needs_openrouter_key() {
+        loop {
+            match crate::config::setup_api_key_interactive() {
+                Ok(_) => break,
+                Err(err) if err == "No API key provided" => {
+                    println!();
+                    println!("  An API key is required to continue.");
+                    print!("  Press Enter to try again, or Ctrl+C to exit...");
+                    io::stdout().flush().map_err(|e| e.to_string())?;
+                    let mut _input = String::new();
+                    let _ = io::stdin().read_line(&mut _input);
+                    println!();
+                }
+                Err(err) => return Err(err),
             }
-            Err(err) => return Err(err),
         }
     }
 
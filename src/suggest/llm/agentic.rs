@@ -1,89 +1,30 @@
 //! Agentic LLM client with tool-calling support.
 //!
-//! Enables models to explore codebases by calling tools (grep, read, ls)
-//! in a loop until they have enough context to complete their task.
-
-use super::client::{api_key, create_http_client, send_with_retry, REQUEST_TIMEOUT_SECS};
-use super::models::Model;
-use super::tools::{execute_tool, get_tool_definitions, ToolCall, ToolDefinition};
-use serde::{Deserialize, Serialize};
+//! Enables models to explore codebases by calling tools (read_file, grep,
+//! find_symbol, ...)
+//! in a loop until they have enough context to complete their task. Every
+//! tool call is recorded in the session's audit trail (see `audit`).
+
+use super::audit;
+use super::models::{Task, Usage};
+use super::parse::merge_usage;
+use super::provider::{configured_provider, ChatRequest, LlmProvider, Message};
+use super::tools::{describe_tool_call, ToolCall, ToolContext};
 use std::path::Path;
+use std::time::Instant;
 
 /// Response from an agentic LLM call
 #[derive(Debug)]
 pub struct AgenticResponse {
     pub content: String,
-}
-
-/// A message in the conversation
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Message {
-    pub role: String,
-    pub content: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub tool_calls: Option<Vec<ToolCallMessage>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub tool_call_id: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ToolCallMessage {
-    pub id: String,
-    #[serde(rename = "type")]
-    pub call_type: String,
-    pub function: FunctionCallMessage,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FunctionCallMessage {
-    pub name: String,
-    pub arguments: String,
-}
-
-#[derive(Serialize)]
-struct ChatRequest {
-    model: String,
-    messages: Vec<Message>,
-    max_tokens: u32,
-    stream: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    response_format: Option<ResponseFormat>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    tools: Option<Vec<ToolDefinition>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    provider: Option<ProviderConfig>,
-}
-
-#[derive(Serialize)]
-struct ResponseFormat {
-    #[serde(rename = "type")]
-    format_type: String,
-}
-
-#[derive(Serialize)]
-struct ProviderConfig {
-    allow_fallbacks: bool,
-}
-
-#[derive(Deserialize)]
-struct ChatResponse {
-    choices: Vec<Choice>,
-}
-
-#[derive(Deserialize)]
-struct Choice {
-    message: ResponseMessage,
-}
-
-#[derive(Deserialize)]
-struct ResponseMessage {
-    content: Option<String>,
-    tool_calls: Option<Vec<ToolCallMessage>>,
+    /// Summed over every round of the loop, including the final forced answer
+    pub usage: Option<Usage>,
 }
 
 /// Call LLM with tool-calling capability.
 ///
-/// The model can call tools (grep, read, ls) to explore the codebase.
+/// The model can call tools (read_file, grep, find_symbol, ...) to explore
+/// the codebase.
 /// The function loops until the model returns a final text response.
 /// Now includes automatic retry with exponential backoff for transient failures.
 ///
@@ -94,32 +35,62 @@ struct ResponseMessage {
 pub async fn call_llm_agentic(
     system: &str,
     user: &str,
-    model: Model,
+    task: Task,
     repo_root: &Path,
     json_mode: bool,
     max_iterations: usize,
 ) -> anyhow::Result<AgenticResponse> {
-    let api_key = api_key().ok_or_else(|| {
-        anyhow::anyhow!("No API key configured. Run 'cosmos --setup' to get started.")
-    })?;
+    call_llm_agentic_with_progress(
+        system,
+        user,
+        task,
+        repo_root,
+        json_mode,
+        max_iterations,
+        &|_| {},
+    )
+    .await
+}
 
-    let client = create_http_client(REQUEST_TIMEOUT_SECS)?;
+/// `call_llm_agentic`, reporting each tool call (e.g. `$ rg -n parse src`)
+/// to `on_progress` before it runs
+pub async fn call_llm_agentic_with_progress(
+    system: &str,
+    user: &str,
+    task: Task,
+    repo_root: &Path,
+    json_mode: bool,
+    max_iterations: usize,
+    on_progress: &(dyn Fn(&str) + Send + Sync),
+) -> anyhow::Result<AgenticResponse> {
+    let provider = configured_provider()?;
+    let _ = json_mode; // Tool-calling rounds can't use JSON response format
+    run_loop(
+        provider.as_ref(),
+        system,
+        user,
+        task,
+        repo_root,
+        max_iterations,
+        on_progress,
+    )
+    .await
+}
 
-    let tools = get_tool_definitions();
-    let mut messages = vec![
-        Message {
-            role: "system".to_string(),
-            content: Some(system.to_string()),
-            tool_calls: None,
-            tool_call_id: None,
-        },
-        Message {
-            role: "user".to_string(),
-            content: Some(user.to_string()),
-            tool_calls: None,
-            tool_call_id: None,
-        },
-    ];
+async fn run_loop(
+    provider: &dyn LlmProvider,
+    system: &str,
+    user: &str,
+    task: Task,
+    repo_root: &Path,
+    max_iterations: usize,
+    on_progress: &(dyn Fn(&str) + Send + Sync),
+) -> anyhow::Result<AgenticResponse> {
+    let tool_context = ToolContext::for_repo(repo_root);
+    let tools = tool_context.definitions();
+    let mut messages = vec![Message::system(system), Message::user(user)];
+    let run = audit::begin_run(task);
+    let mut usage = None;
 
     let mut iteration = 0;
 
@@ -130,72 +101,59 @@ pub async fn call_llm_agentic(
             // Force the model to respond with what it has
             break;
         }
-        // Note: json_mode is accepted for API compatibility but not currently used
-        // during the agentic loop since tool calls don't use JSON response format
-        let response_format: Option<ResponseFormat> = None;
-        let _ = json_mode; // Silence unused warning
-
-        let request = ChatRequest {
-            model: model.id().to_string(),
-            messages: messages.clone(),
-            max_tokens: model.max_tokens(),
-            stream: false,
-            response_format,
-            tools: Some(tools.clone()),
-            provider: Some(ProviderConfig {
-                allow_fallbacks: true,
-            }),
-        };
+        let mut request = ChatRequest::new(task, messages.clone());
+        request.tools = Some(tools.clone());
 
-        // Use shared retry helper - handles timeouts, rate limits, server errors
-        let text = send_with_retry(&client, &api_key, &request).await?;
-
-        let parsed: ChatResponse = serde_json::from_str(&text)
-            .map_err(|e| anyhow::anyhow!("Failed to parse response: {}\n{}", e, text))?;
-
-        let choice = parsed
-            .choices
-            .first()
-            .ok_or_else(|| anyhow::anyhow!("No response from model"))?;
+        // The provider retries timeouts, rate limits and server errors
+        let response = provider.chat(&request).await?;
+        usage = merge_usage(usage, response.usage.clone());
 
         // Check if model wants to call tools
-        if let Some(tool_calls) = &choice.message.tool_calls {
-            if !tool_calls.is_empty() {
-                // Add assistant message with tool calls
+        let tool_calls = &response.tool_calls;
+        if !tool_calls.is_empty() {
+            // Add assistant message with tool calls
+            messages.push(Message {
+                role: "assistant".to_string(),
+                content: response.content.clone(),
+                tool_calls: Some(tool_calls.clone()),
+                tool_call_id: None,
+            });
+
+            // Execute each tool and add results
+            for tc in tool_calls {
+                let tool_call = ToolCall {
+                    id: tc.id.clone(),
+                    function: super::tools::FunctionCall {
+                        name: tc.function.name.clone(),
+                        arguments: tc.function.arguments.clone(),
+                    },
+                };
+
+                on_progress(&describe_tool_call(&tool_call));
+                let started = Instant::now();
+                let result = tool_context.execute(&tool_call);
+                audit::record(
+                    run,
+                    iteration,
+                    &tool_call,
+                    &result.content,
+                    started.elapsed(),
+                );
+
                 messages.push(Message {
-                    role: "assistant".to_string(),
-                    content: choice.message.content.clone(),
-                    tool_calls: Some(tool_calls.clone()),
-                    tool_call_id: None,
+                    role: "tool".to_string(),
+                    content: Some(result.content),
+                    tool_calls: None,
+                    tool_call_id: Some(tc.id.clone()),
                 });
-
-                // Execute each tool and add results
-                for tc in tool_calls {
-                    let tool_call = ToolCall {
-                        id: tc.id.clone(),
-                        function: super::tools::FunctionCall {
-                            name: tc.function.name.clone(),
-                            arguments: tc.function.arguments.clone(),
-                        },
-                    };
-
-                    let result = execute_tool(repo_root, &tool_call);
-
-                    messages.push(Message {
-                        role: "tool".to_string(),
-                        content: Some(result.content),
-                        tool_calls: None,
-                        tool_call_id: Some(tc.id.clone()),
-                    });
-                }
-
-                // Continue loop to get next response
-                continue;
             }
+
+            // Continue loop to get next response
+            continue;
         }
 
         // Model returned final response (no tool calls)
-        let content = choice.message.content.clone().unwrap_or_default();
+        let content = response.content.unwrap_or_default();
 
         // Validate we got actual content
         if content.trim().is_empty() {
@@ -204,41 +162,18 @@ pub async fn call_llm_agentic(
             ));
         }
 
-        return Ok(AgenticResponse { content });
+        return Ok(AgenticResponse { content, usage });
     }
 
     // If we broke out of loop (hit max iterations), make one final call WITHOUT tools
     // to force the model to respond with whatever it has
-    messages.push(Message {
-        role: "user".to_string(),
-        content: Some("You've gathered enough context. Now respond with your JSON suggestions based on what you've learned. No more tool calls.".to_string()),
-        tool_calls: None,
-        tool_call_id: None,
-    });
-
-    let final_request = ChatRequest {
-        model: model.id().to_string(),
-        messages: messages.clone(),
-        max_tokens: model.max_tokens(),
-        stream: false,
-        response_format: None,
-        tools: None, // No tools - force text response
-        provider: Some(ProviderConfig {
-            allow_fallbacks: true,
-        }),
-    };
-
-    // Use shared retry helper for final request too
-    let text = send_with_retry(&client, &api_key, &final_request).await?;
-
-    let parsed: ChatResponse = serde_json::from_str(&text)
-        .map_err(|e| anyhow::anyhow!("Failed to parse final response: {}\n{}", e, text))?;
-
-    let content = parsed
-        .choices
-        .first()
-        .and_then(|c| c.message.content.clone())
-        .unwrap_or_default();
+    messages.push(Message::user("You've gathered enough context. Now respond with your JSON suggestions based on what you've learned. No more tool calls."));
+
+    // No tools - force text response
+    let final_request = ChatRequest::new(task, messages);
+    let response = provider.chat(&final_request).await?;
+    let usage = merge_usage(usage, response.usage);
+    let content = response.content.unwrap_or_default();
 
     // Validate we got actual content
     if content.trim().is_empty() {
@@ -247,12 +182,117 @@ pub async fn call_llm_agentic(
         ));
     }
 
-    Ok(AgenticResponse { content })
+    Ok(AgenticResponse { content, usage })
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::provider::{ChatResponse, FunctionCallMessage, ToolCallMessage};
     use super::*;
+    use futures::future::BoxFuture;
+    use std::sync::Mutex;
+
+    /// Replies with queued responses in order
+    struct Scripted(Mutex<Vec<ChatResponse>>);
+
+    impl LlmProvider for Scripted {
+        fn name(&self) -> &str {
+            "scripted"
+        }
+
+        fn chat<'a>(
+            &'a self,
+            _request: &'a ChatRequest,
+        ) -> BoxFuture<'a, anyhow::Result<ChatResponse>> {
+            Box::pin(async move { Ok(self.0.lock().unwrap().remove(0)) })
+        }
+
+        fn chat_stream<'a>(
+            &'a self,
+            request: &'a ChatRequest,
+            _on_text: &'a mut (dyn FnMut(&str) + Send),
+        ) -> BoxFuture<'a, anyhow::Result<ChatResponse>> {
+            self.chat(request)
+        }
+
+        fn balance(&self) -> BoxFuture<'_, anyhow::Result<Option<f64>>> {
+            Box::pin(async { Ok(None) })
+        }
+    }
+
+    fn reply(
+        content: Option<&str>,
+        list_dir: Option<&str>,
+        tokens: u32,
+        cost: f64,
+    ) -> ChatResponse {
+        ChatResponse {
+            content: content.map(String::from),
+            tool_calls: list_dir
+                .map(|path| {
+                    vec![ToolCallMessage {
+                        id: "call_1".to_string(),
+                        call_type: "function".to_string(),
+                        function: FunctionCallMessage {
+                            name: "list_dir".to_string(),
+                            arguments: serde_json::json!({ "path": path }).to_string(),
+                        },
+                    }]
+                })
+                .unwrap_or_default(),
+            usage: Some(Usage {
+                prompt_tokens: tokens,
+                completion_tokens: 0,
+                total_tokens: tokens,
+                cost: Some(cost),
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_usage_is_summed_across_rounds() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // One tool round, then an answer
+        let provider = Scripted(Mutex::new(vec![
+            reply(None, Some("."), 100, 0.01),
+            reply(Some("done"), None, 40, 0.02),
+        ]));
+        let response = run_loop(
+            &provider,
+            "sys",
+            "user",
+            Task::Review,
+            dir.path(),
+            4,
+            &|_| {},
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.content, "done");
+        let usage = response.usage.unwrap();
+        assert_eq!(usage.total_tokens, 140);
+        assert!((usage.cost() - 0.03).abs() < 1e-9);
+
+        // Out of rounds: the forced final answer is counted too
+        let provider = Scripted(Mutex::new(vec![
+            reply(None, Some("."), 100, 0.01),
+            reply(Some("forced"), None, 10, 0.005),
+        ]));
+        let response = run_loop(
+            &provider,
+            "sys",
+            "user",
+            Task::Review,
+            dir.path(),
+            1,
+            &|_| {},
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.content, "forced");
+        assert_eq!(response.usage.unwrap().total_tokens, 110);
+    }
 
     #[test]
     fn test_message_serialization_basic() {
@@ -306,12 +346,12 @@ mod tests {
 
     #[test]
     fn test_tool_definition_serialization() {
-        let tools = get_tool_definitions();
-        assert_eq!(tools.len(), 1);
-        assert_eq!(tools[0].function.name, "shell");
+        let tools = ToolContext::new(Path::new("."), true).definitions();
+        assert_eq!(tools[0].function.name, "read_file");
 
         let json = serde_json::to_string(&tools[0]).unwrap();
-        assert!(json.contains("shell"));
-        assert!(json.contains("command"));
+        assert!(json.contains("\"type\":\"function\""));
+        assert!(json.contains("read_file"));
+        assert!(json.contains("path"));
     }
 }
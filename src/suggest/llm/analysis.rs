@@ -1,14 +1,16 @@
-use super::agentic::call_llm_agentic;
-use super::client::{call_llm_with_usage, truncate_str};
-use super::models::{Model, Usage};
-use super::parse::parse_codebase_suggestions;
+use super::agentic::call_llm_agentic_with_progress;
+use super::client::{call_llm_streaming, truncate_str};
+use super::models::{Model, Task, Usage};
+use super::parse::{merge_usage, parse_codebase_suggestions};
 use super::prompt_utils::format_repo_memory_section;
 use super::prompts::{ANALYZE_CODEBASE_AGENTIC_SYSTEM, ASK_QUESTION_SYSTEM};
+use super::provider::Message;
 use super::summaries::discover_project_context;
-use crate::cache::DomainGlossary;
+use crate::cache::{AskTurn, DomainGlossary};
 use crate::context::WorkContext;
+use crate::grouping::heuristics::is_test_file;
 use crate::index::{CodebaseIndex, PatternKind, SymbolKind};
-use crate::suggest::Suggestion;
+use crate::suggest::{Priority, Suggestion, SuggestionKind};
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
@@ -22,12 +24,17 @@ use crate::index::GOD_MODULE_LOC_THRESHOLD;
 const HIGH_COMPLEXITY_THRESHOLD: f64 = 20.0;
 
 /// Ask cosmos a general question about the codebase
-/// Uses the Smart model for thoughtful, well-reasoned responses in plain English
+/// Uses the Ask tier (Balanced by default) for thoughtful, well-reasoned responses in plain English.
+/// The answer is streamed: `on_text` receives each fragment as it arrives.
+/// `history` holds the conversation's earlier turns; the most recent that
+/// fit in the history budget are sent along.
 pub async fn ask_question(
     index: &CodebaseIndex,
     context: &WorkContext,
     question: &str,
+    history: &[AskTurn],
     repo_memory: Option<String>,
+    on_text: &mut (dyn FnMut(&str) + Send),
 ) -> anyhow::Result<(String, Option<Usage>)> {
     // Build context about the codebase
     let stats = index.stats();
@@ -40,9 +47,8 @@ pub async fn ask_question(
 
     // Get symbols for context (used internally, not exposed to user)
     let symbols: Vec<_> = index
-        .files
-        .values()
-        .flat_map(|f| f.symbols.iter())
+        .symbols
+        .iter()
         .filter(|s| {
             matches!(
                 s.kind,
@@ -78,10 +84,38 @@ QUESTION:
         question
     );
 
-    let response = call_llm_with_usage(ASK_QUESTION_SYSTEM, &user, Model::Balanced, false).await?;
+    // Earlier turns go first, so the project context stays next to the
+    // question being asked
+    let budget = ASK_HISTORY_MAX_TOKENS.min(Model::for_task(Task::Ask).spec().context_window / 4);
+    let mut messages = vec![Message::system(ASK_QUESTION_SYSTEM)];
+    for turn in history_within_budget(history, budget) {
+        messages.push(Message::user(&turn.question));
+        messages.push(Message::assistant(&turn.answer));
+    }
+    messages.push(Message::user(&user));
+
+    let response = call_llm_streaming(messages, Task::Ask, on_text).await?;
     Ok((response.content, response.usage))
 }
 
+/// Upper bound on tokens of earlier turns sent with a follow-up question
+const ASK_HISTORY_MAX_TOKENS: u32 = 6000;
+
+/// The most recent turns whose question and answer fit in `budget` tokens
+/// (estimated at 4 bytes per token), oldest first
+fn history_within_budget(history: &[AskTurn], budget: u32) -> &[AskTurn] {
+    let mut used = 0usize;
+    let mut start = history.len();
+    for turn in history.iter().rev() {
+        used += (turn.question.len() + turn.answer.len()) / 4;
+        if used > budget as usize {
+            break;
+        }
+        start -= 1;
+    }
+    &history[start..]
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 //  LEAN HYBRID ANALYSIS (Compact Context + Surgical Tool Use)
 // ═══════════════════════════════════════════════════════════════════════════
@@ -101,27 +135,31 @@ const MAX_CONTINUATION_ATTEMPTS: usize = 2;
 /// 4. If fewer than MIN_SUGGESTIONS returned, makes continuation calls to get more
 ///
 /// This balances accuracy (model can verify) with speed/cost (minimal calls).
+/// Each tool call is reported to `on_progress` as it happens.
 pub async fn analyze_codebase_agentic(
     repo_root: &Path,
     index: &CodebaseIndex,
     context: &WorkContext,
     repo_memory: Option<String>,
     glossary: Option<&DomainGlossary>,
+    on_progress: &(dyn Fn(&str) + Send + Sync),
 ) -> anyhow::Result<(Vec<Suggestion>, Option<Usage>)> {
     let user_prompt = build_lean_analysis_prompt(index, context, repo_memory.as_deref(), glossary);
 
-    // Use Speed model (gpt-oss-120b) with surgical tool access
+    // Speed tier by default (gpt-oss-120b) with surgical tool access
     // 8 iterations allows for good exploration
-    let response = call_llm_agentic(
+    let response = call_llm_agentic_with_progress(
         ANALYZE_CODEBASE_AGENTIC_SYSTEM,
         &user_prompt,
-        Model::Speed,
+        Task::Analysis,
         repo_root,
         false,
         8, // max iterations - suggestions need exploration
+        on_progress,
     )
     .await?;
 
+    let mut usage = response.usage;
     let mut suggestions = parse_codebase_suggestions(&response.content)?;
 
     // If we got fewer than MIN_SUGGESTIONS, make continuation calls to get more
@@ -131,15 +169,17 @@ pub async fn analyze_codebase_agentic(
         let needed = MIN_SUGGESTIONS - suggestions.len();
 
         let continuation_prompt = build_continuation_prompt(&suggestions, needed);
-        let continuation_response = call_llm_agentic(
+        let continuation_response = call_llm_agentic_with_progress(
             ANALYZE_CODEBASE_AGENTIC_SYSTEM,
             &continuation_prompt,
-            Model::Speed,
+            Task::Analysis,
             repo_root,
             false,
             4, // fewer iterations for continuation - context already gathered
+            on_progress,
         )
         .await?;
+        usage = merge_usage(usage, continuation_response.usage);
 
         match parse_codebase_suggestions(&continuation_response.content) {
             Ok(additional) => {
@@ -162,7 +202,27 @@ pub async fn analyze_codebase_agentic(
         }
     }
 
-    Ok((suggestions, None))
+    demote_covered_testing_suggestions(&mut suggestions, index);
+
+    Ok((suggestions, usage))
+}
+
+/// Testing suggestions belong on untested code; drop ones aimed at already-covered
+/// files to low priority so they don't crowd out real gaps.
+fn demote_covered_testing_suggestions(suggestions: &mut [Suggestion], index: &CodebaseIndex) {
+    for s in suggestions.iter_mut() {
+        if s.kind != SuggestionKind::Testing {
+            continue;
+        }
+        if index
+            .files
+            .get(&s.file)
+            .map(|f| f.has_tests())
+            .unwrap_or(false)
+        {
+            s.priority = Priority::Low;
+        }
+    }
 }
 
 /// Build a prompt asking for additional suggestions to reach the minimum
@@ -187,7 +247,7 @@ Focus on areas you haven't explored yet:
 - Security concerns
 - Code that could fail silently
 
-Use shell tools to explore and verify. Return ONLY the new suggestions as a JSON array."#,
+Use the tools to explore and verify. Return ONLY the new suggestions as a JSON array."#,
         existing.len(),
         MIN_SUGGESTIONS,
         needed,
@@ -248,7 +308,7 @@ fn build_lean_analysis_prompt(
         }
     }
     let mut dir_list: Vec<_> = dirs.into_iter().collect();
-    dir_list.sort_by(|a, b| b.1.cmp(&a.1));
+    dir_list.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
 
     if !dir_list.is_empty() {
         let areas: Vec<_> = dir_list
@@ -318,8 +378,7 @@ fn build_lean_analysis_prompt(
 
     // TODOs
     let todos: Vec<_> = index
-        .patterns
-        .iter()
+        .patterns()
         .filter(|p| matches!(p.kind, PatternKind::TodoMarker))
         .take(4)
         .collect();
@@ -336,12 +395,95 @@ fn build_lean_analysis_prompt(
         sections.push(s);
     }
 
+    // Complex code with no covering tests - where Testing suggestions should go
+    let mut untested: Vec<_> = index
+        .files
+        .iter()
+        .filter(|(path, f)| {
+            f.complexity > HIGH_COMPLEXITY_THRESHOLD && !f.has_tests() && !is_test_file(path)
+        })
+        .collect();
+    untested.sort_by(|a, b| {
+        b.1.complexity
+            .partial_cmp(&a.1.complexity)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    if !untested.is_empty() {
+        let mut s =
+            String::from("\n[UNTESTED] Complex code with no tests (kind \"testing\" goes here):");
+        for (path, f) in untested.iter().take(4) {
+            s.push_str(&format!(
+                "\n• {} (complexity {:.0}) - {}",
+                path.display(),
+                f.complexity,
+                truncate_str(&f.summary.purpose, 50)
+            ));
+        }
+        sections.push(s);
+    }
+
+    // Injection sinks flagged by the parser - deterministic, so worth checking first
+    let sinks: Vec<_> = index
+        .patterns()
+        .filter(|p| p.kind.is_security())
+        .take(6)
+        .collect();
+    if !sinks.is_empty() {
+        let mut s = String::from(
+            "\n[SECURITY] Possible injection sinks or inline secrets (confirm, then report as kind \"security\"):",
+        );
+        for p in &sinks {
+            let rel = p.file.strip_prefix(&index.root).unwrap_or(&p.file);
+            let cell = index
+                .files
+                .get(rel)
+                .and_then(|f| f.notebook_location(p.line))
+                .map(|(cell, line)| format!(" (notebook cell {}, line {})", cell, line))
+                .unwrap_or_default();
+            s.push_str(&format!(
+                "\n• {}:{}{} - {}",
+                rel.display(),
+                p.line,
+                cell,
+                truncate_str(&p.description, 80)
+            ));
+            if priority_files.len() < 3 && !priority_files.iter().any(|f| f == rel) {
+                priority_files.push(rel.to_path_buf());
+            }
+        }
+        sections.push(s);
+    }
+
+    // Dockerfiles, CI, Terraform, SQL and scripts - unpinned versions, scripts ignoring errors
+    let infra: Vec<_> = index
+        .patterns()
+        .filter(|p| !p.kind.is_security() && !matches!(p.kind, PatternKind::TodoMarker))
+        .filter(|p| {
+            let rel = p.file.strip_prefix(&index.root).unwrap_or(&p.file);
+            index.files.get(rel).is_some_and(|f| !f.language.is_code())
+        })
+        .take(4)
+        .collect();
+    if !infra.is_empty() {
+        let mut s = String::from("\n[INFRA] Build/deploy config issues:");
+        for p in &infra {
+            let rel = p.file.strip_prefix(&index.root).unwrap_or(&p.file);
+            s.push_str(&format!(
+                "\n• {}:{} - {}",
+                rel.display(),
+                p.line,
+                truncate_str(&p.description, 80)
+            ));
+        }
+        sections.push(s);
+    }
+
     // ═══ CODE PREVIEW (first 35 lines of top priority files) ═══
     if !priority_files.is_empty() {
         let mut preview_section = String::from("\n\n═══ CODE PREVIEW ═══");
         for path in priority_files.iter().take(3) {
             let full_path = index.root.join(path);
-            if let Ok(content) = std::fs::read_to_string(&full_path) {
+            if let Ok(content) = crate::index::notebook::read_editable(&full_path) {
                 let lines: String = content
                     .lines()
                     .take(35)
@@ -390,18 +532,18 @@ TIERED DISCOVERY:
 1. THE GIST → understand project purpose
 2. KEY AREAS → identify interesting modules
 3. PRIORITY FILES → pick files to investigate
-4. Use grep/head to find and read code
+4. Use grep/read_file to find and read code
 
 SURGICAL COMMANDS (save tokens!):
-• grep -n 'fn foo' <file> → find line number
-• sed -n '45,75p' <file> → read 30 lines around match
-• head -50 <file> → read file start
-• rg 'pattern' → search entire codebase
+• find_symbol(foo) → where foo is defined
+• read_file(<file>, 45, 75) → read 30 lines around a match
+• file_summary(<file>) → purpose and dependents without reading it
+• grep(pattern, glob) → search the codebase
 
 EXAMPLE WORKFLOW:
 1. See 'handles API calls' in summary
-2. grep -n 'async fn' src/api.rs → find functions
-3. sed -n '120,160p' src/api.rs → read around interesting function
+2. grep('async fn', 'src/api.rs') → find functions
+3. read_file(src/api.rs, 120, 160) → read around interesting function
 4. Find issue → record with evidence
 
 RULES:
@@ -412,3 +554,24 @@ RULES:
 
     sections.join("")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_keeps_most_recent_turns_in_budget() {
+        let turn = |question: &str, answer_len: usize| AskTurn {
+            question: question.to_string(),
+            answer: "x".repeat(answer_len),
+            asked_at: chrono::Utc::now(),
+        };
+        let history = vec![turn("first", 4000), turn("second", 400), turn("third", 400)];
+
+        let kept = history_within_budget(&history, 500);
+        assert_eq!(kept.len(), 2);
+        assert_eq!(kept[0].question, "second");
+        assert_eq!(history_within_budget(&history, 5000).len(), 3);
+        assert!(history_within_budget(&history, 10).is_empty());
+    }
+}
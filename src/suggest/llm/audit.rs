@@ -0,0 +1,175 @@
+//! Audit trail of the tools the model called
+//!
+//! Every `call_llm_agentic` is a run; each tool call it makes is written to
+//! the session's `.cosmos/audit/` log as it finishes and kept in memory so
+//! the tool trace overlay can show what was read or run for the current
+//! suggestions, preview or review.
+
+use super::models::Task;
+use super::tools::ToolCall;
+use crate::cache::{AuditEntry, Cache, MAX_AUDIT_OUTPUT_BYTES};
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// One agentic call and the tool calls it made, oldest first
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditRun {
+    pub id: u64,
+    pub task: Task,
+    pub started: DateTime<Utc>,
+    pub calls: Vec<AuditEntry>,
+}
+
+struct AuditLog {
+    repo_root: PathBuf,
+    session_start: DateTime<Utc>,
+    runs: Vec<AuditRun>,
+}
+
+/// Trail for this session; nothing is recorded until `install`
+static AUDIT: Mutex<Option<AuditLog>> = Mutex::new(None);
+
+fn with_log<T>(f: impl FnOnce(&mut AuditLog) -> T) -> Option<T> {
+    AUDIT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_mut()
+        .map(f)
+}
+
+/// Start this session's audit log, dropping logs of old sessions
+pub fn install(repo_root: &Path) {
+    let _ = Cache::new(repo_root).prune_audit_logs();
+    *AUDIT.lock().unwrap_or_else(|e| e.into_inner()) = Some(AuditLog {
+        repo_root: repo_root.to_path_buf(),
+        session_start: Utc::now(),
+        runs: Vec::new(),
+    });
+}
+
+/// Open a run for an agentic call; its id goes to `record`
+pub fn begin_run(task: Task) -> u64 {
+    with_log(|log| {
+        let id = log.runs.len() as u64 + 1;
+        log.runs.push(AuditRun {
+            id,
+            task,
+            started: Utc::now(),
+            calls: Vec::new(),
+        });
+        id
+    })
+    .unwrap_or(0)
+}
+
+/// Record a finished tool call of run `run`
+pub fn record(run: u64, iteration: usize, call: &ToolCall, output: &str, duration: Duration) {
+    with_log(|log| {
+        let Some(entry_run) = log.runs.iter_mut().find(|r| r.id == run) else {
+            return;
+        };
+        let (output, truncated) = clip(output);
+        let entry = AuditEntry {
+            at: Utc::now(),
+            run,
+            task: entry_run.task.name().to_string(),
+            iteration,
+            tool: call.function.name.clone(),
+            arguments: call.function.arguments.clone(),
+            output,
+            truncated,
+            duration_ms: duration.as_millis() as u64,
+        };
+        let _ = Cache::new(&log.repo_root).append_audit(log.session_start, &entry);
+        entry_run.calls.push(entry);
+    });
+}
+
+/// This session's runs of `task`, newest first
+pub fn runs(task: Task) -> Vec<AuditRun> {
+    with_log(|log| {
+        log.runs
+            .iter()
+            .rev()
+            .filter(|run| run.task == task)
+            .cloned()
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Where this session's tool calls are written
+pub fn log_path() -> Option<PathBuf> {
+    with_log(|log| Cache::new(&log.repo_root).audit_log_path(log.session_start))
+}
+
+fn clip(output: &str) -> (String, bool) {
+    if output.len() <= MAX_AUDIT_OUTPUT_BYTES {
+        return (output.to_string(), false);
+    }
+    let mut end = MAX_AUDIT_OUTPUT_BYTES;
+    while !output.is_char_boundary(end) {
+        end -= 1;
+    }
+    (output[..end].to_string(), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tools::FunctionCall;
+    use super::*;
+
+    #[test]
+    fn test_runs_are_recorded_and_written() {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("cosmos_audit_{}", nanos));
+        std::fs::create_dir_all(&root).unwrap();
+        install(&root);
+
+        let call = ToolCall {
+            id: "call_1".to_string(),
+            function: FunctionCall {
+                name: "grep".to_string(),
+                arguments: r#"{"pattern":"fn main"}"#.to_string(),
+            },
+        };
+        let review = begin_run(Task::Review);
+        let preview = begin_run(Task::Preview);
+        record(
+            preview,
+            1,
+            &call,
+            "src/main.rs:1: fn main()",
+            Duration::from_millis(12),
+        );
+        record(
+            review,
+            2,
+            &call,
+            &"é".repeat(MAX_AUDIT_OUTPUT_BYTES),
+            Duration::ZERO,
+        );
+
+        let previews = runs(Task::Preview);
+        let previewed = &previews.iter().find(|r| r.id == preview).unwrap().calls[0];
+        assert_eq!(previewed.tool, "grep");
+        assert_eq!(previewed.duration_ms, 12);
+        let reviews = runs(Task::Review);
+        let reviewed = &reviews.iter().find(|r| r.id == review).unwrap().calls[0];
+        assert!(reviewed.truncated);
+        assert_eq!(reviewed.iteration, 2);
+        assert!(reviewed.output.len() <= MAX_AUDIT_OUTPUT_BYTES);
+
+        let written = std::fs::read_to_string(log_path().unwrap()).unwrap();
+        assert_eq!(written.lines().count(), 2);
+        let first: AuditEntry = serde_json::from_str(written.lines().next().unwrap()).unwrap();
+        assert_eq!(first.task, "preview");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,395 @@
+//! Record/replay of LLM calls
+//!
+//! `cosmos --record FILE` writes every request the client layer sends,
+//! with the provider's response, to a cassette (JSON Lines). `cosmos
+//! --replay FILE` answers from the cassette instead: no network, API key
+//! or spend, and the same responses every run.
+//!
+//! Requests match on a normalized form: task, response shape, tool names
+//! and message text with line endings, trailing whitespace and the repo
+//! root smoothed out. The model ID is left out so a cassette replays under
+//! any provider configuration. Identical requests recorded more than once
+//! are answered in recorded order.
+
+use super::provider::{ChatRequest, ChatResponse, LlmProvider, ResponseMode};
+use crate::util::hash_bytes;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Placeholder for the repo root in normalized requests
+const REPO_PLACEHOLDER: &str = "<repo>";
+
+/// One recorded call
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    request: Value,
+    response: ChatResponse,
+}
+
+pub struct Cassette {
+    path: PathBuf,
+    /// Replaced in prompts so cassettes move between checkouts
+    repo_root: String,
+    /// Recorded responses by request key; `None` when recording
+    replay: Option<Mutex<HashMap<String, VecDeque<ChatResponse>>>>,
+    /// Serializes appends from concurrent calls
+    write_lock: Mutex<()>,
+}
+
+impl Cassette {
+    /// Start recording to `path`, replacing any cassette already there
+    pub fn record(path: &Path, repo_root: &Path) -> anyhow::Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, "")?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            repo_root: root_pattern(repo_root),
+            replay: None,
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    /// Load `path` for replay
+    pub fn replay(path: &Path, repo_root: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Cannot read cassette {}: {}", path.display(), e))?;
+        let mut responses: HashMap<String, VecDeque<ChatResponse>> = HashMap::new();
+        for (number, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: Entry = serde_json::from_str(line).map_err(|e| {
+                anyhow::anyhow!(
+                    "Malformed cassette {} (line {}): {}",
+                    path.display(),
+                    number + 1,
+                    e
+                )
+            })?;
+            responses
+                .entry(request_key(&entry.request))
+                .or_default()
+                .push_back(entry.response);
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            repo_root: root_pattern(repo_root),
+            replay: Some(Mutex::new(responses)),
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_some()
+    }
+
+    /// Provider-independent form of a request, as stored in the cassette
+    fn normalize(&self, request: &ChatRequest) -> Value {
+        let text = |s: &str| {
+            let s = if self.repo_root.is_empty() {
+                s.to_string()
+            } else {
+                s.replace(&self.repo_root, REPO_PLACEHOLDER)
+            };
+            s.lines()
+                .map(str::trim_end)
+                .collect::<Vec<_>>()
+                .join("\n")
+                .trim()
+                .to_string()
+        };
+        let response = match &request.response {
+            ResponseMode::Text => json!("text"),
+            ResponseMode::Json => json!("json"),
+            ResponseMode::Schema { name, schema } => json!({ "name": name, "schema": schema }),
+        };
+        let tools = request
+            .tools
+            .as_ref()
+            .map(|tools| tools.iter().map(|t| t.function.name).collect::<Vec<_>>());
+        let messages: Vec<Value> = request
+            .messages
+            .iter()
+            .map(|m| {
+                let tool_calls = m.tool_calls.as_ref().map(|calls| {
+                    calls
+                        .iter()
+                        .map(|c| json!({ "name": c.function.name, "arguments": text(&c.function.arguments) }))
+                        .collect::<Vec<_>>()
+                });
+                json!({
+                    "role": m.role,
+                    "content": m.content.as_deref().map(text),
+                    "tool_calls": tool_calls,
+                })
+            })
+            .collect();
+        json!({
+            "task": request.task.name(),
+            "response": response,
+            "tools": tools,
+            "messages": messages,
+        })
+    }
+
+    fn append(&self, request: &ChatRequest, response: &ChatResponse) -> anyhow::Result<()> {
+        let entry = Entry {
+            request: self.normalize(request),
+            response: response.clone(),
+        };
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        let _guard = self.write_lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut file = OpenOptions::new().append(true).open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// The recorded response for `request`. Repeats are served in order;
+    /// the last one is reused once they run out.
+    fn lookup(&self, request: &ChatRequest) -> anyhow::Result<ChatResponse> {
+        let Some(replay) = &self.replay else {
+            anyhow::bail!(
+                "Cassette {} is recording, not replaying",
+                self.path.display()
+            );
+        };
+        let key = request_key(&self.normalize(request));
+        let mut responses = replay.lock().unwrap_or_else(|e| e.into_inner());
+        let queue = responses.get_mut(&key).filter(|q| !q.is_empty()).ok_or_else(|| {
+            anyhow::anyhow!(
+                "No recorded {} response in cassette {}. The prompt changed since it was recorded; record it again with --record.",
+                request.task.name(),
+                self.path.display()
+            )
+        })?;
+        Ok(if queue.len() > 1 {
+            queue.pop_front().unwrap_or_default()
+        } else {
+            queue[0].clone()
+        })
+    }
+}
+
+/// The repo root as it appears in prompts; empty (no substitution) for `/`
+fn root_pattern(repo_root: &Path) -> String {
+    let root = repo_root.display().to_string();
+    if root.trim_end_matches('/').is_empty() {
+        String::new()
+    } else {
+        root
+    }
+}
+
+fn request_key(normalized: &Value) -> String {
+    hash_bytes(normalized.to_string().as_bytes())
+}
+
+static ACTIVE: RwLock<Option<Arc<Cassette>>> = RwLock::new(None);
+
+/// Record or replay every LLM call from now on
+pub fn install(cassette: Cassette) {
+    *ACTIVE.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(cassette));
+}
+
+/// The installed cassette, if any
+pub fn active() -> Option<Arc<Cassette>> {
+    ACTIVE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Whether calls are being answered from a cassette
+pub fn is_replaying() -> bool {
+    active().is_some_and(|cassette| cassette.is_replaying())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  PROVIDERS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Passes calls through to a real backend and records them
+pub struct Recorder {
+    inner: Box<dyn LlmProvider>,
+    cassette: Arc<Cassette>,
+}
+
+impl Recorder {
+    pub fn new(inner: Box<dyn LlmProvider>, cassette: Arc<Cassette>) -> Self {
+        Self { inner, cassette }
+    }
+}
+
+impl LlmProvider for Recorder {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn chat<'a>(&'a self, request: &'a ChatRequest) -> BoxFuture<'a, anyhow::Result<ChatResponse>> {
+        Box::pin(async move {
+            let response = self.inner.chat(request).await?;
+            self.cassette.append(request, &response)?;
+            Ok(response)
+        })
+    }
+
+    fn chat_stream<'a>(
+        &'a self,
+        request: &'a ChatRequest,
+        on_text: &'a mut (dyn FnMut(&str) + Send),
+    ) -> BoxFuture<'a, anyhow::Result<ChatResponse>> {
+        Box::pin(async move {
+            let response = self.inner.chat_stream(request, on_text).await?;
+            self.cassette.append(request, &response)?;
+            Ok(response)
+        })
+    }
+
+    fn balance(&self) -> BoxFuture<'_, anyhow::Result<Option<f64>>> {
+        self.inner.balance()
+    }
+}
+
+/// Answers calls from a cassette
+pub struct Player(pub Arc<Cassette>);
+
+impl LlmProvider for Player {
+    fn name(&self) -> &str {
+        "cassette"
+    }
+
+    fn chat<'a>(&'a self, request: &'a ChatRequest) -> BoxFuture<'a, anyhow::Result<ChatResponse>> {
+        Box::pin(async move { self.0.lookup(request) })
+    }
+
+    fn chat_stream<'a>(
+        &'a self,
+        request: &'a ChatRequest,
+        on_text: &'a mut (dyn FnMut(&str) + Send),
+    ) -> BoxFuture<'a, anyhow::Result<ChatResponse>> {
+        Box::pin(async move {
+            let response = self.0.lookup(request)?;
+            if let Some(content) = &response.content {
+                on_text(content);
+            }
+            Ok(response)
+        })
+    }
+
+    fn balance(&self) -> BoxFuture<'_, anyhow::Result<Option<f64>>> {
+        Box::pin(async { Ok(None) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::suggest::llm::models::Task;
+    use crate::suggest::llm::provider::Message;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Replies "reply N" to the Nth call
+    struct Counter(AtomicUsize);
+
+    impl LlmProvider for Counter {
+        fn name(&self) -> &str {
+            "counter"
+        }
+
+        fn chat<'a>(
+            &'a self,
+            _request: &'a ChatRequest,
+        ) -> BoxFuture<'a, anyhow::Result<ChatResponse>> {
+            let n = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+            Box::pin(async move {
+                Ok(ChatResponse {
+                    content: Some(format!("reply {}", n)),
+                    ..ChatResponse::default()
+                })
+            })
+        }
+
+        fn chat_stream<'a>(
+            &'a self,
+            request: &'a ChatRequest,
+            _on_text: &'a mut (dyn FnMut(&str) + Send),
+        ) -> BoxFuture<'a, anyhow::Result<ChatResponse>> {
+            self.chat(request)
+        }
+
+        fn balance(&self) -> BoxFuture<'_, anyhow::Result<Option<f64>>> {
+            Box::pin(async { Ok(None) })
+        }
+    }
+
+    fn temp_dir(label: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("cosmos_cassette_{}_{}", label, nanos));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn ask(root: &Path, question: &str) -> ChatRequest {
+        ChatRequest::new(
+            Task::Ask,
+            vec![
+                Message::system("Answer briefly."),
+                Message::user(&format!("In {}: {}", root.display(), question)),
+            ],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_replay_serves_recorded_responses() {
+        let dir = temp_dir("roundtrip");
+        let path = dir.join("session.jsonl");
+        let recorder = Recorder::new(
+            Box::new(Counter(AtomicUsize::new(0))),
+            Arc::new(Cassette::record(&path, Path::new("/work/a")).unwrap()),
+        );
+        for question in ["why?", "how?", "why?"] {
+            recorder
+                .chat(&ask(Path::new("/work/a"), question))
+                .await
+                .unwrap();
+        }
+
+        // Different checkout and trailing whitespace still match
+        let player = Player(Arc::new(
+            Cassette::replay(&path, Path::new("/ci/b")).unwrap(),
+        ));
+        let reply = |question: &'static str| {
+            let player = &player;
+            async move {
+                player
+                    .chat(&ask(Path::new("/ci/b"), question))
+                    .await
+                    .unwrap()
+                    .content
+                    .unwrap()
+            }
+        };
+        assert_eq!(reply("why?  ").await, "reply 1");
+        assert_eq!(reply("how?").await, "reply 2");
+        assert_eq!(reply("why?").await, "reply 3");
+        // Repeats past the recording reuse the last response
+        assert_eq!(reply("why?").await, "reply 3");
+
+        let err = player
+            .chat(&ask(Path::new("/ci/b"), "what?"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("No recorded ask response"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,17 +1,9 @@
-use super::models::{Model, Usage};
-use crate::config::Config;
+use super::models::{Task, Usage};
+use super::provider::{configured_provider, ChatRequest, Message, ResponseMode};
+use crate::config::{Config, ProviderSettings};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
-/// OpenRouter direct API URL (BYOK mode)
-pub(crate) const OPENROUTER_URL: &str = "https://openrouter.ai/api/v1/chat/completions";
-
-/// Get the configured OpenRouter API key, if any.
-pub(crate) fn api_key() -> Option<String> {
-    let mut config = Config::load();
-    config.get_api_key()
-}
-
 /// Response from LLM including content and usage stats
 #[derive(Debug)]
 pub struct LlmResponse {
@@ -19,144 +11,16 @@ pub struct LlmResponse {
     pub usage: Option<Usage>,
 }
 
-#[derive(Serialize)]
-struct ChatRequest {
-    model: String,
-    messages: Vec<Message>,
-    max_tokens: u32,
-    stream: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    response_format: Option<ResponseFormat>,
-    /// OpenRouter provider configuration for automatic fallback
-    #[serde(skip_serializing_if = "Option::is_none")]
-    provider: Option<ProviderConfig>,
-}
-
-/// OpenRouter provider configuration
-#[derive(Serialize)]
-struct ProviderConfig {
-    /// Allow OpenRouter to try other providers if the primary fails
-    allow_fallbacks: bool,
-}
-
-/// Response format configuration for OpenRouter
-/// Supports both simple JSON mode and structured output with schema
-#[derive(Serialize)]
-struct ResponseFormat {
-    #[serde(rename = "type")]
-    format_type: String,
-    /// JSON Schema for structured output (optional)
-    #[serde(skip_serializing_if = "Option::is_none")]
-    json_schema: Option<JsonSchemaWrapper>,
-}
-
-/// Wrapper for JSON Schema in structured output mode
-#[derive(Serialize)]
-struct JsonSchemaWrapper {
-    /// Name of the schema (used for reference)
-    name: String,
-    /// Whether to strictly enforce the schema
-    strict: bool,
-    /// The JSON schema definition
-    schema: serde_json::Value,
-}
-
-#[derive(Serialize, Deserialize)]
-struct Message {
-    role: String,
-    content: String,
-}
-
-// ═══════════════════════════════════════════════════════════════════════════
-//  ANTHROPIC PROMPT CACHING SUPPORT
-// ═══════════════════════════════════════════════════════════════════════════
-// Anthropic models support prompt caching via multipart message format with
-// cache_control breakpoints. Cached reads are 0.1x input pricing.
-
-/// Cache control for Anthropic prompt caching
-#[derive(Serialize, Clone)]
-struct CacheControl {
-    #[serde(rename = "type")]
-    cache_type: String, // "ephemeral"
-}
-
-/// A content part in a multipart message (for caching)
-#[derive(Serialize, Clone)]
-struct ContentPart {
-    #[serde(rename = "type")]
-    part_type: String, // "text"
-    text: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    cache_control: Option<CacheControl>,
-}
-
-/// Content can be either a simple string or multipart array
-#[derive(Serialize, Clone)]
-#[serde(untagged)]
-enum MessageContent2 {
-    Text(String),
-    Parts(Vec<ContentPart>),
-}
-
-/// Message with multipart content support (for caching)
-#[derive(Serialize, Clone)]
-struct CachedMessage {
-    role: String,
-    content: MessageContent2,
-}
-
-/// Chat request with cached message support
-#[derive(Serialize)]
-struct CachedChatRequest {
-    model: String,
-    messages: Vec<CachedMessage>,
-    max_tokens: u32,
-    stream: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    response_format: Option<ResponseFormat>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    provider: Option<ProviderConfig>,
-}
-
-/// Build messages with caching enabled on the system prompt
-fn build_cached_messages(system: &str, user: &str) -> Vec<CachedMessage> {
-    vec![
-        CachedMessage {
-            role: "system".to_string(),
-            content: MessageContent2::Parts(vec![ContentPart {
-                part_type: "text".to_string(),
-                text: system.to_string(),
-                cache_control: Some(CacheControl {
-                    cache_type: "ephemeral".to_string(),
-                }),
-            }]),
-        },
-        CachedMessage {
-            role: "user".to_string(),
-            content: MessageContent2::Text(user.to_string()),
-        },
-    ]
-}
-
-#[derive(Deserialize)]
-struct ChatResponse {
-    choices: Vec<Choice>,
-    usage: Option<Usage>,
-}
-
-#[derive(Deserialize)]
-struct Choice {
-    message: MessageContent,
-}
-
-#[derive(Deserialize)]
-struct MessageContent {
-    content: String,
-}
-
-/// Check if LLM is available (either BYOK or managed)
+/// Check if LLM is available (an OpenRouter key, or a self-hosted backend)
 pub fn is_available() -> bool {
-    api_key().is_some()
+    if super::cassette::is_replaying() {
+        return true;
+    }
+    let mut config = Config::load();
+    match config.provider_settings() {
+        ProviderSettings::OpenRouter => config.get_api_key().is_some(),
+        ProviderSettings::OpenAiCompatible { .. } => true,
+    }
 }
 
 /// Rate limit retry configuration
@@ -165,9 +29,9 @@ pub(crate) const INITIAL_BACKOFF_MS: u64 = 2000; // 2 seconds
 pub(crate) const BACKOFF_MULTIPLIER: u64 = 2; // Exponential backoff
 pub(crate) const REQUEST_TIMEOUT_SECS: u64 = 60;
 
-/// Extract retry-after hint from OpenRouter response (if present)
+/// Extract retry-after hint from a provider response (if present)
 fn parse_retry_after(text: &str) -> Option<u64> {
-    // OpenRouter may include retry-after in response body or we estimate
+    // Providers may include retry-after in response body or we estimate
     // Look for patterns like "retry after X seconds" or "wait X seconds"
     let text_lower = text.to_lowercase();
     if let Some(pos) = text_lower.find("retry") {
@@ -199,48 +63,56 @@ pub(crate) fn is_retryable_network_error(err: &reqwest::Error) -> bool {
     err.is_timeout() || err.is_connect()
 }
 
-/// OpenRouter error response (can come with 200 status for upstream errors)
+/// Error body (OpenRouter can send these with a 200 status for upstream errors)
 #[derive(Deserialize)]
-pub(crate) struct OpenRouterError {
-    pub error: OpenRouterApiError,
+pub(crate) struct ProviderError {
+    pub error: ProviderApiError,
 }
 
 #[derive(Deserialize)]
-pub(crate) struct OpenRouterApiError {
+pub(crate) struct ProviderApiError {
     pub message: String,
     #[serde(default)]
     pub code: Option<i32>,
 }
 
-/// Send a request to OpenRouter with automatic retry on transient failures.
+/// Where and how a provider's chat completions are sent
+pub(crate) struct Endpoint<'a> {
+    /// Provider name for error messages
+    pub name: &'a str,
+    pub url: String,
+    pub headers: Vec<(&'static str, String)>,
+    /// Appended to the "Invalid API key" error
+    pub setup_hint: &'static str,
+}
+
+/// POST a request, retrying transient failures until the provider answers
+/// with a success status.
 ///
 /// Handles:
 /// - Network errors (timeout, connection failures)
 /// - Rate limits (429)
 /// - Server errors (5xx)
-/// - OpenRouter's 200-with-error responses
 ///
-/// Returns the response text on success, or an error after all retries exhausted.
-pub(crate) async fn send_with_retry<T: Serialize>(
+/// `timeout` bounds the whole exchange, body included.
+async fn post_with_retry<T: Serialize>(
     client: &reqwest::Client,
-    api_key: &str,
+    endpoint: &Endpoint<'_>,
     request_body: &T,
-) -> anyhow::Result<String> {
+    timeout: Duration,
+) -> anyhow::Result<reqwest::Response> {
     let mut last_error = String::new();
     let mut retry_count = 0;
 
     while retry_count <= MAX_RETRIES {
-        // Build request with OpenRouter headers
-        let response = match client
-            .post(OPENROUTER_URL)
-            .header("Content-Type", "application/json")
-            .header("HTTP-Referer", "https://cosmos.dev")
-            .header("X-Title", "Cosmos")
-            .header("Authorization", format!("Bearer {}", api_key))
-            .json(request_body)
-            .send()
-            .await
-        {
+        let mut request = client
+            .post(&endpoint.url)
+            .timeout(timeout)
+            .header("Content-Type", "application/json");
+        for (name, value) in &endpoint.headers {
+            request = request.header(*name, value);
+        }
+        let response = match request.json(request_body).send().await {
             Ok(response) => response,
             Err(err) => {
                 last_error = err.to_string();
@@ -250,11 +122,15 @@ pub(crate) async fn send_with_retry<T: Serialize>(
                     tokio::time::sleep(tokio::time::Duration::from_secs(retry_after)).await;
                     continue;
                 }
-                return Err(map_timeout_error(err));
+                return Err(map_timeout_error(endpoint.name, err));
             }
         };
 
         let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
         let text = match response.text().await {
             Ok(text) => text,
             Err(err) => {
@@ -265,35 +141,9 @@ pub(crate) async fn send_with_retry<T: Serialize>(
                     tokio::time::sleep(tokio::time::Duration::from_secs(retry_after)).await;
                     continue;
                 }
-                return Err(map_timeout_error(err));
+                return Err(map_timeout_error(endpoint.name, err));
             }
         };
-
-        if status.is_success() {
-            // OpenRouter sometimes returns errors with 200 status (upstream provider issues)
-            if let Ok(err_resp) = serde_json::from_str::<OpenRouterError>(&text) {
-                let is_retryable = err_resp
-                    .error
-                    .code
-                    .map(|c| c >= 500 || c == 429)
-                    .unwrap_or(true);
-
-                if is_retryable && retry_count < MAX_RETRIES {
-                    retry_count += 1;
-                    let retry_after = backoff_secs(retry_count);
-                    tokio::time::sleep(tokio::time::Duration::from_secs(retry_after)).await;
-                    continue;
-                }
-
-                return Err(anyhow::anyhow!(
-                    "OpenRouter error: {}",
-                    truncate_str(&err_resp.error.message, 200)
-                ));
-            }
-
-            return Ok(text);
-        }
-
         last_error = text.clone();
 
         // Rate limit - retry with backoff
@@ -314,14 +164,14 @@ pub(crate) async fn send_with_retry<T: Serialize>(
 
         // Non-retryable error or max retries exceeded
         let error_msg = match status.as_u16() {
-            401 => "Invalid API key. Run 'cosmos --setup' to update it.".to_string(),
+            401 => format!("Invalid API key. {}", endpoint.setup_hint),
             429 => format!(
-                "Rate limited by OpenRouter after {} retries. Try again in a few minutes. (Press 'e' to view error log)",
-                retry_count
+                "Rate limited by {} after {} retries. Try again in a few minutes. (Press 'e' to view error log)",
+                endpoint.name, retry_count
             ),
             500..=599 => format!(
-                "OpenRouter server error ({}). The service may be temporarily unavailable.",
-                status
+                "{} server error ({}). The service may be temporarily unavailable.",
+                endpoint.name, status
             ),
             _ => format!("API error {}: {}", status, truncate_str(&text, 200)),
         };
@@ -332,7 +182,142 @@ pub(crate) async fn send_with_retry<T: Serialize>(
     Err(anyhow::anyhow!("{}", last_error))
 }
 
-/// Create a configured HTTP client for OpenRouter requests
+/// Send a request to a provider with automatic retry on transient failures,
+/// including 200-with-error responses (OpenRouter upstream failures).
+///
+/// Returns the response text on success, or an error after all retries exhausted.
+pub(crate) async fn send_with_retry<T: Serialize>(
+    client: &reqwest::Client,
+    endpoint: &Endpoint<'_>,
+    request_body: &T,
+) -> anyhow::Result<String> {
+    let timeout = Duration::from_secs(REQUEST_TIMEOUT_SECS);
+    let mut retry_count = 0;
+
+    loop {
+        let response = post_with_retry(client, endpoint, request_body, timeout).await?;
+        let text = match response.text().await {
+            Ok(text) => text,
+            Err(err) => {
+                if is_retryable_network_error(&err) && retry_count < MAX_RETRIES {
+                    retry_count += 1;
+                    let retry_after = backoff_secs(retry_count);
+                    tokio::time::sleep(tokio::time::Duration::from_secs(retry_after)).await;
+                    continue;
+                }
+                return Err(map_timeout_error(endpoint.name, err));
+            }
+        };
+
+        // OpenRouter sometimes returns errors with 200 status (upstream provider issues)
+        if let Ok(err_resp) = serde_json::from_str::<ProviderError>(&text) {
+            let is_retryable = err_resp
+                .error
+                .code
+                .map(|c| c >= 500 || c == 429)
+                .unwrap_or(true);
+
+            if is_retryable && retry_count < MAX_RETRIES {
+                retry_count += 1;
+                let retry_after = backoff_secs(retry_count);
+                tokio::time::sleep(tokio::time::Duration::from_secs(retry_after)).await;
+                continue;
+            }
+
+            return Err(anyhow::anyhow!(
+                "{} error: {}",
+                endpoint.name,
+                truncate_str(&err_resp.error.message, 200)
+            ));
+        }
+
+        return Ok(text);
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  SERVER-SENT EVENTS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Upper bound for a streamed reply; chunks keep arriving, so this only
+/// stops a server that stalls mid-answer
+const STREAM_TIMEOUT_SECS: u64 = 300;
+
+/// Splits a `text/event-stream` body into event payloads.
+///
+/// Bytes arrive in arbitrary pieces (possibly mid-line, mid-character).
+/// Comment lines (`: keep-alive`) and fields other than `data` are ignored;
+/// the data lines of one event are joined with newlines.
+#[derive(Debug, Default)]
+struct SseDecoder {
+    pending: Vec<u8>,
+    data: Vec<String>,
+}
+
+impl SseDecoder {
+    /// Feed bytes; returns the payloads of every event they complete
+    fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        while let Some(newline) = self.pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=newline).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    events.push(self.data.join("\n"));
+                    self.data.clear();
+                }
+            } else if let Some(value) = line.strip_prefix("data:") {
+                self.data
+                    .push(value.strip_prefix(' ').unwrap_or(value).to_string());
+            }
+        }
+        events
+    }
+
+    /// Payload of a final event the server didn't terminate with a blank line
+    fn finish(mut self) -> Option<String> {
+        let mut events = self.push(b"\n\n");
+        events.pop()
+    }
+}
+
+/// Send a streaming request and hand each event payload to `on_event` as
+/// it arrives, stopping at the `[DONE]` sentinel.
+///
+/// Retries happen only before the stream starts: once data has been handed
+/// on, a dropped connection is an error rather than a silent restart.
+pub(crate) async fn send_streaming<T: Serialize>(
+    client: &reqwest::Client,
+    endpoint: &Endpoint<'_>,
+    request_body: &T,
+    on_event: &mut (dyn FnMut(&str) -> anyhow::Result<()> + Send),
+) -> anyhow::Result<()> {
+    let timeout = Duration::from_secs(STREAM_TIMEOUT_SECS);
+    let mut response = post_with_retry(client, endpoint, request_body, timeout).await?;
+    let mut decoder = SseDecoder::default();
+    loop {
+        let chunk = response
+            .chunk()
+            .await
+            .map_err(|err| map_timeout_error(endpoint.name, err))?;
+        let Some(bytes) = chunk else {
+            if let Some(event) = decoder.finish().filter(|e| e.trim() != "[DONE]") {
+                on_event(&event)?;
+            }
+            return Ok(());
+        };
+        for event in decoder.push(&bytes) {
+            if event.trim() == "[DONE]" {
+                return Ok(());
+            }
+            on_event(&event)?;
+        }
+    }
+}
+
+/// Create a configured HTTP client for provider requests
 pub(crate) fn create_http_client(timeout_secs: u64) -> anyhow::Result<reqwest::Client> {
     reqwest::Client::builder()
         .timeout(Duration::from_secs(timeout_secs))
@@ -345,58 +330,37 @@ pub(crate) fn create_http_client(timeout_secs: u64) -> anyhow::Result<reqwest::C
 pub(crate) async fn call_llm_with_usage(
     system: &str,
     user: &str,
-    model: Model,
+    task: Task,
     json_mode: bool,
 ) -> anyhow::Result<LlmResponse> {
-    let api_key = api_key().ok_or_else(|| {
-        anyhow::anyhow!("No API key configured. Run 'cosmos --setup' to get started.")
-    })?;
-
-    let client = create_http_client(REQUEST_TIMEOUT_SECS)?;
-
-    let response_format = if json_mode {
-        Some(ResponseFormat {
-            format_type: "json_object".to_string(),
-            json_schema: None,
-        })
-    } else {
-        None
-    };
-
-    let request = ChatRequest {
-        model: model.id().to_string(),
-        messages: vec![
-            Message {
-                role: "system".to_string(),
-                content: system.to_string(),
-            },
-            Message {
-                role: "user".to_string(),
-                content: user.to_string(),
-            },
-        ],
-        max_tokens: model.max_tokens(),
-        stream: false,
-        response_format,
-        provider: Some(ProviderConfig {
-            allow_fallbacks: true,
-        }),
-    };
-
-    let text = send_with_retry(&client, &api_key, &request).await?;
+    let provider = configured_provider()?;
+    let mut request = ChatRequest::new(task, vec![Message::system(system), Message::user(user)]);
+    if json_mode {
+        request.response = ResponseMode::Json;
+    }
 
-    let parsed: ChatResponse = serde_json::from_str(&text)
-        .map_err(|e| anyhow::anyhow!("Failed to parse OpenRouter response: {}\n{}", e, text))?;
+    let response = provider.chat(&request).await?;
+    Ok(LlmResponse {
+        content: response.content.unwrap_or_default(),
+        usage: response.usage,
+    })
+}
 
-    let content = parsed
-        .choices
-        .first()
-        .map(|c| c.message.content.clone())
-        .unwrap_or_default();
+/// Call LLM API for a plain-text reply to a conversation, streaming it:
+/// `on_text` receives each fragment as it arrives. Returns the full reply
+/// with usage stats.
+pub(crate) async fn call_llm_streaming(
+    messages: Vec<Message>,
+    task: Task,
+    on_text: &mut (dyn FnMut(&str) + Send),
+) -> anyhow::Result<LlmResponse> {
+    let provider = configured_provider()?;
+    let request = ChatRequest::new(task, messages);
 
+    let response = provider.chat_stream(&request, on_text).await?;
     Ok(LlmResponse {
-        content,
-        usage: parsed.usage,
+        content: response.content.unwrap_or_default(),
+        usage: response.usage,
     })
 }
 
@@ -419,7 +383,7 @@ pub struct StructuredResponse<T> {
 /// # Arguments
 /// * `system` - System prompt (will be cached)
 /// * `user` - User message (not cached - changes each call)
-/// * `model` - Model to use (caching only works with Anthropic models)
+/// * `task` - Task being run; picks the model tier (caching only works with Anthropic models)
 /// * `schema_name` - Name for the schema (e.g., "fix_content")
 /// * `schema` - JSON Schema definition
 ///
@@ -428,50 +392,23 @@ pub struct StructuredResponse<T> {
 pub(crate) async fn call_llm_structured_cached<T>(
     system: &str,
     user: &str,
-    model: Model,
+    task: Task,
     schema_name: &str,
     schema: serde_json::Value,
 ) -> anyhow::Result<StructuredResponse<T>>
 where
     T: serde::de::DeserializeOwned,
 {
-    let api_key = api_key().ok_or_else(|| {
-        anyhow::anyhow!("No API key configured. Run 'cosmos --setup' to get started.")
-    })?;
-
-    let client = create_http_client(REQUEST_TIMEOUT_SECS)?;
-
-    let response_format = ResponseFormat {
-        format_type: "json_schema".to_string(),
-        json_schema: Some(JsonSchemaWrapper {
-            name: schema_name.to_string(),
-            strict: true,
-            schema,
-        }),
+    let provider = configured_provider()?;
+    let mut request = ChatRequest::new(task, vec![Message::system(system), Message::user(user)]);
+    request.response = ResponseMode::Schema {
+        name: schema_name.to_string(),
+        schema,
     };
+    request.cache_system_prompt = true;
 
-    // Use cached messages with cache_control on system prompt
-    let request = CachedChatRequest {
-        model: model.id().to_string(),
-        messages: build_cached_messages(system, user),
-        max_tokens: model.max_tokens(),
-        stream: false,
-        response_format: Some(response_format),
-        provider: Some(ProviderConfig {
-            allow_fallbacks: true,
-        }),
-    };
-
-    let text = send_with_retry(&client, &api_key, &request).await?;
-
-    let parsed: ChatResponse = serde_json::from_str(&text)
-        .map_err(|e| anyhow::anyhow!("Failed to parse OpenRouter response: {}\n{}", e, text))?;
-
-    let content = parsed
-        .choices
-        .first()
-        .map(|c| c.message.content.clone())
-        .unwrap_or_default();
+    let response = provider.chat(&request).await?;
+    let content = response.content.unwrap_or_default();
 
     let data: T = serde_json::from_str(&content).map_err(|e| {
         anyhow::anyhow!(
@@ -483,15 +420,18 @@ where
 
     Ok(StructuredResponse {
         data,
-        usage: parsed.usage,
+        usage: response.usage,
     })
 }
 
-fn map_timeout_error(err: reqwest::Error) -> anyhow::Error {
+fn map_timeout_error(provider: &str, err: reqwest::Error) -> anyhow::Error {
     if err.is_timeout() {
-        anyhow::anyhow!("OpenRouter request timed out. Please try again.")
+        anyhow::anyhow!("{} request timed out. Please try again.", provider)
     } else if err.is_connect() {
-        anyhow::anyhow!("Could not connect to OpenRouter. Check your network and try again.")
+        anyhow::anyhow!(
+            "Could not connect to {}. Check your network and try again.",
+            provider
+        )
     } else {
         err.into()
     }
@@ -512,44 +452,31 @@ pub(crate) fn truncate_str(s: &str, max_chars: usize) -> &str {
     }
 }
 
-/// OpenRouter credits API URL
-const OPENROUTER_CREDITS_URL: &str = "https://openrouter.ai/api/v1/credits";
-
-/// Response from OpenRouter credits endpoint
-#[derive(Deserialize)]
-struct CreditsResponse {
-    data: CreditsData,
-}
-
-#[derive(Deserialize)]
-struct CreditsData {
-    total_credits: f64,
-    total_usage: f64,
-}
-
-/// Fetch the current account balance from OpenRouter.
-/// Returns the remaining credits (total_credits - total_usage).
+/// Fetch the current account balance from the provider.
+/// Errors if the backend doesn't report one (self-hosted servers).
 pub async fn fetch_account_balance() -> anyhow::Result<f64> {
-    let api_key = api_key().ok_or_else(|| anyhow::anyhow!("No API key configured"))?;
-
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(10))
-        .build()?;
-
-    let response = client
-        .get(OPENROUTER_CREDITS_URL)
-        .header("Authorization", format!("Bearer {}", api_key))
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!(
-            "Failed to fetch balance: {}",
-            response.status()
-        ));
+    let provider = configured_provider()?;
+    provider
+        .balance()
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("{} does not report a balance", provider.name()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_decoder_reassembles_split_events() {
+        let mut decoder = SseDecoder::default();
+        assert!(decoder.push(b": OPENROUTER PROCESSING\n\n").is_empty());
+        assert!(decoder.push(b"data: {\"a\":").is_empty());
+        assert_eq!(decoder.push(b"1}\r\n\r\ndata: x\n"), vec!["{\"a\":1}"]);
+        // Multi-byte characters split across network chunks survive
+        let bytes = "data: é\n\n".as_bytes();
+        assert!(decoder.push(&bytes[..7]).is_empty());
+        assert_eq!(decoder.push(&bytes[7..]), vec!["x\né"]);
+        decoder.push(b"data: [DONE]");
+        assert_eq!(decoder.finish().as_deref(), Some("[DONE]"));
     }
-
-    let credits: CreditsResponse = response.json().await?;
-    let remaining = credits.data.total_credits - credits.data.total_usage;
-    Ok(remaining)
 }
@@ -1,8 +1,10 @@
 use super::agentic::call_llm_agentic;
 use super::client::{call_llm_structured_cached, StructuredResponse};
-use super::models::{Model, Usage};
-use super::parse::{parse_json_with_retry, truncate_content, truncate_content_around_line};
-use super::prompt_utils::format_repo_memory_section;
+use super::models::{Task, Usage};
+use super::parse::{
+    merge_usage, parse_json_with_retry, truncate_content, truncate_content_around_line,
+};
+use super::prompt_utils::{format_index_context_section, format_repo_memory_section};
 use super::prompts::{fix_content_system, multi_file_fix_system, FIX_PREVIEW_AGENTIC_SYSTEM};
 use crate::suggest::Suggestion;
 use serde::{Deserialize, Serialize};
@@ -275,7 +277,7 @@ async fn call_llm_structured_with_fallback<T>(
     system: &str,
     user_full: &str,
     user_excerpt: &str,
-    model: Model,
+    task: Task,
     schema_name: &str,
     schema: serde_json::Value,
 ) -> anyhow::Result<StructuredResponse<T>>
@@ -283,7 +285,7 @@ where
     T: serde::de::DeserializeOwned,
 {
     // Use cached version - caches the system prompt for Anthropic models
-    match call_llm_structured_cached::<T>(system, user_full, model, schema_name, schema.clone())
+    match call_llm_structured_cached::<T>(system, user_full, task, schema_name, schema.clone())
         .await
     {
         Ok(response) => Ok(response),
@@ -291,7 +293,7 @@ where
             let message = err.to_string();
             // Handle context limit by trying with smaller excerpt
             if is_context_limit_error(&message) && user_full != user_excerpt {
-                call_llm_structured_cached::<T>(system, user_excerpt, model, schema_name, schema)
+                call_llm_structured_cached::<T>(system, user_excerpt, task, schema_name, schema)
                     .await
             } else {
                 Err(err)
@@ -309,6 +311,7 @@ pub async fn generate_fix_content(
     suggestion: &Suggestion,
     plan: &FixPreview,
     repo_memory: Option<String>,
+    index_context: Option<String>,
     is_new_file: bool,
 ) -> anyhow::Result<AppliedFix> {
     let plan_text = format!(
@@ -328,8 +331,11 @@ pub async fn generate_fix_content(
             .unwrap_or_default()
     );
 
-    let memory_section =
-        format_repo_memory_section(repo_memory.as_deref(), "Repo conventions / decisions");
+    let memory_section = format!(
+        "{}{}",
+        format_repo_memory_section(repo_memory.as_deref(), "Repo conventions / decisions"),
+        format_index_context_section(index_context.as_deref())
+    );
 
     let new_file_note = if is_new_file {
         "\nNOTE: This file is new (currently empty). Use old_string=\"\" to insert full content."
@@ -364,7 +370,7 @@ pub async fn generate_fix_content(
         &fix_content_system(),
         &user_full,
         &user_excerpt,
-        Model::Smart,
+        Task::Fix,
         "fix_response",
         fix_response_schema(),
     )
@@ -599,6 +605,7 @@ pub async fn generate_multi_file_fix(
     suggestion: &Suggestion,
     plan: &FixPreview,
     repo_memory: Option<String>,
+    index_context: Option<String>,
 ) -> anyhow::Result<MultiFileAppliedFix> {
     if files.is_empty() {
         return Err(anyhow::anyhow!("No files provided for multi-file fix"));
@@ -622,8 +629,11 @@ pub async fn generate_multi_file_fix(
             .unwrap_or_default()
     );
 
-    let memory_section =
-        format_repo_memory_section(repo_memory.as_deref(), "Repo conventions / decisions");
+    let memory_section = format!(
+        "{}{}",
+        format_repo_memory_section(repo_memory.as_deref(), "Repo conventions / decisions"),
+        format_index_context_section(index_context.as_deref())
+    );
 
     // Build full and excerpted file sections
     let files_section_full: String = files
@@ -688,7 +698,7 @@ pub async fn generate_multi_file_fix(
         &multi_file_fix_system(),
         &user_full,
         &user_excerpt,
-        Model::Smart,
+        Task::Fix,
         "multi_file_fix_response",
         multi_file_fix_response_schema(),
     )
@@ -835,22 +845,26 @@ pub async fn generate_fix_preview_agentic(
     suggestion: &Suggestion,
     modifier: Option<&str>,
     repo_memory: Option<String>,
-) -> anyhow::Result<FixPreview> {
+    index_context: Option<String>,
+) -> anyhow::Result<(FixPreview, Option<Usage>)> {
     let modifier_text = modifier
         .map(|m| format!("\n\nUser modification request: {}", m))
         .unwrap_or_default();
 
-    let memory_section =
-        format_repo_memory_section(repo_memory.as_deref(), "Repo conventions / decisions");
+    let memory_section = format!(
+        "{}{}",
+        format_repo_memory_section(repo_memory.as_deref(), "Repo conventions / decisions"),
+        format_index_context_section(index_context.as_deref())
+    );
 
     // Pre-read the relevant file content (we know exactly where to look)
     let file_path = repo_root.join(&suggestion.file);
-    let file_content = std::fs::read_to_string(&file_path).unwrap_or_default();
+    let file_content = crate::index::notebook::read_editable(&file_path).unwrap_or_default();
 
     // Extract ~80 lines around the suggestion line for better context
     let target_line = suggestion.line.unwrap_or(1);
     let lines: Vec<&str> = file_content.lines().collect();
-    let start = target_line.saturating_sub(30).max(0);
+    let start = target_line.saturating_sub(30);
     let end = (target_line + 50).min(lines.len());
 
     let code_section: String = lines
@@ -877,8 +891,8 @@ CODE (lines {}-{}):
 VERIFY:
 1. Does this issue exist in the code above?
 2. If you need more context:
-   • grep -n 'pattern' {} → find related code
-   • sed -n 'X,Yp' {} → read specific lines
+   • grep(pattern, glob: "{}") → find related code
+   • read_file({}, start, end) → read specific lines
 3. Return JSON immediately (minimize tool calls)"#,
         suggestion.file.display(),
         target_line,
@@ -893,12 +907,12 @@ VERIFY:
         suggestion.file.display(),
     );
 
-    // Use Speed model with surgical tool access
+    // Speed tier by default, with surgical tool access
     // 3 iterations - code already provided, minimal exploration needed
     let response = call_llm_agentic(
         FIX_PREVIEW_AGENTIC_SYSTEM,
         &user,
-        Model::Speed,
+        Task::Preview,
         repo_root,
         false,
         3, // max iterations - verification has code upfront
@@ -906,10 +920,11 @@ VERIFY:
     .await?;
 
     // Parse the final response as JSON
-    let (parsed, _correction_usage): (serde_json::Value, _) =
+    let (parsed, correction_usage): (serde_json::Value, _) =
         parse_json_with_retry(&response.content, "fix preview").await?;
 
-    build_fix_preview(parsed, modifier.map(String::from))
+    let preview = build_fix_preview(parsed, modifier.map(String::from))?;
+    Ok((preview, merge_usage(response.usage, correction_usage)))
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -1,5 +1,5 @@
 use super::client::call_llm_with_usage;
-use super::models::{Model, Usage};
+use super::models::{Task, Usage};
 use super::parse::{merge_usage, parse_json_with_retry};
 use super::prompts::GROUPING_CLASSIFY_SYSTEM;
 use crate::cache::normalize_summary_path;
@@ -53,7 +53,12 @@ pub async fn classify_grouping_candidates(
 
     let file_contexts: Vec<FileContext> = candidates
         .iter()
-        .filter_map(|path| index.files.get(path).map(|f| build_file_context(path, f)))
+        .filter_map(|path| {
+            index
+                .files
+                .get(path)
+                .map(|f| build_file_context(index, path, f))
+        })
         .collect();
 
     if file_contexts.is_empty() {
@@ -66,7 +71,7 @@ pub async fn classify_grouping_candidates(
     );
 
     let response =
-        call_llm_with_usage(GROUPING_CLASSIFY_SYSTEM, &user, Model::Balanced, true).await?;
+        call_llm_with_usage(GROUPING_CLASSIFY_SYSTEM, &user, Task::Grouping, true).await?;
 
     let (parsed, correction_usage): (GroupingAiResponse, _) =
         parse_json_with_retry(&response.content, "grouping classification").await?;
@@ -93,16 +98,19 @@ pub async fn classify_grouping_candidates(
     Ok((suggestions, total_usage))
 }
 
-fn build_file_context(path: &std::path::Path, file: &crate::index::FileIndex) -> FileContext {
+fn build_file_context(
+    index: &CodebaseIndex,
+    path: &std::path::Path,
+    file: &crate::index::FileIndex,
+) -> FileContext {
     let exports = file.summary.exports.iter().take(6).cloned().collect();
-    let symbols = file
-        .symbols
-        .iter()
+    let symbols = index
+        .file_symbols(file)
         .take(8)
         .map(|s| format!("{:?}: {}", s.kind, s.name))
         .collect();
     let external_imports = file
-        .dependencies
+        .dependencies()
         .iter()
         .filter(|d| d.is_external)
         .take(8)
@@ -135,6 +143,11 @@ fn language_label(language: Language) -> &'static str {
         Language::TypeScript => "typescript",
         Language::Python => "python",
         Language::Go => "go",
+        Language::Dockerfile => "dockerfile",
+        Language::Yaml => "yaml",
+        Language::Terraform => "terraform",
+        Language::Sql => "sql",
+        Language::Shell => "shell",
         Language::Unknown => "unknown",
     }
 }
@@ -0,0 +1,453 @@
+//! Spending caps over the cost ledger
+//!
+//! Every provider call is checked against the configured budgets before it
+//! is sent and recorded in `.cosmos/ledger.jsonl` when it returns (see
+//! `provider::Metered`). A call is refused when the spend so far plus the
+//! task's typical call cost would pass a cap. The UI asks the same question
+//! before starting an action and, if the user confirms, grants one approval
+//! (`approve`). The next background task to start claims it and carries it
+//! (`claim_approval`, `run_approved`), so only that action's calls skip the
+//! caps; anything running alongside it is still checked.
+
+use super::models::{Task, Usage};
+use crate::cache::{Cache, LedgerEntry};
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// How many recent calls of a task to average for its cost estimate
+const ESTIMATE_WINDOW: usize = 20;
+
+/// Spending caps in USD; unset means unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Budgets {
+    /// Since Cosmos was started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_usd: Option<f64>,
+    /// Since local midnight
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_usd: Option<f64>,
+    /// Everything recorded for this repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo_usd: Option<f64>,
+}
+
+impl Budgets {
+    /// The stricter of the user's and the project's cap, field by field; a
+    /// committed project config can tighten the user's caps but not loosen them
+    pub fn layered(user: Option<&Budgets>, project: Option<&Budgets>) -> Result<Self, String> {
+        let user = user.copied().unwrap_or_default();
+        let project = project.copied().unwrap_or_default();
+        let errors: Vec<String> = user
+            .caps()
+            .chain(project.caps())
+            .filter(|(_, limit)| !limit.is_finite() || *limit < 0.0)
+            .map(|(kind, limit)| {
+                format!(
+                    "budgets.{}: must be a non-negative amount, got {}",
+                    kind.key(),
+                    limit
+                )
+            })
+            .collect();
+        if !errors.is_empty() {
+            return Err(errors.join("\n"));
+        }
+        let stricter = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        Ok(Self {
+            session_usd: stricter(user.session_usd, project.session_usd),
+            daily_usd: stricter(user.daily_usd, project.daily_usd),
+            repo_usd: stricter(user.repo_usd, project.repo_usd),
+        })
+    }
+
+    fn caps(&self) -> impl Iterator<Item = (BudgetKind, f64)> {
+        [
+            (BudgetKind::Session, self.session_usd),
+            (BudgetKind::Daily, self.daily_usd),
+            (BudgetKind::Repo, self.repo_usd),
+        ]
+        .into_iter()
+        .filter_map(|(kind, limit)| limit.map(|l| (kind, l)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetKind {
+    Session,
+    Daily,
+    Repo,
+}
+
+impl BudgetKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BudgetKind::Session => "Session",
+            BudgetKind::Daily => "Daily",
+            BudgetKind::Repo => "Repo",
+        }
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            BudgetKind::Session => "session_usd",
+            BudgetKind::Daily => "daily_usd",
+            BudgetKind::Repo => "repo_usd",
+        }
+    }
+}
+
+/// A cap the next call would pass
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overrun {
+    pub kind: BudgetKind,
+    pub spent: f64,
+    pub limit: f64,
+    /// Typical cost of the call about to be made
+    pub estimate: f64,
+}
+
+impl std::fmt::Display for Overrun {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} budget of ${:.2} reached (spent ${:.2}, next call ~${:.2}). Raise budgets.{} in your config to continue.",
+            self.kind.label(),
+            self.limit,
+            self.spent,
+            self.estimate,
+            self.kind.key()
+        )
+    }
+}
+
+/// Spend per task or per model, for the cost breakdown overlay
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpendLine {
+    pub name: String,
+    pub calls: usize,
+    pub tokens: u64,
+    pub cost: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CostBreakdown {
+    pub session: f64,
+    pub today: f64,
+    pub repo: f64,
+    pub budgets: Budgets,
+    /// Whole-repo spend by task, most expensive first
+    pub by_task: Vec<SpendLine>,
+    /// Whole-repo spend by model, most expensive first
+    pub by_model: Vec<SpendLine>,
+}
+
+struct Ledger {
+    repo_root: PathBuf,
+    entries: Vec<LedgerEntry>,
+    budgets: Budgets,
+    session_start: DateTime<Utc>,
+    /// Approval the user granted that no action has claimed yet
+    granted: Option<Approval>,
+    next_approval: u64,
+}
+
+impl Ledger {
+    fn spent(&self, kind: BudgetKind) -> f64 {
+        let today = Local::now().date_naive();
+        self.entries
+            .iter()
+            .filter(|e| match kind {
+                BudgetKind::Session => e.at >= self.session_start,
+                BudgetKind::Daily => e.at.with_timezone(&Local).date_naive() == today,
+                BudgetKind::Repo => true,
+            })
+            .map(|e| e.cost)
+            .sum()
+    }
+
+    /// Mean cost of the task's recent calls (0 before its first call)
+    fn estimate(&self, task: Task) -> f64 {
+        let recent: Vec<f64> = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|e| e.task == task.name())
+            .take(ESTIMATE_WINDOW)
+            .map(|e| e.cost)
+            .collect();
+        if recent.is_empty() {
+            0.0
+        } else {
+            recent.iter().sum::<f64>() / recent.len() as f64
+        }
+    }
+
+    fn overrun(&self, task: Task) -> Option<Overrun> {
+        let estimate = self.estimate(task);
+        self.budgets.caps().find_map(|(kind, limit)| {
+            let spent = self.spent(kind);
+            (spent + estimate > limit).then_some(Overrun {
+                kind,
+                spent,
+                limit,
+                estimate,
+            })
+        })
+    }
+
+    fn breakdown(&self) -> CostBreakdown {
+        CostBreakdown {
+            session: self.spent(BudgetKind::Session),
+            today: self.spent(BudgetKind::Daily),
+            repo: self.spent(BudgetKind::Repo),
+            budgets: self.budgets,
+            by_task: group(&self.entries, |e| &e.task),
+            by_model: group(&self.entries, |e| &e.model),
+        }
+    }
+}
+
+fn group(entries: &[LedgerEntry], key: impl Fn(&LedgerEntry) -> &str) -> Vec<SpendLine> {
+    let mut groups: BTreeMap<&str, SpendLine> = BTreeMap::new();
+    for entry in entries {
+        let name = key(entry);
+        let line = groups.entry(name).or_insert_with(|| SpendLine {
+            name: name.to_string(),
+            ..Default::default()
+        });
+        line.calls += 1;
+        line.tokens += u64::from(entry.prompt_tokens) + u64::from(entry.completion_tokens);
+        line.cost += entry.cost;
+    }
+    let mut lines: Vec<SpendLine> = groups.into_values().collect();
+    lines.sort_by(|a, b| b.cost.total_cmp(&a.cost).then_with(|| a.name.cmp(&b.name)));
+    lines
+}
+
+/// Ledger for this run; calls go unmetered until `install`
+static LEDGER: Mutex<Option<Ledger>> = Mutex::new(None);
+
+fn with_ledger<T>(f: impl FnOnce(&mut Ledger) -> T) -> Option<T> {
+    LEDGER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_mut()
+        .map(f)
+}
+
+/// Load the repo's ledger and start metering calls against `budgets`
+pub fn install(repo_root: &Path, budgets: Budgets) {
+    let entries = Cache::new(repo_root).load_ledger();
+    *LEDGER.lock().unwrap_or_else(|e| e.into_inner()) = Some(Ledger {
+        repo_root: repo_root.to_path_buf(),
+        entries,
+        budgets,
+        session_start: Utc::now(),
+        granted: None,
+        next_approval: 0,
+    });
+}
+
+/// The user's consent for one action to go over budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Approval(u64);
+
+tokio::task_local! {
+    /// Approval carried by the action running on this task
+    static APPROVAL: Approval;
+}
+
+/// Gate for a call about to be sent; calls made inside `run_approved`
+/// skip the caps
+pub fn check(task: Task) -> Result<(), Overrun> {
+    if APPROVAL.try_with(|_| ()).is_ok() {
+        return Ok(());
+    }
+    match with_ledger(|ledger| ledger.overrun(task)) {
+        Some(Some(overrun)) => Err(overrun),
+        _ => Ok(()),
+    }
+}
+
+/// Grant one approval for the action about to start, replacing any
+/// unclaimed one
+pub fn approve() {
+    with_ledger(|ledger| {
+        ledger.next_approval += 1;
+        ledger.granted = Some(Approval(ledger.next_approval));
+    });
+}
+
+/// Whether an approval is waiting for its action to start
+pub fn approval_pending() -> bool {
+    with_ledger(|ledger| ledger.granted.is_some()).unwrap_or(false)
+}
+
+/// Take the pending approval, if any, for the action starting now
+pub fn claim_approval() -> Option<Approval> {
+    with_ledger(|ledger| ledger.granted.take()).flatten()
+}
+
+/// Drop an approval its action never claimed (e.g. the replayed key did
+/// nothing)
+pub fn withdraw_approval() {
+    with_ledger(|ledger| ledger.granted = None);
+}
+
+/// Run `fut` with `approval`'s calls exempt from the caps
+pub async fn run_approved<F: Future>(approval: Option<Approval>, fut: F) -> F::Output {
+    match approval {
+        Some(approval) => APPROVAL.scope(approval, fut).await,
+        None => fut.await,
+    }
+}
+
+/// Record a finished call
+pub fn record(task: Task, model: &str, usage: Option<&Usage>) {
+    with_ledger(|ledger| {
+        let entry = LedgerEntry {
+            at: Utc::now(),
+            task: task.name().to_string(),
+            model: model.to_string(),
+            prompt_tokens: usage.map_or(0, |u| u.prompt_tokens),
+            completion_tokens: usage.map_or(0, |u| u.completion_tokens),
+            cost: usage.map_or(0.0, Usage::cost),
+        };
+        let _ = Cache::new(&ledger.repo_root).append_ledger(&entry);
+        ledger.entries.push(entry);
+    });
+}
+
+/// Current spend against the budgets, grouped by task and model
+pub fn breakdown() -> CostBreakdown {
+    with_ledger(|ledger| ledger.breakdown()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn entry(task: &str, model: &str, cost: f64, age: Duration) -> LedgerEntry {
+        LedgerEntry {
+            at: Utc::now() - age,
+            task: task.to_string(),
+            model: model.to_string(),
+            prompt_tokens: 100,
+            completion_tokens: 50,
+            cost,
+        }
+    }
+
+    #[test]
+    fn test_budgets_gate_on_spend_plus_estimate() {
+        let mut ledger = Ledger {
+            repo_root: PathBuf::new(),
+            entries: vec![
+                entry("fix", "opus", 0.40, Duration::days(3)),
+                entry("fix", "opus", 0.20, Duration::minutes(5)),
+                entry("ask", "sonnet", 0.05, Duration::minutes(1)),
+            ],
+            budgets: Budgets::default(),
+            session_start: Utc::now() - Duration::minutes(2),
+            granted: None,
+            next_approval: 0,
+        };
+        assert!(ledger.overrun(Task::Fix).is_none());
+        assert_eq!(ledger.spent(BudgetKind::Session), 0.05);
+        assert!((ledger.spent(BudgetKind::Repo) - 0.65).abs() < 1e-9);
+
+        // A fix averages $0.30: fine under $0.50 per session, not under $0.30
+        ledger.budgets.session_usd = Some(0.50);
+        assert!(ledger.overrun(Task::Fix).is_none());
+        ledger.budgets.session_usd = Some(0.30);
+        let overrun = ledger.overrun(Task::Fix).unwrap();
+        assert_eq!(overrun.kind, BudgetKind::Session);
+        assert!((overrun.estimate - 0.30).abs() < 1e-9);
+        // A task never run before is estimated at zero
+        assert!(ledger.overrun(Task::Review).is_none());
+
+        ledger.budgets = Budgets {
+            repo_usd: Some(0.60),
+            ..Default::default()
+        };
+        assert_eq!(ledger.overrun(Task::Ask).unwrap().kind, BudgetKind::Repo);
+
+        let breakdown = ledger.breakdown();
+        assert_eq!(breakdown.by_task[0].name, "fix");
+        assert_eq!(breakdown.by_task[0].calls, 2);
+        assert_eq!(breakdown.by_model[1].name, "sonnet");
+        assert_eq!(breakdown.by_model[1].tokens, 150);
+    }
+
+    #[test]
+    fn test_budgets_layer_and_validate() {
+        let user = Budgets {
+            session_usd: Some(2.0),
+            daily_usd: Some(10.0),
+            repo_usd: None,
+        };
+        let project = Budgets {
+            daily_usd: Some(5.0),
+            ..Default::default()
+        };
+        let budgets = Budgets::layered(Some(&user), Some(&project)).unwrap();
+        assert_eq!(budgets.session_usd, Some(2.0));
+        assert_eq!(budgets.daily_usd, Some(5.0));
+
+        // A project can't raise the user's cap
+        let generous = Budgets {
+            session_usd: Some(100.0),
+            daily_usd: Some(50.0),
+            repo_usd: Some(1.0),
+        };
+        let budgets = Budgets::layered(Some(&user), Some(&generous)).unwrap();
+        assert_eq!(budgets.session_usd, Some(2.0));
+        assert_eq!(budgets.daily_usd, Some(10.0));
+        assert_eq!(budgets.repo_usd, Some(1.0));
+
+        let bad = Budgets {
+            repo_usd: Some(-1.0),
+            ..Default::default()
+        };
+        let err = Budgets::layered(None, Some(&bad)).unwrap_err();
+        assert!(err.contains("budgets.repo_usd"));
+    }
+
+    #[tokio::test]
+    async fn test_approval_covers_only_the_claiming_task() {
+        let root = std::env::temp_dir().join(format!(
+            "cosmos_ledger_{}",
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        install(
+            &root,
+            Budgets {
+                session_usd: Some(0.0),
+                ..Default::default()
+            },
+        );
+        record(Task::Fix, "opus", None);
+        with_ledger(|ledger| ledger.entries.last_mut().unwrap().cost = 0.01);
+        assert!(check(Task::Fix).is_err());
+
+        approve();
+        assert!(approval_pending());
+        let approval = claim_approval();
+        assert!(approval.is_some() && !approval_pending());
+        let approved = tokio::spawn(run_approved(approval, async { check(Task::Fix) }));
+        let bystander = tokio::spawn(run_approved(claim_approval(), async { check(Task::Fix) }));
+        assert!(approved.await.unwrap().is_ok());
+        assert!(bystander.await.unwrap().is_err());
+        assert!(check(Task::Fix).is_err());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -1,13 +1,18 @@
 pub mod agentic;
 pub mod analysis;
+pub mod audit;
+pub mod cassette;
 pub mod client;
 pub mod fix;
 pub mod grouping;
+pub mod ledger;
 pub mod models;
 pub mod parse;
 pub mod prompt_utils;
 pub mod prompts;
+pub mod provider;
 pub mod review;
+pub mod sandbox;
 pub mod summaries;
 pub mod tools;
 
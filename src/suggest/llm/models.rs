@@ -1,7 +1,18 @@
-use serde::Deserialize;
+//! Model tiers, the tasks that use them, and the resolved per-tier settings
+//!
+//! Call sites ask for a `Task`; the active `ModelTable` maps it to a tier
+//! and the tier to a model ID, token limit and context window. The table
+//! starts from built-in defaults for the configured provider, then applies
+//! the `models` section of the user config and of `.cosmos/config.json`
+//! (project wins). `main` validates and installs it at startup.
+
+use crate::config::ProviderSettings;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::RwLock;
 
 /// Models available for suggestions
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Model {
     /// Speed tier - fast, cheap model for summaries and classification (gpt-oss-120b)
     Speed,
@@ -11,25 +22,299 @@ pub enum Model {
     Smart,
 }
 
-/// Maximum tokens for all model tiers
+/// Default maximum completion tokens for all model tiers
 const MODEL_MAX_TOKENS: u32 = 16384;
 
+/// Default context window for the OpenRouter tiers
+const MODEL_CONTEXT_WINDOW: u32 = 131_072;
+
+/// Conservative defaults for self-hosted models, whose limits we can't know
+const LOCAL_MAX_TOKENS: u32 = 8192;
+const LOCAL_CONTEXT_WINDOW: u32 = 32_768;
+
 impl Model {
-    pub fn id(&self) -> &'static str {
+    pub const ALL: [Model; 3] = [Model::Speed, Model::Balanced, Model::Smart];
+
+    /// Name used in config files
+    pub fn name(&self) -> &'static str {
+        match self {
+            Model::Speed => "speed",
+            Model::Balanced => "balanced",
+            Model::Smart => "smart",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| m.name() == name)
+    }
+
+    /// The tier the active configuration assigns to a task
+    pub fn for_task(task: Task) -> Self {
+        active().tier_for(task)
+    }
+
+    /// ID and token limits from the active configuration
+    pub fn spec(&self) -> ModelSpec {
+        active().spec(*self).clone()
+    }
+}
+
+/// LLM-backed jobs whose tier can be reassigned in config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Task {
+    /// File summaries during indexing
+    Summaries,
+    /// Classifying ambiguous files into layers for grouping
+    Grouping,
+    /// Finding suggestions in the codebase
+    Analysis,
+    /// Answering questions in Ask mode
+    Ask,
+    /// Verifying a suggestion before it is applied
+    Preview,
+    /// Generating fixes (including fixes for review findings)
+    Fix,
+    /// Adversarial review of applied changes
+    Review,
+    /// Fixing malformed JSON in another task's reply
+    Repair,
+}
+
+impl Task {
+    pub const ALL: [Task; 8] = [
+        Task::Summaries,
+        Task::Grouping,
+        Task::Analysis,
+        Task::Ask,
+        Task::Preview,
+        Task::Fix,
+        Task::Review,
+        Task::Repair,
+    ];
+
+    /// Name used in config files
+    pub fn name(&self) -> &'static str {
         match self {
-            Model::Speed => "openai/gpt-oss-120b:nitro",
-            Model::Balanced => "anthropic/claude-sonnet-4.5:nitro",
-            Model::Smart => "anthropic/claude-opus-4.5:nitro",
+            Task::Summaries => "summaries",
+            Task::Grouping => "grouping",
+            Task::Analysis => "analysis",
+            Task::Ask => "ask",
+            Task::Preview => "preview",
+            Task::Fix => "fix",
+            Task::Review => "review",
+            Task::Repair => "repair",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.name() == name)
+    }
+
+    fn default_tier(&self) -> Model {
+        match self {
+            Task::Summaries | Task::Analysis | Task::Preview | Task::Review | Task::Repair => {
+                Model::Speed
+            }
+            Task::Grouping | Task::Ask => Model::Balanced,
+            Task::Fix => Model::Smart,
+        }
+    }
+}
+
+/// Resolved settings for one tier
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelSpec {
+    pub id: String,
+    /// Upper bound on completion tokens per request
+    pub max_tokens: u32,
+    /// Prompt plus completion tokens the model accepts
+    pub context_window: u32,
+}
+
+/// The `models` section of the user or project config
+///
+/// Names are kept as strings so a typo is reported by validation instead
+/// of making the whole config file unreadable.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelSettings {
+    /// Per-tier overrides, keyed by tier name (`speed`, `balanced`, `smart`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tiers: BTreeMap<String, TierOverride>,
+    /// Task name to tier name, e.g. `"review": "smart"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tasks: BTreeMap<String, String>,
+}
+
+/// Fields left unset keep the value from the layer below
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TierOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u32>,
+}
+
+/// Tier settings and task assignments in effect for this run
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelTable {
+    tiers: BTreeMap<Model, ModelSpec>,
+    tasks: BTreeMap<Task, Model>,
+}
+
+impl Default for ModelTable {
+    fn default() -> Self {
+        Self::defaults_for(&ProviderSettings::OpenRouter)
+    }
+}
+
+impl ModelTable {
+    /// Built-in settings for a provider. OpenAI-compatible servers run
+    /// their one configured model for every tier.
+    pub fn defaults_for(provider: &ProviderSettings) -> Self {
+        let tiers = Model::ALL
+            .into_iter()
+            .map(|tier| {
+                let spec = match provider {
+                    ProviderSettings::OpenRouter => ModelSpec {
+                        id: match tier {
+                            Model::Speed => "openai/gpt-oss-120b:nitro",
+                            Model::Balanced => "anthropic/claude-sonnet-4.5:nitro",
+                            Model::Smart => "anthropic/claude-opus-4.5:nitro",
+                        }
+                        .to_string(),
+                        max_tokens: MODEL_MAX_TOKENS,
+                        context_window: MODEL_CONTEXT_WINDOW,
+                    },
+                    ProviderSettings::OpenAiCompatible { model, .. } => ModelSpec {
+                        id: model.clone(),
+                        max_tokens: LOCAL_MAX_TOKENS,
+                        context_window: LOCAL_CONTEXT_WINDOW,
+                    },
+                };
+                (tier, spec)
+            })
+            .collect();
+        let tasks = Task::ALL
+            .into_iter()
+            .map(|task| (task, task.default_tier()))
+            .collect();
+        Self { tiers, tasks }
+    }
+
+    /// Provider defaults, then user settings, then project settings.
+    /// Every problem found is reported, one per line.
+    pub fn resolve(
+        provider: &ProviderSettings,
+        user: Option<&ModelSettings>,
+        project: Option<&ModelSettings>,
+    ) -> Result<Self, String> {
+        let mut table = Self::defaults_for(provider);
+        let mut errors = Vec::new();
+        for (source, settings) in [("user config", user), ("project config", project)] {
+            if let Some(settings) = settings {
+                table.apply(source, settings, &mut errors);
+            }
+        }
+        for (tier, spec) in &table.tiers {
+            let tier = tier.name();
+            if spec.id.trim().is_empty() || spec.id.chars().any(char::is_whitespace) {
+                errors.push(format!(
+                    "models.tiers.{}: invalid model id '{}'",
+                    tier, spec.id
+                ));
+            }
+            if spec.max_tokens == 0 {
+                errors.push(format!(
+                    "models.tiers.{}: max_tokens must be positive",
+                    tier
+                ));
+            }
+            if spec.context_window <= spec.max_tokens {
+                errors.push(format!(
+                    "models.tiers.{}: context_window ({}) must be larger than max_tokens ({})",
+                    tier, spec.context_window, spec.max_tokens
+                ));
+            }
+        }
+        if errors.is_empty() {
+            Ok(table)
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
+    fn apply(&mut self, source: &str, settings: &ModelSettings, errors: &mut Vec<String>) {
+        let tier_names = Model::ALL.map(|m| m.name()).join(", ");
+        for (name, over) in &settings.tiers {
+            let Some(tier) = Model::from_name(name) else {
+                errors.push(format!(
+                    "{}: unknown tier '{}' in models.tiers (expected {})",
+                    source, name, tier_names
+                ));
+                continue;
+            };
+            let spec = self.tiers.get_mut(&tier).expect("every tier has a spec");
+            if let Some(id) = &over.id {
+                spec.id = id.clone();
+            }
+            if let Some(max_tokens) = over.max_tokens {
+                spec.max_tokens = max_tokens;
+            }
+            if let Some(context_window) = over.context_window {
+                spec.context_window = context_window;
+            }
+        }
+        for (name, tier_name) in &settings.tasks {
+            let Some(task) = Task::from_name(name) else {
+                errors.push(format!(
+                    "{}: unknown task '{}' in models.tasks (expected {})",
+                    source,
+                    name,
+                    Task::ALL.map(|t| t.name()).join(", ")
+                ));
+                continue;
+            };
+            match Model::from_name(tier_name) {
+                Some(tier) => {
+                    self.tasks.insert(task, tier);
+                }
+                None => errors.push(format!(
+                    "{}: task '{}' assigned to unknown tier '{}' (expected {})",
+                    source, name, tier_name, tier_names
+                )),
+            }
         }
     }
 
-    pub fn max_tokens(&self) -> u32 {
-        MODEL_MAX_TOKENS
+    pub fn spec(&self, tier: Model) -> &ModelSpec {
+        &self.tiers[&tier]
+    }
+
+    pub fn tier_for(&self, task: Task) -> Model {
+        self.tasks[&task]
     }
 }
 
+/// Table installed at startup; built-in OpenRouter defaults until then
+static ACTIVE: RwLock<Option<ModelTable>> = RwLock::new(None);
+
+/// Make `table` the one every LLM call resolves against
+pub fn install(table: ModelTable) {
+    *ACTIVE.write().unwrap_or_else(|e| e.into_inner()) = Some(table);
+}
+
+fn active() -> ModelTable {
+    ACTIVE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
 /// API usage information from OpenRouter
-#[derive(Deserialize, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct Usage {
     #[serde(default)]
     pub prompt_tokens: u32,
@@ -58,15 +343,56 @@ mod tests {
 
     #[test]
     fn test_model_ids() {
-        assert!(Model::Speed.id().contains("gpt"));
-        assert!(Model::Balanced.id().contains("claude"));
-        assert!(Model::Smart.id().contains("claude"));
+        let table = ModelTable::default();
+        assert!(table.spec(Model::Speed).id.contains("gpt"));
+        assert!(table.spec(Model::Balanced).id.contains("claude"));
+        assert!(table.spec(Model::Smart).id.contains("claude"));
     }
 
     #[test]
     fn test_model_max_tokens() {
-        assert_eq!(Model::Speed.max_tokens(), MODEL_MAX_TOKENS);
-        assert_eq!(Model::Smart.max_tokens(), MODEL_MAX_TOKENS);
+        let table = ModelTable::default();
+        assert_eq!(table.spec(Model::Speed).max_tokens, MODEL_MAX_TOKENS);
+        assert_eq!(table.spec(Model::Smart).max_tokens, MODEL_MAX_TOKENS);
+    }
+
+    #[test]
+    fn test_model_table_layers_and_validation() {
+        let user: ModelSettings = serde_json::from_str(
+            r#"{"tiers": {"smart": {"id": "anthropic/claude-opus-4.6", "max_tokens": 32000}}, "tasks": {"review": "smart"}}"#,
+        )
+        .unwrap();
+        let project: ModelSettings =
+            serde_json::from_str(r#"{"tasks": {"review": "balanced"}}"#).unwrap();
+        let table = ModelTable::resolve(&ProviderSettings::OpenRouter, Some(&user), Some(&project))
+            .unwrap();
+        let smart = table.spec(Model::Smart);
+        assert_eq!(smart.id, "anthropic/claude-opus-4.6");
+        assert_eq!(smart.max_tokens, 32000);
+        assert_eq!(smart.context_window, MODEL_CONTEXT_WINDOW);
+        // Project config wins over user config
+        assert_eq!(table.tier_for(Task::Review), Model::Balanced);
+        assert_eq!(table.tier_for(Task::Fix), Model::Smart);
+
+        let local = ProviderSettings::OpenAiCompatible {
+            base_url: "http://localhost:8080/v1".to_string(),
+            model: "qwen2.5-coder".to_string(),
+            api_key_env: None,
+        };
+        let table = ModelTable::resolve(&local, None, None).unwrap();
+        assert_eq!(table.spec(Model::Speed).id, "qwen2.5-coder");
+        assert_eq!(table.spec(Model::Smart).id, "qwen2.5-coder");
+
+        let bad: ModelSettings = serde_json::from_str(
+            r#"{"tiers": {"fast": {}, "speed": {"max_tokens": 200000}}, "tasks": {"reveiw": "smart", "fix": "genius"}}"#,
+        )
+        .unwrap();
+        let err = ModelTable::resolve(&ProviderSettings::OpenRouter, None, Some(&bad)).unwrap_err();
+        assert_eq!(err.lines().count(), 4);
+        assert!(err.contains("unknown tier 'fast'"));
+        assert!(err.contains("unknown task 'reveiw'"));
+        assert!(err.contains("unknown tier 'genius'"));
+        assert!(err.contains("models.tiers.speed: context_window"));
     }
 
     #[test]
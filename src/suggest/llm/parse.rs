@@ -1,5 +1,5 @@
 use super::client::{call_llm_with_usage, truncate_str, LlmResponse};
-use super::models::{Model, Usage};
+use super::models::{Task, Usage};
 use crate::suggest::{Priority, Suggestion, SuggestionKind, SuggestionSource};
 use serde::Deserialize;
 use std::collections::HashMap;
@@ -114,6 +114,7 @@ pub(crate) fn parse_codebase_suggestions(response: &str) -> anyhow::Result<Vec<S
                 "documentation" => SuggestionKind::Documentation,
                 "testing" => SuggestionKind::Testing,
                 "refactoring" => SuggestionKind::Refactoring,
+                "security" => SuggestionKind::Security,
                 _ => SuggestionKind::Improvement,
             };
 
@@ -399,7 +400,7 @@ RULES:
         truncate_str(original_response, 4000) // Limit size for correction prompt
     );
 
-    call_llm_with_usage(system, &user, Model::Speed, true).await
+    call_llm_with_usage(system, &user, Task::Repair, true).await
 }
 
 /// Merge two optional Usage values, summing their token counts and costs
@@ -5,3 +5,11 @@ pub(crate) fn format_repo_memory_section(repo_memory: Option<&str>, heading: &st
         .map(|m| format!("\n\n{}:\n{}", heading, m))
         .unwrap_or_default()
 }
+
+/// Index-derived context (type hierarchy, covering tests); already carries its own headings
+pub(crate) fn format_index_context_section(index_context: Option<&str>) -> String {
+    index_context
+        .filter(|h| !h.trim().is_empty())
+        .map(|h| format!("\n\n{}", h))
+        .unwrap_or_default()
+}
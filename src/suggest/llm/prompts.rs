@@ -81,7 +81,7 @@ MULTI-FILE:
     )
 }
 
-/// Agentic verification prompt - model uses shell to find and verify issues
+/// Agentic verification prompt - model uses tools to find and verify issues
 pub const FIX_PREVIEW_AGENTIC_SYSTEM: &str = r#"Verify if reported issue exists in code PROVIDED BELOW.
 
 Code is already included - you should NOT need tool calls (only for different files).
@@ -108,16 +108,16 @@ FIELD RULES:
 
 Respond with JSON now."#;
 
-/// Agentic codebase analysis prompt - model explores with shell before suggesting
-pub const ANALYZE_CODEBASE_AGENTIC_SYSTEM: &str = r#"Senior code reviewer with shell access. Find genuine improvements that help users, not just cleaner code.
+/// Agentic codebase analysis prompt - model explores with tools before suggesting
+pub const ANALYZE_CODEBASE_AGENTIC_SYSTEM: &str = r#"Senior code reviewer with code exploration tools. Find genuine improvements that help users, not just cleaner code.
 
 CRITICAL: You MUST return EXACTLY 10 suggestions. No more, no less.
 
-SHELL: rg, grep, cat, head, find, ls, cargo check. Be bold - git is your safety net.
+TOOLS: list_dir, grep, read_file, find_symbol, who_imports, file_summary (and shell, when offered).
 
 WORKFLOW:
 1. Read PROJECT CONTEXT to understand app purpose
-2. Explore structure: ls, find . -name "*.rs" | head -20
+2. Explore structure: list_dir, file_summary
 3. Read [CHANGED] files and dependencies first
 4. ONLY suggest issues verified by reading actual code
 5. Return EXACTLY 10 findings as JSON (count before submitting)
@@ -126,7 +126,7 @@ OUTPUT (JSON array):
 [{
   "file": "path/to/file.rs",
   "additional_files": ["other.rs"],
-  "kind": "bugfix|improvement|optimization|refactoring|security|reliability",
+  "kind": "bugfix|improvement|optimization|refactoring|security|testing|reliability",
   "priority": "high|medium|low",
   "summary": "Plain English user impact - NO code terms",
   "detail": "Technical: function names, code refs, fix guidance",
@@ -143,10 +143,11 @@ BAD summary: "processQueue() throws on empty batch" (code terms belong in detail
 
 LOOK FOR:
 - Bugs: race conditions, off-by-one, null handling, swallowed errors
-- Security: hardcoded secrets, injection, path traversal
+- Security: hardcoded secrets, injection, path traversal (check [SECURITY] sinks first)
 - Reliability: missing retries/timeouts, silent failures
 - Performance: N+1 queries, blocking in async
 - Refactoring: repeated patterns, complex conditionals, magic numbers
+- Testing: only for [UNTESTED] code - never suggest tests for files that already have them
 
 MULTI-FILE: Use "additional_files" for renames, extractions, or interface changes.
 
@@ -0,0 +1,759 @@
+//! LLM provider backends
+//!
+//! Every call site builds a provider-neutral `ChatRequest` (messages, a
+//! model tier, the response shape, optional tools) and hands it to the
+//! configured `LlmProvider`. Both backends speak the OpenAI chat
+//! completions wire format; they differ in endpoint, auth, OpenRouter's
+//! routing and prompt-caching extensions, and whether the account balance
+//! can be queried. Model names and token limits come from the tier's
+//! `ModelSpec`.
+
+use super::cassette;
+use super::client::{
+    create_http_client, send_streaming, send_with_retry, Endpoint, ProviderApiError,
+    REQUEST_TIMEOUT_SECS,
+};
+use super::ledger;
+use super::models::{Model, ModelSpec, Task, Usage};
+use super::tools::ToolDefinition;
+use crate::config::{Config, ProviderSettings};
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// OpenRouter chat completions endpoint
+const OPENROUTER_URL: &str = "https://openrouter.ai/api/v1/chat/completions";
+
+/// OpenRouter credits endpoint (account balance)
+const OPENROUTER_CREDITS_URL: &str = "https://openrouter.ai/api/v1/credits";
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  PROVIDER-NEUTRAL REQUEST / RESPONSE
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// A message in the conversation (OpenAI chat format)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub role: String,
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ToolCallMessage>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl Message {
+    pub fn system(content: &str) -> Self {
+        Self::text("system", content)
+    }
+
+    pub fn user(content: &str) -> Self {
+        Self::text("user", content)
+    }
+
+    pub fn assistant(content: &str) -> Self {
+        Self::text("assistant", content)
+    }
+
+    fn text(role: &str, content: &str) -> Self {
+        Self {
+            role: role.to_string(),
+            content: Some(content.to_string()),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallMessage {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub call_type: String,
+    pub function: FunctionCallMessage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionCallMessage {
+    pub name: String,
+    pub arguments: String,
+}
+
+/// Shape the response must take
+#[derive(Debug, Clone, Default)]
+pub enum ResponseMode {
+    #[default]
+    Text,
+    /// Any JSON object
+    Json,
+    /// JSON matching a schema (structured output)
+    Schema {
+        name: String,
+        schema: serde_json::Value,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct ChatRequest {
+    /// What the call is for; picks the tier and labels the cost ledger
+    pub task: Task,
+    pub model: Model,
+    pub messages: Vec<Message>,
+    pub response: ResponseMode,
+    pub tools: Option<Vec<ToolDefinition>>,
+    /// Mark the system prompt cacheable, where the backend supports it
+    pub cache_system_prompt: bool,
+}
+
+impl ChatRequest {
+    pub fn new(task: Task, messages: Vec<Message>) -> Self {
+        Self {
+            task,
+            model: Model::for_task(task),
+            messages,
+            response: ResponseMode::Text,
+            tools: None,
+            cache_system_prompt: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatResponse {
+    pub content: Option<String>,
+    pub tool_calls: Vec<ToolCallMessage>,
+    pub usage: Option<Usage>,
+}
+
+/// A chat-completions backend
+pub trait LlmProvider: Send + Sync {
+    /// Short name for error messages ("OpenRouter", "http://localhost:8080/v1")
+    fn name(&self) -> &str;
+
+    /// Run one chat completion (with retries on transient failures)
+    fn chat<'a>(&'a self, request: &'a ChatRequest) -> BoxFuture<'a, anyhow::Result<ChatResponse>>;
+
+    /// Like `chat`, but streamed: `on_text` receives each fragment of the
+    /// reply as it arrives. For plain-text replies; tool calls aren't
+    /// collected from the stream.
+    fn chat_stream<'a>(
+        &'a self,
+        request: &'a ChatRequest,
+        on_text: &'a mut (dyn FnMut(&str) + Send),
+    ) -> BoxFuture<'a, anyhow::Result<ChatResponse>>;
+
+    /// Remaining account balance in USD; `None` if the backend has no such notion
+    fn balance(&self) -> BoxFuture<'_, anyhow::Result<Option<f64>>>;
+}
+
+/// The provider selected by the user's config, metered against the budgets.
+/// An installed cassette records its calls, or stands in for it entirely
+/// when replaying (unmetered, since replays cost nothing).
+pub fn configured_provider() -> anyhow::Result<Box<dyn LlmProvider>> {
+    let cassette = cassette::active();
+    if let Some(cassette) = cassette.as_ref().filter(|c| c.is_replaying()) {
+        return Ok(Box::new(cassette::Player(cassette.clone())));
+    }
+    let mut config = Config::load();
+    let mut provider: Box<dyn LlmProvider> = match config.provider_settings() {
+        ProviderSettings::OpenRouter => {
+            let api_key = config.get_api_key().ok_or_else(|| {
+                anyhow::anyhow!("No API key configured. Run 'cosmos --setup' to get started.")
+            })?;
+            Box::new(OpenRouter::new(api_key)?)
+        }
+        ProviderSettings::OpenAiCompatible {
+            base_url,
+            api_key_env,
+            ..
+        } => {
+            let api_key = api_key_env.and_then(|var| std::env::var(var).ok());
+            Box::new(OpenAiCompatible::new(&base_url, api_key)?)
+        }
+    };
+    if let Some(cassette) = cassette {
+        provider = Box::new(cassette::Recorder::new(provider, cassette));
+    }
+    Ok(Box::new(Metered(provider)))
+}
+
+/// Refuses calls that would pass a spending cap and records the rest in
+/// the cost ledger
+pub struct Metered(Box<dyn LlmProvider>);
+
+impl Metered {
+    fn record(request: &ChatRequest, response: &ChatResponse) {
+        ledger::record(
+            request.task,
+            &request.model.spec().id,
+            response.usage.as_ref(),
+        );
+    }
+}
+
+impl LlmProvider for Metered {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn chat<'a>(&'a self, request: &'a ChatRequest) -> BoxFuture<'a, anyhow::Result<ChatResponse>> {
+        Box::pin(async move {
+            ledger::check(request.task).map_err(|overrun| anyhow::anyhow!("{}", overrun))?;
+            let response = self.0.chat(request).await?;
+            Self::record(request, &response);
+            Ok(response)
+        })
+    }
+
+    fn chat_stream<'a>(
+        &'a self,
+        request: &'a ChatRequest,
+        on_text: &'a mut (dyn FnMut(&str) + Send),
+    ) -> BoxFuture<'a, anyhow::Result<ChatResponse>> {
+        Box::pin(async move {
+            ledger::check(request.task).map_err(|overrun| anyhow::anyhow!("{}", overrun))?;
+            let response = self.0.chat_stream(request, on_text).await?;
+            Self::record(request, &response);
+            Ok(response)
+        })
+    }
+
+    fn balance(&self) -> BoxFuture<'_, anyhow::Result<Option<f64>>> {
+        self.0.balance()
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  WIRE FORMAT
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[derive(Serialize)]
+struct WireRequest<'a> {
+    model: String,
+    messages: Vec<WireMessage<'a>>,
+    max_tokens: u32,
+    stream: bool,
+    /// Ask for a final usage chunk when streaming
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<&'a [ToolDefinition]>,
+    /// OpenRouter routing: allow other upstream providers if the primary fails
+    #[serde(skip_serializing_if = "Option::is_none")]
+    provider: Option<serde_json::Value>,
+}
+
+/// A message whose content may be split into parts (for cache breakpoints)
+#[derive(Serialize)]
+struct WireMessage<'a> {
+    role: &'a str,
+    content: Option<WireContent<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<&'a [ToolCallMessage]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_call_id: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum WireContent<'a> {
+    Text(&'a str),
+    Parts(Vec<serde_json::Value>),
+}
+
+#[derive(Deserialize)]
+struct WireResponse {
+    choices: Vec<WireChoice>,
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct WireChoice {
+    message: WireResponseMessage,
+}
+
+#[derive(Deserialize)]
+struct WireResponseMessage {
+    content: Option<String>,
+    tool_calls: Option<Vec<ToolCallMessage>>,
+}
+
+/// Smallest completion worth sending a request for
+const MIN_COMPLETION_TOKENS: u32 = 1024;
+
+/// Rough prompt size, at ~4 characters per token for English and code
+fn estimate_prompt_tokens(request: &ChatRequest) -> u32 {
+    let messages: usize = request
+        .messages
+        .iter()
+        .map(|m| {
+            m.content.as_deref().map_or(0, str::len)
+                + m.tool_calls
+                    .iter()
+                    .flatten()
+                    .map(|c| c.function.arguments.len())
+                    .sum::<usize>()
+        })
+        .sum();
+    let tools = request
+        .tools
+        .as_ref()
+        .and_then(|t| serde_json::to_string(t).ok())
+        .map_or(0, |t| t.len());
+    u32::try_from((messages + tools) / 4).unwrap_or(u32::MAX)
+}
+
+/// Completion tokens to request: the tier's `max_tokens`, shrunk so prompt
+/// plus completion fit the context window (servers reject requests that don't)
+fn completion_budget(request: &ChatRequest, spec: &ModelSpec) -> anyhow::Result<u32> {
+    let prompt = estimate_prompt_tokens(request);
+    let room = spec.context_window.saturating_sub(prompt);
+    if room < MIN_COMPLETION_TOKENS {
+        anyhow::bail!(
+            "Prompt (~{} tokens) does not fit the {}-token context window of {}. Raise `context_window` for the {} tier or assign this task to a larger model in the `models` config.",
+            prompt,
+            spec.context_window,
+            spec.id,
+            request.model.name()
+        );
+    }
+    Ok(room.min(spec.max_tokens))
+}
+
+/// Encode a request; `cache_control` marks the system prompt cacheable
+/// (Anthropic models via OpenRouter; cached reads are 0.1x input pricing)
+fn wire_request(request: &ChatRequest, cache_control: bool) -> anyhow::Result<WireRequest<'_>> {
+    let spec = request.model.spec();
+    let max_tokens = completion_budget(request, &spec)?;
+    let messages = request
+        .messages
+        .iter()
+        .map(|m| {
+            let content = m.content.as_deref().map(|text| {
+                if cache_control && request.cache_system_prompt && m.role == "system" {
+                    WireContent::Parts(vec![serde_json::json!({
+                        "type": "text",
+                        "text": text,
+                        "cache_control": { "type": "ephemeral" },
+                    })])
+                } else {
+                    WireContent::Text(text)
+                }
+            });
+            WireMessage {
+                role: &m.role,
+                content,
+                tool_calls: m.tool_calls.as_deref(),
+                tool_call_id: m.tool_call_id.as_deref(),
+            }
+        })
+        .collect();
+
+    let response_format = match &request.response {
+        ResponseMode::Text => None,
+        ResponseMode::Json => Some(serde_json::json!({ "type": "json_object" })),
+        ResponseMode::Schema { name, schema } => Some(serde_json::json!({
+            "type": "json_schema",
+            "json_schema": { "name": name, "strict": true, "schema": schema },
+        })),
+    };
+
+    Ok(WireRequest {
+        model: spec.id,
+        messages,
+        max_tokens,
+        stream: false,
+        stream_options: None,
+        response_format,
+        tools: request.tools.as_deref(),
+        provider: None,
+    })
+}
+
+/// One `data:` event of a streamed reply
+#[derive(Deserialize)]
+struct WireChunk {
+    #[serde(default)]
+    choices: Vec<WireChunkChoice>,
+    usage: Option<Usage>,
+    error: Option<ProviderApiError>,
+}
+
+#[derive(Deserialize)]
+struct WireChunkChoice {
+    #[serde(default)]
+    delta: WireDelta,
+}
+
+#[derive(Deserialize, Default)]
+struct WireDelta {
+    content: Option<String>,
+}
+
+/// Stream `body` to `endpoint`, forwarding text deltas and assembling the
+/// full reply
+async fn stream_reply(
+    client: &reqwest::Client,
+    endpoint: &Endpoint<'_>,
+    mut body: WireRequest<'_>,
+    on_text: &mut (dyn FnMut(&str) + Send),
+) -> anyhow::Result<ChatResponse> {
+    body.stream = true;
+    body.stream_options = Some(serde_json::json!({ "include_usage": true }));
+    let mut reply = ChatResponse::default();
+    let mut content = String::new();
+    send_streaming(client, endpoint, &body, &mut |event| {
+        let chunk: WireChunk = serde_json::from_str(event).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to parse {} stream event: {}\n{}",
+                endpoint.name,
+                e,
+                event
+            )
+        })?;
+        if let Some(error) = chunk.error {
+            anyhow::bail!("{} error: {}", endpoint.name, error.message);
+        }
+        for choice in chunk.choices {
+            if let Some(text) = choice.delta.content.filter(|t| !t.is_empty()) {
+                on_text(&text);
+                content.push_str(&text);
+            }
+        }
+        if chunk.usage.is_some() {
+            reply.usage = chunk.usage;
+        }
+        Ok(())
+    })
+    .await?;
+    reply.content = Some(content);
+    Ok(reply)
+}
+
+fn parse_response(provider: &str, text: &str) -> anyhow::Result<ChatResponse> {
+    let parsed: WireResponse = serde_json::from_str(text)
+        .map_err(|e| anyhow::anyhow!("Failed to parse {} response: {}\n{}", provider, e, text))?;
+    let Some(choice) = parsed.choices.into_iter().next() else {
+        return Ok(ChatResponse {
+            usage: parsed.usage,
+            ..Default::default()
+        });
+    };
+    Ok(ChatResponse {
+        content: choice.message.content,
+        tool_calls: choice.message.tool_calls.unwrap_or_default(),
+        usage: parsed.usage,
+    })
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  OPENROUTER
+// ═══════════════════════════════════════════════════════════════════════════
+
+pub struct OpenRouter {
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl OpenRouter {
+    pub fn new(api_key: String) -> anyhow::Result<Self> {
+        Ok(Self {
+            api_key,
+            client: create_http_client(REQUEST_TIMEOUT_SECS)?,
+        })
+    }
+}
+
+impl OpenRouter {
+    fn endpoint(&self) -> Endpoint<'_> {
+        Endpoint {
+            name: self.name(),
+            url: OPENROUTER_URL.to_string(),
+            headers: vec![
+                ("HTTP-Referer", "https://cosmos.dev".to_string()),
+                ("X-Title", "Cosmos".to_string()),
+                ("Authorization", format!("Bearer {}", self.api_key)),
+            ],
+            setup_hint: "Run 'cosmos --setup' to update it.",
+        }
+    }
+
+    fn body<'a>(&self, request: &'a ChatRequest) -> anyhow::Result<WireRequest<'a>> {
+        let mut body = wire_request(request, true)?;
+        body.provider = Some(serde_json::json!({ "allow_fallbacks": true }));
+        Ok(body)
+    }
+}
+
+/// Response from OpenRouter credits endpoint
+#[derive(Deserialize)]
+struct CreditsResponse {
+    data: CreditsData,
+}
+
+#[derive(Deserialize)]
+struct CreditsData {
+    total_credits: f64,
+    total_usage: f64,
+}
+
+impl LlmProvider for OpenRouter {
+    fn name(&self) -> &str {
+        "OpenRouter"
+    }
+
+    fn chat<'a>(&'a self, request: &'a ChatRequest) -> BoxFuture<'a, anyhow::Result<ChatResponse>> {
+        Box::pin(async move {
+            let body = self.body(request)?;
+            let text = send_with_retry(&self.client, &self.endpoint(), &body).await?;
+            parse_response(self.name(), &text)
+        })
+    }
+
+    fn chat_stream<'a>(
+        &'a self,
+        request: &'a ChatRequest,
+        on_text: &'a mut (dyn FnMut(&str) + Send),
+    ) -> BoxFuture<'a, anyhow::Result<ChatResponse>> {
+        Box::pin(async move {
+            let body = self.body(request)?;
+            stream_reply(&self.client, &self.endpoint(), body, on_text).await
+        })
+    }
+
+    fn balance(&self) -> BoxFuture<'_, anyhow::Result<Option<f64>>> {
+        Box::pin(async move {
+            let client = reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()?;
+            let response = client
+                .get(OPENROUTER_CREDITS_URL)
+                .header("Authorization", format!("Bearer {}", self.api_key))
+                .send()
+                .await?;
+
+            if !response.status().is_success() {
+                return Err(anyhow::anyhow!(
+                    "Failed to fetch balance: {}",
+                    response.status()
+                ));
+            }
+
+            let credits: CreditsResponse = response.json().await?;
+            Ok(Some(credits.data.total_credits - credits.data.total_usage))
+        })
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  OPENAI-COMPATIBLE SERVERS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// llama.cpp server, vLLM, Ollama, or any other `/chat/completions` endpoint
+pub struct OpenAiCompatible {
+    base_url: String,
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+impl OpenAiCompatible {
+    pub fn new(base_url: &str, api_key: Option<String>) -> anyhow::Result<Self> {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        url::Url::parse(&base_url)
+            .map_err(|e| anyhow::anyhow!("Invalid LLM base URL '{}': {}", base_url, e))?;
+        Ok(Self {
+            base_url,
+            api_key,
+            client: create_http_client(REQUEST_TIMEOUT_SECS)?,
+        })
+    }
+}
+
+impl OpenAiCompatible {
+    fn endpoint(&self) -> Endpoint<'_> {
+        Endpoint {
+            name: self.name(),
+            url: format!("{}/chat/completions", self.base_url),
+            headers: self
+                .api_key
+                .iter()
+                .map(|key| ("Authorization", format!("Bearer {}", key)))
+                .collect(),
+            setup_hint: "Check the key in the variable named by `api_key_env`.",
+        }
+    }
+}
+
+impl LlmProvider for OpenAiCompatible {
+    fn name(&self) -> &str {
+        &self.base_url
+    }
+
+    fn chat<'a>(&'a self, request: &'a ChatRequest) -> BoxFuture<'a, anyhow::Result<ChatResponse>> {
+        Box::pin(async move {
+            let body = wire_request(request, false)?;
+            let text = send_with_retry(&self.client, &self.endpoint(), &body).await?;
+            parse_response(self.name(), &text)
+        })
+    }
+
+    fn chat_stream<'a>(
+        &'a self,
+        request: &'a ChatRequest,
+        on_text: &'a mut (dyn FnMut(&str) + Send),
+    ) -> BoxFuture<'a, anyhow::Result<ChatResponse>> {
+        Box::pin(async move {
+            let body = wire_request(request, false)?;
+            stream_reply(&self.client, &self.endpoint(), body, on_text).await
+        })
+    }
+
+    fn balance(&self) -> BoxFuture<'_, anyhow::Result<Option<f64>>> {
+        Box::pin(async { Ok(None) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    /// Serve one canned chat completion; returns the URL and the raw request
+    fn stand_in_server(
+        response: &'static str,
+    ) -> (String, std::thread::JoinHandle<(String, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1/", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut head = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(len) = line.to_lowercase().strip_prefix("content-length:") {
+                    content_length = len.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+                head.push_str(&line);
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let mut stream = reader.into_inner();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response.len(),
+                response
+            )
+            .unwrap();
+            (head, String::from_utf8(body).unwrap())
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_openai_compatible_round_trip() {
+        let (url, server) = stand_in_server(
+            r#"{"choices":[{"message":{"content":null,"tool_calls":[{"id":"call_1","type":"function","function":{"name":"shell","arguments":"{\"command\":\"ls\"}"}}]}}],"usage":{"prompt_tokens":12,"completion_tokens":3,"total_tokens":15}}"#,
+        );
+        let provider = OpenAiCompatible::new(&url, None).unwrap();
+
+        let mut request = ChatRequest::new(
+            Task::Fix,
+            vec![Message::system("be brief"), Message::user("list files")],
+        );
+        request.cache_system_prompt = true;
+        request.response = ResponseMode::Json;
+        let response = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(provider.chat(&request))
+            .unwrap();
+
+        assert_eq!(response.tool_calls[0].function.name, "shell");
+        assert_eq!(response.usage.unwrap().total_tokens, 15);
+
+        let (head, body) = server.join().unwrap();
+        assert!(head.starts_with("POST /v1/chat/completions "));
+        assert!(!head.to_lowercase().contains("authorization"));
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["model"], Model::Smart.spec().id);
+        // No OpenRouter extensions: plain string content, no routing config
+        assert_eq!(body["messages"][0]["content"], "be brief");
+        assert!(body.get("provider").is_none());
+        assert_eq!(body["response_format"]["type"], "json_object");
+    }
+
+    #[test]
+    fn test_openai_compatible_streams_text() {
+        let (url, server) = stand_in_server(concat!(
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"It parses \"}}]}\n\n",
+            ": keep-alive\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"**config**.\"}}]}\n\n",
+            "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":9,\"completion_tokens\":4,\"total_tokens\":13}}\n\n",
+            "data: [DONE]\n\n",
+        ));
+        let provider = OpenAiCompatible::new(&url, None).unwrap();
+        let request = ChatRequest::new(Task::Ask, vec![Message::user("what is this?")]);
+
+        let mut fragments = Vec::new();
+        let mut on_text = |text: &str| fragments.push(text.to_string());
+        let response = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(provider.chat_stream(&request, &mut on_text))
+            .unwrap();
+
+        assert_eq!(fragments, vec!["It parses ", "**config**."]);
+        assert_eq!(response.content.as_deref(), Some("It parses **config**."));
+        assert_eq!(response.usage.unwrap().total_tokens, 13);
+
+        let (_, body) = server.join().unwrap();
+        let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["stream_options"]["include_usage"], true);
+    }
+
+    #[test]
+    fn test_completion_budget_fits_context_window() {
+        let spec = ModelSpec {
+            id: "local".to_string(),
+            max_tokens: 8192,
+            context_window: 32_768,
+        };
+        let request = |chars: usize| {
+            ChatRequest::new(Task::Summaries, vec![Message::user(&"x".repeat(chars))])
+        };
+        assert_eq!(completion_budget(&request(400), &spec).unwrap(), 8192);
+        // ~28k prompt tokens leave ~4.7k for the reply
+        assert_eq!(completion_budget(&request(112_000), &spec).unwrap(), 4768);
+        let err = completion_budget(&request(130_000), &spec).unwrap_err();
+        assert!(err.to_string().contains("context window of local"));
+    }
+
+    #[test]
+    fn test_openrouter_wire_request_marks_system_prompt_cacheable() {
+        let mut request = ChatRequest::new(
+            Task::Ask,
+            vec![Message::system("rules"), Message::user("question")],
+        );
+        request.cache_system_prompt = true;
+        let body = serde_json::to_value(wire_request(&request, true).unwrap()).unwrap();
+        assert_eq!(
+            body["messages"][0]["content"][0]["cache_control"]["type"],
+            "ephemeral"
+        );
+        assert_eq!(body["messages"][1]["content"], "question");
+    }
+}
@@ -4,8 +4,8 @@ use super::fix::{
     apply_edits_with_context, fix_response_schema, normalize_generated_content, AppliedFix,
     FixResponse,
 };
-use super::models::{Model, Usage};
-use super::parse::parse_json_with_retry;
+use super::models::{Task, Usage};
+use super::parse::{merge_usage, parse_json_with_retry};
 use super::prompt_utils::format_repo_memory_section;
 use super::prompts::{review_fix_system_prompt, review_system_prompt};
 use serde::{Deserialize, Serialize};
@@ -93,9 +93,9 @@ pub async fn verify_changes(
     // Build compact diff summary (not full content)
     let user = build_lean_review_prompt(files_with_content, fix_context);
 
-    // Use Speed model with surgical tool access for fast, accurate review
+    // Speed tier by default, with surgical tool access for fast, accurate review
     // 4 iterations - diff already provided, occasional context needed
-    let response = call_llm_agentic(&system, &user, Model::Speed, &repo_root, false, 4).await?;
+    let response = call_llm_agentic(&system, &user, Task::Review, &repo_root, false, 4).await?;
 
     // Parse the response with self-correction on failure
     let (parsed, correction_usage): (ReviewResponseJson, _) =
@@ -104,7 +104,7 @@ pub async fn verify_changes(
     Ok(VerificationReview {
         findings: parsed.findings,
         summary: parsed.summary,
-        usage: correction_usage, // Agentic doesn't track usage, but correction might
+        usage: merge_usage(response.usage, correction_usage),
     })
 }
 
@@ -164,8 +164,8 @@ fn build_lean_review_prompt(
 Find bugs, logic errors, and issues in the diff above.
 
 SURGICAL COMMANDS (if needed):
-• grep -n 'pattern' <file> → find related code
-• sed -n '50,80p' <file> → read around specific line
+• grep(pattern, glob: \"<file>\") → find related code
+• read_file(<file>, 50, 80) → read around specific line
 
 MINIMIZE tool calls - most issues should be visible in the diff.
 Return findings as JSON.",
@@ -307,7 +307,7 @@ pub async fn fix_review_findings(
     let response: StructuredResponse<FixResponse> = call_llm_structured_cached(
         &system,
         &user,
-        Model::Smart,
+        Task::Fix,
         "fix_response",
         fix_response_schema(),
     )
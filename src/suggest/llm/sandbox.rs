@@ -0,0 +1,864 @@
+//! Sandbox for the agent's `shell` tool
+//!
+//! On Linux, commands run under Landlock: the repo and system directories
+//! are readable, nothing is writable except a per-command scratch directory
+//! (HOME and TMPDIR point there), and the rest of the filesystem is out of
+//! reach. Network access is cut with a fresh network namespace; Landlock's
+//! TCP rules alone would leave UDP and UNIX sockets open, so where
+//! unprivileged namespaces are disabled (or on macOS and old kernels) only
+//! an allowlist of read-only commands runs. Either way the environment is scrubbed (no API
+//! keys), the whole process group is killed at the deadline, and output is
+//! capped.
+
+use crate::util::{run_command_isolated, CommandRunResult};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Wall-clock limit per shell command
+pub const SHELL_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Bytes kept per output stream (the tool result is cut further)
+const MAX_CAPTURE_BYTES: usize = 64 * 1024;
+
+/// How commands are contained on this machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Isolation {
+    /// Landlock filesystem rules plus a network namespace
+    Kernel,
+    /// Only allowlisted read-only commands
+    Allowlist,
+}
+
+impl Isolation {
+    /// Probed once per process
+    pub fn current() -> Self {
+        #[cfg(target_os = "linux")]
+        {
+            static PROBED: std::sync::OnceLock<Isolation> = std::sync::OnceLock::new();
+            *PROBED.get_or_init(|| {
+                if linux::available() {
+                    Isolation::Kernel
+                } else {
+                    Isolation::Allowlist
+                }
+            })
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            Isolation::Allowlist
+        }
+    }
+}
+
+/// Run `command` with `sh -c` in `root`, contained as `Isolation::current()`
+/// allows. `Err` means it didn't run (refused, or the sandbox failed).
+pub fn run(root: &Path, command: &str, timeout: Duration) -> Result<CommandRunResult, String> {
+    let isolation = Isolation::current();
+    if isolation == Isolation::Allowlist {
+        check_allowlist(command)?;
+    }
+
+    let scratch = scratch_dir()?;
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command])
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .env_clear()
+        .env("PATH", safe_path())
+        .env("HOME", &scratch)
+        .env("TMPDIR", &scratch)
+        .env("LANG", "C.UTF-8")
+        .env("TERM", "dumb")
+        .env("PAGER", "cat")
+        .env("GIT_PAGER", "cat")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_CONFIG_NOSYSTEM", "1");
+
+    #[cfg(target_os = "linux")]
+    let _ruleset = if isolation == Isolation::Kernel {
+        Some(linux::confine(&mut cmd, root, &scratch)?)
+    } else {
+        None
+    };
+
+    let result = run_command_isolated(&mut cmd, timeout, MAX_CAPTURE_BYTES);
+    let _ = std::fs::remove_dir_all(&scratch);
+    result
+}
+
+/// A fresh private directory; creation fails rather than reuse anything
+/// already at the (guessable) path under the shared temp dir
+fn scratch_dir() -> Result<PathBuf, String> {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let dir = std::env::temp_dir().join(format!("cosmos-shell-{}-{}", std::process::id(), nanos));
+    let mut builder = std::fs::DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder
+        .create(&dir)
+        .map_err(|e| format!("Failed to create scratch dir: {}", e))?;
+    Ok(dir)
+}
+
+/// The user's PATH without relative entries
+fn safe_path() -> String {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let dirs: Vec<PathBuf> = std::env::split_paths(&path)
+        .filter(|dir| dir.is_absolute())
+        .collect();
+    std::env::join_paths(dirs)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| "/usr/local/bin:/usr/bin:/bin".to_string())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  ALLOWLIST FALLBACK
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Programs that only read (given the per-program flag checks below)
+const ALLOWED_PROGRAMS: &[&str] = &[
+    "cat", "head", "tail", "grep", "egrep", "fgrep", "rg", "sed", "wc", "sort", "uniq", "cut",
+    "tr", "nl", "ls", "find", "tree", "file", "stat", "diff", "git", "echo", "printf", "pwd",
+    "true", "false", "sleep",
+];
+
+/// Shell syntax that could redirect, chain, expand or escape; without it
+/// each word reaches the program exactly as written
+const REFUSED_SYNTAX: &[&str] = &[
+    ";", "&", ">", "<", "`", "$", "||", "\n", "\\", "'", "\"", "*", "?", "[", "]", "{", "}", "(",
+    ")", "#",
+];
+
+/// Diff output options shared by `git diff`, `log`, `show` and `shortlog`
+const GIT_DIFF_FLAGS: &[&str] = &[
+    "-p",
+    "-u",
+    "--patch",
+    "-s",
+    "--no-patch",
+    "--stat",
+    "--numstat",
+    "--shortstat",
+    "--name-only",
+    "--name-status",
+    "--raw",
+    "--summary",
+    "-U",
+    "--unified",
+    "-w",
+    "--ignore-all-space",
+    "-b",
+    "--ignore-space-change",
+    "--word-diff",
+    "--color",
+    "--no-color",
+    "--no-ext-diff",
+    "--no-textconv",
+    "-M",
+    "--find-renames",
+    "--no-renames",
+    "--diff-filter",
+    "--relative",
+    "--minimal",
+    "--patience",
+    "--histogram",
+    "--abbrev",
+    "--full-index",
+];
+
+/// Commit selection and formatting for `git log`, `show` and `shortlog`
+const GIT_LOG_FLAGS: &[&str] = &[
+    "--oneline",
+    "--format",
+    "--pretty",
+    "--graph",
+    "--decorate",
+    "--no-decorate",
+    "--all",
+    "--branches",
+    "--tags",
+    "-n",
+    "--max-count",
+    "--skip",
+    "--since",
+    "--after",
+    "--until",
+    "--before",
+    "--author",
+    "--committer",
+    "--grep",
+    "-i",
+    "--regexp-ignore-case",
+    "--all-match",
+    "--invert-grep",
+    "-E",
+    "-F",
+    "-S",
+    "-G",
+    "--pickaxe-regex",
+    "-L",
+    "--follow",
+    "--reverse",
+    "--first-parent",
+    "--no-merges",
+    "--merges",
+    "--date",
+    "--abbrev-commit",
+    "--topo-order",
+    "--date-order",
+];
+
+const GIT_DIFF_ONLY_FLAGS: &[&str] = &["--cached", "--staged", "--merge-base", "-R", "--quiet"];
+
+const GIT_BLAME_FLAGS: &[&str] = &[
+    "-L",
+    "-l",
+    "-s",
+    "-e",
+    "-w",
+    "-M",
+    "-C",
+    "--porcelain",
+    "--line-porcelain",
+    "--date",
+    "--show-name",
+    "--show-number",
+    "-n",
+    "--root",
+    "-f",
+    "-t",
+];
+
+const GIT_STATUS_FLAGS: &[&str] = &[
+    "-s",
+    "--short",
+    "-b",
+    "--branch",
+    "--porcelain",
+    "--long",
+    "-u",
+    "--untracked-files",
+    "--ignored",
+    "-z",
+    "--no-renames",
+    "-v",
+    "--verbose",
+];
+
+const GIT_GREP_FLAGS: &[&str] = &[
+    "-n",
+    "--line-number",
+    "-i",
+    "--ignore-case",
+    "-w",
+    "--word-regexp",
+    "-v",
+    "--invert-match",
+    "-l",
+    "--files-with-matches",
+    "-L",
+    "--files-without-match",
+    "-c",
+    "--count",
+    "-e",
+    "-E",
+    "--extended-regexp",
+    "-F",
+    "--fixed-strings",
+    "-h",
+    "-H",
+    "--full-name",
+    "-A",
+    "-B",
+    "-C",
+    "--context",
+    "--after-context",
+    "--before-context",
+    "--cached",
+    "--untracked",
+    "--max-depth",
+    "-I",
+    "-o",
+    "--only-matching",
+    "-q",
+    "--quiet",
+    "-W",
+    "--function-context",
+    "-p",
+    "--show-function",
+    "--column",
+];
+
+const GIT_LS_FILES_FLAGS: &[&str] = &[
+    "-c",
+    "--cached",
+    "-d",
+    "--deleted",
+    "-m",
+    "--modified",
+    "-o",
+    "--others",
+    "-i",
+    "--ignored",
+    "--exclude-standard",
+    "-s",
+    "--stage",
+    "-u",
+    "--unmerged",
+    "-z",
+    "--full-name",
+    "--error-unmatch",
+    "--directory",
+];
+
+const GIT_REV_PARSE_FLAGS: &[&str] = &[
+    "--short",
+    "--abbrev-ref",
+    "--verify",
+    "--symbolic-full-name",
+    "--show-toplevel",
+    "--show-prefix",
+    "--is-inside-work-tree",
+    "-q",
+    "--quiet",
+];
+
+const GIT_SHORTLOG_FLAGS: &[&str] = &["-s", "--summary", "--numbered", "-e", "--email"];
+
+/// Listing forms only; names after `--list` are patterns, not new branches
+const GIT_BRANCH_FLAGS: &[&str] = &[
+    "-a",
+    "--all",
+    "-r",
+    "--remotes",
+    "-v",
+    "-vv",
+    "--verbose",
+    "-l",
+    "--list",
+    "--show-current",
+    "--no-color",
+];
+
+/// Flags each read-only git subcommand accepts
+fn git_flags(subcommand: &str) -> Option<Vec<&'static str>> {
+    let tables: &[&[&str]] = match subcommand {
+        "log" | "show" => &[GIT_LOG_FLAGS, GIT_DIFF_FLAGS],
+        "diff" => &[GIT_DIFF_FLAGS, GIT_DIFF_ONLY_FLAGS],
+        "shortlog" => &[GIT_SHORTLOG_FLAGS, GIT_LOG_FLAGS],
+        "blame" => &[GIT_BLAME_FLAGS],
+        "status" => &[GIT_STATUS_FLAGS],
+        "grep" => &[GIT_GREP_FLAGS],
+        "ls-files" => &[GIT_LS_FILES_FLAGS],
+        "rev-parse" => &[GIT_REV_PARSE_FLAGS],
+        "branch" => &[GIT_BRANCH_FLAGS],
+        _ => return None,
+    };
+    Some(tables.iter().flat_map(|t| t.iter().copied()).collect())
+}
+
+/// Refuse anything but a pipeline of allowlisted, read-only commands on
+/// repo-relative paths
+fn check_allowlist(command: &str) -> Result<(), String> {
+    for token in REFUSED_SYNTAX {
+        if command.contains(token) {
+            return Err(format!(
+                "Command not allowed: '{}' is unavailable without a kernel sandbox (no quoting, globs, redirects or expansions). Use a single read-only command or a pipeline of plain words.",
+                token.escape_debug()
+            ));
+        }
+    }
+
+    for segment in command.split('|') {
+        let words: Vec<&str> = segment.split_whitespace().collect();
+        let Some((program, args)) = words.split_first() else {
+            return Err("Command not allowed: empty pipeline stage".to_string());
+        };
+        if !ALLOWED_PROGRAMS.contains(program) {
+            return Err(format!(
+                "Command not allowed: {} is not on the read-only allowlist ({})",
+                program,
+                ALLOWED_PROGRAMS.join(", ")
+            ));
+        }
+        if let Some(arg) = args.iter().find(|a| leaves_repo(a)) {
+            return Err(format!(
+                "Command not allowed: {} is outside the repository",
+                arg
+            ));
+        }
+        let refused = |flag: &str| {
+            args.iter()
+                .any(|a| *a == flag || a.starts_with(&format!("{}=", flag)))
+        };
+        // A single-letter flag may be bundled (`-ro out`) or carry its value (`-oout`)
+        let short = |letter: char| {
+            args.iter()
+                .any(|a| a.starts_with('-') && !a.starts_with("--") && a[1..].contains(letter))
+        };
+        let positionals = args.iter().filter(|a| !a.starts_with('-')).count();
+        let reason = match *program {
+            "sed" if !is_sed_print(args) => Some("only `sed -n N,Mp <file>...` is allowed"),
+            "find"
+                if [
+                    "-exec", "-execdir", "-ok", "-okdir", "-delete", "-fprint", "-fprint0",
+                    "-fprintf", "-fls",
+                ]
+                .iter()
+                .any(|f| refused(f)) =>
+            {
+                Some("find actions that run commands or write files are disabled")
+            }
+            "sort" if short('o') || refused("--output") || refused("--compress-program") => {
+                Some("sort -o writes files")
+            }
+            "tree" if short('o') || short('R') || refused("--output") => {
+                Some("tree -o and -R write files")
+            }
+            "uniq" if positionals > 1 => Some("uniq with an output file writes it"),
+            "file" if short('C') || refused("--compile") => Some("file -C writes files"),
+            "rg" if refused("--pre") || refused("--pre-glob") => Some("rg --pre runs commands"),
+            "git" => git_refusal(args),
+            _ => None,
+        };
+        if let Some(reason) = reason {
+            return Err(format!("Command not allowed: {}", reason));
+        }
+    }
+    Ok(())
+}
+
+/// Whether a word names something outside the repo, either as written or
+/// as the value of an option (`--file=/etc/passwd`, `-f/etc/passwd`)
+fn leaves_repo(word: &str) -> bool {
+    let mut candidates = vec![word];
+    if let Some(rest) = word.strip_prefix('-') {
+        match word.split_once('=') {
+            Some((_, value)) => candidates.push(value),
+            None if !rest.starts_with('-') => candidates.push(rest.get(1..).unwrap_or("")),
+            None => {}
+        }
+    }
+    // `~` only expands at the start of a word or after `=` / `:` (`HEAD~1` is fine)
+    word.contains("=~")
+        || word.contains(":~")
+        || candidates.iter().any(|value| {
+            let path = Path::new(value);
+            value.starts_with('~')
+                || path.is_absolute()
+                || path.components().any(|c| c == Component::ParentDir)
+        })
+}
+
+/// `sed -n N,Mp <file>...` (or `Np`): one print script and nothing else
+fn is_sed_print(args: &[&str]) -> bool {
+    let [quiet, script, files @ ..] = args else {
+        return false;
+    };
+    let is_line = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    let is_print = match script.strip_suffix('p').map(|range| range.split_once(',')) {
+        Some(Some((start, end))) => is_line(start) && is_line(end),
+        Some(None) => is_line(&script[..script.len() - 1]),
+        None => false,
+    };
+    *quiet == "-n" && is_print && files.iter().all(|f| !f.starts_with('-'))
+}
+
+/// Only `--no-pager` before the subcommand, and only that subcommand's
+/// read-only flags after it
+fn git_refusal(args: &[&str]) -> Option<&'static str> {
+    let globals = args.iter().take_while(|a| a.starts_with('-')).count();
+    if args[..globals].iter().any(|a| *a != "--no-pager") {
+        return Some("git options before the subcommand are disabled (except --no-pager)");
+    }
+    let (subcommand, rest) = args[globals..].split_first()?;
+    let Some(allowed) = git_flags(subcommand) else {
+        return Some(
+            "only read-only git subcommands (log, show, diff, blame, status, grep, ls-files, rev-parse, shortlog, branch --list) are allowed",
+        );
+    };
+
+    let mut positionals = false;
+    let mut after_separator = false;
+    for arg in rest {
+        if after_separator || !arg.starts_with('-') || *arg == "-" {
+            positionals = true;
+            continue;
+        }
+        if *arg == "--" {
+            after_separator = true;
+            continue;
+        }
+        let known = if arg.starts_with("--") {
+            allowed.contains(&arg.split_once('=').map_or(*arg, |(name, _)| name))
+        } else {
+            // `-5`, or a known flag with a numeric value attached (`-n5`, `-U3`)
+            let digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+            digits(&arg[1..])
+                || allowed.contains(arg)
+                || (arg.len() > 2 && allowed.contains(&&arg[..2]) && digits(&arg[2..]))
+        };
+        if !known {
+            return Some("that git option isn't on the read-only list for this subcommand");
+        }
+    }
+    if *subcommand == "branch" && positionals && !rest.iter().any(|a| *a == "--list" || *a == "-l")
+    {
+        return Some("git branch can only list branches");
+    }
+    None
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+//  LINUX: LANDLOCK + NETWORK NAMESPACE
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::fs::File;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::process::CommandExt;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+    use std::sync::OnceLock;
+
+    const CREATE_RULESET_VERSION: u32 = 1 << 0;
+    const RULE_PATH_BENEATH: libc::c_int = 1;
+
+    const ACCESS_FS_EXECUTE: u64 = 1 << 0;
+    const ACCESS_FS_WRITE_FILE: u64 = 1 << 1;
+    const ACCESS_FS_READ_FILE: u64 = 1 << 2;
+    const ACCESS_FS_READ_DIR: u64 = 1 << 3;
+    /// Everything ABI 1 can restrict (execute .. make symlink)
+    const ACCESS_FS_V1: u64 = (1 << 13) - 1;
+    const ACCESS_FS_REFER: u64 = 1 << 13;
+    const ACCESS_FS_TRUNCATE: u64 = 1 << 14;
+    const ACCESS_FS_IOCTL_DEV: u64 = 1 << 15;
+    const ACCESS_NET_BIND_TCP: u64 = 1 << 0;
+    const ACCESS_NET_CONNECT_TCP: u64 = 1 << 1;
+
+    /// System directories commands need to load and run
+    const SYSTEM_DIRS: &[&str] = &[
+        "/usr",
+        "/bin",
+        "/sbin",
+        "/lib",
+        "/lib32",
+        "/lib64",
+        "/etc",
+        "/opt",
+        "/nix/store",
+    ];
+
+    #[repr(C)]
+    struct RulesetAttr {
+        handled_access_fs: u64,
+        handled_access_net: u64,
+    }
+
+    #[repr(C, packed)]
+    struct PathBeneathAttr {
+        allowed_access: u64,
+        parent_fd: i32,
+    }
+
+    /// Landlock ABI version, 0 when unsupported
+    fn abi() -> i64 {
+        // SAFETY: version query; no pointers are dereferenced
+        let version = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                std::ptr::null::<RulesetAttr>(),
+                0usize,
+                CREATE_RULESET_VERSION,
+            )
+        };
+        version.max(0)
+    }
+
+    /// Whether unprivileged user + network namespaces work here
+    fn network_namespaces() -> bool {
+        static PROBED: OnceLock<bool> = OnceLock::new();
+        *PROBED.get_or_init(|| {
+            let mut probe = Command::new("true");
+            // SAFETY: `unshare` is async-signal-safe
+            unsafe {
+                probe.pre_exec(|| {
+                    if libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    Ok(())
+                });
+            }
+            probe.status().is_ok_and(|s| s.success())
+        })
+    }
+
+    pub(super) fn available() -> bool {
+        let abi = abi();
+        abi >= 1 && network_namespaces()
+    }
+
+    /// Build the ruleset for `root` and arrange for the child to enter it.
+    /// The returned fd must stay open until the child has spawned.
+    pub(super) fn confine(
+        command: &mut Command,
+        root: &Path,
+        scratch: &Path,
+    ) -> Result<OwnedFd, String> {
+        let abi = abi();
+        let mut handled_fs = ACCESS_FS_V1;
+        if abi >= 2 {
+            handled_fs |= ACCESS_FS_REFER;
+        }
+        if abi >= 3 {
+            handled_fs |= ACCESS_FS_TRUNCATE;
+        }
+        if abi >= 5 {
+            handled_fs |= ACCESS_FS_IOCTL_DEV;
+        }
+        let attr = RulesetAttr {
+            handled_access_fs: handled_fs,
+            handled_access_net: ACCESS_NET_BIND_TCP | ACCESS_NET_CONNECT_TCP,
+        };
+        // ABI 1-3 only know the filesystem field
+        let attr_size = if abi >= 4 {
+            std::mem::size_of::<RulesetAttr>()
+        } else {
+            std::mem::size_of::<u64>()
+        };
+        // SAFETY: `attr` outlives the call and `attr_size` is within it
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                &attr as *const RulesetAttr,
+                attr_size,
+                0u32,
+            )
+        };
+        if fd < 0 {
+            return Err(format!(
+                "Sandbox unavailable: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        // SAFETY: the kernel just returned this fd to us
+        let ruleset = unsafe { OwnedFd::from_raw_fd(fd as i32) };
+
+        let read = ACCESS_FS_EXECUTE | ACCESS_FS_READ_FILE | ACCESS_FS_READ_DIR;
+        let mut rules: Vec<(PathBuf, u64)> = vec![
+            (root.to_path_buf(), read),
+            (scratch.to_path_buf(), handled_fs),
+            (
+                PathBuf::from("/dev/null"),
+                (ACCESS_FS_READ_FILE
+                    | ACCESS_FS_WRITE_FILE
+                    | ACCESS_FS_TRUNCATE
+                    | ACCESS_FS_IOCTL_DEV)
+                    & handled_fs,
+            ),
+            (PathBuf::from("/dev/urandom"), ACCESS_FS_READ_FILE),
+        ];
+        rules.extend(SYSTEM_DIRS.iter().map(|dir| (PathBuf::from(dir), read)));
+        // Tools installed outside the system dirs (e.g. ~/.cargo/bin/rg)
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        if let Some(path) = std::env::var_os("PATH") {
+            rules.extend(
+                std::env::split_paths(&path)
+                    .filter(|dir| dir.is_absolute() && Some(dir) != home.as_ref())
+                    .map(|dir| (dir, read)),
+            );
+        }
+        for (path, access) in rules {
+            // Missing directories (no /lib32, no /nix) are skipped
+            let Ok(file) = File::options()
+                .read(true)
+                .custom_flags(libc::O_PATH | libc::O_CLOEXEC)
+                .open(&path)
+            else {
+                continue;
+            };
+            // Directories-only rights can't be granted on a file
+            let access = if file.metadata().is_ok_and(|m| m.is_dir()) {
+                access
+            } else {
+                access
+                    & !ACCESS_FS_READ_DIR
+                    & (ACCESS_FS_EXECUTE
+                        | ACCESS_FS_READ_FILE
+                        | ACCESS_FS_WRITE_FILE
+                        | ACCESS_FS_TRUNCATE
+                        | ACCESS_FS_IOCTL_DEV)
+            };
+            let rule = PathBeneathAttr {
+                allowed_access: access,
+                parent_fd: file.as_raw_fd(),
+            };
+            // SAFETY: `rule` outlives the call; both fds are open
+            let added = unsafe {
+                libc::syscall(
+                    libc::SYS_landlock_add_rule,
+                    ruleset.as_raw_fd(),
+                    RULE_PATH_BENEATH,
+                    &rule as *const PathBeneathAttr,
+                    0u32,
+                )
+            };
+            if added != 0 {
+                return Err(format!(
+                    "Sandbox setup failed for {}: {}",
+                    path.display(),
+                    std::io::Error::last_os_error()
+                ));
+            }
+        }
+
+        let ruleset_fd = ruleset.as_raw_fd();
+        // SAFETY: only async-signal-safe syscalls run between fork and exec
+        unsafe {
+            command.pre_exec(move || {
+                if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                if libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                if libc::syscall(libc::SYS_landlock_restrict_self, ruleset_fd, 0u32) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        Ok(ruleset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Instant;
+
+    fn temp_repo(label: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("cosmos_sandbox_{}_{}", label, nanos));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_allowlist() {
+        for ok in [
+            "grep -n main src/main.rs",
+            "sed -n 10,40p src/lib.rs",
+            "rg parse src | head -20",
+            "git log --oneline -5",
+            "git --no-pager diff --stat HEAD~1",
+            "git grep -n needle -- src",
+            "git branch -a",
+            "git branch --list feature",
+            "find . -name Cargo.toml",
+            "sort -r src/lib.rs | uniq",
+        ] {
+            assert_eq!(check_allowlist(ok), Ok(()), "{}", ok);
+        }
+        for refused in [
+            "rm -r -f src",
+            "sed -i 's/a/b/' src/lib.rs",
+            "cat /etc/passwd",
+            "cat ../secrets",
+            "cat ~/.ssh/id_rsa",
+            "echo hi > file",
+            "ls; rm x",
+            "find . -delete",
+            "git -c core.pager=sh log",
+            "git commit -am x",
+            "curl example.com",
+            "cat $(which sh)",
+            "git grep -Orm needle",
+            "git grep --open-files-in-pager=rm needle",
+            "git branch evil",
+            "git log --output=out.txt",
+            "git diff --ext-diff",
+            "sed -n -e 1p -e wout.txt a.txt",
+            "sed -n 1p -s a.txt",
+            "sed -n -f script.sed a.txt",
+            "sed -n 1e a.txt",
+            "tree -o out.txt",
+            "sort -ro out.txt src/lib.rs",
+            "uniq a.txt out.txt",
+            "cat \\/etc/passwd",
+            "grep --file=/etc/passwd src",
+            "grep -f../secrets src",
+            "rg --ignore-file=../x parse",
+            "grep -n 'fn main' src/main.rs",
+            "cat src/*.rs",
+        ] {
+            assert!(check_allowlist(refused).is_err(), "{}", refused);
+        }
+    }
+
+    #[test]
+    fn test_repo_is_read_only_and_env_is_scrubbed() {
+        let root = temp_repo("readonly");
+        fs::write(root.join("keep.txt"), "original").unwrap();
+
+        for command in [
+            "rm -f keep.txt",
+            "echo changed > keep.txt",
+            "sed -i 's/original/changed/' keep.txt",
+        ] {
+            let _ = run(&root, command, SHELL_TIMEOUT);
+        }
+        assert_eq!(
+            fs::read_to_string(root.join("keep.txt")).unwrap(),
+            "original"
+        );
+
+        let result = run(&root, "echo \"$HOME\"", SHELL_TIMEOUT).unwrap();
+        let real_home = std::env::var("HOME").unwrap_or_default();
+        assert!(!real_home.is_empty() && !result.stdout.contains(&real_home));
+        assert!(result.stdout.contains("cosmos-shell-"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_files_outside_repo_are_hidden() {
+        if Isolation::current() != Isolation::Kernel {
+            return;
+        }
+        let root = temp_repo("inside");
+        let outside = temp_repo("outside");
+        fs::write(outside.join("secret.txt"), "token").unwrap();
+
+        let command = format!("cat {}", outside.join("secret.txt").display());
+        let result = run(&root, &command, SHELL_TIMEOUT).unwrap();
+        assert!(!result.stdout.contains("token"));
+        assert!(result.stderr.contains("Permission denied"));
+
+        let _ = fs::remove_dir_all(&root);
+        let _ = fs::remove_dir_all(&outside);
+    }
+
+    #[test]
+    fn test_timeout_kills_command() {
+        let root = temp_repo("timeout");
+        let start = Instant::now();
+        let result = run(&root, "sleep 10", Duration::from_millis(200)).unwrap();
+        assert!(result.timed_out);
+        assert!(start.elapsed() < Duration::from_secs(5));
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -1,5 +1,5 @@
 use super::client::call_llm_with_usage;
-use super::models::{Model, Usage};
+use super::models::{Task, Usage};
 use super::parse::{parse_summaries_and_terms_response, SummariesAndTerms};
 use super::prompts::SUMMARY_BATCH_SYSTEM;
 use crate::cache::DomainGlossary;
@@ -380,7 +380,7 @@ async fn generate_summary_batch(
     let user_prompt = build_batch_context(index, files, project_context);
 
     let response =
-        call_llm_with_usage(SUMMARY_BATCH_SYSTEM, &user_prompt, Model::Speed, true).await?;
+        call_llm_with_usage(SUMMARY_BATCH_SYSTEM, &user_prompt, Task::Summaries, true).await?;
 
     // Try to parse the response
     match parse_summaries_and_terms_response(&response.content, &index.root) {
@@ -442,15 +442,13 @@ fn build_batch_context(index: &CodebaseIndex, files: &[PathBuf], project_context
 
     for path in files {
         if let Some(file_index) = index.files.get(path) {
-            let func_count = file_index
-                .symbols
-                .iter()
+            let func_count = index
+                .file_symbols(file_index)
                 .filter(|s| matches!(s.kind, SymbolKind::Function | SymbolKind::Method))
                 .count();
 
-            let struct_count = file_index
-                .symbols
-                .iter()
+            let struct_count = index
+                .file_symbols(file_index)
                 .filter(|s| {
                     matches!(
                         s.kind,
@@ -463,12 +461,11 @@ fn build_batch_context(index: &CodebaseIndex, files: &[PathBuf], project_context
                 .count();
 
             // Get public exports
-            let exports: Vec<_> = file_index
-                .symbols
-                .iter()
+            let exports: Vec<_> = index
+                .file_symbols(file_index)
                 .filter(|s| s.visibility == crate::index::Visibility::Public)
                 .take(10)
-                .map(|s| s.name.as_str())
+                .map(|s| s.name)
                 .collect();
 
             let exports_str = if exports.is_empty() {
@@ -478,7 +475,7 @@ fn build_batch_context(index: &CodebaseIndex, files: &[PathBuf], project_context
             };
 
             let deps: Vec<_> = file_index
-                .dependencies
+                .dependencies()
                 .iter()
                 .filter(|d| !d.is_external)
                 .take(5)
@@ -502,7 +499,7 @@ fn build_batch_context(index: &CodebaseIndex, files: &[PathBuf], project_context
             ));
 
             // Add doc comments if available
-            if let Ok(content) = std::fs::read_to_string(index.root.join(path)) {
+            if let Ok(content) = crate::index::notebook::read_editable(&index.root.join(path)) {
                 let doc_lines: Vec<_> = content
                     .lines()
                     .take(10)
@@ -1,11 +1,32 @@
 //! Agentic tool definitions and execution for LLM-driven code exploration.
 //!
-//! Philosophy: Let the model do its best work. Git is the safety net.
-//! We provide a single powerful shell tool instead of limited primitives.
-
+//! The model explores through purpose-built, read-only tools backed by Rust
+//! code: `read_file`, `grep` and `list_dir` for the working tree, and
+//! `find_symbol`, `who_imports` and `file_summary` for the index. Every path
+//! argument is validated against the repo root. The raw `shell` tool is
+//! still offered unless the user or project config turns it off.
+
+use super::sandbox;
+use crate::cache::Cache;
+use crate::config::Config;
+use crate::index::{is_ignored, CodebaseIndex, MAX_INDEX_FILE_BYTES};
+use crate::util::resolve_repo_path_allow_new;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
-use std::process::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use walkdir::WalkDir;
+
+/// Tool output is cut at this many bytes (~1k tokens), at a line boundary
+const MAX_OUTPUT_BYTES: usize = 4000;
+
+/// Lines `read_file` returns when no end is given
+const DEFAULT_READ_LINES: usize = 200;
+
+/// `grep` stops after this many matching lines
+const MAX_GREP_MATCHES: usize = 100;
 
 /// Tool definitions for the LLM
 #[derive(Debug, Clone, Serialize)]
@@ -42,37 +63,569 @@ pub struct ToolResult {
     pub content: String,
 }
 
-/// Get all available tool definitions - just one powerful shell tool
-pub fn get_tool_definitions() -> Vec<ToolDefinition> {
-    vec![ToolDefinition {
+/// `tools` section of the user and project config
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolSettings {
+    /// Offer the raw `shell` tool (on unless set to false)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shell: Option<bool>,
+}
+
+impl ToolSettings {
+    /// Either config can turn the shell off; a repo can't turn it back on
+    /// for a user who disabled it
+    pub fn shell_enabled(user: Option<&ToolSettings>, project: Option<&ToolSettings>) -> bool {
+        [user, project]
+            .into_iter()
+            .flatten()
+            .all(|settings| settings.shell != Some(false))
+    }
+}
+
+fn function(
+    name: &'static str,
+    description: &'static str,
+    parameters: serde_json::Value,
+) -> ToolDefinition {
+    ToolDefinition {
         tool_type: "function",
         function: FunctionDefinition {
-            name: "shell",
-            description: r#"Execute a shell command. Output truncated at 4KB.
+            name,
+            description,
+            parameters,
+        },
+    }
+}
+
+fn structured_tool_definitions() -> Vec<ToolDefinition> {
+    vec![
+        function(
+            "read_file",
+            "Read lines of a file, numbered. Lines are 1-based and inclusive; without `end` you get 200 lines from `start`. Read the lines around a match rather than whole files.",
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Path relative to the repo root" },
+                    "start": { "type": "integer", "description": "First line (default 1)" },
+                    "end": { "type": "integer", "description": "Last line" }
+                },
+                "required": ["path"]
+            }),
+        ),
+        function(
+            "grep",
+            "Search file contents with a regex. Returns `path:line: text` for up to 100 matches.",
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "pattern": { "type": "string", "description": "Regular expression (Rust syntax)" },
+                    "glob": { "type": "string", "description": "Only search matching files, e.g. `*.rs` or `src/**/*.ts`" }
+                },
+                "required": ["pattern"]
+            }),
+        ),
+        function(
+            "list_dir",
+            "List a directory. Subdirectories end in `/`.",
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Directory relative to the repo root (default: the root)" }
+                }
+            }),
+        ),
+        function(
+            "find_symbol",
+            "Find where a function, type, constant or other symbol is defined. Returns `path:start-end kind name`.",
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "description": "Exact symbol name" }
+                },
+                "required": ["name"]
+            }),
+        ),
+        function(
+            "who_imports",
+            "List the files that import a file.",
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Path relative to the repo root" }
+                },
+                "required": ["path"]
+            }),
+        ),
+        function(
+            "file_summary",
+            "Summarize a file without reading it: purpose, exports, what it depends on and what uses it.",
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Path relative to the repo root" }
+                },
+                "required": ["path"]
+            }),
+        ),
+    ]
+}
+
+fn shell_tool_definition() -> ToolDefinition {
+    function(
+        "shell",
+        r#"Run a shell command in a read-only sandbox: no writes to the repo, no network, 20s limit. Output truncated at 4KB.
+Prefer read_file, grep and find_symbol; use this for anything they can't do.
 
 SURGICAL WORKFLOW (saves tokens):
 1. grep -n 'pattern' <file> → find line numbers
 2. sed -n '50,80p' <file> → read 30 lines around match
 
-COMMANDS:
-• grep -n 'fn foo' file.rs → find function
-• sed -n '45,75p' file.rs → read around line 60
-• head -50 file.rs → file start
-• rg 'pattern' → search codebase
-
 AVOID: cat (too many tokens)"#,
-            parameters: serde_json::json!({
-                "type": "object",
-                "properties": {
-                    "command": {
-                        "type": "string",
-                        "description": "The shell command to execute"
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "command": {
+                    "type": "string",
+                    "description": "The shell command to execute"
+                }
+            },
+            "required": ["command"]
+        }),
+    )
+}
+
+const NOT_INDEXED: &str =
+    "The repository is not indexed yet. Use grep, list_dir or read_file instead.";
+
+/// What the tools of one agentic call can see and do
+pub struct ToolContext {
+    root: PathBuf,
+    shell: bool,
+    /// Read from the index cache on the first index query. A miss isn't
+    /// re-indexed here: that would stall the tool call on a full parse.
+    index: OnceLock<Option<CodebaseIndex>>,
+}
+
+impl ToolContext {
+    pub fn new(root: &Path, shell: bool) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            shell,
+            index: OnceLock::new(),
+        }
+    }
+
+    /// Tools for `root`, with the shell as the user's and repo's config allow
+    pub fn for_repo(root: &Path) -> Self {
+        let user = Config::load();
+        let project = Cache::new(root).load_project_config();
+        let shell = ToolSettings::shell_enabled(user.tools.as_ref(), project.tools.as_ref());
+        Self::new(root, shell)
+    }
+
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        let mut tools = structured_tool_definitions();
+        if self.shell {
+            tools.push(shell_tool_definition());
+        }
+        tools
+    }
+
+    /// Execute a tool call and return the result
+    pub fn execute(&self, tool_call: &ToolCall) -> ToolResult {
+        let content = match serde_json::from_str::<Value>(&tool_call.function.arguments) {
+            Err(e) => format!("Invalid arguments: {}", e),
+            Ok(args) => match tool_call.function.name.as_str() {
+                "read_file" => self.read_file(&args),
+                "grep" => self.grep(&args),
+                "list_dir" => self.list_dir(&args),
+                "find_symbol" => self.find_symbol(&args),
+                "who_imports" => self.who_imports(&args),
+                "file_summary" => self.file_summary(&args),
+                "shell" if self.shell => execute_shell(&self.root, &tool_call.function.arguments),
+                "shell" => "The shell tool is disabled for this repository. Use read_file, grep, list_dir, find_symbol, who_imports or file_summary.".to_string(),
+                _ => format!("Unknown tool: {}", tool_call.function.name),
+            },
+        };
+
+        ToolResult {
+            tool_call_id: tool_call.id.clone(),
+            content: cap_output(content),
+        }
+    }
+
+    /// Resolve a repo-relative path argument, refusing anything outside the repo
+    fn resolve(&self, args: &Value, key: &str) -> Result<(PathBuf, PathBuf), String> {
+        let raw = args
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .unwrap_or("");
+        if raw.is_empty() || raw == "." {
+            return Ok((self.root.clone(), PathBuf::new()));
+        }
+        let candidate = Path::new(raw.strip_prefix("./").unwrap_or(raw));
+        let resolved = resolve_repo_path_allow_new(&self.root, candidate)?;
+        // The leaf itself may be a symlink out of the repo
+        if let Ok(target) = resolved.absolute.canonicalize() {
+            let root = self
+                .root
+                .canonicalize()
+                .map_err(|e| format!("Failed to resolve repo root: {}", e))?;
+            if !target.starts_with(&root) {
+                return Err(format!("Path escapes repository: {}", candidate.display()));
+            }
+            return Ok((target, resolved.relative));
+        }
+        Ok((resolved.absolute, resolved.relative))
+    }
+
+    fn index(&self) -> Option<&CodebaseIndex> {
+        self.index
+            .get_or_init(|| {
+                Cache::new(&self.root)
+                    .load_index_cache(&self.root)
+                    .map(|cached| cached.index)
+            })
+            .as_ref()
+    }
+
+    fn read_file(&self, args: &Value) -> String {
+        let (absolute, relative) = match self.resolve(args, "path") {
+            Ok(paths) if !paths.1.as_os_str().is_empty() => paths,
+            Ok(_) => return "Missing `path`".to_string(),
+            Err(e) => return e,
+        };
+        match fs::metadata(&absolute) {
+            Ok(meta) if meta.len() > MAX_INDEX_FILE_BYTES => {
+                return format!(
+                    "{} is too large to read ({} bytes, limit {})",
+                    relative.display(),
+                    meta.len(),
+                    MAX_INDEX_FILE_BYTES
+                )
+            }
+            Ok(_) => {}
+            Err(e) => return format!("Cannot read {}: {}", relative.display(), e),
+        }
+        let content = match fs::read_to_string(&absolute) {
+            Ok(content) => content,
+            Err(e) => return format!("Cannot read {}: {}", relative.display(), e),
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        let start = args
+            .get("start")
+            .and_then(Value::as_u64)
+            .map_or(1, |n| usize::try_from(n.max(1)).unwrap_or(usize::MAX));
+        let end = args
+            .get("end")
+            .and_then(Value::as_u64)
+            .map_or(start.saturating_add(DEFAULT_READ_LINES - 1), |n| {
+                usize::try_from(n).unwrap_or(usize::MAX)
+            })
+            .min(lines.len());
+        if start > end {
+            return format!(
+                "{} has {} lines; nothing in {}-{}",
+                relative.display(),
+                lines.len(),
+                start,
+                end
+            );
+        }
+        let mut out = format!(
+            "{} (lines {}-{} of {})\n",
+            relative.display(),
+            start,
+            end,
+            lines.len()
+        );
+        for (number, line) in lines.iter().enumerate().take(end).skip(start - 1) {
+            out.push_str(&format!("{:>5}  {}\n", number + 1, line));
+        }
+        out
+    }
+
+    fn grep(&self, args: &Value) -> String {
+        let Some(pattern) = args.get("pattern").and_then(Value::as_str) else {
+            return "Missing `pattern`".to_string();
+        };
+        let regex = match Regex::new(pattern) {
+            Ok(regex) => regex,
+            Err(e) => return format!("Invalid pattern: {}", e),
+        };
+        let glob = match args.get("glob").and_then(Value::as_str) {
+            Some(glob) if !glob.trim().is_empty() => match glob_regex(glob.trim()) {
+                Ok(re) => Some((re, glob.contains('/'))),
+                Err(e) => return format!("Invalid glob: {}", e),
+            },
+            _ => None,
+        };
+
+        let mut matches = Vec::new();
+        for entry in walk(&self.root) {
+            let Ok(relative) = entry.path().strip_prefix(&self.root) else {
+                continue;
+            };
+            if let Some((glob, whole_path)) = &glob {
+                let subject = if *whole_path {
+                    relative.to_string_lossy()
+                } else {
+                    entry.file_name().to_string_lossy()
+                };
+                if !glob.is_match(&subject) {
+                    continue;
+                }
+            }
+            if entry
+                .metadata()
+                .map_or(true, |m| m.len() > MAX_INDEX_FILE_BYTES)
+            {
+                continue;
+            }
+            // Binary and non-UTF-8 files are skipped
+            let Ok(content) = fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            for (number, line) in content.lines().enumerate() {
+                if regex.is_match(line) {
+                    matches.push(format!(
+                        "{}:{}: {}",
+                        relative.display(),
+                        number + 1,
+                        crate::util::truncate(line.trim(), 200)
+                    ));
+                    if matches.len() == MAX_GREP_MATCHES {
+                        matches.push(format!("... (stopped at {} matches)", MAX_GREP_MATCHES));
+                        return matches.join("\n");
                     }
-                },
-                "required": ["command"]
-            }),
-        },
-    }]
+                }
+            }
+        }
+        if matches.is_empty() {
+            "No matches".to_string()
+        } else {
+            matches.join("\n")
+        }
+    }
+
+    fn list_dir(&self, args: &Value) -> String {
+        let (absolute, relative) = match self.resolve(args, "path") {
+            Ok(paths) => paths,
+            Err(e) => return e,
+        };
+        let entries = match fs::read_dir(&absolute) {
+            Ok(entries) => entries,
+            Err(e) => return format!("Cannot list {}: {}", relative.display(), e),
+        };
+        let mut names: Vec<String> = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| !is_ignored(&e.path()))
+            .map(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                if e.path().is_dir() {
+                    format!("{}/", name)
+                } else {
+                    name
+                }
+            })
+            .collect();
+        names.sort();
+        if names.is_empty() {
+            "(empty)".to_string()
+        } else {
+            names.join("\n")
+        }
+    }
+
+    fn find_symbol(&self, args: &Value) -> String {
+        let Some(name) = args.get("name").and_then(Value::as_str).map(str::trim) else {
+            return "Missing `name`".to_string();
+        };
+        let Some(index) = self.index() else {
+            return NOT_INDEXED.to_string();
+        };
+        let format = |s: crate::index::symbols::SymbolRef| {
+            format!(
+                "{}:{}-{} {:?} {}",
+                s.file.display(),
+                s.line,
+                s.end_line,
+                s.kind,
+                s.name
+            )
+        };
+        let exact: Vec<String> = index.symbols.find_symbol(name).map(format).collect();
+        if !exact.is_empty() {
+            return exact.join("\n");
+        }
+        let needle = name.to_lowercase();
+        let similar: Vec<String> = index
+            .symbols
+            .iter()
+            .filter(|s| s.name.to_lowercase().contains(&needle))
+            .take(20)
+            .map(format)
+            .collect();
+        if similar.is_empty() {
+            format!("No symbol named {}", name)
+        } else {
+            format!(
+                "No symbol named exactly {}. Similar:\n{}",
+                name,
+                similar.join("\n")
+            )
+        }
+    }
+
+    fn who_imports(&self, args: &Value) -> String {
+        let relative = match self.resolve(args, "path") {
+            Ok((_, relative)) if !relative.as_os_str().is_empty() => relative,
+            Ok(_) => return "Missing `path`".to_string(),
+            Err(e) => return e,
+        };
+        let Some(index) = self.index() else {
+            return NOT_INDEXED.to_string();
+        };
+        match index.files.get(&relative) {
+            None => format!("{} is not in the code index", relative.display()),
+            Some(file) if file.summary.used_by.is_empty() => {
+                format!("No indexed file imports {}", relative.display())
+            }
+            Some(file) => {
+                let mut users: Vec<String> = file
+                    .summary
+                    .used_by
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect();
+                users.sort();
+                users.join("\n")
+            }
+        }
+    }
+
+    fn file_summary(&self, args: &Value) -> String {
+        let relative = match self.resolve(args, "path") {
+            Ok((_, relative)) if !relative.as_os_str().is_empty() => relative,
+            Ok(_) => return "Missing `path`".to_string(),
+            Err(e) => return e,
+        };
+        let Some(index) = self.index() else {
+            return NOT_INDEXED.to_string();
+        };
+        let Some(file) = index.files.get(&relative) else {
+            return format!("{} is not in the code index", relative.display());
+        };
+        let list = |paths: &[PathBuf]| {
+            if paths.is_empty() {
+                "none".to_string()
+            } else {
+                paths
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        };
+        let mut out = format!("{}\n", relative.display());
+        if let Some(entry) = Cache::new(&self.root)
+            .load_llm_summaries_cache()
+            .and_then(|cache| cache.summaries.get(&relative).cloned())
+        {
+            out.push_str(&format!("Summary: {}\n", entry.summary));
+        }
+        let summary = &file.summary;
+        out.push_str(&format!("Purpose: {}\n", summary.purpose));
+        out.push_str(&format!("Metrics: {}\n", summary.metrics));
+        if !summary.exports.is_empty() {
+            out.push_str(&format!("Exports: {}\n", summary.exports.join(", ")));
+        }
+        out.push_str(&format!("Depends on: {}\n", list(&summary.depends_on)));
+        out.push_str(&format!("Used by: {}\n", list(&summary.used_by)));
+        out
+    }
+}
+
+/// Files under `root`, skipping the directories the indexer ignores
+fn walk(root: &Path) -> impl Iterator<Item = walkdir::DirEntry> {
+    WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !is_ignored(e.path()))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+}
+
+/// Shell-style glob (`*`, `**`, `?`) as an anchored regex
+fn glob_regex(glob: &str) -> Result<Regex, regex::Error> {
+    let mut pattern = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    pattern.push_str("(?:.*/)?");
+                } else {
+                    pattern.push_str(".*");
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    Regex::new(&pattern)
+}
+
+/// Truncate at a line boundary to keep output clean. 4KB ≈ 1k tokens,
+/// which forces surgical, targeted reads.
+fn cap_output(result: String) -> String {
+    if result.len() <= MAX_OUTPUT_BYTES {
+        return result;
+    }
+    let mut limit = MAX_OUTPUT_BYTES;
+    while !result.is_char_boundary(limit) {
+        limit -= 1;
+    }
+    // Find last newline before limit to avoid cutting mid-line
+    let truncate_at = result[..limit].rfind('\n').unwrap_or(limit);
+    format!(
+        "{}\n\n... (truncated - read narrower line ranges or grep for specific sections)",
+        &result[..truncate_at]
+    )
+}
+
+/// One-line description of a tool call for progress display
+pub fn describe_tool_call(tool_call: &ToolCall) -> String {
+    let args: Value = serde_json::from_str(&tool_call.function.arguments).unwrap_or_default();
+    let arg = |key: &str| args.get(key).and_then(Value::as_str).unwrap_or("").trim();
+    match tool_call.function.name.as_str() {
+        "shell" if !arg("command").is_empty() => format!("$ {}", arg("command")),
+        "shell" => "Running a shell command".to_string(),
+        "read_file" => {
+            let start = args.get("start").and_then(Value::as_u64);
+            let end = args.get("end").and_then(Value::as_u64);
+            match (start, end) {
+                (Some(start), Some(end)) => format!("Reading {}:{}-{}", arg("path"), start, end),
+                (Some(start), None) => format!("Reading {} from line {}", arg("path"), start),
+                _ => format!("Reading {}", arg("path")),
+            }
+        }
+        "grep" if !arg("glob").is_empty() => {
+            format!("Searching {} for /{}/", arg("glob"), arg("pattern"))
+        }
+        "grep" => format!("Searching for /{}/", arg("pattern")),
+        "list_dir" if arg("path").is_empty() => "Listing the repo root".to_string(),
+        "list_dir" => format!("Listing {}", arg("path")),
+        "find_symbol" => format!("Finding {}", arg("name")),
+        "who_imports" => format!("Finding importers of {}", arg("path")),
+        "file_summary" => format!("Summarizing {}", arg("path")),
+        name => format!("Calling {}", name),
+    }
 }
 
 /// Commands/patterns that are blocked for safety (system-level destruction)
@@ -93,19 +646,6 @@ const BLOCKED_PATTERNS: &[&str] = &[
     "wget | bash",
 ];
 
-/// Execute a tool call and return the result
-pub fn execute_tool(root: &Path, tool_call: &ToolCall) -> ToolResult {
-    let content = match tool_call.function.name.as_str() {
-        "shell" => execute_shell(root, &tool_call.function.arguments),
-        _ => format!("Unknown tool: {}", tool_call.function.name),
-    };
-
-    ToolResult {
-        tool_call_id: tool_call.id.clone(),
-        content,
-    }
-}
-
 /// Execute shell command with safety checks
 fn execute_shell(root: &Path, args_json: &str) -> String {
     #[derive(Deserialize)]
@@ -136,52 +676,40 @@ fn execute_shell(root: &Path, args_json: &str) -> String {
         return format!("Repository root does not exist: {}", root.display());
     }
 
-    // Execute the command
-    let output = Command::new("sh")
-        .args(["-c", command])
-        .current_dir(root)
-        .output();
-
-    match output {
-        Ok(out) => {
-            let stdout = String::from_utf8_lossy(&out.stdout);
-            let stderr = String::from_utf8_lossy(&out.stderr);
-            let exit_code = out.status.code().unwrap_or(-1);
-
-            let mut result = String::new();
-
-            if !stdout.is_empty() {
-                result.push_str(&stdout);
-            }
+    let out = match sandbox::run(root, command, sandbox::SHELL_TIMEOUT) {
+        Ok(out) => out,
+        Err(e) => return e,
+    };
 
-            if !stderr.is_empty() {
-                if !result.is_empty() {
-                    result.push_str("\n--- stderr ---\n");
-                }
-                result.push_str(&stderr);
-            }
+    let mut result = String::new();
 
-            if result.is_empty() {
-                result = format!("Command completed with exit code {}", exit_code);
-            } else if exit_code != 0 {
-                result.push_str(&format!("\n[exit code: {}]", exit_code));
-            }
+    if !out.stdout.is_empty() {
+        result.push_str(&out.stdout);
+    }
 
-            // Truncate at line boundary to keep output clean
-            // 4KB ≈ 1k tokens - forces surgical, targeted reads
-            if result.len() > 4000 {
-                // Find last newline before limit to avoid cutting mid-line
-                let truncate_at = result[..4000].rfind('\n').unwrap_or(4000);
-                format!(
-                    "{}\n\n... (truncated - use grep/sed for specific sections)",
-                    &result[..truncate_at]
-                )
-            } else {
-                result
-            }
+    if !out.stderr.is_empty() {
+        if !result.is_empty() {
+            result.push_str("\n--- stderr ---\n");
         }
-        Err(e) => format!("Failed to execute command: {}", e),
+        result.push_str(&out.stderr);
+    }
+
+    let exit_code = out.status.and_then(|s| s.code()).unwrap_or(-1);
+    if out.timed_out {
+        result.push_str(&format!(
+            "\n[timed out after {}s; the command was killed]",
+            sandbox::SHELL_TIMEOUT.as_secs()
+        ));
+    } else if result.is_empty() {
+        result = format!("Command completed with exit code {}", exit_code);
+    } else if exit_code != 0 {
+        result.push_str(&format!("\n[exit code: {}]", exit_code));
     }
+    if out.truncated {
+        result.push_str("\n[output truncated]");
+    }
+
+    result
 }
 
 #[cfg(test)]
@@ -190,6 +718,20 @@ mod tests {
     use std::fs;
     use tempfile::tempdir;
 
+    fn execute_tool(root: &Path, call: &ToolCall) -> ToolResult {
+        ToolContext::new(root, true).execute(call)
+    }
+
+    fn call(name: &str, arguments: serde_json::Value) -> ToolCall {
+        ToolCall {
+            id: "1".to_string(),
+            function: FunctionCall {
+                name: name.to_string(),
+                arguments: arguments.to_string(),
+            },
+        }
+    }
+
     #[test]
     fn test_shell_echo() {
         let dir = tempdir().unwrap();
@@ -284,7 +826,7 @@ mod tests {
     }
 
     #[test]
-    fn test_shell_allows_rm_in_repo() {
+    fn test_shell_cannot_rm_in_repo() {
         let dir = tempdir().unwrap();
         let file = dir.path().join("delete_me.txt");
         fs::write(&file, "temporary").unwrap();
@@ -298,10 +840,9 @@ mod tests {
             },
         };
 
-        let result = execute_tool(dir.path(), &call);
-        // Should succeed - rm within repo is allowed
-        assert!(!result.content.contains("blocked"));
-        assert!(!file.exists()); // File should be deleted
+        execute_tool(dir.path(), &call);
+        // The sandbox keeps the repo read-only
+        assert!(file.exists());
     }
 
     #[test]
@@ -419,7 +960,7 @@ mod tests {
     }
 
     #[test]
-    fn test_shell_write_file() {
+    fn test_shell_cannot_write_file() {
         let dir = tempdir().unwrap();
 
         let call = ToolCall {
@@ -430,18 +971,12 @@ mod tests {
             },
         };
 
-        let result = execute_tool(dir.path(), &call);
-        assert!(!result.content.contains("blocked"));
-
-        // Verify file was created
-        let created = dir.path().join("newfile.txt");
-        assert!(created.exists());
-        let content = fs::read_to_string(&created).unwrap();
-        assert!(content.contains("new content"));
+        execute_tool(dir.path(), &call);
+        assert!(!dir.path().join("newfile.txt").exists());
     }
 
     #[test]
-    fn test_shell_sed_edit() {
+    fn test_shell_cannot_sed_edit() {
         let dir = tempdir().unwrap();
         let file = dir.path().join("test.txt");
         fs::write(&file, "hello world").unwrap();
@@ -450,17 +985,12 @@ mod tests {
             id: "1".to_string(),
             function: FunctionCall {
                 name: "shell".to_string(),
-                // macOS sed requires -i '' for in-place editing
                 arguments: r#"{"command": "sed -i.bak 's/hello/goodbye/' test.txt"}"#.to_string(),
             },
         };
 
-        let result = execute_tool(dir.path(), &call);
-        assert!(!result.content.contains("blocked"));
-
-        let content = fs::read_to_string(&file).unwrap();
-        assert!(content.contains("goodbye"));
-        assert!(!content.contains("hello"));
+        execute_tool(dir.path(), &call);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "hello world");
     }
 
     #[test]
@@ -503,13 +1033,195 @@ mod tests {
 
     #[test]
     fn test_tool_definitions() {
-        let tools = get_tool_definitions();
-        assert_eq!(tools.len(), 1);
+        let dir = tempdir().unwrap();
+        let names = |shell: bool| -> Vec<&'static str> {
+            ToolContext::new(dir.path(), shell)
+                .definitions()
+                .iter()
+                .map(|t| t.function.name)
+                .collect()
+        };
+        assert_eq!(
+            names(false),
+            [
+                "read_file",
+                "grep",
+                "list_dir",
+                "find_symbol",
+                "who_imports",
+                "file_summary"
+            ]
+        );
+        assert_eq!(names(true).last(), Some(&"shell"));
+    }
+
+    #[test]
+    fn test_shell_disabled_is_refused() {
+        let dir = tempdir().unwrap();
+        let result = ToolContext::new(dir.path(), false)
+            .execute(&call("shell", serde_json::json!({"command": "touch x"})));
+        assert!(result.content.contains("disabled"));
+        assert!(!dir.path().join("x").exists());
+    }
+
+    #[test]
+    fn test_shell_settings_layering() {
+        let off = ToolSettings { shell: Some(false) };
+        let on = ToolSettings { shell: Some(true) };
+        assert!(ToolSettings::shell_enabled(None, None));
+        assert!(!ToolSettings::shell_enabled(Some(&off), None));
+        assert!(!ToolSettings::shell_enabled(None, Some(&off)));
+        // A repo can't re-enable what the user turned off
+        assert!(!ToolSettings::shell_enabled(Some(&off), Some(&on)));
+    }
+
+    #[test]
+    fn test_read_file_range_and_path_checks() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "one\ntwo\nthree\nfour").unwrap();
+
+        let result = execute_tool(
+            dir.path(),
+            &call(
+                "read_file",
+                serde_json::json!({"path": "a.txt", "start": 2, "end": 3}),
+            ),
+        );
+        assert!(result.content.starts_with("a.txt (lines 2-3 of 4)"));
+        assert!(result.content.contains("    2  two"));
+        assert!(result.content.contains("    3  three"));
+        assert!(!result.content.contains("four"));
+
+        for path in ["../a.txt", "/etc/passwd"] {
+            let result = execute_tool(
+                dir.path(),
+                &call("read_file", serde_json::json!({ "path": path })),
+            );
+            assert!(result.content.contains("not allowed"), "{}", result.content);
+        }
+
+        let result = execute_tool(
+            dir.path(),
+            &call(
+                "read_file",
+                serde_json::json!({"path": "a.txt", "start": u64::MAX}),
+            ),
+        );
+        assert!(result.content.contains("nothing in"), "{}", result.content);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_leaf_outside_repo_is_refused() {
+        let dir = tempdir().unwrap();
+        let outside = tempdir().unwrap();
+        fs::write(outside.path().join("id_rsa"), "secret key").unwrap();
+        fs::create_dir(outside.path().join("keys")).unwrap();
+        std::os::unix::fs::symlink(outside.path().join("id_rsa"), dir.path().join("link")).unwrap();
+        std::os::unix::fs::symlink(outside.path().join("keys"), dir.path().join("keys")).unwrap();
+
+        let read = execute_tool(
+            dir.path(),
+            &call("read_file", serde_json::json!({"path": "link"})),
+        );
+        assert!(!read.content.contains("secret key"));
+        assert!(
+            read.content.contains("escapes repository"),
+            "{}",
+            read.content
+        );
+        let listed = execute_tool(
+            dir.path(),
+            &call("list_dir", serde_json::json!({"path": "keys"})),
+        );
+        assert!(
+            listed.content.contains("escapes repository"),
+            "{}",
+            listed.content
+        );
+    }
+
+    #[test]
+    fn test_grep_with_glob_and_list_dir() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+        fs::create_dir_all(dir.path().join("node_modules")).unwrap();
+        fs::write(dir.path().join("src/lib.rs"), "fn parse() {}\n").unwrap();
+        fs::write(dir.path().join("src/nested/deep.rs"), "// parse later\n").unwrap();
+        fs::write(dir.path().join("src/notes.md"), "parse\n").unwrap();
+        fs::write(dir.path().join("node_modules/dep.rs"), "fn parse() {}\n").unwrap();
+
+        let result = execute_tool(
+            dir.path(),
+            &call(
+                "grep",
+                serde_json::json!({"pattern": "parse", "glob": "src/**/*.rs"}),
+            ),
+        );
+        assert_eq!(
+            result.content,
+            "src/lib.rs:1: fn parse() {}\nsrc/nested/deep.rs:1: // parse later"
+        );
+
+        let result = execute_tool(dir.path(), &call("list_dir", serde_json::json!({})));
+        assert_eq!(result.content, "src/");
+        let result = execute_tool(
+            dir.path(),
+            &call("list_dir", serde_json::json!({"path": "src"})),
+        );
+        assert_eq!(result.content, "lib.rs\nnested/\nnotes.md");
+    }
+
+    #[test]
+    fn test_index_tools() {
+        // The indexer skips dot-directories, so not `tempdir()` (`.tmpXXXX`)
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("cosmos_tools_index_{}", nanos));
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(
+            root.join("src/lib.rs"),
+            "mod util;\n\npub fn run() {\n    util::helper();\n}\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/util.rs"), "pub fn helper() {}\n").unwrap();
+        let tools = ToolContext::new(&root, false);
+        let result = tools.execute(&call("find_symbol", serde_json::json!({"name": "helper"})));
+        assert!(result.content.contains("not indexed"), "{}", result.content);
+
+        let index = CodebaseIndex::new(&root).unwrap();
+        Cache::new(&root).save_index_cache(&index).unwrap();
+        let tools = ToolContext::new(&root, false);
+
+        let result = tools.execute(&call("find_symbol", serde_json::json!({"name": "helper"})));
+        assert!(
+            result.content.starts_with("src/util.rs:1-1"),
+            "{}",
+            result.content
+        );
+
+        let result = tools.execute(&call("find_symbol", serde_json::json!({"name": "help"})));
+        assert!(result.content.contains("Similar:"), "{}", result.content);
+
+        let result = tools.execute(&call(
+            "file_summary",
+            serde_json::json!({"path": "src/util.rs"}),
+        ));
+        assert!(
+            result.content.contains("Exports: helper"),
+            "{}",
+            result.content
+        );
+
+        let result = tools.execute(&call(
+            "who_imports",
+            serde_json::json!({"path": "src/nope.rs"}),
+        ));
+        assert!(result.content.contains("not in the code index"));
 
-        let shell_tool = &tools[0];
-        assert_eq!(shell_tool.tool_type, "function");
-        assert_eq!(shell_tool.function.name, "shell");
-        assert!(shell_tool.function.description.contains("shell"));
+        let _ = fs::remove_dir_all(&root);
     }
 
     #[test]
@@ -46,6 +46,8 @@ pub enum SuggestionKind {
     Testing,
     /// Code refactoring (extract, rename, restructure)
     Refactoring,
+    /// Security risk (injection sinks, unsafe input handling)
+    Security,
 }
 
 impl SuggestionKind {
@@ -59,6 +61,7 @@ impl SuggestionKind {
             SuggestionKind::Documentation => "Docs",
             SuggestionKind::Testing => "Test",
             SuggestionKind::Refactoring => "Refactor",
+            SuggestionKind::Security => "Security",
         }
     }
 }
@@ -193,7 +196,15 @@ impl SuggestionEngine {
     /// Add a suggestion from LLM
     pub fn add_llm_suggestion(&mut self, suggestion: Suggestion) {
         self.suggestions.push(suggestion);
-        self.suggestions.sort_by(|a, b| b.priority.cmp(&a.priority));
+        self.suggestions
+            .sort_by_key(|s| std::cmp::Reverse(s.priority));
+    }
+
+    /// Add suggestions from static checks (manifests, lockfiles)
+    pub fn add_static_suggestions(&mut self, suggestions: Vec<Suggestion>) {
+        self.suggestions.extend(suggestions);
+        self.suggestions
+            .sort_by_key(|s| std::cmp::Reverse(s.priority));
     }
 
     /// Sort suggestions by priority first, then git context (changed files, blast radius).
@@ -219,6 +230,7 @@ impl SuggestionEngine {
 
         let kind_weight = |k: SuggestionKind| -> i64 {
             match k {
+                SuggestionKind::Security => 45,
                 SuggestionKind::BugFix => 40,
                 SuggestionKind::Refactoring => 30,
                 SuggestionKind::Optimization => 25,
@@ -15,9 +15,9 @@ pub use render::render;
 
 // Re-export all types for backward compatibility
 pub use types::{
-    ActivePanel, AskCosmosState, FileChange, InputMode, LoadingState, Overlay, PendingChange,
-    ReviewState, ShipState, ShipStep, Toast, ToastKind, VerifyState, ViewMode, WorkflowStep,
-    SPINNER_FRAMES,
+    ActivePanel, AskCosmosState, BudgetReplay, FileChange, InputMode, LoadingState, MemoryMode,
+    Overlay, PendingChange, ReviewState, ShipState, ShipStep, Toast, ToastKind, VerifyState,
+    ViewMode, WorkflowStep, SPINNER_FRAMES,
 };
 
 use crate::context::WorkContext;
@@ -75,6 +75,12 @@ pub struct App {
     // Question answer cache
     pub question_cache: crate::cache::QuestionCache,
 
+    // Per-project settings (.cosmos/config.json)
+    pub project_config: crate::config::ProjectConfig,
+
+    // Dependency licenses checked against the project's policy
+    pub license_report: crate::manifest::license::LicenseReport,
+
     // Cost tracking
     pub session_cost: f64,            // Total USD spent this session
     pub session_tokens: u32,          // Total tokens used this session
@@ -86,6 +92,8 @@ pub struct App {
 
     // Summary generation progress (completed, total)
     pub summary_progress: Option<(usize, usize)>,
+    /// Latest step of a running analysis (e.g. the tool call being made)
+    pub analysis_progress: Option<String>,
     /// Files that failed summary generation (for retry visibility)
     pub summary_failed_files: Vec<PathBuf>,
 
@@ -167,12 +175,15 @@ impl App {
             repo_memory: crate::cache::RepoMemory::default(),
             glossary: crate::cache::DomainGlossary::default(),
             question_cache: crate::cache::QuestionCache::default(),
+            project_config: crate::config::ProjectConfig::default(),
+            license_report: crate::manifest::license::LicenseReport::default(),
             session_cost: 0.0,
             session_tokens: 0,
             active_model: None,
             wallet_balance: None,
             needs_summary_generation: false,
             summary_progress: None,
+            analysis_progress: None,
             summary_failed_files: Vec::new(),
             file_tree,
             filtered_tree,
@@ -292,11 +303,20 @@ impl App {
         self.apply_filter();
     }
 
-    /// Enter question mode
+    /// Enter question mode; with a conversation open, the question is a
+    /// follow-up and no openers are suggested
     pub fn start_question(&mut self) {
         self.input_mode = InputMode::Question;
         self.question_input.clear();
-        self.question_suggestions = Self::generate_question_suggestions();
+        let follow_up = self
+            .ask_cosmos_state
+            .as_ref()
+            .is_some_and(|state| !state.thread.turns.is_empty());
+        self.question_suggestions = if follow_up {
+            Vec::new()
+        } else {
+            Self::generate_question_suggestions()
+        };
         self.question_suggestion_selected = 0;
     }
 
@@ -602,25 +622,23 @@ impl App {
 
     /// Show file detail overlay for currently selected file
     pub fn show_file_detail(&mut self) {
-        match self.view_mode {
-            ViewMode::Flat => {
-                if let Some(entry) = self.filtered_tree.get(self.project_selected) {
-                    self.overlay = Overlay::FileDetail {
-                        path: entry.path.clone(),
-                        scroll: 0,
-                    };
-                }
-            }
-            ViewMode::Grouped => {
-                if let Some(entry) = self.filtered_grouped_tree.get(self.project_selected) {
-                    if let Some(path) = &entry.path {
-                        self.overlay = Overlay::FileDetail {
-                            path: path.clone(),
-                            scroll: 0,
-                        };
-                    }
-                }
-            }
+        let path = match self.view_mode {
+            ViewMode::Flat => self
+                .filtered_tree
+                .get(self.project_selected)
+                .map(|entry| entry.path.clone()),
+            ViewMode::Grouped => self
+                .filtered_grouped_tree
+                .get(self.project_selected)
+                .and_then(|entry| entry.path.clone()),
+        };
+        if let Some(path) = path {
+            let hierarchy = self.index.type_hierarchy_for_file(&path);
+            self.overlay = Overlay::FileDetail {
+                path,
+                scroll: 0,
+                hierarchy,
+            };
         }
     }
 
@@ -711,12 +729,119 @@ impl App {
         self.overlay = Overlay::None;
     }
 
-    /// Show inquiry response in the right panel (Ask Cosmos mode)
+    /// Check the spending caps before an action that calls the LLM for
+    /// `task`. Over a cap, opens the confirm prompt (which replays the action
+    /// on approval) and returns false. The replay itself passes, since it
+    /// holds the approval.
+    pub fn budget_gate(
+        &mut self,
+        task: crate::suggest::llm::models::Task,
+        replay: BudgetReplay,
+    ) -> bool {
+        if crate::suggest::llm::ledger::approval_pending() {
+            return true;
+        }
+        match crate::suggest::llm::ledger::check(task) {
+            Ok(()) => true,
+            Err(overrun) => {
+                self.overlay = Overlay::BudgetConfirm {
+                    message: overrun.to_string(),
+                    replay,
+                };
+                false
+            }
+        }
+    }
+
+    /// Show `question` as the next turn of the open conversation (or a new
+    /// one) while its answer is fetched
+    pub fn begin_inquiry(&mut self, question: String) {
+        let state = self.ask_cosmos_state.get_or_insert_with(Default::default);
+        state.turn = state.thread.turns.len();
+        state.pending = Some(question);
+        state.response.clear();
+        state.scroll = 0;
+        state.streaming = false;
+    }
+
+    /// Complete the pending question with its answer (Ask Cosmos mode).
+    /// Completing a streamed answer keeps the reader's scroll position.
+    /// Without a pending question there is nothing to answer.
     pub fn show_inquiry(&mut self, response: String) {
+        let Some(state) = &mut self.ask_cosmos_state else {
+            return;
+        };
+        let Some(question) = state.pending.take() else {
+            return;
+        };
+        if !state.streaming {
+            state.scroll = 0;
+        }
+        state.thread.turns.push(crate::cache::AskTurn {
+            question,
+            answer: response,
+            asked_at: chrono::Utc::now(),
+        });
+        state.turn = state.thread.turns.len() - 1;
+        state.response.clear();
+        state.streaming = false;
+    }
+
+    /// Append a fragment of a streamed answer to the pending question
+    pub fn append_inquiry(&mut self, text: &str) {
+        let state = self.ask_cosmos_state.get_or_insert_with(Default::default);
+        state.streaming = true;
+        state.response.push_str(text);
+    }
+
+    /// The pending question failed. A partial answer stays readable;
+    /// otherwise the panel goes back to the last answered turn.
+    pub fn fail_inquiry(&mut self) {
+        let Some(state) = &mut self.ask_cosmos_state else {
+            return;
+        };
+        state.streaming = false;
+        if state.pending.is_some() && state.response.is_empty() {
+            state.pending = None;
+            if state.thread.turns.is_empty() {
+                self.ask_cosmos_state = None;
+            } else {
+                state.turn = state.thread.turns.len() - 1;
+            }
+        }
+    }
+
+    /// Drop the open conversation and ask a fresh question
+    pub fn new_conversation(&mut self) {
+        self.ask_cosmos_state = None;
+        self.start_question();
+    }
+
+    /// Reopen a past conversation at its last turn
+    pub fn open_ask_thread(&mut self, thread: crate::cache::AskThread) {
         self.ask_cosmos_state = Some(AskCosmosState {
-            response,
-            scroll: 0,
+            turn: thread.turns.len().saturating_sub(1),
+            thread,
+            ..Default::default()
         });
+        self.workflow_step = WorkflowStep::Suggestions;
+    }
+
+    /// Show the previous (`back`) or next turn of the conversation
+    pub fn ask_cosmos_switch_turn(&mut self, back: bool) {
+        if let Some(state) = &mut self.ask_cosmos_state {
+            let last =
+                (state.thread.turns.len() + usize::from(state.pending.is_some())).saturating_sub(1);
+            let turn = if back {
+                state.turn.saturating_sub(1)
+            } else {
+                (state.turn + 1).min(last)
+            };
+            if turn != state.turn {
+                state.turn = turn;
+                state.scroll = 0;
+            }
+        }
     }
 
     /// Exit ask cosmos mode and return to suggestions
@@ -762,6 +887,209 @@ impl App {
         }
     }
 
+    // ═══════════════════════════════════════════════════════════════════════════
+    //  REPO MEMORY OVERLAY
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Open the repo memory editor
+    pub fn open_memory_overlay(&mut self) {
+        self.overlay = Overlay::Memory {
+            selected: 0,
+            query: String::new(),
+            mode: MemoryMode::Browse,
+        };
+    }
+
+    /// Entries shown in the memory overlay (filtered, newest first)
+    pub fn memory_entries(&self) -> Vec<&crate::cache::MemoryEntry> {
+        match &self.overlay {
+            Overlay::Memory { query, .. } => self.repo_memory.search(query),
+            _ => Vec::new(),
+        }
+    }
+
+    fn memory_selected_id(&self) -> Option<uuid::Uuid> {
+        let Overlay::Memory { selected, .. } = &self.overlay else {
+            return None;
+        };
+        self.memory_entries().get(*selected).map(|e| e.id)
+    }
+
+    fn set_memory_mode(&mut self, new_mode: MemoryMode) {
+        if let Overlay::Memory { mode, .. } = &mut self.overlay {
+            *mode = new_mode;
+        }
+    }
+
+    /// Move the focus in the memory overlay
+    pub fn memory_navigate(&mut self, delta: isize) {
+        let len = self.memory_entries().len();
+        if let Overlay::Memory { selected, .. } = &mut self.overlay {
+            *selected = if delta > 0 {
+                (*selected + delta as usize).min(len.saturating_sub(1))
+            } else {
+                selected.saturating_sub(delta.unsigned_abs())
+            };
+        }
+    }
+
+    pub fn memory_start_search(&mut self) {
+        self.set_memory_mode(MemoryMode::Search);
+    }
+
+    /// Leave search mode, optionally dropping the filter
+    pub fn memory_end_search(&mut self, clear: bool) {
+        if let Overlay::Memory {
+            selected,
+            query,
+            mode,
+        } = &mut self.overlay
+        {
+            if clear {
+                query.clear();
+                *selected = 0;
+            }
+            *mode = MemoryMode::Browse;
+        }
+    }
+
+    /// Start editing the focused entry, or a new one
+    pub fn memory_start_edit(&mut self, new_entry: bool) {
+        let id = if new_entry {
+            None
+        } else {
+            match self.memory_selected_id() {
+                Some(id) => Some(id),
+                None => return,
+            }
+        };
+        let text = id
+            .and_then(|id| self.repo_memory.entries.iter().find(|e| e.id == id))
+            .map(|e| e.text.clone())
+            .unwrap_or_default();
+        self.set_memory_mode(MemoryMode::Edit { id, text });
+    }
+
+    pub fn memory_confirm_delete(&mut self) {
+        if self.memory_selected_id().is_some() {
+            self.set_memory_mode(MemoryMode::ConfirmDelete);
+        }
+    }
+
+    pub fn memory_cancel(&mut self) {
+        self.set_memory_mode(MemoryMode::Browse);
+    }
+
+    /// Type into the search filter or the entry being edited
+    pub fn memory_input_push(&mut self, c: char) {
+        if let Overlay::Memory {
+            selected,
+            query,
+            mode,
+        } = &mut self.overlay
+        {
+            match mode {
+                MemoryMode::Search => {
+                    query.push(c);
+                    *selected = 0;
+                }
+                MemoryMode::Edit { text, .. } => text.push(c),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn memory_input_pop(&mut self) {
+        if let Overlay::Memory {
+            selected,
+            query,
+            mode,
+        } = &mut self.overlay
+        {
+            match mode {
+                MemoryMode::Search => {
+                    query.pop();
+                    *selected = 0;
+                }
+                MemoryMode::Edit { text, .. } => {
+                    text.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Save the entry being edited
+    pub fn memory_commit_edit(&mut self) -> Result<(), String> {
+        let Overlay::Memory {
+            mode: MemoryMode::Edit { id, text },
+            ..
+        } = &self.overlay
+        else {
+            return Ok(());
+        };
+        let (id, text) = (*id, text.trim().to_string());
+        if text.is_empty() {
+            return Err("Memory text can't be empty".to_string());
+        }
+        match id {
+            Some(id) => {
+                self.repo_memory.update(id, &text);
+            }
+            None => {
+                if self.repo_memory.add(&text).is_none() {
+                    return Err("Already remembered".to_string());
+                }
+            }
+        }
+        self.memory_cancel();
+        self.save_repo_memory()
+    }
+
+    /// Delete the focused entry (after confirmation)
+    pub fn memory_delete_selected(&mut self) -> Result<(), String> {
+        if let Some(id) = self.memory_selected_id() {
+            self.repo_memory.remove(id);
+        }
+        self.memory_cancel();
+        self.memory_navigate(0);
+        self.save_repo_memory()
+    }
+
+    /// Share the focused entry with the team, or make it private again.
+    /// Returns the toast to show.
+    pub fn memory_toggle_shared(&mut self) -> Result<String, String> {
+        if !crate::cache::Cache::new(&self.repo_path).is_sharing() {
+            return Err("Run `cosmos share` to share memory with your team".to_string());
+        }
+        let Some(shared) = self
+            .memory_selected_id()
+            .and_then(|id| self.repo_memory.toggle_shared(id))
+        else {
+            return Ok(String::new());
+        };
+        self.save_repo_memory()?;
+        Ok(if shared {
+            "Shared with the team in .cosmos/shared/".to_string()
+        } else {
+            "Kept to this clone".to_string()
+        })
+    }
+
+    /// Save a convention straight from a suggestion or review finding
+    pub fn remember(&mut self, text: &str) -> Result<bool, String> {
+        if self.repo_memory.add(text).is_none() {
+            return Ok(false);
+        }
+        self.save_repo_memory().map(|_| true)
+    }
+
+    fn save_repo_memory(&self) -> Result<(), String> {
+        crate::cache::Cache::new(&self.repo_path)
+            .save_repo_memory(&self.repo_memory)
+            .map_err(|e| format!("Failed to save memory: {}", e))
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     //  RESET COSMOS OVERLAY
     // ═══════════════════════════════════════════════════════════════════════════
@@ -782,12 +1110,40 @@ impl App {
         self.overlay = Overlay::Reset {
             options,
             selected: 0,
+            sizes: Vec::new(),
+            cache_size: 0,
+            budget: 0,
         };
+        self.refresh_reset_sizes();
+    }
+
+    /// Re-measure the caches shown in the reset overlay
+    pub fn refresh_reset_sizes(&mut self) {
+        let cache = crate::cache::Cache::new(&self.repo_path);
+        let budget_bytes = self.project_config.cache_budget_bytes();
+        if let Overlay::Reset {
+            options,
+            sizes,
+            cache_size,
+            budget,
+            ..
+        } = &mut self.overlay
+        {
+            *sizes = options
+                .iter()
+                .map(|(option, _)| cache.reset_option_size(*option))
+                .collect();
+            *cache_size = cache.cache_size();
+            *budget = budget_bytes;
+        }
     }
 
     /// Navigate in reset overlay
     pub fn reset_navigate(&mut self, delta: isize) {
-        if let Overlay::Reset { options, selected } = &mut self.overlay {
+        if let Overlay::Reset {
+            options, selected, ..
+        } = &mut self.overlay
+        {
             let len = options.len();
             if len == 0 {
                 return;
@@ -802,7 +1158,10 @@ impl App {
 
     /// Toggle selection of the currently focused reset option
     pub fn reset_toggle_selected(&mut self) {
-        if let Overlay::Reset { options, selected } = &mut self.overlay {
+        if let Overlay::Reset {
+            options, selected, ..
+        } = &mut self.overlay
+        {
             if let Some((_, is_selected)) = options.get_mut(*selected) {
                 *is_selected = !*is_selected;
             }
@@ -889,7 +1248,10 @@ impl App {
         match &mut self.overlay {
             Overlay::Help { scroll }
             | Overlay::FileDetail { scroll, .. }
-            | Overlay::StartupCheck { scroll, .. } => {
+            | Overlay::StartupCheck { scroll, .. }
+            | Overlay::LicenseReport { scroll }
+            | Overlay::Costs { scroll }
+            | Overlay::ToolTrace { scroll, .. } => {
                 *scroll += 1;
             }
             _ => {}
@@ -901,7 +1263,10 @@ impl App {
         match &mut self.overlay {
             Overlay::Help { scroll }
             | Overlay::FileDetail { scroll, .. }
-            | Overlay::StartupCheck { scroll, .. } => {
+            | Overlay::StartupCheck { scroll, .. }
+            | Overlay::LicenseReport { scroll }
+            | Overlay::Costs { scroll }
+            | Overlay::ToolTrace { scroll, .. } => {
                 *scroll = scroll.saturating_sub(1);
             }
             _ => {}
@@ -1101,6 +1466,7 @@ impl App {
         file_path: PathBuf,
         additional_files: Vec<PathBuf>,
         summary: String,
+        related_tests: Vec<PathBuf>,
     ) {
         self.verify_state = VerifyState {
             suggestion_id: Some(suggestion_id),
@@ -1112,6 +1478,7 @@ impl App {
             scroll: 0,
             show_technical_details: false,
             preview_hashes: std::collections::HashMap::new(),
+            related_tests,
         };
         self.workflow_step = WorkflowStep::Verify;
         self.loading = LoadingState::GeneratingPreview;
@@ -376,7 +376,10 @@ fn get_hint_buttons(app: &App) -> Vec<FooterButton> {
         }
         ActivePanel::Suggestions => match app.workflow_step {
             WorkflowStep::Suggestions => {
-                vec![hint_button("i", "ask")]
+                vec![hint_button("i", "ask"), hint_button("m", "remember")]
+            }
+            WorkflowStep::Review if !app.review_state.findings.is_empty() => {
+                vec![hint_button("m", "remember")]
             }
             _ => vec![],
         },
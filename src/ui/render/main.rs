@@ -5,6 +5,7 @@ use crate::ui::{
     ActivePanel, App, AskCosmosState, InputMode, LoadingState, ShipStep, ViewMode, WorkflowStep,
     SPINNER_FRAMES,
 };
+use crate::util::truncate;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
@@ -511,6 +512,19 @@ fn render_suggestions_content<'a>(
             Span::styled(format!("{} ", spinner), Style::default().fg(Theme::WHITE)),
             Span::styled(message, Style::default().fg(Theme::GREY_300)),
         ]));
+        // What the analysis is doing right now (tool calls)
+        if let (LoadingState::GeneratingSuggestions, Some(step)) =
+            (app.loading, &app.analysis_progress)
+        {
+            let step = step.lines().next().unwrap_or_default();
+            lines.push(Line::from(vec![
+                Span::styled("      ", Style::default()),
+                Span::styled(
+                    truncate(step, inner_width.saturating_sub(8)),
+                    Style::default().fg(Theme::GREY_500),
+                ),
+            ]));
+        }
         return;
     }
 
@@ -812,6 +826,35 @@ fn render_verify_content<'a>(
             content.push(Line::from(""));
         }
 
+        // Tests that exercise the affected code
+        if state.related_tests.is_empty() {
+            content.push(Line::from(vec![
+                Span::styled("  Tests:", Style::default().fg(Theme::GREY_400)),
+                Span::styled(
+                    " none cover this code",
+                    Style::default().fg(Theme::GREY_500),
+                ),
+            ]));
+        } else {
+            content.push(Line::from(vec![Span::styled(
+                "  Tests to run:",
+                Style::default().fg(Theme::GREY_400),
+            )]));
+            for test in state.related_tests.iter().take(5) {
+                content.push(Line::from(vec![Span::styled(
+                    format!("    · {}", test.display()),
+                    Style::default().fg(Theme::GREY_300),
+                )]));
+            }
+            if state.related_tests.len() > 5 {
+                content.push(Line::from(vec![Span::styled(
+                    format!("    +{} more", state.related_tests.len() - 5),
+                    Style::default().fg(Theme::GREY_500),
+                )]));
+            }
+        }
+        content.push(Line::from(""));
+
         // Technical details section (toggleable)
         if state.show_technical_details {
             content.push(Line::from(vec![Span::styled(
@@ -1446,6 +1489,17 @@ fn render_question_mode_content<'a>(lines: &mut Vec<Line<'a>>, app: &App, visibl
 
     lines.push(Line::from(""));
 
+    if let Some(state) = app
+        .ask_cosmos_state
+        .as_ref()
+        .filter(|state| !state.thread.turns.is_empty())
+    {
+        lines.push(Line::from(vec![Span::styled(
+            format!("  Follow-up to: {}", truncate(state.thread.title(), 60)),
+            Style::default().fg(Theme::GREY_400),
+        )]));
+    }
+
     // Show suggested questions when input is empty
     if app.question_input.is_empty() && !app.question_suggestions.is_empty() {
         lines.push(Line::from(vec![Span::styled(
@@ -1548,24 +1602,49 @@ fn render_ask_cosmos_content<'a>(
     visible_height: usize,
     inner_width: usize,
 ) {
-    let _ = app; // silence unused warning
-
     // Top padding for breathing room (matching other panels)
     lines.push(Line::from(""));
 
-    // Parse markdown and render with styling
+    // The turn on screen: an answered one, or the pending question
+    let turns = &ask_state.thread.turns;
+    let (question, answer) = match turns.get(ask_state.turn) {
+        Some(turn) => (turn.question.as_str(), turn.answer.as_str()),
+        None => (
+            ask_state.pending.as_deref().unwrap_or_default(),
+            ask_state.response.as_str(),
+        ),
+    };
+    let turn_count = turns.len() + usize::from(ask_state.pending.is_some());
+
+    // Parse markdown (possibly partial while streaming) and render with styling
     let text_width = inner_width.saturating_sub(6);
-    let parsed_lines = markdown::parse_markdown(&ask_state.response, text_width);
+    let mut padded_lines: Vec<Line<'static>> = Vec::new();
+    for (i, text) in wrap_text(question, text_width).into_iter().enumerate() {
+        padded_lines.push(Line::from(vec![
+            Span::styled(
+                if i == 0 { "  › " } else { "    " },
+                Style::default().fg(Theme::GREY_400),
+            ),
+            Span::styled(
+                text,
+                Style::default()
+                    .fg(Theme::WHITE)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]));
+    }
+    padded_lines.push(Line::from(""));
 
     // Add simple left padding to each line (matching verify/suggestions pattern)
-    let padded_lines: Vec<Line<'static>> = parsed_lines
-        .into_iter()
-        .map(|line| {
-            let mut spans = vec![Span::styled("  ", Style::default())];
-            spans.extend(line.spans);
-            Line::from(spans)
-        })
-        .collect();
+    padded_lines.extend(
+        markdown::parse_markdown(answer, text_width)
+            .into_iter()
+            .map(|line| {
+                let mut spans = vec![Span::styled("  ", Style::default())];
+                spans.extend(line.spans);
+                Line::from(spans)
+            }),
+    );
 
     // Calculate available height for content
     // Account for: 1 empty top + 1 scroll indicator + 1 empty + 1 hint = 4 lines overhead
@@ -1578,22 +1657,35 @@ fn render_ask_cosmos_content<'a>(
         lines.push(line.clone());
     }
 
-    // Scroll indicator (if content exceeds visible area)
+    // Scroll indicator (if content exceeds visible area) and turn position
+    let mut position = Vec::new();
     if total_lines > content_height {
-        lines.push(Line::from(vec![Span::styled(
-            format!(
-                "  ↕ {}/{}",
-                scroll + 1,
-                total_lines.saturating_sub(content_height) + 1
-            ),
-            Style::default().fg(Theme::GREY_500),
-        )]));
+        position.push(format!(
+            "↕ {}/{}",
+            scroll + 1,
+            total_lines.saturating_sub(content_height) + 1
+        ));
+    }
+    if turn_count > 1 {
+        position.push(format!("turn {}/{}", ask_state.turn + 1, turn_count));
+    }
+    lines.push(Line::from(vec![Span::styled(
+        format!("  {}", position.join("  ·  ")),
+        Style::default().fg(Theme::GREY_500),
+    )]));
+
+    let answering = ask_state.turn >= turns.len()
+        && (ask_state.streaming || app.loading == LoadingState::Answering);
+    if answering {
+        let spinner = SPINNER_FRAMES[app.loading_frame % SPINNER_FRAMES.len()];
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {} ", spinner), Style::default().fg(Theme::WHITE)),
+            Span::styled("Answering...", Style::default().fg(Theme::GREY_500)),
+        ]));
     } else {
         lines.push(Line::from(""));
     }
 
-    lines.push(Line::from(""));
-
     // Action hints at bottom
     lines.push(Line::from(vec![
         Span::styled("  ", Style::default()),
@@ -1603,6 +1695,24 @@ fn render_ask_cosmos_content<'a>(
         ),
         Span::styled(" scroll ", Style::default().fg(Theme::GREY_400)),
         Span::styled("   ", Style::default()),
+        Span::styled(
+            " ←→ ",
+            Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
+        ),
+        Span::styled(" turns ", Style::default().fg(Theme::GREY_400)),
+        Span::styled("   ", Style::default()),
+        Span::styled(
+            " i ",
+            Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
+        ),
+        Span::styled(" follow up ", Style::default().fg(Theme::GREY_400)),
+        Span::styled("   ", Style::default()),
+        Span::styled(
+            " n ",
+            Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
+        ),
+        Span::styled(" new ", Style::default().fg(Theme::GREY_400)),
+        Span::styled("   ", Style::default()),
         Span::styled(
             " Esc ",
             Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
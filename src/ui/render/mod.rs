@@ -17,8 +17,9 @@ use footer::render_footer;
 use header::render_header;
 use main::render_main;
 use overlays::{
-    render_file_detail, render_help, render_reset_overlay, render_startup_check,
-    render_update_overlay, render_welcome,
+    render_ask_history, render_budget_confirm, render_costs_overlay, render_file_detail,
+    render_help, render_license_report, render_memory_overlay, render_reset_overlay,
+    render_startup_check, render_tool_trace, render_update_overlay, render_welcome,
 };
 use toast::render_toast;
 
@@ -48,13 +49,32 @@ pub fn render(frame: &mut Frame, app: &App) {
     // Overlays
     match &app.overlay {
         Overlay::Help { scroll } => render_help(frame, *scroll),
-        Overlay::FileDetail { path, scroll } => {
+        Overlay::FileDetail {
+            path,
+            scroll,
+            hierarchy,
+        } => {
             if let Some(file_index) = app.index.files.get(path) {
-                render_file_detail(frame, path, file_index, app.get_llm_summary(path), *scroll);
+                let symbols: Vec<_> = app.index.file_symbols(file_index).collect();
+                render_file_detail(
+                    frame,
+                    path,
+                    file_index,
+                    &symbols,
+                    app.get_llm_summary(path),
+                    hierarchy,
+                    *scroll,
+                );
             }
         }
-        Overlay::Reset { options, selected } => {
-            render_reset_overlay(frame, options, *selected);
+        Overlay::Reset {
+            options,
+            selected,
+            sizes,
+            cache_size,
+            budget,
+        } => {
+            render_reset_overlay(frame, options, *selected, sizes, *cache_size, *budget);
         }
         Overlay::StartupCheck {
             changed_count,
@@ -89,6 +109,33 @@ pub fn render(frame: &mut Frame, app: &App) {
         Overlay::Welcome => {
             render_welcome(frame);
         }
+        Overlay::LicenseReport { scroll } => {
+            render_license_report(frame, &app.license_report, *scroll);
+        }
+        Overlay::Costs { scroll } => {
+            render_costs_overlay(frame, &crate::suggest::llm::ledger::breakdown(), *scroll);
+        }
+        Overlay::ToolTrace { task, scroll } => {
+            render_tool_trace(
+                frame,
+                *task,
+                &crate::suggest::llm::audit::runs(*task),
+                *scroll,
+            );
+        }
+        Overlay::AskHistory { threads, selected } => {
+            render_ask_history(frame, threads, *selected);
+        }
+        Overlay::BudgetConfirm { message, .. } => {
+            render_budget_confirm(frame, message);
+        }
+        Overlay::Memory {
+            selected,
+            query,
+            mode,
+        } => {
+            render_memory_overlay(frame, &app.memory_entries(), *selected, query, mode);
+        }
         Overlay::None => {}
     }
 
@@ -1,5 +1,6 @@
 use crate::ui::helpers::{centered_rect, wrap_text};
 use crate::ui::theme::Theme;
+use crate::util::{format_bytes, truncate};
 use ratatui::{
     layout::{Constraint, Direction, Layout},
     style::{Modifier, Style},
@@ -83,7 +84,10 @@ pub(super) fn render_help(frame: &mut Frame, scroll: usize) {
     help_text.extend(section_start("Actions"));
     help_text.push(section_spacer());
     help_text.push(key_row("↵", "Select / apply"));
-    help_text.push(key_row("i", "Ask Cosmos"));
+    help_text.push(key_row("i", "Ask Cosmos (follow up when open)"));
+    help_text.push(key_row("h", "Past conversations"));
+    help_text.push(key_row("m", "Remember suggestion/finding"));
+    help_text.push(key_row("M", "Repo memory"));
     help_text.push(key_row("?", "Show help"));
     help_text.push(key_row("q", "Quit"));
     help_text.push(section_spacer());
@@ -94,6 +98,9 @@ pub(super) fn render_help(frame: &mut Frame, scroll: usize) {
     help_text.push(section_spacer());
     help_text.push(key_row("R", "Reset Cosmos"));
     help_text.push(key_row("U", "Check for updates"));
+    help_text.push(key_row("L", "Dependency licenses"));
+    help_text.push(key_row("$", "Costs and budgets"));
+    help_text.push(key_row("t", "Tools the AI called"));
     help_text.push(section_spacer());
     help_text.push(section_end());
 
@@ -118,7 +125,9 @@ pub(super) fn render_file_detail(
     frame: &mut Frame,
     path: &Path,
     file_index: &crate::index::FileIndex,
+    symbols: &[crate::index::symbols::SymbolRef],
     llm_summary: Option<&String>,
+    hierarchy: &[String],
     _scroll: usize,
 ) {
     let area = centered_rect(70, 75, frame.area());
@@ -197,8 +206,7 @@ pub(super) fn render_file_detail(
     lines.push(Line::from(""));
 
     // Metrics bar
-    let func_count = file_index
-        .symbols
+    let func_count = symbols
         .iter()
         .filter(|s| {
             matches!(
@@ -207,8 +215,7 @@ pub(super) fn render_file_detail(
             )
         })
         .count();
-    let struct_count = file_index
-        .symbols
+    let struct_count = symbols
         .iter()
         .filter(|s| {
             matches!(
@@ -353,6 +360,36 @@ pub(super) fn render_file_detail(
         )]));
     }
 
+    // Type hierarchy section (impls, extends, implementors elsewhere)
+    if !hierarchy.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled("    ╭─ ", Style::default().fg(Theme::GREY_600)),
+            Span::styled("Type Hierarchy", Style::default().fg(Theme::GREY_300)),
+            Span::styled(
+                " ─".to_string() + &"─".repeat(inner_width.saturating_sub(21)) + "╮",
+                Style::default().fg(Theme::GREY_600),
+            ),
+        ]));
+        for entry in hierarchy {
+            for (i, line) in wrap_text(entry, inner_width.saturating_sub(8))
+                .iter()
+                .enumerate()
+            {
+                let prefix = if i == 0 { "◆ " } else { "  " };
+                lines.push(Line::from(vec![
+                    Span::styled("    │  ", Style::default().fg(Theme::GREY_600)),
+                    Span::styled(prefix, Style::default().fg(Theme::GREY_400)),
+                    Span::styled(line.to_string(), Style::default().fg(Theme::GREY_200)),
+                ]));
+            }
+        }
+        lines.push(Line::from(vec![Span::styled(
+            "    ╰".to_string() + &"─".repeat(inner_width.saturating_sub(4)) + "╯",
+            Style::default().fg(Theme::GREY_600),
+        )]));
+    }
+
     lines.push(Line::from(""));
     lines.push(Line::from(vec![
         Span::styled("    ", Style::default()),
@@ -380,8 +417,11 @@ pub(super) fn render_reset_overlay(
     frame: &mut Frame,
     options: &[(crate::cache::ResetOption, bool)],
     selected: usize,
+    sizes: &[u64],
+    cache_size: u64,
+    budget: u64,
 ) {
-    let area = centered_rect(55, 50, frame.area());
+    let area = centered_rect(60, 50, frame.area());
     frame.render_widget(Clear, area);
 
     let mut lines: Vec<Line> = Vec::new();
@@ -409,9 +449,10 @@ pub(super) fn render_reset_overlay(
         // Selection indicator
         let indicator = if is_focused { "▸ " } else { "  " };
 
-        // Format: "▸ [x] Label                (description)"
+        // Format: "▸ [x] Label                 1.2 MB  (description)"
         let label = option.label();
         let desc = option.description();
+        let size = sizes.get(i).copied().unwrap_or(0);
 
         // Calculate padding for alignment
         let label_width = 22;
@@ -434,12 +475,32 @@ pub(super) fn render_reset_overlay(
                     Style::default().fg(checkbox_color),
                 ),
                 Span::styled(padded_label, Style::default().fg(Theme::GREY_100)),
+                Span::styled(
+                    format!("{:>9}  ", format_bytes(size)),
+                    Style::default().fg(Theme::GREY_400),
+                ),
                 Span::styled(format!("({})", desc), Style::default().fg(Theme::GREY_500)),
             ])
             .style(line_style),
         );
     }
 
+    // Disk usage against the budget
+    lines.push(Line::from(""));
+    let usage_color = if cache_size > budget {
+        Theme::YELLOW
+    } else {
+        Theme::GREY_400
+    };
+    lines.push(Line::from(Span::styled(
+        format!(
+            "  Caches use {} of {} budget",
+            format_bytes(cache_size),
+            format_bytes(budget)
+        ),
+        Style::default().fg(usage_color),
+    )));
+
     // Separator and help
     lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
@@ -458,6 +519,11 @@ pub(super) fn render_reset_overlay(
             Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
         ),
         Span::styled(" reset  ", Style::default().fg(Theme::GREY_400)),
+        Span::styled(
+            " g ",
+            Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
+        ),
+        Span::styled(" collect garbage  ", Style::default().fg(Theme::GREY_400)),
         Span::styled(
             " Esc ",
             Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
@@ -480,6 +546,763 @@ pub(super) fn render_reset_overlay(
     frame.render_widget(paragraph, area);
 }
 
+pub(super) fn render_license_report(
+    frame: &mut Frame,
+    report: &crate::manifest::license::LicenseReport,
+    scroll: usize,
+) {
+    use crate::manifest::license::{LicenseEntry, LicenseStatus};
+
+    let area = centered_rect(65, 70, frame.area());
+    frame.render_widget(Clear, area);
+
+    let outer_block = Block::default()
+        .title(" Licenses ")
+        .title_style(Style::default().fg(Theme::GREY_100))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Theme::ACCENT))
+        .style(Style::default().bg(Theme::GREY_800));
+
+    let inner_area = outer_block.inner(area);
+    frame.render_widget(outer_block, area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(2)])
+        .split(inner_area);
+
+    fn entry_line(entry: &LicenseEntry) -> Line<'static> {
+        let color = match entry.status {
+            LicenseStatus::Allowed => Theme::GREEN,
+            LicenseStatus::Denied { .. } => Theme::RED,
+            LicenseStatus::NotAllowed | LicenseStatus::Unknown => Theme::YELLOW,
+        };
+        let name = match &entry.version {
+            Some(version) => format!("{} {}", entry.name, version),
+            None => entry.name.clone(),
+        };
+        Line::from(vec![
+            Span::styled(
+                format!("    {:<32} ", name),
+                Style::default().fg(Theme::GREY_100),
+            ),
+            Span::styled(
+                format!(
+                    "{:<24} ",
+                    entry.license.as_deref().unwrap_or("unknown license")
+                ),
+                Style::default().fg(Theme::GREY_300),
+            ),
+            Span::styled(entry.status.label(), Style::default().fg(color)),
+        ])
+    }
+
+    let mut lines: Vec<Line> = Vec::new();
+    lines.push(Line::from(""));
+
+    if report.policy.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  No license policy configured. Add a \"licenses\" section with",
+            Style::default().fg(Theme::GREY_300),
+        )));
+        lines.push(Line::from(Span::styled(
+            "  \"allow\" / \"deny\" lists to .cosmos/config.json to check dependencies.",
+            Style::default().fg(Theme::GREY_300),
+        )));
+        lines.push(Line::from(""));
+    }
+
+    let violations: Vec<&LicenseEntry> = report.violations().collect();
+    lines.push(Line::from(Span::styled(
+        format!(
+            "  {} dependencies · {} violation{} · {} unknown",
+            report.entries.len(),
+            violations.len(),
+            if violations.len() == 1 { "" } else { "s" },
+            report.unknown_count()
+        ),
+        Style::default()
+            .fg(Theme::WHITE)
+            .add_modifier(Modifier::BOLD),
+    )));
+    for project in &report.project_licenses {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "  Project license: {} ({})",
+                project.license,
+                project.manifest.display()
+            ),
+            Style::default().fg(Theme::GREY_400),
+        )));
+    }
+
+    if !violations.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "  Violations",
+            Style::default().fg(Theme::RED),
+        )));
+        for entry in &violations {
+            lines.push(entry_line(entry));
+        }
+    }
+
+    let mut current_manifest: Option<&Path> = None;
+    for entry in &report.entries {
+        if current_manifest != Some(entry.manifest.as_path()) {
+            current_manifest = Some(entry.manifest.as_path());
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                format!("  {}", entry.manifest.display()),
+                Style::default().fg(Theme::ACCENT),
+            )));
+        }
+        lines.push(entry_line(entry));
+    }
+    lines.push(Line::from(""));
+
+    let body = Paragraph::new(lines).scroll((scroll as u16, 0));
+    frame.render_widget(body, layout[0]);
+
+    let footer_lines = vec![
+        Line::from(Span::styled(
+            "  ─────────────────────────────────────────────────",
+            Style::default().fg(Theme::GREY_600),
+        )),
+        Line::from(vec![
+            Span::styled("   ", Style::default()),
+            Span::styled(
+                " ↑↓ ",
+                Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
+            ),
+            Span::styled(" scroll  ", Style::default().fg(Theme::GREY_400)),
+            Span::styled(
+                " e ",
+                Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
+            ),
+            Span::styled(" export  ", Style::default().fg(Theme::GREY_400)),
+            Span::styled(
+                " Esc ",
+                Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
+            ),
+            Span::styled(" close", Style::default().fg(Theme::GREY_400)),
+        ]),
+    ];
+    frame.render_widget(Paragraph::new(footer_lines), layout[1]);
+}
+
+pub(super) fn render_costs_overlay(
+    frame: &mut Frame,
+    breakdown: &crate::suggest::llm::ledger::CostBreakdown,
+    scroll: usize,
+) {
+    use crate::suggest::llm::ledger::SpendLine;
+
+    let area = centered_rect(60, 65, frame.area());
+    frame.render_widget(Clear, area);
+
+    let outer_block = Block::default()
+        .title(" Costs ")
+        .title_style(Style::default().fg(Theme::GREY_100))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Theme::ACCENT))
+        .style(Style::default().bg(Theme::GREY_800));
+
+    let inner_area = outer_block.inner(area);
+    frame.render_widget(outer_block, area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(2)])
+        .split(inner_area);
+
+    fn total_line(label: &str, spent: f64, limit: Option<f64>) -> Line<'static> {
+        let (limit_text, color) = match limit {
+            Some(limit) if spent >= limit => (format!(" of ${:.2}", limit), Theme::RED),
+            Some(limit) if spent >= limit * 0.8 => (format!(" of ${:.2}", limit), Theme::YELLOW),
+            Some(limit) => (format!(" of ${:.2}", limit), Theme::GREEN),
+            None => ("  (no budget)".to_string(), Theme::GREY_400),
+        };
+        Line::from(vec![
+            Span::styled(
+                format!("    {:<12}", label),
+                Style::default().fg(Theme::GREY_300),
+            ),
+            Span::styled(format!("${:.4}", spent), Style::default().fg(Theme::WHITE)),
+            Span::styled(limit_text, Style::default().fg(color)),
+        ])
+    }
+
+    fn spend_line(line: &SpendLine) -> Line<'static> {
+        Line::from(vec![
+            Span::styled(
+                format!("    {:<36} ", truncate(&line.name, 36)),
+                Style::default().fg(Theme::GREY_100),
+            ),
+            Span::styled(
+                format!("{:>5} calls {:>9} tok ", line.calls, line.tokens),
+                Style::default().fg(Theme::GREY_400),
+            ),
+            Span::styled(
+                format!("${:.4}", line.cost),
+                Style::default().fg(Theme::WHITE),
+            ),
+        ])
+    }
+
+    let mut lines: Vec<Line> = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "  Spend",
+            Style::default()
+                .fg(Theme::WHITE)
+                .add_modifier(Modifier::BOLD),
+        )),
+        total_line("Session", breakdown.session, breakdown.budgets.session_usd),
+        total_line("Today", breakdown.today, breakdown.budgets.daily_usd),
+        total_line("Repo", breakdown.repo, breakdown.budgets.repo_usd),
+    ];
+
+    for (title, spend) in [
+        ("By task", &breakdown.by_task),
+        ("By model", &breakdown.by_model),
+    ] {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("  {}", title),
+            Style::default().fg(Theme::ACCENT),
+        )));
+        if spend.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "    No calls recorded yet",
+                Style::default().fg(Theme::GREY_400),
+            )));
+        }
+        lines.extend(spend.iter().map(spend_line));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "  Set caps under \"budgets\" in your config. Ledger: .cosmos/ledger.jsonl",
+        Style::default().fg(Theme::GREY_500),
+    )));
+
+    let body = Paragraph::new(lines).scroll((scroll as u16, 0));
+    frame.render_widget(body, layout[0]);
+
+    let footer_lines = vec![
+        Line::from(Span::styled(
+            "  ─────────────────────────────────────────────────",
+            Style::default().fg(Theme::GREY_600),
+        )),
+        Line::from(vec![
+            Span::styled("   ", Style::default()),
+            Span::styled(
+                " ↑↓ ",
+                Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
+            ),
+            Span::styled(" scroll  ", Style::default().fg(Theme::GREY_400)),
+            Span::styled(
+                " Esc ",
+                Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
+            ),
+            Span::styled(" close", Style::default().fg(Theme::GREY_400)),
+        ]),
+    ];
+    frame.render_widget(Paragraph::new(footer_lines), layout[1]);
+}
+
+pub(super) fn render_tool_trace(
+    frame: &mut Frame,
+    task: crate::suggest::llm::models::Task,
+    runs: &[crate::suggest::llm::audit::AuditRun],
+    scroll: usize,
+) {
+    use crate::suggest::llm::tools::{describe_tool_call, FunctionCall, ToolCall};
+
+    /// Output lines shown per call
+    const OUTPUT_PREVIEW_LINES: usize = 4;
+
+    let area = centered_rect(75, 75, frame.area());
+    frame.render_widget(Clear, area);
+
+    let outer_block = Block::default()
+        .title(format!(" Tool calls · {} ", task.name()))
+        .title_style(Style::default().fg(Theme::GREY_100))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Theme::ACCENT))
+        .style(Style::default().bg(Theme::GREY_800));
+
+    let inner_area = outer_block.inner(area);
+    frame.render_widget(outer_block, area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(2)])
+        .split(inner_area);
+    let width = layout[0].width.saturating_sub(8) as usize;
+
+    let mut lines: Vec<Line> = vec![Line::from("")];
+    if runs.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("  No {} tool calls recorded this session", task.name()),
+            Style::default().fg(Theme::GREY_400),
+        )));
+    }
+
+    for (i, run) in runs.iter().enumerate() {
+        let latest = if i == 0 { "  (latest)" } else { "" };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!(
+                    "  Run {} · {}",
+                    run.id,
+                    run.started.with_timezone(&chrono::Local).format("%H:%M:%S")
+                ),
+                Style::default()
+                    .fg(Theme::WHITE)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(" · {} tool calls{}", run.calls.len(), latest),
+                Style::default().fg(Theme::GREY_400),
+            ),
+        ]));
+        for entry in &run.calls {
+            let call = ToolCall {
+                id: String::new(),
+                function: FunctionCall {
+                    name: entry.tool.clone(),
+                    arguments: entry.arguments.clone(),
+                },
+            };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("    {:>2}  ", entry.iteration),
+                    Style::default().fg(Theme::GREY_500),
+                ),
+                Span::styled(
+                    truncate(&describe_tool_call(&call), width.saturating_sub(12)),
+                    Style::default().fg(Theme::ACCENT),
+                ),
+                Span::styled(
+                    format!("  {}ms", entry.duration_ms),
+                    Style::default().fg(Theme::GREY_500),
+                ),
+            ]));
+            lines.push(Line::from(Span::styled(
+                format!("        {}", truncate(&entry.arguments, width)),
+                Style::default().fg(Theme::GREY_400),
+            )));
+            let output: Vec<&str> = entry.output.lines().collect();
+            for line in output.iter().take(OUTPUT_PREVIEW_LINES) {
+                lines.push(Line::from(Span::styled(
+                    format!("        │ {}", truncate(line, width)),
+                    Style::default().fg(Theme::GREY_300),
+                )));
+            }
+            let hidden = output.len().saturating_sub(OUTPUT_PREVIEW_LINES);
+            if hidden > 0 || entry.truncated {
+                let more = if entry.truncated {
+                    "        │ … (output cut in the log)".to_string()
+                } else {
+                    format!("        │ … {} more lines", hidden)
+                };
+                lines.push(Line::from(Span::styled(
+                    more,
+                    Style::default().fg(Theme::GREY_500),
+                )));
+            }
+        }
+        lines.push(Line::from(""));
+    }
+    if let Some(path) = crate::suggest::llm::audit::log_path() {
+        lines.push(Line::from(Span::styled(
+            format!("  Full log: {}", path.display()),
+            Style::default().fg(Theme::GREY_500),
+        )));
+    }
+
+    let body = Paragraph::new(lines).scroll((scroll as u16, 0));
+    frame.render_widget(body, layout[0]);
+
+    let footer_lines = vec![
+        Line::from(Span::styled(
+            "  ─────────────────────────────────────────────────",
+            Style::default().fg(Theme::GREY_600),
+        )),
+        Line::from(vec![
+            Span::styled("   ", Style::default()),
+            Span::styled(
+                " ↑↓ ",
+                Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
+            ),
+            Span::styled(" scroll  ", Style::default().fg(Theme::GREY_400)),
+            Span::styled(
+                " Esc ",
+                Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
+            ),
+            Span::styled(" close", Style::default().fg(Theme::GREY_400)),
+        ]),
+    ];
+    frame.render_widget(Paragraph::new(footer_lines), layout[1]);
+}
+
+pub(super) fn render_ask_history(
+    frame: &mut Frame,
+    threads: &[crate::cache::AskThread],
+    selected: usize,
+) {
+    let area = centered_rect(65, 70, frame.area());
+    frame.render_widget(Clear, area);
+
+    let outer_block = Block::default()
+        .title(" Past Conversations ")
+        .title_style(Style::default().fg(Theme::GREY_100))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Theme::ACCENT))
+        .style(Style::default().bg(Theme::GREY_800));
+
+    let inner_area = outer_block.inner(area);
+    frame.render_widget(outer_block, area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(2)])
+        .split(inner_area);
+
+    // Thread list, scrolled to keep the focus visible
+    let text_width = (layout[0].width as usize).saturating_sub(30).max(10);
+    let mut lines: Vec<Line> = vec![Line::from("")];
+    let mut focus_line = 0;
+    for (i, thread) in threads.iter().enumerate() {
+        let is_focused = i == selected;
+        if is_focused {
+            focus_line = lines.len();
+        }
+        let line_style = if is_focused {
+            Style::default().bg(Theme::GREY_700)
+        } else {
+            Style::default()
+        };
+        let turns = thread.turns.len();
+        lines.push(
+            Line::from(vec![
+                Span::styled(
+                    if is_focused { "  ▸ " } else { "    " },
+                    Style::default().fg(Theme::ACCENT),
+                ),
+                Span::styled(
+                    format!(
+                        "{:<17}",
+                        thread
+                            .updated_at()
+                            .with_timezone(&chrono::Local)
+                            .format("%Y-%m-%d %H:%M")
+                    ),
+                    Style::default().fg(Theme::GREY_500),
+                ),
+                Span::styled(
+                    truncate(thread.title(), text_width),
+                    Style::default().fg(Theme::GREY_100),
+                ),
+                Span::styled(
+                    if turns > 1 {
+                        format!("  +{} more", turns - 1)
+                    } else {
+                        String::new()
+                    },
+                    Style::default().fg(Theme::GREY_500),
+                ),
+            ])
+            .style(line_style),
+        );
+    }
+    let visible = layout[0].height as usize;
+    let scroll = (focus_line + 1).saturating_sub(visible);
+    frame.render_widget(Paragraph::new(lines).scroll((scroll as u16, 0)), layout[0]);
+
+    let footer_lines = vec![
+        Line::from(Span::styled(
+            "  ─────────────────────────────────────────────────",
+            Style::default().fg(Theme::GREY_600),
+        )),
+        Line::from(vec![
+            Span::styled("   ", Style::default()),
+            Span::styled(
+                " ↑↓ ",
+                Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
+            ),
+            Span::styled(" browse  ", Style::default().fg(Theme::GREY_400)),
+            Span::styled(
+                " ↵ ",
+                Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
+            ),
+            Span::styled(" open  ", Style::default().fg(Theme::GREY_400)),
+            Span::styled(
+                " Esc ",
+                Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
+            ),
+            Span::styled(" close", Style::default().fg(Theme::GREY_400)),
+        ]),
+    ];
+    frame.render_widget(Paragraph::new(footer_lines), layout[1]);
+}
+
+pub(super) fn render_budget_confirm(frame: &mut Frame, message: &str) {
+    let area = centered_rect(50, 30, frame.area());
+    frame.render_widget(Clear, area);
+
+    let mut lines: Vec<Line> = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "  Spending cap reached",
+            Style::default()
+                .fg(Theme::YELLOW)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    let width = area.width.saturating_sub(6) as usize;
+    for line in wrap_text(message, width) {
+        lines.push(Line::from(Span::styled(
+            format!("  {}", line),
+            Style::default().fg(Theme::GREY_300),
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("  ", Style::default()),
+        Span::styled(
+            " y ",
+            Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
+        ),
+        Span::styled(
+            " continue this once  ",
+            Style::default().fg(Theme::GREY_400),
+        ),
+        Span::styled(
+            " n ",
+            Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400),
+        ),
+        Span::styled(" cancel", Style::default().fg(Theme::GREY_400)),
+    ]));
+
+    let block = Block::default()
+        .title(" Budget ")
+        .title_style(Style::default().fg(Theme::GREY_100))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Theme::ACCENT))
+        .style(Style::default().bg(Theme::GREY_800));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
+
+pub(super) fn render_memory_overlay(
+    frame: &mut Frame,
+    entries: &[&crate::cache::MemoryEntry],
+    selected: usize,
+    query: &str,
+    mode: &crate::ui::MemoryMode,
+) {
+    use crate::ui::MemoryMode;
+
+    let area = centered_rect(65, 70, frame.area());
+    frame.render_widget(Clear, area);
+
+    let outer_block = Block::default()
+        .title(" Repo Memory ")
+        .title_style(Style::default().fg(Theme::GREY_100))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Theme::ACCENT))
+        .style(Style::default().bg(Theme::GREY_800));
+
+    let inner_area = outer_block.inner(area);
+    frame.render_widget(outer_block, area);
+
+    let editor_height = if matches!(mode, MemoryMode::Edit { .. }) {
+        5
+    } else {
+        0
+    };
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(1),
+            Constraint::Length(editor_height),
+            Constraint::Length(2),
+        ])
+        .split(inner_area);
+
+    // Search line
+    let searching = *mode == MemoryMode::Search;
+    let search_line = if searching || !query.is_empty() {
+        Line::from(vec![
+            Span::styled("  / ", Style::default().fg(Theme::ACCENT)),
+            Span::styled(query.to_string(), Style::default().fg(Theme::WHITE)),
+            Span::styled(
+                if searching { "█" } else { "" },
+                Style::default().fg(Theme::GREY_400),
+            ),
+        ])
+    } else {
+        Line::from(Span::styled(
+            "  Conventions and decisions Cosmos includes in every prompt",
+            Style::default().fg(Theme::GREY_300),
+        ))
+    };
+    let count = Line::from(Span::styled(
+        format!(
+            "  {} entr{}",
+            entries.len(),
+            if entries.len() == 1 { "y" } else { "ies" }
+        ),
+        Style::default().fg(Theme::GREY_500),
+    ));
+    frame.render_widget(
+        Paragraph::new(vec![Line::from(""), search_line, count]),
+        layout[0],
+    );
+
+    // Entry list, scrolled to keep the focus visible
+    let text_width = (layout[1].width as usize).saturating_sub(23).max(10);
+    let mut lines: Vec<Line> = Vec::new();
+    let mut focus_line = 0;
+    if entries.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            if query.is_empty() {
+                "  Nothing remembered yet. Press n to add a convention,"
+            } else {
+                "  No entries match the search."
+            },
+            Style::default().fg(Theme::GREY_400),
+        )));
+        if query.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "  or m on a suggestion or review finding.",
+                Style::default().fg(Theme::GREY_400),
+            )));
+        }
+    }
+    for (i, entry) in entries.iter().enumerate() {
+        let is_focused = i == selected;
+        if is_focused {
+            focus_line = lines.len();
+        }
+        let deleting = is_focused && *mode == MemoryMode::ConfirmDelete;
+        let line_style = if is_focused {
+            Style::default().bg(Theme::GREY_700)
+        } else {
+            Style::default()
+        };
+        let text_color = if deleting {
+            Theme::RED
+        } else {
+            Theme::GREY_100
+        };
+        for (n, text) in wrap_text(&entry.text, text_width).into_iter().enumerate() {
+            let prefix = match (n, is_focused) {
+                (0, true) => "  ▸ ",
+                _ => "    ",
+            };
+            let date = if n == 0 {
+                entry.created_at.format("%Y-%m-%d").to_string()
+            } else {
+                String::new()
+            };
+            let tag = if n == 0 && entry.shared {
+                "team "
+            } else {
+                "     "
+            };
+            lines.push(
+                Line::from(vec![
+                    Span::styled(prefix, Style::default().fg(Theme::ACCENT)),
+                    Span::styled(
+                        format!("{:<11}", date),
+                        Style::default().fg(Theme::GREY_500),
+                    ),
+                    Span::styled(tag, Style::default().fg(Theme::ACCENT)),
+                    Span::styled(text, Style::default().fg(text_color)),
+                ])
+                .style(line_style),
+            );
+        }
+    }
+    let visible = layout[1].height as usize;
+    let scroll = (focus_line + 1).saturating_sub(visible);
+    frame.render_widget(Paragraph::new(lines).scroll((scroll as u16, 0)), layout[1]);
+
+    // Editor
+    if let MemoryMode::Edit { id, text } = mode {
+        let editor = Paragraph::new(format!("{}█", text))
+            .wrap(Wrap { trim: false })
+            .style(Style::default().fg(Theme::WHITE))
+            .block(
+                Block::default()
+                    .title(if id.is_some() {
+                        " Edit entry "
+                    } else {
+                        " New entry "
+                    })
+                    .title_style(Style::default().fg(Theme::GREY_300))
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Theme::GREY_500)),
+            );
+        frame.render_widget(editor, layout[2]);
+    }
+
+    let key =
+        |k: &'static str| Span::styled(k, Style::default().fg(Theme::GREY_900).bg(Theme::GREY_400));
+    let label = |l: &'static str| Span::styled(l, Style::default().fg(Theme::GREY_400));
+    let hints = match mode {
+        MemoryMode::Browse => vec![
+            key(" n "),
+            label(" new  "),
+            key(" e "),
+            label(" edit  "),
+            key(" d "),
+            label(" delete  "),
+            key(" s "),
+            label(" share  "),
+            key(" / "),
+            label(" search  "),
+            key(" Esc "),
+            label(" close"),
+        ],
+        MemoryMode::Search => vec![
+            key(" ↵ "),
+            label(" keep filter  "),
+            key(" Esc "),
+            label(" clear"),
+        ],
+        MemoryMode::Edit { .. } => {
+            vec![key(" ↵ "), label(" save  "), key(" Esc "), label(" cancel")]
+        }
+        MemoryMode::ConfirmDelete => vec![
+            Span::styled(" Delete this entry? ", Style::default().fg(Theme::RED)),
+            key(" y "),
+            label(" delete  "),
+            key(" Esc "),
+            label(" keep"),
+        ],
+    };
+    let mut hint_line = vec![Span::styled("   ", Style::default())];
+    hint_line.extend(hints);
+    let footer_lines = vec![
+        Line::from(Span::styled(
+            "  ─────────────────────────────────────────────────",
+            Style::default().fg(Theme::GREY_600),
+        )),
+        Line::from(hint_line),
+    ];
+    frame.render_widget(Paragraph::new(footer_lines), layout[3]);
+}
+
 pub(super) fn render_startup_check(
     frame: &mut Frame,
     changed_count: usize,
@@ -110,7 +110,7 @@ pub(super) fn build_grouped_tree(
             if group.expanded {
                 // Add features first, sorted by file count (largest first)
                 let mut sorted_features: Vec<_> = group.features.iter().collect();
-                sorted_features.sort_by(|a, b| b.files.len().cmp(&a.files.len()));
+                sorted_features.sort_by_key(|f| std::cmp::Reverse(f.files.len()));
 
                 for feature in sorted_features {
                     if feature.files.is_empty() {
@@ -99,6 +99,8 @@ pub enum Overlay {
     FileDetail {
         path: PathBuf,
         scroll: usize,
+        /// Type hierarchy lines, computed when the overlay opens
+        hierarchy: Vec<String>,
     },
     /// Reset cosmos - selective cache/data reset
     Reset {
@@ -106,6 +108,12 @@ pub enum Overlay {
         options: Vec<(crate::cache::ResetOption, bool)>,
         /// Currently focused option index
         selected: usize,
+        /// Bytes on disk for each option, same order as `options`
+        sizes: Vec<u64>,
+        /// Bytes used by the regenerable caches the budget covers
+        cache_size: u64,
+        /// Configured `.cosmos/` budget in bytes
+        budget: u64,
     },
     /// Startup check - shown when cosmos starts with unsaved work
     StartupCheck {
@@ -133,6 +141,62 @@ pub enum Overlay {
     },
     /// Welcome overlay - shown on first run to explain the basics
     Welcome,
+    /// Dependency licenses checked against the project's policy
+    LicenseReport {
+        scroll: usize,
+    },
+    /// Repo memory editor - list, search, add, edit and delete entries
+    Memory {
+        /// Focused entry within the filtered list
+        selected: usize,
+        /// Case-insensitive filter on entry text
+        query: String,
+        mode: MemoryMode,
+    },
+    /// LLM spend this session, today and for the repo, by task and model
+    Costs {
+        scroll: usize,
+    },
+    /// Tools the model called for the current suggestions, preview or review
+    ToolTrace {
+        task: crate::suggest::llm::models::Task,
+        scroll: usize,
+    },
+    /// Past Ask Cosmos conversations, most recently active first
+    AskHistory {
+        threads: Vec<crate::cache::AskThread>,
+        selected: usize,
+    },
+    /// A spending cap was reached; `y` approves the action and replays it
+    BudgetConfirm {
+        message: String,
+        replay: BudgetReplay,
+    },
+}
+
+/// The action held back by a budget confirm prompt
+#[derive(Debug, Clone, PartialEq)]
+pub enum BudgetReplay {
+    /// Key press to re-dispatch in normal mode
+    Key(crossterm::event::KeyEvent),
+    /// Ask Cosmos question to resubmit
+    Question(String),
+}
+
+/// What the memory overlay is doing
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum MemoryMode {
+    #[default]
+    Browse,
+    /// Typing into the search filter
+    Search,
+    /// Editing an entry's text (`None` = a new entry)
+    Edit {
+        id: Option<uuid::Uuid>,
+        text: String,
+    },
+    /// Waiting for `y` to delete the focused entry
+    ConfirmDelete,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -190,6 +254,8 @@ pub struct VerifyState {
     pub show_technical_details: bool,
     /// File content hashes captured during preview (for change detection)
     pub preview_hashes: HashMap<PathBuf, String>,
+    /// Tests covering the affected files (what to run after applying)
+    pub related_tests: Vec<PathBuf>,
 }
 
 impl VerifyState {
@@ -240,8 +306,17 @@ pub struct ShipState {
 /// State for the Ask Cosmos panel mode
 #[derive(Debug, Clone, Default)]
 pub struct AskCosmosState {
+    /// The conversation so far, saved to `.cosmos/` after each answer
+    pub thread: crate::cache::AskThread,
+    /// Question being answered, shown after the thread's turns
+    pub pending: Option<String>,
+    /// Answer to `pending` as it arrives
     pub response: String,
+    /// Turn on screen; `thread.turns.len()` is the pending question
+    pub turn: usize,
     pub scroll: usize,
+    /// The answer is still arriving
+    pub streaming: bool,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
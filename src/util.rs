@@ -1,6 +1,8 @@
 use std::io::{BufReader, Read};
 use std::path::{Component, Path, PathBuf};
 use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -28,11 +30,39 @@ pub struct CommandRunResult {
     pub stdout: String,
     pub stderr: String,
     pub timed_out: bool,
+    /// Output past the capture limit was dropped
+    pub truncated: bool,
 }
 
 pub fn run_command_with_timeout(
     command: &mut Command,
     timeout: Duration,
+) -> Result<CommandRunResult, String> {
+    run_command(command, timeout, usize::MAX, false)
+}
+
+/// Like `run_command_with_timeout`, for untrusted commands: the command gets
+/// its own process group, which is killed as a whole once the command exits,
+/// times out or passes `max_output_bytes` of output (so background children
+/// can't outlive it), and at most `max_output_bytes` of each stream is kept.
+pub fn run_command_isolated(
+    command: &mut Command,
+    timeout: Duration,
+    max_output_bytes: usize,
+) -> Result<CommandRunResult, String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    run_command(command, timeout, max_output_bytes, true)
+}
+
+fn run_command(
+    command: &mut Command,
+    timeout: Duration,
+    max_output_bytes: usize,
+    kill_group: bool,
 ) -> Result<CommandRunResult, String> {
     let mut child = command
         .stdout(Stdio::piped())
@@ -49,50 +79,113 @@ pub fn run_command_with_timeout(
         .take()
         .ok_or_else(|| "Failed to capture stderr".to_string())?;
 
-    let stdout_handle = thread::spawn(move || {
-        let mut buf = Vec::new();
-        let mut reader = BufReader::new(stdout);
-        let _ = reader.read_to_end(&mut buf);
-        buf
-    });
-    let stderr_handle = thread::spawn(move || {
-        let mut buf = Vec::new();
-        let mut reader = BufReader::new(stderr);
-        let _ = reader.read_to_end(&mut buf);
-        buf
-    });
+    let capped = Arc::new(AtomicBool::new(false));
+    let stdout_handle = {
+        let capped = Arc::clone(&capped);
+        thread::spawn(move || read_capped(stdout, max_output_bytes, &capped))
+    };
+    let stderr_handle = {
+        let capped = Arc::clone(&capped);
+        thread::spawn(move || read_capped(stderr, max_output_bytes, &capped))
+    };
 
-    let start = Instant::now();
-    let mut timed_out = false;
-    let status = loop {
-        match child.try_wait() {
-            Ok(Some(status)) => break Some(status),
-            Ok(None) => {
-                if start.elapsed() >= timeout {
-                    timed_out = true;
-                    let _ = child.kill();
-                    match child.wait() {
-                        Ok(status) => break Some(status),
-                        Err(_) => break None,
-                    }
-                }
-                thread::sleep(Duration::from_millis(50));
+    // Only called while the child is unreaped, so its pid (and with it the
+    // process group id) can't have been handed to anything else yet
+    let kill = |child: &mut std::process::Child| {
+        #[cfg(unix)]
+        if kill_group {
+            // SAFETY: plain syscall; a negative pid addresses the process group
+            unsafe {
+                libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
             }
-            Err(e) => return Err(format!("Failed to wait for command: {}", e)),
         }
+        let _ = child.kill();
     };
 
-    let stdout_bytes = stdout_handle.join().unwrap_or_default();
-    let stderr_bytes = stderr_handle.join().unwrap_or_default();
+    let start = Instant::now();
+    let mut timed_out = false;
+    let mut exited = false;
+    loop {
+        if !exited {
+            exited = has_exited(&mut child, kill_group)
+                .map_err(|e| format!("Failed to wait for command: {}", e))?;
+        }
+        // With a process group, background processes left holding the pipes
+        // are waited for until the deadline, then killed with the group
+        let drained = stdout_handle.is_finished() && stderr_handle.is_finished();
+        if exited && (drained || !kill_group) {
+            break;
+        }
+        if start.elapsed() >= timeout {
+            timed_out = true;
+            break;
+        }
+        if kill_group && capped.load(Ordering::Relaxed) {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    kill(&mut child);
+    let status = child.wait().ok();
+
+    let (stdout_bytes, stdout_truncated) = stdout_handle.join().unwrap_or_default();
+    let (stderr_bytes, stderr_truncated) = stderr_handle.join().unwrap_or_default();
 
     Ok(CommandRunResult {
         status,
         stdout: String::from_utf8_lossy(&stdout_bytes).to_string(),
         stderr: String::from_utf8_lossy(&stderr_bytes).to_string(),
         timed_out,
+        truncated: stdout_truncated || stderr_truncated,
     })
 }
 
+/// Whether the child has exited. For process groups it is left unreaped
+/// (a zombie) so the group id stays reserved until `wait`.
+fn has_exited(child: &mut std::process::Child, kill_group: bool) -> std::io::Result<bool> {
+    #[cfg(unix)]
+    if kill_group {
+        // SAFETY: `info` is zeroed and only written by the kernel
+        unsafe {
+            let mut info: libc::siginfo_t = std::mem::zeroed();
+            let waited = libc::waitid(
+                libc::P_PID,
+                child.id() as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOHANG | libc::WNOWAIT,
+            );
+            if waited != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            return Ok(info.si_pid() != 0);
+        }
+    }
+    Ok(child.try_wait()?.is_some())
+}
+
+/// Read a stream to the end, keeping the first `max` bytes; `capped` is set
+/// once more than that arrives
+fn read_capped(stream: impl Read, max: usize, capped: &AtomicBool) -> (Vec<u8>, bool) {
+    let mut buf = Vec::new();
+    let mut reader = BufReader::new(stream);
+    let mut chunk = [0u8; 8192];
+    let mut truncated = false;
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                let room = max.saturating_sub(buf.len());
+                if n > room {
+                    truncated = true;
+                    capped.store(true, Ordering::Relaxed);
+                }
+                buf.extend_from_slice(&chunk[..n.min(room)]);
+            }
+        }
+    }
+    (buf, truncated)
+}
+
 pub struct RepoPath {
     pub absolute: PathBuf,
     pub relative: PathBuf,
@@ -172,12 +265,28 @@ pub fn hash_str(content: &str) -> String {
     hash_bytes(content.as_bytes())
 }
 
+/// Human-readable byte count ("812 B", "4.2 MB")
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{hash_str, resolve_repo_path_allow_new, truncate};
+    use super::{hash_str, resolve_repo_path_allow_new, run_command_isolated, truncate};
     use std::fs;
     use std::path::PathBuf;
-    use std::time::{SystemTime, UNIX_EPOCH};
+    use std::process::Command;
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
     #[test]
     fn test_truncate_unicode_safe() {
@@ -218,4 +327,46 @@ mod tests {
 
         let _ = fs::remove_dir_all(&root);
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_command_isolated_stops_runaway_output_and_stragglers() {
+        let start = Instant::now();
+        let mut flood = Command::new("sh");
+        flood.args(["-c", "while :; do echo spam; done"]);
+        let result = run_command_isolated(&mut flood, Duration::from_secs(30), 1024).unwrap();
+        assert!(result.truncated && !result.timed_out);
+        assert!(result.stdout.len() <= 1024);
+
+        let mut straggler = Command::new("sh");
+        straggler.args(["-c", "sleep 30 & echo done"]);
+        let result =
+            run_command_isolated(&mut straggler, Duration::from_millis(500), 1024).unwrap();
+        assert_eq!(result.stdout.trim(), "done");
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_run_command_isolated_kills_detached_children_on_exit() {
+        let mut detached = Command::new("sh");
+        detached.args(["-c", "sleep 999 >/dev/null 2>&1 & echo $!"]);
+        let result = run_command_isolated(&mut detached, Duration::from_secs(10), 1024).unwrap();
+        assert!(!result.timed_out);
+        let pid: u32 = result.stdout.trim().parse().unwrap();
+
+        // Killed processes may linger briefly as zombies until reparented and reaped
+        let gone = || match fs::read_to_string(format!("/proc/{}/stat", pid)) {
+            Ok(stat) => stat
+                .rsplit(')')
+                .next()
+                .is_some_and(|rest| rest.trim_start().starts_with('Z')),
+            Err(_) => true,
+        };
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !gone() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(gone(), "background sleep {} outlived the call", pid);
+    }
 }